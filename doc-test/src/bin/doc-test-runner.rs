@@ -0,0 +1,283 @@
+//! Runs the doctests generated from the markdown, and sums them up by page
+//! and code block.
+//!
+//! `cargo test` reports a failing block by the name of the generated item,
+//! in the middle of everything rustdoc prints. The runner drives the same
+//! `cargo test --doc`, then lists the failing blocks first, by markdown file
+//! and line, followed by a table of every block.
+//!
+//! It uses its own target directory, so it can run while another build of
+//! the crate holds the lock on `target/`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
+
+const USAGE: &str = "\
+usage: doc-test-runner [--content <dir>] [--filter <glob>] [--fail-fast]
+
+    --content <dir>   test this directory instead of the website's content/
+    --filter <glob>   only test markdown files matching a glob or substring
+    --fail-fast       stop at the first failing block";
+
+#[derive(Debug, Default)]
+struct Args {
+    content: Option<PathBuf>,
+    filter: Option<String>,
+    fail_fast: bool,
+}
+
+/// How one doctest went.
+#[derive(Debug)]
+struct Outcome {
+    /// The generated item, like `tokio::tutorial::spawning_block_3_md`.
+    item: String,
+
+    /// The result rustdoc printed: `ok`, `FAILED` or `ignored`.
+    result: String,
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let home = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let mut cmd = Command::new(cargo);
+    cmd.arg("test")
+        .arg("--doc")
+        .arg("--manifest-path")
+        .arg(home.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(home.join("target/runner"))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    match &args.content {
+        Some(content) => cmd.env("DOC_TEST_CONTENT", content),
+        None => cmd.env_remove("DOC_TEST_CONTENT"),
+    };
+    match &args.filter {
+        Some(filter) => cmd.env("DOC_TEST_FILTER", filter),
+        None => cmd.env_remove("DOC_TEST_FILTER"),
+    };
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            eprintln!("failed to run cargo: {}", err);
+            process::exit(2);
+        }
+    };
+
+    // Cargo's own output only matters if the doctests can't be built.
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stderr.read_to_string(&mut out);
+        out
+    });
+
+    let mut outcomes = vec![];
+    let mut generated = None;
+    let mut output = String::new();
+
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if let Some((file, outcome)) = parse_result(&line) {
+            generated.get_or_insert(file);
+            let failed = outcome.result == "FAILED";
+            outcomes.push(outcome);
+
+            if failed && args.fail_fast {
+                let _ = child.kill();
+                break;
+            }
+        }
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    let status = child.wait().unwrap();
+    let stderr = stderr.join().unwrap();
+
+    if outcomes.is_empty() {
+        // Either nothing was built, or nothing was selected.
+        if !status.success() {
+            eprint!("{}", stderr);
+            eprintln!("doc-test-runner: the doctests failed to build");
+            process::exit(2);
+        }
+        println!("no code blocks were tested");
+        return;
+    }
+
+    // The generated file says which markdown block each item comes from.
+    let origins = generated
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|code| origins(&code))
+        .unwrap_or_default();
+    let content = args
+        .content
+        .clone()
+        .unwrap_or_else(|| home.join("../content"));
+    let content = content.canonicalize().unwrap_or(content);
+    let prefix = format!("{}/", content.to_string_lossy().replace('\\', "/"));
+
+    let origin = |item: &str| -> String {
+        match origins.get(item) {
+            Some(origin) => origin.strip_prefix(&prefix).unwrap_or(origin).to_string(),
+            None => item.to_string(),
+        }
+    };
+
+    let failed: Vec<_> = outcomes
+        .iter()
+        .filter(|outcome| outcome.result == "FAILED")
+        .collect();
+
+    if !failed.is_empty() {
+        // rustdoc's details, then where to look.
+        if let Some(start) = output.find("\nfailures:\n") {
+            let details = &output[start + 1..];
+            let end = details.find("\nfailures:\n").unwrap_or(details.len());
+            println!("{}", &details[..end]);
+        }
+
+        println!("failed:");
+        for outcome in &failed {
+            println!("    {}", origin(&outcome.item));
+        }
+        println!();
+    }
+
+    let mut rows: Vec<_> = outcomes
+        .iter()
+        .map(|outcome| {
+            let origin = origin(&outcome.item);
+            let (page, line) = match origin.rsplit_once(':') {
+                Some((page, line)) => (page.to_string(), line.to_string()),
+                None => (origin, String::new()),
+            };
+            (page, line, outcome.result.as_str())
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let line = |row: &(String, String, &str)| row.1.parse::<usize>().unwrap_or(0);
+        a.0.cmp(&b.0).then(line(a).cmp(&line(b)))
+    });
+
+    let width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max(4);
+    println!("{:width$}  {:>5}  result", "page", "line", width = width);
+    for (page, line, result) in &rows {
+        println!("{:width$}  {:>5}  {}", page, line, result, width = width);
+    }
+
+    let passed = outcomes.iter().filter(|o| o.result == "ok").count();
+    let ignored = outcomes.iter().filter(|o| o.result == "ignored").count();
+    println!();
+    println!(
+        "{} passed, {} failed, {} ignored",
+        passed,
+        failed.len(),
+        ignored
+    );
+
+    if !failed.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--content" => {
+                let dir = args.next().ok_or("--content needs a directory")?;
+                parsed.content = Some(PathBuf::from(dir));
+            }
+            "--filter" => {
+                let filter = args.next().ok_or("--filter needs a glob")?;
+                parsed.filter = Some(filter);
+            }
+            "--fail-fast" => parsed.fail_fast = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Parse one of the lines rustdoc prints per doctest:
+///
+/// ```text
+/// test <file> - <item> (line <n>) ... <result>
+/// ```
+fn parse_result(line: &str) -> Option<(String, Outcome)> {
+    let (test, result) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+    let (file, item) = test.split_once(" - ")?;
+    let (item, _) = item.rsplit_once(" (line ")?;
+
+    Some((
+        file.to_string(),
+        Outcome {
+            item: item.to_string(),
+            result: result.to_string(),
+        },
+    ))
+}
+
+/// Map each item of the generated code to the `path:line` in its
+/// `/// From` comment.
+fn origins(code: &str) -> HashMap<String, String> {
+    let mut origins = HashMap::new();
+    let mut modules = vec![];
+    let mut from = None;
+
+    for line in code.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(" {"))
+        {
+            modules.push(name);
+        } else if line == "}" {
+            modules.pop();
+        } else if let Some(origin) = line.strip_prefix("/// From ") {
+            from = Some(origin);
+        } else if let Some(name) = line
+            .strip_prefix("pub fn ")
+            .and_then(|rest| rest.strip_suffix("() {}"))
+        {
+            if let Some(origin) = from.take() {
+                let mut path = modules.join("::");
+                if !path.is_empty() {
+                    path.push_str("::");
+                }
+                path.push_str(name);
+                origins.insert(path, origin.to_string());
+            }
+        }
+    }
+
+    origins
+}
@@ -0,0 +1,191 @@
+//! Removing what rustdoc shouldn't see from a markdown file.
+//!
+//! The website renders the content with MDX, so pages have YAML front
+//! matter, HTML comments and JSX components like `<Aside>` in them. rustdoc
+//! would render the components as broken HTML and test fences commented out
+//! with `<!-- -->`. All three are removed here, at block level, the way the
+//! content uses them.
+//!
+//! Removed lines are replaced with empty ones, so line numbers still match
+//! the original file, and code blocks are copied byte for byte. Comments
+//! starting with `doc-test:` are directives for the generator, and are kept.
+//!
+//! Code blocks are found by pulldown-cmark, like in `fence`, so the ones in
+//! list items and block quotes are left alone too. MDX also takes a fence
+//! right below a component line to open a block, where CommonMark sees more
+//! HTML, so those are still looked for line by line.
+
+use super::fence;
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+use std::collections::HashMap;
+
+/// What the line being looked at is part of.
+enum State {
+    Text,
+    /// A code block, opened with this fence.
+    Code(String),
+    /// A code block found by pulldown-cmark, up to this line.
+    Block(usize),
+    /// An HTML comment, up to `-->`.
+    Comment,
+    /// A JSX tag spanning several lines, up to `>`.
+    Tag,
+}
+
+/// `markdown` without front matter, HTML comments and JSX components. The
+/// text inside a component is kept.
+pub fn clean(markdown: &str) -> String {
+    let mut dst = String::with_capacity(markdown.len());
+    let mut lines = markdown.split_inclusive('\n').enumerate().peekable();
+    let blocks = code_blocks(markdown);
+
+    // Front matter is only recognized on the very first line.
+    if lines.peek().map(|(_, line)| trim_newline(line)) == Some("---") {
+        let mut closed = false;
+        let mut skipped = vec![];
+
+        for (_, line) in lines.by_ref() {
+            skipped.push(line);
+            if skipped.len() > 1 && trim_newline(line) == "---" {
+                closed = true;
+                break;
+            }
+        }
+
+        if closed {
+            for line in &skipped {
+                dst.push_str(newline(line));
+            }
+        } else {
+            // Not front matter after all, just a thematic break.
+            skipped.iter().for_each(|line| dst.push_str(line));
+        }
+    }
+
+    let mut state = State::Text;
+
+    for (index, line) in lines {
+        let content = trim_newline(line);
+
+        match &state {
+            State::Block(last) => {
+                if index == *last {
+                    state = State::Text;
+                }
+                dst.push_str(line);
+            }
+            State::Code(fence) => {
+                if fence::closes(content, fence) {
+                    state = State::Text;
+                }
+                dst.push_str(line);
+            }
+            State::Comment => match content.find("-->") {
+                Some(end) => {
+                    state = State::Text;
+                    let rest = &content[end + 3..];
+                    dst.push_str(&clean_text(rest, &mut state));
+                    dst.push_str(newline(line));
+                }
+                None => dst.push_str(newline(line)),
+            },
+            State::Tag => match content.find('>') {
+                Some(end) => {
+                    state = State::Text;
+                    let rest = &content[end + 1..];
+                    dst.push_str(&clean_text(rest, &mut state));
+                    dst.push_str(newline(line));
+                }
+                None => dst.push_str(newline(line)),
+            },
+            State::Text => {
+                if let Some(&last) = blocks.get(&index) {
+                    if index != last {
+                        state = State::Block(last);
+                    }
+                    dst.push_str(line);
+                } else if let Some((fence, _)) = fence::opening_fence(content) {
+                    state = State::Code(fence.to_string());
+                    dst.push_str(line);
+                } else {
+                    dst.push_str(&clean_text(content, &mut state));
+                    dst.push_str(newline(line));
+                }
+            }
+        }
+    }
+
+    dst
+}
+
+/// The fenced code blocks pulldown-cmark finds in `markdown`, as the index
+/// of their first line mapped to the index of their last one.
+fn code_blocks(markdown: &str) -> HashMap<usize, usize> {
+    let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let mut blocks = HashMap::new();
+
+    for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+        if let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(_))) = event {
+            let first = markdown[..range.start].matches('\n').count();
+            let source = markdown[range].trim_end_matches(['\r', '\n']);
+            blocks.insert(first, first + source.matches('\n').count());
+        }
+    }
+
+    blocks
+}
+
+/// Remove the comments and JSX tags starting `text`, which isn't in a code
+/// block. `state` is updated if one of them goes on past the end of `text`.
+fn clean_text(mut text: &str, state: &mut State) -> String {
+    loop {
+        let rest = text.trim_start();
+
+        // Directives for the generator are kept. They are read after the
+        // file is cleaned, and rustdoc doesn't show comments anyway.
+        if rest.starts_with("<!-- doc-test:") {
+            return text.to_string();
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            match comment.find("-->") {
+                Some(end) => text = &comment[end + 3..],
+                None => {
+                    *state = State::Comment;
+                    return String::new();
+                }
+            }
+        } else if is_component(rest) {
+            match rest.find('>') {
+                Some(end) => text = &rest[end + 1..],
+                None => {
+                    *state = State::Tag;
+                    return String::new();
+                }
+            }
+        } else if rest.is_empty() {
+            return String::new();
+        } else {
+            return text.to_string();
+        }
+    }
+}
+
+/// Whether `text` starts with a JSX component tag. Components are
+/// capitalized, unlike HTML elements: `<Aside>`, `</Aside>` or `<Aside />`.
+fn is_component(text: &str) -> bool {
+    let name = text.strip_prefix("</").or_else(|| text.strip_prefix('<'));
+    matches!(name.and_then(|name| name.chars().next()), Some(c) if c.is_ascii_uppercase())
+}
+
+/// `line` without its line ending.
+fn trim_newline(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Just the line ending of `line`, if it has one.
+fn newline(line: &str) -> &str {
+    &line[trim_newline(line).len()..]
+}
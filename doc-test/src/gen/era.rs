@@ -0,0 +1,86 @@
+//! Crates for the code blocks written against older releases of Tokio.
+//!
+//! A block marked with `era=<era>`, like `rust,era=tokio-0.2`, can't compile
+//! against the dependencies of the doc-test crate. Each era gets a crate of
+//! its own instead, generated in `OUT_DIR` with the dependencies of the time.
+//! Building those takes a while, so they are only tested when
+//! `DOC_TEST_ERAS` is set.
+
+/// A past release of Tokio, and what a crate using it depended on.
+#[derive(Debug, PartialEq)]
+pub struct Era {
+    pub name: &'static str,
+    pub edition: &'static str,
+
+    /// The `[dependencies]` of the era's crate, one TOML line each.
+    pub dependencies: &'static [&'static str],
+}
+
+pub const ERAS: &[Era] = &[
+    Era {
+        name: "tokio-0.1",
+        edition: "2018",
+        dependencies: &[r#"tokio = "0.1""#, r#"futures = "0.1""#, r#"bytes = "0.4""#],
+    },
+    Era {
+        name: "tokio-0.2",
+        edition: "2018",
+        dependencies: &[
+            r#"tokio = { version = "0.2", features = ["full"] }"#,
+            r#"futures = "0.3""#,
+            r#"bytes = "0.5""#,
+        ],
+    },
+    Era {
+        name: "tokio-0.3",
+        edition: "2018",
+        dependencies: &[
+            r#"tokio = { version = "0.3", features = ["full"] }"#,
+            r#"futures = "0.3""#,
+            r#"bytes = "0.6""#,
+        ],
+    },
+];
+
+/// The era called `name`.
+pub fn find(name: &str) -> Option<&'static Era> {
+    ERAS.iter().find(|era| era.name == name)
+}
+
+/// The names of every era, for error messages.
+pub fn names() -> String {
+    let names: Vec<_> = ERAS.iter().map(|era| era.name).collect();
+    names.join(", ")
+}
+
+impl Era {
+    /// The name of the era's crate.
+    pub fn package(&self) -> String {
+        format!("doc-test-{}", self.name.replace('.', "-"))
+    }
+
+    /// The `Cargo.toml` of the era's crate.
+    pub fn manifest(&self) -> String {
+        let mut manifest = format!(
+            "[package]\n\
+             name = \"{}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"{}\"\n\
+             publish = false\n\
+             \n\
+             # Keep the crate out of any workspace it is generated in.\n\
+             [workspace]\n\
+             \n\
+             [dependencies]\n",
+            self.package(),
+            self.edition
+        );
+
+        for dependency in self.dependencies {
+            manifest.push_str(dependency);
+            manifest.push('\n');
+        }
+
+        manifest
+    }
+}
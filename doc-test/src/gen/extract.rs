@@ -0,0 +1,195 @@
+//! The work done for each markdown file, spread over threads and cached.
+//!
+//! Checking the front matter, cleaning up the markdown and parsing its code
+//! blocks only depends on the file's contents. The results are kept in a
+//! directory of `OUT_DIR`, keyed by a hash of the contents, so a build after
+//! editing one page only redoes that page.
+
+use super::clean;
+use super::fence::{self, Block};
+use super::front_matter;
+use super::labels;
+
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Part of every cache key. Bump it whenever what is extracted from a file
+/// changes, so results from an older generator aren't used.
+pub const VERSION: u32 = 2;
+
+/// What is learned from a markdown file.
+#[derive(Debug, PartialEq)]
+pub struct Extracted {
+    /// The file without front matter, comments and components, as rustdoc
+    /// gets it.
+    pub source: String,
+
+    /// The code blocks of `source`, or `None` if one is never closed.
+    pub blocks: Option<Vec<Block>>,
+
+    /// Why the front matter can't be used, if it can't.
+    pub front_matter: Option<String>,
+
+    /// The lines of the blocks without an info string.
+    pub unlabelled: Vec<usize>,
+}
+
+/// Extract what is needed from `raw`, the contents of a markdown file.
+pub fn extract(raw: &str) -> Extracted {
+    let source = clean::clean(raw);
+    let blocks = fence::parse(&source).ok();
+    let unlabelled = match &blocks {
+        Some(blocks) => labels::unlabelled(&source, blocks),
+        None => vec![],
+    };
+
+    Extracted {
+        front_matter: front_matter::check(raw).err().map(|err| err.to_string()),
+        source,
+        blocks,
+        unlabelled,
+    }
+}
+
+/// The cache key of a file with `raw` as its contents.
+///
+/// `DefaultHasher` is only stable for a given Rust release, which is all
+/// that is needed of a cache in `OUT_DIR`.
+pub fn key(version: u32, raw: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    version.hash(&mut hasher);
+    raw.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Extraction results stored in a directory.
+pub struct Cache {
+    dir: PathBuf,
+    extractions: AtomicUsize,
+}
+
+impl Cache {
+    pub fn new(dir: &Path) -> Cache {
+        fs::create_dir_all(dir).unwrap();
+
+        Cache {
+            dir: dir.to_path_buf(),
+            extractions: AtomicUsize::new(0),
+        }
+    }
+
+    /// How many files had to be extracted, rather than read from the cache.
+    pub fn extractions(&self) -> usize {
+        self.extractions.load(Ordering::Relaxed)
+    }
+
+    /// What is extracted from `raw`, from the cache if it is in there.
+    pub fn get(&self, raw: &str) -> Extracted {
+        let path = self.dir.join(format!("{}.json", key(VERSION, raw)));
+
+        // A file that can't be read or parsed is just a miss.
+        let cached = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .and_then(|json| from_json(&json));
+        if let Some(extracted) = cached {
+            return extracted;
+        }
+
+        self.extractions.fetch_add(1, Ordering::Relaxed);
+        let extracted = extract(raw);
+        fs::write(&path, to_json(&extracted).to_string()).unwrap();
+        extracted
+    }
+
+    /// Read and extract each of `paths`, on as many threads as there are
+    /// cores. The results are in the same order as `paths`.
+    pub fn get_all(&self, paths: &[PathBuf]) -> Vec<Extracted> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = paths.len().div_ceil(threads).max(1);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = paths
+                .chunks(chunk)
+                .map(|paths| {
+                    scope.spawn(move || {
+                        paths
+                            .iter()
+                            .map(|path| self.get(&fs::read_to_string(path).unwrap()))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+fn to_json(extracted: &Extracted) -> Value {
+    let blocks = extracted.blocks.as_ref().map(|blocks| {
+        blocks
+            .iter()
+            .map(|block| {
+                json!({
+                    "line": block.line,
+                    "fence": block.fence,
+                    "info": block.info,
+                    "code": block.code,
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+
+    json!({
+        "source": extracted.source,
+        "blocks": blocks,
+        "front_matter": extracted.front_matter,
+        "unlabelled": extracted.unlabelled,
+    })
+}
+
+fn from_json(json: &Value) -> Option<Extracted> {
+    let string = |value: &Value| value.as_str().map(str::to_string);
+    let number = |value: &Value| value.as_u64().map(|n| n as usize);
+
+    let blocks = match &json["blocks"] {
+        Value::Null => None,
+        blocks => Some(
+            blocks
+                .as_array()?
+                .iter()
+                .map(|block| {
+                    Some(Block {
+                        line: number(&block["line"])?,
+                        fence: string(&block["fence"])?,
+                        info: string(&block["info"])?,
+                        code: string(&block["code"])?,
+                    })
+                })
+                .collect::<Option<_>>()?,
+        ),
+    };
+
+    Some(Extracted {
+        source: string(&json["source"])?,
+        blocks,
+        front_matter: match &json["front_matter"] {
+            Value::Null => None,
+            reason => Some(string(reason)?),
+        },
+        unlabelled: json["unlabelled"]
+            .as_array()?
+            .iter()
+            .map(number)
+            .collect::<Option<_>>()?,
+    })
+}
@@ -0,0 +1,258 @@
+//! Splitting markdown into its fenced code blocks.
+//!
+//! The markdown is parsed with pulldown-cmark, the parser rustdoc uses, so a
+//! block is found wherever rustdoc would test it: fenced with backticks or
+//! tildes, and inside of list items and block quotes too. The code is what
+//! is left once the indentation and `>` of those containers are removed.
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+/// A fenced code block.
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    /// Line of the opening fence, starting at 1.
+    pub line: usize,
+
+    /// The opening fence, without the info string, e.g. "```".
+    pub fence: String,
+
+    /// Everything after the opening fence, e.g. "rust,no_run".
+    pub info: String,
+
+    /// The lines between the fences, each ending with a newline.
+    pub code: String,
+}
+
+/// How rustdoc treats a Rust code block, going by its info string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// Compiled and run. `should_panic` blocks are in here too.
+    Run,
+
+    /// Compiled, but not run.
+    NoRun,
+
+    /// Expected not to compile.
+    CompileFail,
+
+    /// Not compiled at all.
+    Ignore,
+}
+
+/// A code block that is never closed, before the end of the file or of the
+/// list item or quote it is in.
+#[derive(Debug, PartialEq)]
+pub struct Unterminated {
+    /// Line of the opening fence, starting at 1.
+    pub line: usize,
+}
+
+/// Attributes rustdoc understands in an info string. A block whose info
+/// string has anything else in it, like `text` or `rs`, isn't Rust.
+const RUSTDOC_ATTRS: &[&str] = &[
+    "rust",
+    "ignore",
+    "should_panic",
+    "no_run",
+    "compile_fail",
+    "test_harness",
+    "edition2015",
+    "edition2018",
+    "edition2021",
+];
+
+impl Block {
+    /// Whether rustdoc would test this block.
+    pub fn is_rust(&self) -> bool {
+        self.attrs()
+            .all(|attr| RUSTDOC_ATTRS.contains(&attr) || is_error_code(attr) || is_ours(attr))
+    }
+
+    /// The features of the doc-test crate this block needs, from
+    /// `requires=<feature>` attributes. These are ours, not rustdoc's.
+    pub fn requires(&self) -> Vec<&str> {
+        self.attrs()
+            .filter_map(|attr| value(attr, "requires"))
+            .collect()
+    }
+
+    /// The era of Tokio this block was written for, from an `era=<era>`
+    /// attribute. `None` means the current one.
+    pub fn era(&self) -> Option<&str> {
+        self.attrs().find_map(|attr| value(attr, "era"))
+    }
+
+    /// Lints allowed in the snippet clippy checks, from `allow=<lint>`
+    /// attributes, for blocks showing what not to do.
+    pub fn allows(&self) -> Vec<&str> {
+        self.attrs()
+            .filter_map(|attr| value(attr, "allow"))
+            .collect()
+    }
+
+    /// Whether this block talks to a mini-redis server, from a
+    /// `needs-server` attribute. rustdoc doesn't run it; `tests/server.rs`
+    /// does, with a server to talk to.
+    pub fn needs_server(&self) -> bool {
+        self.attrs().any(|attr| attr == "needs-server")
+    }
+
+    /// Whether the code the reader sees is knowingly incomplete, from a
+    /// `fragment` attribute, like an `impl` cut off halfway. Otherwise it
+    /// has to parse on its own.
+    pub fn is_fragment(&self) -> bool {
+        self.attrs().any(|attr| attr == "fragment")
+    }
+
+    /// The info string to give rustdoc, without our own attributes. A block
+    /// that needs a server is `no_run` for rustdoc.
+    pub fn rustdoc_info(&self) -> String {
+        if !self.attrs().any(is_ours) {
+            return self.info.clone();
+        }
+
+        let mut attrs: Vec<_> = self.attrs().filter(|attr| !is_ours(attr)).collect();
+        if self.needs_server() && !attrs.contains(&"no_run") {
+            attrs.push("no_run");
+        }
+        attrs.join(",")
+    }
+
+    /// How rustdoc treats this block, if it is Rust. `ignore` wins over
+    /// every other attribute, and a `compile_fail` block is never run.
+    pub fn kind(&self) -> Kind {
+        let has = |name| self.attrs().any(|attr| attr == name);
+
+        if has("ignore") {
+            Kind::Ignore
+        } else if has("compile_fail") {
+            Kind::CompileFail
+        } else if has("no_run") || self.needs_server() {
+            Kind::NoRun
+        } else {
+            Kind::Run
+        }
+    }
+
+    /// The attributes in the info string.
+    pub fn attrs(&self) -> impl Iterator<Item = &str> {
+        self.info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|attr| !attr.is_empty())
+    }
+}
+
+/// Parse every fenced code block out of `markdown`.
+pub fn parse(markdown: &str) -> Result<Vec<Block>, Unterminated> {
+    let mut blocks = vec![];
+    let mut open: Option<Block> = None;
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                // The range starts at the fence, after any container.
+                let line = markdown[..range.start].matches('\n').count() + 1;
+                let source = &markdown[range];
+                let c = source.chars().next().unwrap();
+                let fence = &source[..source.len() - source.trim_start_matches(c).len()];
+
+                // Without a closing fence, the block runs to the end of the
+                // file, or of its container.
+                let last = match source.rsplit_once('\n') {
+                    Some((_, last)) => last.trim_start_matches(['>', ' ']),
+                    None => "",
+                };
+                if !closes(last, fence) {
+                    return Err(Unterminated { line });
+                }
+
+                open = Some(Block {
+                    line,
+                    fence: fence.to_string(),
+                    info: info.trim().to_string(),
+                    code: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut open {
+                    block.code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(open.take()),
+            _ => {}
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Split an opening fence line into the fence and the info string.
+pub fn opening_fence(line: &str) -> Option<(&str, &str)> {
+    let rest = strip_indent(line)?;
+    let c = rest.chars().next()?;
+
+    if c != '`' && c != '~' {
+        return None;
+    }
+
+    let len = rest.len() - rest.trim_start_matches(c).len();
+    if len < 3 {
+        return None;
+    }
+
+    let (fence, info) = rest.split_at(len);
+
+    // "```foo``` bar" is inline code in a paragraph, not a fence.
+    if c == '`' && info.contains('`') {
+        return None;
+    }
+
+    Some((fence, info))
+}
+
+/// Whether `line` closes a block opened with `fence`: the same character,
+/// at least as many times, and nothing else.
+pub fn closes(line: &str, fence: &str) -> bool {
+    let rest = match strip_indent(line) {
+        Some(rest) => rest.trim_end(),
+        None => return false,
+    };
+    let c = fence.chars().next().unwrap();
+
+    rest.len() >= fence.len() && rest.chars().all(|r| r == c)
+}
+
+/// `line` without up to three spaces of indentation. Any more, and the line
+/// can't be a fence.
+fn strip_indent(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ');
+
+    if line.len() - rest.len() > 3 {
+        None
+    } else {
+        Some(rest)
+    }
+}
+
+/// Attributes the generator understands, in the form `<name>=<value>`.
+const OUR_ATTRS: &[&str] = &["requires", "era", "allow"];
+
+/// Attributes the generator understands that have no value.
+const OUR_FLAGS: &[&str] = &["needs-server", "fragment"];
+
+fn is_ours(attr: &str) -> bool {
+    OUR_FLAGS.contains(&attr) || OUR_ATTRS.iter().any(|name| value(attr, name).is_some())
+}
+
+/// The value of `attr` if it is `<name>=<value>`.
+fn value<'a>(attr: &'a str, name: &str) -> Option<&'a str> {
+    attr.strip_prefix(name)?
+        .strip_prefix('=')
+        .filter(|value| !value.is_empty())
+}
+
+/// Whether `attr` is an error code like `E0373`, which `compile_fail` blocks
+/// may list.
+fn is_error_code(attr: &str) -> bool {
+    attr.len() == 5 && attr.starts_with('E') && attr[1..].chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,181 @@
+//! Finding the markdown files to test, and telling Cargo when to look again.
+
+use super::paths;
+
+use glob::{glob, Pattern};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A `DOC_TEST_FILTER` that no file matches.
+#[derive(Debug, PartialEq)]
+pub struct NoMatch {
+    pub filter: String,
+    pub total: usize,
+}
+
+/// A content directory that doesn't exist.
+#[derive(Debug, PartialEq)]
+pub struct Missing {
+    pub path: PathBuf,
+}
+
+/// A file matching the glob that can't be read, like a broken symlink.
+#[derive(Debug, PartialEq)]
+pub struct Skipped {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// The canonical path of the content directory at `path`.
+pub fn content_dir(path: &Path) -> Result<PathBuf, Missing> {
+    match path.canonicalize() {
+        Ok(dir) if dir.is_dir() => Ok(dir),
+        _ => Err(Missing {
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+/// Every markdown file under `root`, sorted by path, and the ones that had
+/// to be skipped.
+///
+/// A file reached through a symlink is only returned once. So is one found
+/// twice on a case-insensitive filesystem, but as that means two names only
+/// differ by case, the second name is reported as skipped.
+pub fn markdown_files(root: &Path) -> (Vec<PathBuf>, Vec<Skipped>) {
+    let pattern = format!("{}/**/*.md", Pattern::escape(&paths::normalize(root)));
+
+    let mut files = vec![];
+    let mut skipped = vec![];
+
+    // The name each file was first found under.
+    let mut seen: HashMap<PathBuf, PathBuf> = HashMap::new();
+
+    for entry in glob(&pattern).unwrap() {
+        let result = entry
+            .map_err(|err| (err.path().to_path_buf(), err.error().to_string()))
+            .and_then(|path| match path.canonicalize() {
+                Ok(canonical) => Ok((path, canonical)),
+                Err(err) => Err((path, err.to_string())),
+            });
+
+        let (name, path) = match result {
+            Ok(found) => found,
+            Err((path, reason)) => {
+                skipped.push(Skipped { path, reason });
+                continue;
+            }
+        };
+
+        match seen.get(&path) {
+            Some(first) => {
+                let (first, name) = (paths::normalize(first), paths::normalize(&name));
+
+                if first != name && first.eq_ignore_ascii_case(&name) {
+                    skipped.push(Skipped {
+                        reason: format!("same file as `{}`, the names only differ by case", first),
+                        path: PathBuf::from(name),
+                    });
+                }
+            }
+            None => {
+                seen.insert(path.clone(), name);
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    (files, skipped)
+}
+
+/// The `cargo:` lines making the build script run again when the content
+/// changes.
+///
+/// Watching each of the `roots` itself catches files that are added later, as
+/// Cargo checks everything in a watched directory. Once a build script prints
+/// any `rerun-if-changed` line, Cargo stops rerunning it on changes to the
+/// rest of the package, so the build script's own sources, `build.rs` and
+/// `src/gen`, are listed too.
+pub fn rerun_lines(manifest_dir: &Path, roots: &[PathBuf], files: &[PathBuf]) -> Vec<String> {
+    let mut lines = vec![manifest_dir.join("build.rs"), manifest_dir.join("src/gen")];
+    lines.extend(roots.iter().cloned());
+    lines.extend(files.iter().cloned());
+
+    lines
+        .into_iter()
+        .map(|path| format!("cargo:rerun-if-changed={}", paths::normalize(&path)))
+        .collect()
+}
+
+/// Whether `filter` selects the file at `rel`, relative to the content root.
+///
+/// A filter with `*`, `?` or `[` in it is a glob, matched against the whole
+/// path, where `*` may span directories. Anything else only has to be part of
+/// the path. Either way, paths are compared with `/` as the separator.
+pub fn matches(filter: &str, rel: &Path) -> bool {
+    let rel = rel.to_string_lossy().replace('\\', "/");
+
+    if filter.contains(['*', '?', '[']) {
+        match Pattern::new(filter) {
+            Ok(pattern) => pattern.matches(&rel),
+            Err(_) => false,
+        }
+    } else {
+        rel.contains(filter)
+    }
+}
+
+/// The files under `root` selected by `filter`. Selecting nothing at all is
+/// an error, as an empty test crate would look like a passing one.
+pub fn filter(files: Vec<PathBuf>, root: &Path, filter: &str) -> Result<Vec<PathBuf>, NoMatch> {
+    let total = files.len();
+
+    let files: Vec<_> = files
+        .into_iter()
+        .filter(|path| matches(filter, path.strip_prefix(root).unwrap_or(path)))
+        .collect();
+
+    if files.is_empty() {
+        return Err(NoMatch {
+            filter: filter.to_string(),
+            total,
+        });
+    }
+
+    Ok(files)
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "content directory `{}` not found; doc-test compiles the code blocks in the \
+             website's markdown, so it needs a full checkout of the repository, `content/` \
+             included",
+            paths::normalize(&self.path)
+        )
+    }
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "skipping `{}`: {}",
+            paths::normalize(&self.path),
+            self.reason
+        )
+    }
+}
+
+impl fmt::Display for NoMatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "DOC_TEST_FILTER={:?} matches none of the {} markdown files",
+            self.filter, self.total
+        )
+    }
+}
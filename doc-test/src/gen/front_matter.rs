@@ -0,0 +1,73 @@
+//! Checking the YAML front matter every page starts with.
+//!
+//! The website reads the page's title, and more, from it. `clean` removes it
+//! before rustdoc sees the page, as long as it is closed; this makes sure
+//! every page has one the website can use.
+
+use serde_yaml::Value;
+use std::fmt;
+
+/// What is wrong with a page's front matter.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The page doesn't start with `---`.
+    Missing,
+
+    /// There is no `---` line closing the front matter.
+    Unterminated,
+
+    /// The front matter isn't YAML, or isn't a mapping.
+    Invalid(String),
+
+    /// There is no `title`, or it isn't a string.
+    NoTitle,
+}
+
+/// Check the front matter of `markdown`, returning the page's title.
+pub fn check(markdown: &str) -> Result<String, Error> {
+    let mut lines = markdown.lines();
+
+    if lines.next().map(str::trim_end) != Some("---") {
+        return Err(Error::Missing);
+    }
+
+    let mut yaml = String::new();
+    let mut closed = false;
+
+    for line in lines {
+        if line.trim_end() == "---" {
+            closed = true;
+            break;
+        }
+        yaml.push_str(line);
+        yaml.push('\n');
+    }
+
+    if !closed {
+        return Err(Error::Unterminated);
+    }
+
+    let value: Value =
+        serde_yaml::from_str(&yaml).map_err(|err| Error::Invalid(err.to_string()))?;
+    let mapping = match value {
+        Value::Mapping(mapping) => mapping,
+        Value::Null => return Err(Error::NoTitle),
+        _ => return Err(Error::Invalid("expected a mapping".to_string())),
+    };
+
+    match mapping.get("title") {
+        Some(Value::String(title)) if !title.trim().is_empty() => Ok(title.clone()),
+        _ => Err(Error::NoTitle),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Missing => write!(fmt, "no front matter, the page should start with `---`"),
+            Error::Unterminated => write!(fmt, "front matter is never closed with `---`"),
+            Error::Invalid(err) => write!(fmt, "front matter isn't valid: {}", err),
+            Error::NoTitle => write!(fmt, "front matter has no `title`"),
+        }
+    }
+}
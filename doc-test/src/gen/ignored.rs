@@ -0,0 +1,233 @@
+//! The list of code blocks allowed to be `ignore`, in `ignored.toml`.
+//!
+//! `ignore` is an easy way to silence a block that stopped compiling, so
+//! each `ignore` block in the tutorial needs an entry saying why. A block is
+//! known by the file it is in and a hash of its code, not by its position, so
+//! adding a block before it doesn't invalidate the entry. Changing the code
+//! does, on purpose: the new code may well compile.
+//!
+//! Entries for blocks that are gone are reported too, so the list doesn't
+//! outlive what it excuses.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The file the entries are in, next to `Cargo.toml`.
+pub const FILE: &str = "ignored.toml";
+
+/// A block allowed to be `ignore`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exception {
+    /// Relative to `content/`, with forward slashes.
+    pub path: String,
+    pub hash: String,
+    pub reason: String,
+}
+
+/// An `ignore` block of a file that was processed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ignored {
+    pub path: String,
+    pub line: usize,
+    pub hash: String,
+}
+
+/// Why the list of exceptions and the blocks disagree.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    /// An `ignore` block without an entry.
+    Unlisted(Ignored),
+
+    /// An entry without an `ignore` block.
+    Stale(Exception),
+}
+
+/// `ignored.toml` can't be read.
+#[derive(Debug, PartialEq)]
+pub struct Invalid {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// The hash of a block's code, as written in the entries: FNV-1a, which
+/// unlike the hashers of `std` is the same on every build.
+pub fn hash(code: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for line in code.lines() {
+        for byte in line.trim_end().bytes().chain(Some(b'\n')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Parse the entries of `ignored.toml`.
+///
+/// Only the part of TOML the file needs is understood: comments, and
+/// `[[ignored]]` tables with `path`, `hash` and `reason` strings in them.
+pub fn parse(toml: &str) -> Result<Vec<Exception>, Invalid> {
+    let mut exceptions = vec![];
+
+    // The keys of the table being read, and the line it starts at.
+    let mut table: Option<(usize, Vec<(String, String)>)> = None;
+
+    for (i, line) in toml.lines().enumerate() {
+        let invalid = |reason: &str| Invalid {
+            line: i + 1,
+            reason: reason.to_string(),
+        };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line == "[[ignored]]" {
+            if let Some((start, keys)) = table.take() {
+                exceptions.push(exception(start, keys)?);
+            }
+            table = Some((i + 1, vec![]));
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(invalid("expected `[[ignored]]` or `key = \"value\"`")),
+        };
+        let keys = match &mut table {
+            Some((_, keys)) => keys,
+            None => return Err(invalid("expected `[[ignored]]` before the first key")),
+        };
+        if !["path", "hash", "reason"].contains(&key) {
+            return Err(invalid(&format!(
+                "unknown key `{}`; expected `path`, `hash` or `reason`",
+                key
+            )));
+        }
+        if keys.iter().any(|(k, _)| k == key) {
+            return Err(invalid(&format!("`{}` is set twice", key)));
+        }
+
+        let value = string(value).map_err(|reason| invalid(&reason))?;
+        keys.push((key.to_string(), value));
+    }
+
+    if let Some((start, keys)) = table {
+        exceptions.push(exception(start, keys)?);
+    }
+
+    Ok(exceptions)
+}
+
+/// The exception made of the `keys` of the table at line `start`.
+fn exception(start: usize, keys: Vec<(String, String)>) -> Result<Exception, Invalid> {
+    let get = |name: &str| {
+        keys.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| Invalid {
+                line: start,
+                reason: format!("the entry has no `{}`", name),
+            })
+    };
+
+    Ok(Exception {
+        path: get("path")?,
+        hash: get("hash")?,
+        reason: get("reason")?,
+    })
+}
+
+/// The contents of a string, followed by nothing but a comment.
+fn string(value: &str) -> Result<String, String> {
+    let quote = match value.chars().next() {
+        Some(quote @ '"') | Some(quote @ '\'') => quote,
+        _ => return Err("expected a string".to_string()),
+    };
+
+    let mut dst = String::new();
+    let mut chars = value[1..].chars();
+
+    loop {
+        match chars.next() {
+            Some(c) if c == quote => break,
+            // Literal strings, in single quotes, have no escapes.
+            Some('\\') if quote == '"' => match chars.next() {
+                Some('"') => dst.push('"'),
+                Some('\\') => dst.push('\\'),
+                Some('n') => dst.push('\n'),
+                Some('t') => dst.push('\t'),
+                _ => return Err("unsupported escape in string".to_string()),
+            },
+            Some(c) => dst.push(c),
+            None => return Err("the string is never closed".to_string()),
+        }
+    }
+
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected `{}` after the string", rest));
+    }
+
+    Ok(dst)
+}
+
+/// Compare the `exceptions` with the `ignore` blocks of the files that were
+/// processed, `processed`. Entries for other files are left alone, as those
+/// may be left out by a feature or `DOC_TEST_FILTER`.
+pub fn check(exceptions: &[Exception], processed: &[String], ignored: &[Ignored]) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    let listed: BTreeSet<_> = exceptions
+        .iter()
+        .map(|exception| (&exception.path[..], &exception.hash[..]))
+        .collect();
+    for block in ignored {
+        if !listed.contains(&(&block.path[..], &block.hash[..])) {
+            problems.push(Problem::Unlisted(block.clone()));
+        }
+    }
+
+    let found: BTreeSet<_> = ignored
+        .iter()
+        .map(|block| (&block.path[..], &block.hash[..]))
+        .collect();
+    for exception in exceptions {
+        if processed.contains(&exception.path)
+            && !found.contains(&(&exception.path[..], &exception.hash[..]))
+        {
+            problems.push(Problem::Stale(exception.clone()));
+        }
+    }
+
+    problems
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Unlisted(block) => write!(
+                fmt,
+                "{}:{}: `ignore` block without an entry; make it compile, or add this to \
+                 {} with the reason it can't:\n\n\
+                 [[ignored]]\npath = {:?}\nhash = {:?}\nreason = \"...\"\n",
+                block.path, block.line, FILE, block.path, block.hash
+            ),
+            Problem::Stale(exception) => write!(
+                fmt,
+                "{}: stale exception: no `ignore` block has the hash {}; remove the entry \
+                 from {}, or update the hash if the block changed",
+                exception.path, exception.hash, FILE
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}:{}: {}", FILE, self.line, self.reason)
+    }
+}
@@ -0,0 +1,34 @@
+//! Checking that every code block says what language it is in.
+//!
+//! rustdoc tests a block without an info string as Rust, but readers and
+//! the website's highlighter can't tell, and neither can whoever edits the
+//! block next. Blocks that are really meant to be unlabelled, like ASCII
+//! diagrams, are allowed with a directive on the line before them:
+//!
+//! ```text
+//! <!-- doc-test: allow-unlabelled -->
+//! ```
+
+use super::fence::Block;
+
+const ALLOW: &str = "<!-- doc-test: allow-unlabelled -->";
+
+/// The lines of the blocks in `markdown` without an info string, other than
+/// the allowed ones. `blocks` are the blocks parsed out of `markdown`.
+pub fn unlabelled(markdown: &str, blocks: &[Block]) -> Vec<usize> {
+    let lines: Vec<_> = markdown.lines().collect();
+
+    blocks
+        .iter()
+        .filter(|block| block.info.is_empty())
+        .filter(|block| {
+            // The nearest line above the block with something on it.
+            let above = lines[..block.line - 1]
+                .iter()
+                .rev()
+                .find(|line| !line.trim().is_empty());
+            above.map(|line| line.trim()) != Some(ALLOW)
+        })
+        .map(|block| block.line)
+        .collect()
+}
@@ -0,0 +1,372 @@
+//! The tree of modules generated for the markdown files.
+
+use super::fence::{self, Kind};
+use super::paths;
+use super::prelude;
+use super::server;
+use super::standalone;
+use super::units;
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A directory of markdown files, turned into a module.
+///
+/// Everything is emitted in a fixed order, directories by name and files by
+/// path, so the generated code only changes when the content does.
+#[derive(Debug)]
+pub struct Level {
+    /// The directory this level was made from, for error messages. Unknown
+    /// for the top level.
+    dir: Option<PathBuf>,
+    nested: BTreeMap<String, Level>,
+    files: Vec<File>,
+}
+
+#[derive(Debug)]
+struct File {
+    path: PathBuf,
+    /// What `include_str!` reads: the cleaned up copy of the file.
+    include: PathBuf,
+    source: String,
+}
+
+/// How each markdown file is turned into doctests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode<'a> {
+    /// One item per Rust code block, or listing split over several, named
+    /// after the file and the block's position in it. A failing doctest
+    /// then says which block failed. Blocks from an older era are left out.
+    Blocks,
+
+    /// Like `Blocks`, but only for the blocks marked with `era=<era>`, for
+    /// the crate testing that era.
+    Era(&'a str),
+
+    /// One module per block tested by `Blocks`, with the block compiled as
+    /// normal code, for clippy. `compile_fail` and `ignore` blocks are left
+    /// out, and `allow=<lint>` attributes are applied.
+    Snippets,
+
+    /// Like `Snippets`, but only for the blocks marked `needs-server`, each
+    /// with a test running its `main` against a mini-redis server.
+    Server,
+
+    /// One item per file, documented with the whole file. Preludes,
+    /// `requires=` and `era=` attributes aren't applied.
+    Include,
+}
+
+/// Why the code for a `Level` couldn't be generated.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Two names in the same module sanitize to the same identifier.
+    Duplicate {
+        ident: String,
+        first: String,
+        second: String,
+    },
+
+    /// A code block is never closed.
+    Unterminated { path: String, line: usize },
+
+    /// A directive is invalid, or in the wrong place.
+    Directive {
+        path: String,
+        line: usize,
+        reason: String,
+    },
+}
+
+/// Rust keywords, reserved words included. None of them can be used as a
+/// module or function name.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Turn a file or directory name into a valid identifier.
+///
+/// Anything other than an ASCII letter, digit or underscore becomes an
+/// underscore. A leading digit gets an underscore in front of it, and a
+/// keyword one after it.
+pub fn sanitize_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if KEYWORDS.contains(&&ident[..]) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+impl Level {
+    pub fn new() -> Level {
+        Level {
+            dir: None,
+            nested: BTreeMap::new(),
+            files: vec![],
+        }
+    }
+
+    /// Add the file at `path`, located at `rel` relative to this level, with
+    /// `source` as its contents. In `Mode::Include`, the file at `include` is
+    /// used instead of `path`; it should have `source` in it.
+    pub fn insert(&mut self, path: PathBuf, include: PathBuf, rel: &[&str], source: String) {
+        if rel.len() == 1 {
+            self.files.push(File {
+                path,
+                include,
+                source,
+            });
+        } else {
+            let nested = self.nested.entry(rel[0].to_string()).or_default();
+
+            // `path` is the rest of `rel` inside of that directory.
+            if nested.dir.is_none() {
+                nested.dir = path.ancestors().nth(rel.len() - 1).map(Path::to_path_buf);
+            }
+
+            nested.insert(path, include, &rel[1..], source);
+        }
+    }
+
+    /// Generate the code for this level's contents.
+    pub fn render(&self, mode: Mode<'_>) -> Result<String, Error> {
+        let mut dst = String::new();
+        self.write_inner(&mut dst, 0, mode)?;
+        Ok(dst)
+    }
+
+    fn write_into(
+        &self,
+        dst: &mut String,
+        ident: &str,
+        level: usize,
+        mode: Mode<'_>,
+    ) -> Result<(), Error> {
+        write_space(dst, level);
+        writeln!(dst, "pub mod {} {{", ident).unwrap();
+
+        self.write_inner(dst, level + 1, mode)?;
+
+        write_space(dst, level);
+        writeln!(dst, "}}").unwrap();
+
+        Ok(())
+    }
+
+    fn write_inner(&self, dst: &mut String, level: usize, mode: Mode<'_>) -> Result<(), Error> {
+        let mut modules = Idents::default();
+
+        for (name, nested) in &self.nested {
+            let origin = match &nested.dir {
+                Some(dir) => paths::normalize(dir),
+                None => name.clone(),
+            };
+            let ident = modules.claim(name, &origin)?;
+            nested.write_into(dst, &ident, level, mode)?;
+        }
+
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut fns = Idents::default();
+
+        for file in files {
+            let path = paths::normalize(&file.path);
+            let stem = Path::new(&file.path).file_stem().unwrap().to_str().unwrap();
+
+            match mode {
+                Mode::Include => {
+                    let ident = fns.claim(&format!("{}_md", stem), &path)?;
+
+                    write_space(dst, level);
+                    let include = paths::normalize(&file.include);
+                    writeln!(dst, "#[doc = include_str!({:?})]", include).unwrap();
+                    write_space(dst, level);
+                    writeln!(dst, "pub fn {}() {{}}", ident).unwrap();
+                }
+                Mode::Blocks | Mode::Era(_) | Mode::Snippets | Mode::Server => {
+                    let era = match mode {
+                        Mode::Era(era) => Some(era),
+                        _ => None,
+                    };
+
+                    let blocks = fence::parse(&file.source).map_err(|err| Error::Unterminated {
+                        path: path.clone(),
+                        line: err.line,
+                    })?;
+                    let directive = |err: prelude::Invalid| Error::Directive {
+                        path: path.clone(),
+                        line: err.line,
+                        reason: err.reason,
+                    };
+                    let templates = prelude::assign(&file.source, &blocks).map_err(directive)?;
+                    let units = units::group(&file.source, &blocks).map_err(directive)?;
+
+                    // Blocks are numbered among all blocks, Rust or not, so
+                    // block 3 is the third one in the file. A unit of several
+                    // blocks is named after the first.
+                    for unit in &units {
+                        let i = unit[0];
+                        let (block, template) = (&blocks[i], templates[i]);
+
+                        if !block.is_rust() || block.era() != era {
+                            continue;
+                        }
+                        let compiled = !matches!(block.kind(), Kind::CompileFail | Kind::Ignore);
+                        if mode == Mode::Snippets && !compiled {
+                            continue;
+                        }
+                        if mode == Mode::Server && !(compiled && block.needs_server()) {
+                            continue;
+                        }
+
+                        let name = format!("{}_block_{}_md", stem, i + 1);
+                        let origin = format!("{}:{}", path, block.line);
+                        let ident = fns.claim(&name, &origin)?;
+
+                        let code = units::code(&blocks, unit);
+                        let code = match template {
+                            Some(template) => template.wrap(&code),
+                            None => code,
+                        };
+                        let unit: Vec<_> = unit.iter().map(|&i| &blocks[i]).collect();
+                        write_space(dst, level);
+                        writeln!(dst, "/// From {}", origin).unwrap();
+
+                        let normal_code = matches!(mode, Mode::Snippets | Mode::Server);
+                        if !normal_code {
+                            let doc = format!(
+                                "{}{}\n{}{}",
+                                block.fence,
+                                block.rustdoc_info(),
+                                code,
+                                block.fence
+                            );
+
+                            write_space(dst, level);
+                            writeln!(dst, "///").unwrap();
+                            write_space(dst, level);
+                            writeln!(dst, "#[doc = {:?}]", doc).unwrap();
+                        }
+
+                        // Without the features it needs, the block isn't
+                        // tested at all.
+                        let mut requires: Vec<_> =
+                            unit.iter().flat_map(|block| block.requires()).collect();
+                        requires.sort_unstable();
+                        requires.dedup();
+                        for feature in requires {
+                            write_space(dst, level);
+                            writeln!(dst, "#[cfg(feature = {:?})]", feature).unwrap();
+                        }
+
+                        if normal_code {
+                            for lint in unit.iter().flat_map(|block| block.allows()) {
+                                write_space(dst, level);
+                                writeln!(dst, "#[allow({})]", lint).unwrap();
+                            }
+                        }
+
+                        write_space(dst, level);
+                        if normal_code {
+                            let mut items = standalone::items(&code);
+                            if mode == Mode::Server {
+                                items = server::substitute(&items).map_err(|server::NoAddr| {
+                                    Error::Directive {
+                                        path: path.clone(),
+                                        line: block.line,
+                                        reason: format!(
+                                            "the block needs a server, but never uses {:?}",
+                                            server::ADDR
+                                        ),
+                                    }
+                                })?;
+                                items.push_str(&server::fixture());
+                            }
+
+                            // The code isn't indented, so multi-line string
+                            // literals keep their contents.
+                            writeln!(dst, "pub mod {} {{", ident).unwrap();
+                            dst.push_str(&items);
+                            write_space(dst, level);
+                            writeln!(dst, "}}").unwrap();
+                        } else {
+                            writeln!(dst, "pub fn {}() {{}}", ident).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Level {
+    fn default() -> Level {
+        Level::new()
+    }
+}
+
+/// The identifiers used so far in a module, and what they were made from.
+#[derive(Default)]
+struct Idents(BTreeMap<String, String>);
+
+impl Idents {
+    /// Sanitize `name`, making sure no other item has the same identifier.
+    /// `origin` describes the item in errors.
+    fn claim(&mut self, name: &str, origin: &str) -> Result<String, Error> {
+        let ident = sanitize_ident(name);
+
+        if let Some(first) = self.0.get(&ident) {
+            return Err(Error::Duplicate {
+                ident,
+                first: first.clone(),
+                second: origin.to_string(),
+            });
+        }
+
+        self.0.insert(ident.clone(), origin.to_string());
+        Ok(ident)
+    }
+}
+
+fn write_space(dst: &mut String, level: usize) {
+    for _ in 0..level {
+        dst.push_str("    ");
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Duplicate {
+                ident,
+                first,
+                second,
+            } => write!(
+                fmt,
+                "`{}` and `{}` would both generate an item named `{}`; rename one of them",
+                first, second, ident
+            ),
+            Error::Unterminated { path, line } => {
+                write!(fmt, "{}:{}: code block is never closed", path, line)
+            }
+            Error::Directive { path, line, reason } => write!(fmt, "{}:{}: {}", path, line, reason),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! The generator behind `build.rs`, turning the markdown in `content/` into
+//! doctests.
+//!
+//! The build script compiles this module in itself, and the library exports
+//! it so the tests and binaries can use it too. Keep it free of anything
+//! from the rest of the library.
+
+pub mod clean;
+pub mod era;
+pub mod extract;
+pub mod fence;
+pub mod files;
+pub mod front_matter;
+pub mod ignored;
+pub mod labels;
+pub mod level;
+pub mod paths;
+pub mod prelude;
+pub mod script;
+pub mod server;
+pub mod standalone;
+pub mod summary;
+pub mod units;
@@ -0,0 +1,54 @@
+//! Writing paths into generated code.
+//!
+//! On Windows, `canonicalize` returns verbatim paths like
+//! `\\?\C:\tokio\content`, and paths are separated with backslashes. Neither
+//! reads well in a message, and a backslash in a string literal starts an
+//! escape sequence, so every path is normalized before it is written out.
+
+use std::path::Path;
+
+/// `path` with forward slashes, and without the verbatim prefix added by
+/// `canonicalize` on Windows. Both Windows and Cargo accept the result.
+pub fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy();
+
+    let path = if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else if let Some(local) = path.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        path.into_owned()
+    };
+
+    path.replace('\\', "/")
+}
+
+/// `code` with the paths in `root` written as relative to `placeholder`
+/// instead, so it reads the same wherever `root` is. Only whole paths are
+/// replaced: with `/content` as the root, neither `/content2/a.md` nor
+/// `/other/content/a.md` changes.
+pub fn replace_root(code: &str, root: &Path, placeholder: &str) -> String {
+    let root = normalize(root);
+    let root = format!("{}/", root.trim_end_matches('/'));
+
+    let mut dst = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for (start, _) in code.match_indices(&root) {
+        let in_path = code[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || "/\\._-~".contains(c));
+        if in_path {
+            continue;
+        }
+
+        dst.push_str(&code[last..start]);
+        dst.push_str(placeholder);
+        dst.push('/');
+        last = start + root.len();
+    }
+
+    dst.push_str(&code[last..]);
+    dst
+}
@@ -0,0 +1,231 @@
+//! Hidden code wrapped around a snippet so it compiles on its own.
+//!
+//! Many snippets assume the imports or the `#[tokio::main]` function of the
+//! example around them. Rather than repeating those in the snippet, a
+//! directive in an HTML comment names a template to wrap it with:
+//!
+//! ```text
+//! <!-- doc-test: prelude=tokio_main -->
+//! ```
+//!
+//! right before a code block applies to that block, and
+//!
+//! ```text
+//! <!-- doc-test: file-prelude=tokio_main -->
+//! ```
+//!
+//! anywhere in the file applies to every Rust block without a prelude of its
+//! own. The template's lines are hidden, so the rendered snippet is
+//! unchanged.
+
+use super::fence::Block;
+
+/// Code wrapped around a snippet.
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    pub name: &'static str,
+
+    /// Lines before the snippet.
+    pub head: &'static [&'static str],
+
+    /// Lines after the snippet.
+    pub tail: &'static [&'static str],
+}
+
+/// The templates directives can name.
+pub const TEMPLATES: &[Template] = &[
+    // Statements that run inside of the runtime.
+    Template {
+        name: "tokio_main",
+        head: &["#[tokio::main]", "async fn main() {"],
+        tail: &["}"],
+    },
+    // Statements using a mini-redis `client`. They are only compiled, as
+    // running them needs a server.
+    Template {
+        name: "mini_redis_client",
+        head: &[
+            "use mini_redis::client;",
+            "",
+            "#[allow(dead_code)]",
+            "async fn snippet() -> mini_redis::Result<()> {",
+            "let mut client = client::connect(\"127.0.0.1:6379\").await?;",
+        ],
+        tail: &["Ok(())", "}", "", "fn main() {}"],
+    },
+    // Statements that are only compiled, like a loop serving connections
+    // forever.
+    Template {
+        name: "no_main",
+        head: &["#[allow(dead_code)]", "async fn snippet() {"],
+        tail: &["}", "", "fn main() {}"],
+    },
+];
+
+/// A directive that can't be applied.
+#[derive(Debug, PartialEq)]
+pub struct Invalid {
+    /// Line of the directive, starting at 1.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Template {
+    /// `code` wrapped with this template, with the template's lines hidden.
+    pub fn wrap(&self, code: &str) -> String {
+        let mut dst = String::new();
+
+        for line in self.head {
+            push_hidden(&mut dst, line);
+        }
+        dst.push_str(code);
+        for line in self.tail {
+            push_hidden(&mut dst, line);
+        }
+
+        dst
+    }
+}
+
+/// The template applying to each of `blocks`, parsed out of `markdown`.
+pub fn assign(markdown: &str, blocks: &[Block]) -> Result<Vec<Option<&'static Template>>, Invalid> {
+    let mut file = None;
+    let mut assigned = vec![None; blocks.len()];
+
+    // A block directive waiting for its block, and the line it is on.
+    let mut pending: Option<(usize, &'static Template)> = None;
+    let mut remaining = blocks.iter().enumerate().peekable();
+    let mut skip_until = 0;
+
+    for (i, line) in markdown.lines().enumerate() {
+        let number = i + 1;
+
+        if number <= skip_until {
+            continue;
+        }
+
+        if let Some((index, block)) = remaining.next_if(|(_, block)| block.line == number) {
+            if let Some((_, template)) = pending.take() {
+                if !block.is_rust() {
+                    return Err(Invalid {
+                        line: number,
+                        reason: "a prelude can only be used on a Rust code block".to_string(),
+                    });
+                }
+                assigned[index] = Some(template);
+            }
+
+            // The code and the closing fence.
+            skip_until = block.line + block.code.lines().count() + 1;
+            continue;
+        }
+
+        match directive(line, number)? {
+            Some(Directive::Prelude {
+                file: true,
+                template,
+            }) => file = Some(template),
+            Some(Directive::Prelude {
+                file: false,
+                template,
+            }) => {
+                if let Some((line, _)) = pending {
+                    return Err(dangling(line));
+                }
+                pending = Some((number, template));
+            }
+            // Other directives may sit between a prelude and its block.
+            Some(Directive::Other) => {}
+            None if line.trim().is_empty() => {}
+            None => {
+                if let Some((line, _)) = pending {
+                    return Err(dangling(line));
+                }
+            }
+        }
+    }
+
+    if let Some((line, _)) = pending {
+        return Err(dangling(line));
+    }
+
+    for (template, block) in assigned.iter_mut().zip(blocks) {
+        if template.is_none() && block.is_rust() {
+            *template = file;
+        }
+    }
+
+    Ok(assigned)
+}
+
+/// Directives handled somewhere else than in this module.
+const OTHER_DIRECTIVES: &[&str] = &["allow-unlabelled", "continues-previous"];
+
+enum Directive {
+    Prelude {
+        /// Whether it applies to the whole file.
+        file: bool,
+        template: &'static Template,
+    },
+    Other,
+}
+
+/// Parse `line` as a directive.
+fn directive(line: &str, number: usize) -> Result<Option<Directive>, Invalid> {
+    let body = match line
+        .trim()
+        .strip_prefix("<!--")
+        .and_then(|rest| rest.strip_suffix("-->"))
+        .and_then(|rest| rest.trim().strip_prefix("doc-test:"))
+    {
+        Some(body) => body.trim(),
+        None => return Ok(None),
+    };
+
+    let invalid = |reason: String| Invalid {
+        line: number,
+        reason,
+    };
+
+    if OTHER_DIRECTIVES.contains(&body) {
+        return Ok(Some(Directive::Other));
+    }
+
+    let (file, name) = if let Some(name) = body.strip_prefix("file-prelude=") {
+        (true, name)
+    } else if let Some(name) = body.strip_prefix("prelude=") {
+        (false, name)
+    } else {
+        return Err(invalid(format!("unknown directive `{}`", body)));
+    };
+
+    match TEMPLATES.iter().find(|template| template.name == name) {
+        Some(template) => Ok(Some(Directive::Prelude { file, template })),
+        None => {
+            let names: Vec<_> = TEMPLATES.iter().map(|template| template.name).collect();
+            Err(invalid(format!(
+                "unknown prelude `{}`, expected one of {}",
+                name,
+                names.join(", ")
+            )))
+        }
+    }
+}
+
+fn dangling(line: usize) -> Invalid {
+    Invalid {
+        line,
+        reason: "a prelude directive must come right before a code block".to_string(),
+    }
+}
+
+/// Push `line` so rustdoc compiles it, but doesn't show it.
+fn push_hidden(dst: &mut String, line: &str) {
+    if line.is_empty() {
+        dst.push_str("#\n");
+    } else {
+        dst.push_str("# ");
+        dst.push_str(line);
+        dst.push('\n');
+    }
+}
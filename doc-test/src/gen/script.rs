@@ -0,0 +1,421 @@
+//! What the build script does, from finding the markdown files to writing
+//! the generated code and reports to `OUT_DIR`.
+//!
+//! `run` reads the environment Cargo sets for build scripts, and the
+//! variables documented below, so it is only meant to be called from
+//! `build.rs`.
+
+use super::fence::Kind;
+use super::ignored::{self, Ignored};
+use super::level::{Level, Mode};
+use super::summary::Summary;
+use super::{era, extract, files, paths};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A directory of `content/` to test. It generates a top-level module named
+/// after it.
+struct Root {
+    name: &'static str,
+
+    /// The feature enabling this root, if it is opt-in.
+    feature: Option<&'static str>,
+
+    /// Whether the content is checked for unlabelled blocks, and for
+    /// `ignore` blocks, too many of them or without an entry in
+    /// `ignored.toml`. Blog posts are left as they were published, and
+    /// ignoring their outdated APIs is expected.
+    strict: bool,
+}
+
+const ROOTS: &[Root] = &[
+    Root {
+        name: "tokio",
+        feature: None,
+        strict: true,
+    },
+    Root {
+        name: "blog",
+        feature: Some("blog"),
+        strict: false,
+    },
+];
+
+/// Generate the doctests, and everything else `OUT_DIR` has, printing
+/// Cargo's instructions along the way. Panics when the content has problems.
+pub fn run() {
+    let home = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let home = Path::new(&home);
+    // Set `DOC_TEST_CONTENT` to test another content directory, laid out
+    // like `content/`. The runner's tests use it for their fixtures.
+    println!("cargo:rerun-if-env-changed=DOC_TEST_CONTENT");
+    let base = match env::var_os("DOC_TEST_CONTENT") {
+        Some(dir) => PathBuf::from(dir),
+        None => home.join("../content"),
+    };
+    let base = match files::content_dir(&base) {
+        Ok(base) => base,
+        Err(err) => panic!("{}", err),
+    };
+
+    let enabled: Vec<_> = ROOTS
+        .iter()
+        .filter(|root| root.feature.is_none_or(feature_enabled))
+        .collect();
+    let roots: Vec<_> = enabled
+        .iter()
+        .map(|root| match files::content_dir(&base.join(root.name)) {
+            Ok(root) => root,
+            Err(err) => panic!("{}", err),
+        })
+        .collect();
+
+    let mut files = vec![];
+    for root in &roots {
+        let (found, skipped) = files::markdown_files(root);
+        files.extend(found);
+
+        for skipped in skipped {
+            println!("cargo:warning={}", skipped);
+        }
+    }
+
+    for line in files::rerun_lines(home, &roots, &files) {
+        println!("{}", line);
+    }
+
+    // Set `DOC_TEST_FILTER` to a substring or glob of paths relative to
+    // `content` to only test some of the files.
+    println!("cargo:rerun-if-env-changed=DOC_TEST_FILTER");
+    let files = match env::var("DOC_TEST_FILTER") {
+        Ok(filter) => {
+            let total = files.len();
+            let files = match files::filter(files, &base, &filter) {
+                Ok(files) => files,
+                Err(err) => panic!("{}", err),
+            };
+
+            println!(
+                "cargo:warning=DOC_TEST_FILTER is set, skipping {} of {} markdown files",
+                total - files.len(),
+                total
+            );
+            files
+        }
+        Err(_) => files,
+    };
+
+    // Set `DOC_TEST_RAW` to test each file as a whole, like before it was
+    // split into blocks.
+    println!("cargo:rerun-if-env-changed=DOC_TEST_RAW");
+    let mode = match env::var_os("DOC_TEST_RAW") {
+        Some(_) => Mode::Include,
+        None => Mode::Blocks,
+    };
+
+    // The largest fraction of a file's Rust blocks that may be `ignore`.
+    println!("cargo:rerun-if-env-changed=DOC_TEST_MAX_IGNORED");
+    let max_ignored = match env::var("DOC_TEST_MAX_IGNORED") {
+        Ok(max) => match max.parse::<f64>() {
+            Ok(max) if (0.0..=1.0).contains(&max) => max,
+            _ => panic!(
+                "DOC_TEST_MAX_IGNORED must be a fraction from 0 to 1, got {:?}",
+                max
+            ),
+        },
+        Err(_) => 0.5,
+    };
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let mut level = Level::new();
+    let mut summaries = vec![];
+
+    // Blocks that aren't tested, because a feature they need isn't enabled.
+    let mut disabled = vec![];
+
+    // Blocks without an info string.
+    let mut unlabelled = vec![];
+
+    // How many blocks each older era has, and blocks naming an unknown one.
+    let mut eras: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut unknown_eras = vec![];
+
+    // Pages whose front matter the website can't use.
+    let mut bad_front_matter = vec![];
+
+    // The strict files, and their `ignore` blocks.
+    let mut processed = vec![];
+    let mut ignored_blocks = vec![];
+
+    let strict: Vec<_> = enabled
+        .iter()
+        .filter(|root| root.strict)
+        .map(|root| format!("{}/", root.name))
+        .collect();
+    let is_strict = |path: &str| strict.iter().any(|root| path.starts_with(root));
+
+    // Reading and parsing the files is what takes time, so it is done on
+    // every core, and only for the files that changed since the last build.
+    let cache = extract::Cache::new(&out_dir.join("cache"));
+    let extracted = cache.get_all(&files);
+
+    for (path, extracted) in files.into_iter().zip(extracted) {
+        let rel = path.strip_prefix(&base).unwrap();
+
+        let mut parts = vec![];
+
+        for part in rel {
+            parts.push(part.to_str().unwrap());
+        }
+
+        if let Some(err) = &extracted.front_matter {
+            bad_front_matter.push(format!("{}: {}", paths::normalize(rel), err));
+        }
+
+        // rustdoc gets a copy of the file without the MDX specific parts.
+        let source = extracted.source;
+        let include = out_dir.join(rel);
+        fs::create_dir_all(include.parent().unwrap()).unwrap();
+        fs::write(&include, &source).unwrap();
+
+        // A block that is never closed is reported by `render` below.
+        if let Some(blocks) = extracted.blocks {
+            if is_strict(&paths::normalize(rel)) {
+                for line in extracted.unlabelled {
+                    unlabelled.push(format!("{}:{}", paths::normalize(rel), line));
+                }
+
+                processed.push(paths::normalize(rel));
+                for block in &blocks {
+                    if block.is_rust() && block.kind() == Kind::Ignore {
+                        ignored_blocks.push(Ignored {
+                            path: paths::normalize(rel),
+                            line: block.line,
+                            hash: ignored::hash(&block.code),
+                        });
+                    }
+                }
+            }
+
+            for block in blocks.iter().filter(|block| block.is_rust()) {
+                if let Some(name) = block.era() {
+                    match era::find(name) {
+                        Some(era) => *eras.entry(era.name).or_default() += 1,
+                        None => unknown_eras.push(format!(
+                            "{}:{}: unknown era `{}`",
+                            paths::normalize(rel),
+                            block.line,
+                            name
+                        )),
+                    }
+                }
+
+                let missing: Vec<_> = block
+                    .requires()
+                    .into_iter()
+                    .filter(|feature| !feature_enabled(feature))
+                    .collect();
+
+                if !missing.is_empty() {
+                    disabled.push(format!(
+                        "{}:{} needs {}",
+                        paths::normalize(rel),
+                        block.line,
+                        missing.join(", ")
+                    ));
+                }
+            }
+
+            summaries.push(Summary::new(&paths::normalize(rel), &blocks));
+        }
+
+        level.insert(path.clone(), include, &parts[..], source);
+    }
+
+    if !bad_front_matter.is_empty() {
+        panic!(
+            "pages need front matter with a `title`:\n    {}",
+            bad_front_matter.join("\n    ")
+        );
+    }
+
+    if !unknown_eras.is_empty() {
+        panic!(
+            "{}\nexpected one of {}",
+            unknown_eras.join("\n"),
+            era::names()
+        );
+    }
+
+    if !unlabelled.is_empty() {
+        panic!(
+            "code blocks need a language, like ```rust or ```text, or an \
+             `<!-- doc-test: allow-unlabelled -->` line before them:\n    {}",
+            unlabelled.join("\n    ")
+        );
+    }
+
+    if !disabled.is_empty() {
+        println!(
+            "cargo:warning={} code blocks aren't tested without more features:",
+            disabled.len()
+        );
+        for block in &disabled {
+            println!("cargo:warning=  {}", block);
+        }
+    }
+
+    let out = out_dir.join("doctests.rs");
+
+    let code = match level.render(mode) {
+        Ok(code) => code,
+        Err(err) => panic!("failed to generate doctests: {}", err),
+    };
+
+    fs::write(&out, code).unwrap();
+
+    // The same blocks as normal code, so clippy lints them too.
+    let code = match level.render(Mode::Snippets) {
+        Ok(code) => code,
+        Err(err) => panic!("failed to generate snippets: {}", err),
+    };
+    fs::write(out_dir.join("snippets.rs"), code).unwrap();
+
+    // The blocks that need a server, as tests running them against one.
+    let code = match level.render(Mode::Server) {
+        Ok(code) => code,
+        Err(err) => panic!("failed to generate server tests: {}", err),
+    };
+    fs::write(out_dir.join("server.rs"), code).unwrap();
+
+    // Set `DOC_TEST_ERAS` to also test the blocks of older eras, each in a
+    // crate of its own.
+    println!("cargo:rerun-if-env-changed=DOC_TEST_ERAS");
+    let test_eras = env::var_os("DOC_TEST_ERAS").is_some();
+
+    for (&name, &count) in &eras {
+        let era = era::find(name).unwrap();
+        let dir = out_dir.join("eras").join(name);
+
+        let code = match level.render(Mode::Era(name)) {
+            Ok(code) => code,
+            Err(err) => panic!("failed to generate doctests: {}", err),
+        };
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), era.manifest()).unwrap();
+        fs::write(dir.join("src/lib.rs"), code).unwrap();
+
+        if !test_eras {
+            println!(
+                "cargo:warning={} code blocks from {} aren't tested; set DOC_TEST_ERAS to test them",
+                count, name
+            );
+            continue;
+        }
+
+        // Doctests are only compiled when they are run, so `cargo build`
+        // wouldn't check anything.
+        let output = Command::new(env::var_os("CARGO").unwrap())
+            .arg("test")
+            .arg("--doc")
+            .arg("--manifest-path")
+            .arg(dir.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(out_dir.join("eras/target"))
+            .output()
+            .unwrap();
+
+        if !output.status.success() {
+            panic!(
+                "the code blocks from {} failed:\n{}{}",
+                name,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+
+    // One line per file, for anyone wondering what is actually tested, then
+    // the totals of each root.
+    let mut report = String::new();
+    for summary in &summaries {
+        report.push_str(&summary.to_string());
+        report.push('\n');
+    }
+    for root in &enabled {
+        let prefix = format!("{}/", root.name);
+        let in_root: Vec<_> = summaries
+            .iter()
+            .filter(|summary| summary.path.starts_with(&prefix))
+            .collect();
+        let line = format!(
+            "{}: {} files, {} Rust blocks",
+            root.name,
+            in_root.len(),
+            in_root.iter().map(|summary| summary.total()).sum::<usize>()
+        );
+
+        // Opt-in roots are new enough to be worth mentioning on every build.
+        if enabled.len() > 1 {
+            println!("cargo:warning={}", line);
+        }
+        report.push_str(&line);
+        report.push('\n');
+    }
+    fs::write(out_dir.join("summary.txt"), report).unwrap();
+
+    let report = serde_json::json!({
+        "files": summaries.iter().map(Summary::to_json).collect::<Vec<_>>(),
+    });
+    fs::write(
+        out_dir.join("report.json"),
+        serde_json::to_string_pretty(&report).unwrap(),
+    )
+    .unwrap();
+
+    for summary in &summaries {
+        if !is_strict(&summary.path) {
+            continue;
+        }
+
+        if let Err(err) = summary.check(max_ignored) {
+            panic!(
+                "{}; annotate fewer blocks with `ignore`, or raise DOC_TEST_MAX_IGNORED",
+                err
+            );
+        }
+    }
+
+    // Every `ignore` block needs a reason.
+    let list = home.join(ignored::FILE);
+    println!("cargo:rerun-if-changed={}", paths::normalize(&list));
+    let exceptions = match fs::read_to_string(&list) {
+        Ok(toml) => match ignored::parse(&toml) {
+            Ok(exceptions) => exceptions,
+            Err(err) => panic!("{}", err),
+        },
+        Err(_) => vec![],
+    };
+
+    let problems = ignored::check(&exceptions, &processed, &ignored_blocks);
+    if !problems.is_empty() {
+        let problems: Vec<_> = problems.iter().map(|problem| problem.to_string()).collect();
+        panic!(
+            "the `ignore` blocks and {} disagree:\n\n{}",
+            ignored::FILE,
+            problems.join("\n")
+        );
+    }
+}
+
+/// Whether the doc-test crate is built with `feature`.
+fn feature_enabled(feature: &str) -> bool {
+    let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+    env::var_os(var).is_some()
+}
@@ -0,0 +1,61 @@
+//! Blocks marked `needs-server`, which talk to a mini-redis server.
+//!
+//! rustdoc only compiles them, as nothing listens on the address the
+//! tutorial uses. Each one is also turned into a test of `tests/server.rs`,
+//! which starts a server on a free port and runs the block's `main` against
+//! it. For that, the address in the block is replaced with the one of that
+//! server: every `"127.0.0.1:6379"` becomes `DOC_TEST_REDIS_ADDR.get()`,
+//! read from a `doc_test::redis::Addr` the test fills in.
+
+/// The address the tutorial runs mini-redis on.
+pub const ADDR: &str = "127.0.0.1:6379";
+
+/// The static the address is replaced with.
+pub const VAR: &str = "DOC_TEST_REDIS_ADDR";
+
+/// The address in a block doesn't appear anywhere, so the server would go
+/// unused.
+#[derive(Debug, PartialEq)]
+pub struct NoAddr;
+
+/// `code` with the address of the tutorial's server replaced by the one of
+/// the test's server.
+///
+/// Only string literals holding exactly the address are replaced; a comment
+/// mentioning it isn't code, and is left as it is.
+pub fn substitute(code: &str) -> Result<String, NoAddr> {
+    let literal = format!("{:?}", ADDR);
+    let replacement = format!("{}.get()", VAR);
+
+    let mut dst = String::with_capacity(code.len());
+    let mut found = false;
+
+    for line in code.split_inclusive('\n') {
+        let (code, comment) = match line.find("//") {
+            Some(start) => line.split_at(start),
+            None => (line, ""),
+        };
+
+        found |= code.contains(&literal);
+        dst.push_str(&code.replace(&literal, &replacement));
+        dst.push_str(comment);
+    }
+
+    if found {
+        Ok(dst)
+    } else {
+        Err(NoAddr)
+    }
+}
+
+/// The items of the module testing a block, besides the block's own code.
+pub fn fixture() -> String {
+    format!(
+        "\n\
+         static {var}: doc_test::redis::Addr = doc_test::redis::Addr::new();\n\
+         \n\
+         #[test]\n\
+         fn runs() {{\n    doc_test::redis::run(&{var}, main);\n}}\n",
+        var = VAR
+    )
+}
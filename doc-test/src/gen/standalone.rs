@@ -0,0 +1,68 @@
+//! Turning a code block into code that compiles outside of rustdoc.
+//!
+//! clippy doesn't look at doctests, so each block tested as one is also
+//! written out as a module of its own, the way rustdoc would compile it:
+//! hidden lines shown, and the code wrapped in a `main` function unless it
+//! has one. What the reader sees, the code without the hidden lines, is
+//! checked on its own.
+
+/// `code` with the `# ` marking hidden lines removed.
+pub fn unhide(code: &str) -> String {
+    let mut dst = String::with_capacity(code.len());
+
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+
+        let line = if trimmed == "#" {
+            ""
+        } else if let Some(rest) = trimmed.strip_prefix("# ") {
+            rest
+        } else if trimmed.starts_with("##") {
+            // `##` escapes a line that starts with `#`.
+            &trimmed[1..]
+        } else {
+            line
+        };
+
+        dst.push_str(line);
+        dst.push('\n');
+    }
+
+    dst
+}
+
+/// `code` as the reader sees it, without the hidden lines.
+pub fn visible(code: &str) -> String {
+    let mut dst = String::with_capacity(code.len());
+
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+
+        let line = if trimmed == "#" || trimmed.starts_with("# ") {
+            continue;
+        } else if trimmed.starts_with("##") {
+            &trimmed[1..]
+        } else {
+            line
+        };
+
+        dst.push_str(line);
+        dst.push('\n');
+    }
+
+    dst
+}
+
+/// The items of a module compiling `code` like rustdoc would.
+pub fn items(code: &str) -> String {
+    let code = unhide(code);
+
+    if code.contains("fn main") {
+        return code;
+    }
+
+    let mut dst = String::from("fn main() {\n");
+    dst.push_str(&code);
+    dst.push_str("}\n");
+    dst
+}
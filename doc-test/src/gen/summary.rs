@@ -0,0 +1,129 @@
+//! How many of each file's Rust blocks are run, only compiled, expected to
+//! fail, or ignored.
+//!
+//! The build script writes every summary to `report.json` in `OUT_DIR`, so
+//! which pages are actually tested can be looked up, or checked in tests.
+//!
+//! `ignore` is an easy way to silence a snippet that stopped compiling, so
+//! the build checks that no file relies on it too much.
+
+use super::fence::{Block, Kind};
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The Rust blocks of one file, counted by `Kind`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub path: String,
+
+    /// Every fenced block, Rust or not.
+    pub blocks: usize,
+
+    /// Attributes used in the info strings of the Rust blocks, other than
+    /// `rust` itself.
+    pub annotations: BTreeSet<String>,
+
+    pub run: usize,
+    pub no_run: usize,
+    pub compile_fail: usize,
+    pub ignore: usize,
+}
+
+/// A file with a larger fraction of `ignore` blocks than allowed.
+#[derive(Debug, PartialEq)]
+pub struct TooManyIgnored {
+    pub path: String,
+    pub ignored: usize,
+    pub total: usize,
+    pub max: f64,
+}
+
+impl Summary {
+    /// Count the Rust blocks among `blocks`, which come from `path`.
+    pub fn new(path: &str, blocks: &[Block]) -> Summary {
+        let mut summary = Summary {
+            path: path.to_string(),
+            blocks: blocks.len(),
+            ..Summary::default()
+        };
+
+        for block in blocks.iter().filter(|block| block.is_rust()) {
+            summary.annotations.extend(
+                block
+                    .attrs()
+                    .filter(|attr| *attr != "rust")
+                    .map(str::to_string),
+            );
+
+            match block.kind() {
+                Kind::Run => summary.run += 1,
+                Kind::NoRun => summary.no_run += 1,
+                Kind::CompileFail => summary.compile_fail += 1,
+                Kind::Ignore => summary.ignore += 1,
+            }
+        }
+
+        summary
+    }
+
+    /// Number of Rust blocks in the file.
+    pub fn total(&self) -> usize {
+        self.run + self.no_run + self.compile_fail + self.ignore
+    }
+
+    /// The summary as it appears in `report.json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path,
+            "blocks": self.blocks,
+            "rust": self.total(),
+            "run": self.run,
+            "no_run": self.no_run,
+            "compile_fail": self.compile_fail,
+            "ignore": self.ignore,
+            "annotations": self.annotations,
+        })
+    }
+
+    /// Fail if more than `max` of the file's Rust blocks are ignored. `max`
+    /// is a fraction, from 0 to 1.
+    pub fn check(&self, max: f64) -> Result<(), TooManyIgnored> {
+        let total = self.total();
+
+        if total > 0 && self.ignore as f64 > max * total as f64 {
+            return Err(TooManyIgnored {
+                path: self.path.clone(),
+                ignored: self.ignore,
+                total,
+                max,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{}: {} run, {} no_run, {} compile_fail, {} ignore",
+            self.path, self.run, self.no_run, self.compile_fail, self.ignore
+        )
+    }
+}
+
+impl fmt::Display for TooManyIgnored {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{}: {} of {} Rust blocks are `ignore`, more than the allowed {}%",
+            self.path,
+            self.ignored,
+            self.total,
+            self.max * 100.0
+        )
+    }
+}
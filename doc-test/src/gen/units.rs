@@ -0,0 +1,71 @@
+//! Listings split over several code blocks.
+//!
+//! A page sometimes builds a listing up over several blocks, with text in
+//! between: a struct in one block, then its `impl` in the next, one method
+//! at a time. None of the parts compiles on its own. A directive on the line
+//! before a block,
+//!
+//! ```text
+//! <!-- doc-test: continues-previous -->
+//! ```
+//!
+//! appends it to the previous Rust block, and the blocks are tested as one.
+//! The first block's attributes and prelude apply to the whole.
+
+use super::fence::Block;
+use super::prelude::Invalid;
+
+const CONTINUES: &str = "<!-- doc-test: continues-previous -->";
+
+/// Group `blocks`, parsed out of `markdown`, into the units they are tested
+/// as, by index. A unit is a block followed by the blocks continuing it.
+pub fn group(markdown: &str, blocks: &[Block]) -> Result<Vec<Vec<usize>>, Invalid> {
+    let lines: Vec<_> = markdown.lines().collect();
+    let mut units: Vec<Vec<usize>> = vec![];
+
+    // The unit of the last Rust block, which the next block may continue.
+    let mut last_rust = None;
+
+    for (i, block) in blocks.iter().enumerate() {
+        // The nearest line above the block with something on it.
+        let directive = (0..block.line - 1)
+            .rev()
+            .find(|&j| !lines[j].trim().is_empty())
+            .filter(|&j| lines[j].trim() == CONTINUES);
+
+        let directive = match directive {
+            Some(j) => j + 1,
+            None => {
+                if block.is_rust() {
+                    last_rust = Some(units.len());
+                }
+                units.push(vec![i]);
+                continue;
+            }
+        };
+
+        if !block.is_rust() {
+            return Err(Invalid {
+                line: directive,
+                reason: "only a Rust code block can continue the previous one".to_string(),
+            });
+        }
+
+        match last_rust {
+            Some(unit) => units[unit].push(i),
+            None => {
+                return Err(Invalid {
+                    line: directive,
+                    reason: "there is no Rust code block before this one to continue".to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(units)
+}
+
+/// The code of a unit: its blocks' code, one after the other.
+pub fn code(blocks: &[Block], unit: &[usize]) -> String {
+    unit.iter().map(|&i| blocks[i].code.as_str()).collect()
+}
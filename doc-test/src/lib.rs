@@ -1,3 +1,19 @@
-#![feature(external_doc)]
+// The tutorial shows complete programs, `main` included.
+#![allow(clippy::needless_doctest_main)]
 
 include!(concat!(env!("OUT_DIR"), "/doctests.rs"));
+
+pub mod gen;
+pub mod redis;
+
+/// Every block tested above, compiled as normal code so clippy lints it.
+///
+/// Only clippy compiles them: elsewhere, a block that doesn't compile should
+/// fail its own doctest rather than the whole crate. Snippets are fragments of
+/// larger programs, so like rustdoc does for doctests, unused code isn't worth
+/// a warning.
+#[cfg(clippy)]
+#[allow(unused)]
+pub mod snippets {
+    include!(concat!(env!("OUT_DIR"), "/snippets.rs"));
+}
@@ -0,0 +1,127 @@
+//! A mini-redis server for the code blocks marked `needs-server`.
+//!
+//! Each test starts a server of its own on a free port, so they can run in
+//! parallel, and stops it when the block is done with it.
+
+use std::fmt::Debug;
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::thread;
+use tokio::net::TcpListener;
+use tokio::runtime;
+use tokio::sync::oneshot;
+
+/// Where the server of a block's test listens. The generated code reads it
+/// in place of the address the tutorial uses.
+pub struct Addr(OnceLock<String>);
+
+impl Addr {
+    pub const fn new() -> Addr {
+        Addr(OnceLock::new())
+    }
+
+    /// The address of the server.
+    ///
+    /// # Panics
+    ///
+    /// When no server was started for the block.
+    pub fn get(&self) -> &str {
+        self.0
+            .get()
+            .expect("no server was started; run the block with `doc_test::redis::run`")
+    }
+}
+
+impl Default for Addr {
+    fn default() -> Addr {
+        Addr::new()
+    }
+}
+
+/// A mini-redis server, running on a thread of its own until it is dropped.
+pub struct Server {
+    addr: String,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /// Start a server on a free port of `127.0.0.1`.
+    pub fn start() -> Server {
+        let (addr_tx, addr_rx) = mpsc::channel();
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+
+        // The block's `main` starts a runtime of its own, which can't be done
+        // from inside of another one.
+        let thread = thread::spawn(move || {
+            let rt = runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(async move {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                addr_tx
+                    .send(listener.local_addr().unwrap().to_string())
+                    .unwrap();
+
+                mini_redis::server::run(listener, shutdown_rx)
+                    .await
+                    .unwrap();
+            });
+        });
+
+        let addr = addr_rx.recv().expect("the server failed to start");
+
+        Server {
+            addr,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        }
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.shutdown.take().unwrap().send(());
+
+        // Don't panic again when the test already did.
+        let result = self.thread.take().unwrap().join();
+        if !thread::panicking() {
+            result.expect("the server panicked");
+        }
+    }
+}
+
+/// What a block's `main` returns.
+pub trait Outcome {
+    /// Panic if `main` failed.
+    fn check(self);
+}
+
+impl Outcome for () {
+    fn check(self) {}
+}
+
+impl<E: Debug> Outcome for Result<(), E> {
+    fn check(self) {
+        if let Err(err) = self {
+            panic!("main returned an error: {:?}", err);
+        }
+    }
+}
+
+/// Run the `main` of a block against a server of its own, whose address is
+/// stored in `addr`.
+pub fn run<T: Outcome>(addr: &Addr, main: fn() -> T) {
+    let server = Server::start();
+    addr.0
+        .set(server.addr().to_string())
+        .expect("the block was run twice");
+
+    main().check();
+}
@@ -0,0 +1,182 @@
+use clean::clean;
+use doc_test::gen::{clean, fence};
+
+/// The code blocks of `markdown`, exactly as written.
+fn blocks(markdown: &str) -> Vec<(String, String)> {
+    fence::parse(markdown)
+        .unwrap()
+        .into_iter()
+        .map(|block| (block.info, block.code))
+        .collect()
+}
+
+#[test]
+fn strips_front_matter() {
+    let markdown = "---\ntitle: \"Async in depth\"\nmenu: tutorial\n---\n\n# Futures\n";
+
+    assert_eq!(clean(markdown), "\n\n\n\n\n# Futures\n");
+}
+
+#[test]
+fn thematic_break_is_not_front_matter() {
+    let markdown = "---\n\nSome text.\n";
+
+    assert_eq!(clean(markdown), markdown);
+}
+
+#[test]
+fn strips_components_and_keeps_their_text() {
+    let markdown = "\
+Intro.
+
+<Aside kind=\"warning\">
+Don't block the runtime.
+
+```rust
+<Aside>
+fn main() {}
+```
+</Aside>
+
+<Spacer
+  height=\"2\"
+/>
+";
+
+    let cleaned = clean(markdown);
+
+    assert_eq!(
+        cleaned,
+        "\
+Intro.
+
+
+Don't block the runtime.
+
+```rust
+<Aside>
+fn main() {}
+```
+
+
+
+
+
+"
+    );
+    assert_eq!(blocks(&cleaned), blocks(markdown));
+}
+
+#[test]
+fn html_elements_and_inline_generics_are_kept() {
+    let markdown = "<div>\n\nA `Vec<u8>` or an `Arc<Mutex<_>>`.\n\n</div>\n";
+
+    assert_eq!(clean(markdown), markdown);
+}
+
+#[test]
+fn strips_comments_with_fences_in_them() {
+    let markdown = "\
+Before.
+
+<!-- Not tested yet:
+```rust
+fn broken() {
+```
+-->
+
+<!-- one line --> After.
+
+```rust
+// <!-- not a comment -->
+fn main() {}
+```
+";
+
+    let cleaned = clean(markdown);
+
+    assert_eq!(
+        cleaned,
+        "\
+Before.
+
+
+
+
+
+
+
+ After.
+
+```rust
+// <!-- not a comment -->
+fn main() {}
+```
+"
+    );
+    assert_eq!(
+        blocks(&cleaned),
+        [(
+            "rust".to_string(),
+            "// <!-- not a comment -->\nfn main() {}\n".to_string()
+        )]
+    );
+}
+
+#[test]
+fn keeps_line_numbers() {
+    let markdown = "---\ntitle: x\n---\n<Aside>\n<!--\n-->\n```rust\nfn main() {}\n```\n";
+
+    let cleaned = clean(markdown);
+
+    assert_eq!(cleaned.lines().count(), markdown.lines().count());
+    assert_eq!(fence::parse(&cleaned).unwrap()[0].line, 7);
+}
+
+#[test]
+fn keeps_crlf_line_endings() {
+    let markdown = "<Aside>\r\ntext\r\n```rust\r\nfn main() {}\r\n```\r\n";
+
+    assert_eq!(
+        clean(markdown),
+        "\r\ntext\r\n```rust\r\nfn main() {}\r\n```\r\n"
+    );
+}
+
+#[test]
+fn keeps_directives() {
+    let markdown = "<!-- doc-test: prelude=tokio_main -->\n```rust\n```\n";
+
+    assert_eq!(clean(markdown), markdown);
+}
+
+#[test]
+fn keeps_code_blocks_in_lists() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/clean/list.md");
+    let markdown = std::fs::read_to_string(path).unwrap();
+
+    let cleaned = clean(&markdown);
+
+    assert_eq!(
+        blocks(&cleaned),
+        [(
+            "rust".to_string(),
+            "<Foo as Bar>::baz();\n<!-- not a comment, and never closed\nlet after = 1;\n"
+                .to_string()
+        )]
+    );
+    assert_eq!(
+        cleaned.lines().skip(9).collect::<Vec<_>>(),
+        [
+            "     ```",
+            "",
+            "2. The text around the block is still cleaned.",
+            "",
+            "",
+            "",
+            "   Kept.",
+            "",
+        ]
+    );
+}
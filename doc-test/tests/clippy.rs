@@ -0,0 +1,178 @@
+//! Lints the code blocks with clippy, and fails on the lints for what the
+//! tutorial shouldn't teach.
+//!
+//! `cargo clippy` already lints the blocks, as `src/lib.rs` compiles them as
+//! normal code. The lints below are enforced even where they are off by
+//! default. A block showing what not to do can allow one with an
+//! `allow=<lint>` attribute.
+
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Lints no code block may trigger.
+const DENIED: &[&str] = &[
+    "clippy::await_holding_lock",
+    "clippy::await_holding_refcell_ref",
+    "clippy::async_yields_async",
+    "clippy::let_underscore_future",
+    "clippy::redundant_clone",
+    "clippy::unused_io_amount",
+];
+
+/// The runs share a target directory, and each one regenerates the snippets
+/// the previous one's findings point into.
+static TARGET: Mutex<()> = Mutex::new(());
+
+/// A denied lint, and the block it is in.
+#[derive(Debug, PartialEq)]
+struct Finding {
+    lint: String,
+
+    /// The block, as `path:line`.
+    origin: String,
+}
+
+/// Run clippy over the blocks of the content directory `content`.
+fn clippy(content: &Path) -> Vec<Finding> {
+    let _target = TARGET.lock().unwrap_or_else(|err| err.into_inner());
+
+    let home = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let mut cmd = Command::new(cargo);
+    cmd.arg("clippy")
+        .arg("--lib")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(home.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(home.join("target/clippy"))
+        .env("DOC_TEST_CONTENT", content)
+        .env_remove("DOC_TEST_FILTER")
+        // CI denies warnings, which would turn the findings into errors.
+        .env_remove("RUSTFLAGS")
+        .arg("--");
+    for lint in DENIED {
+        cmd.arg("--warn").arg(lint);
+    }
+
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        "clippy failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    findings(&String::from_utf8(output.stdout).unwrap())
+}
+
+/// The denied lints in clippy's JSON messages, attributed to their blocks.
+fn findings(messages: &str) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for line in messages.lines() {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+
+        let message = &message["message"];
+        let lint = match message["code"]["code"].as_str() {
+            Some(lint) if DENIED.contains(&lint) => lint,
+            _ => continue,
+        };
+
+        let spans = message["spans"].as_array().into_iter().flatten();
+        for span in spans.filter(|span| span["is_primary"] == true) {
+            let file = span["file_name"].as_str().unwrap();
+            let line = span["line_start"].as_u64().unwrap() as usize;
+
+            let origin = match fs::read_to_string(file) {
+                Ok(generated) => origin(&generated, line).map(str::to_string),
+                Err(_) => None,
+            };
+
+            findings.push(Finding {
+                lint: lint.to_string(),
+                origin: origin.unwrap_or_else(|| format!("{}:{}", file, line)),
+            });
+        }
+    }
+
+    findings
+}
+
+/// Where the code at `line` of the generated snippets comes from, according
+/// to the `/// From` comment of its module.
+fn origin(generated: &str, line: usize) -> Option<&str> {
+    generated
+        .lines()
+        .take(line)
+        .filter_map(|line| line.trim().strip_prefix("/// From "))
+        .last()
+}
+
+#[test]
+fn finds_origins() {
+    let generated = "pub mod tutorial {\n    /// From /content/a.md:3\n    pub mod a_block_1_md {\nfn main() {\n}\n}\n    /// From /content/a.md:9\n    pub mod a_block_2_md {\nfn main() {}\n}\n}\n";
+
+    assert_eq!(origin(generated, 1), None);
+    assert_eq!(origin(generated, 4), Some("/content/a.md:3"));
+    assert_eq!(origin(generated, 9), Some("/content/a.md:9"));
+}
+
+#[test]
+fn parses_messages() {
+    let messages = r#"{"reason":"compiler-artifact","target":{"name":"tokio"}}
+{"reason":"compiler-message","message":{"code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":1,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"code":{"code":"clippy::redundant_clone"},"spans":[{"file_name":"/nowhere/snippets.rs","line_start":7,"is_primary":true},{"file_name":"/nowhere/snippets.rs","line_start":6,"is_primary":false}]}}
+not json
+"#;
+
+    assert_eq!(
+        findings(messages),
+        [Finding {
+            lint: "clippy::redundant_clone".to_string(),
+            origin: "/nowhere/snippets.rs:7".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn detects_denied_lints() {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/clippy");
+    let findings = clippy(&content);
+
+    // Only the block in `lock.md`; `allowed.md` allows the lint.
+    assert_eq!(findings.len(), 1, "{:?}", findings);
+    assert_eq!(findings[0].lint, "clippy::await_holding_lock");
+    assert!(
+        findings[0].origin.ends_with("/tokio/lock.md:16"),
+        "{:?}",
+        findings
+    );
+}
+
+#[test]
+fn content_has_no_denied_lints() {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("../content");
+    let findings = clippy(&content);
+
+    let lines: Vec<_> = findings
+        .iter()
+        .map(|finding| format!("{}: {}", finding.origin, finding.lint))
+        .collect();
+    assert!(
+        findings.is_empty(),
+        "code blocks teach what they shouldn't; fix them, or add `allow=<lint>` to blocks showing \
+         what not to do:\n    {}",
+        lines.join("\n    ")
+    );
+}
@@ -0,0 +1,61 @@
+use doc_test::gen::era::{find, names, ERAS};
+
+#[test]
+fn finds_eras_by_name() {
+    assert_eq!(find("tokio-0.2").unwrap().name, "tokio-0.2");
+    assert!(find("tokio-1").is_none());
+    assert!(find("").is_none());
+}
+
+#[test]
+fn lists_every_era() {
+    assert_eq!(names(), "tokio-0.1, tokio-0.2, tokio-0.3");
+}
+
+#[test]
+fn packages_are_valid_crate_names() {
+    for era in ERAS {
+        let package = era.package();
+        assert!(
+            package
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{}",
+            package
+        );
+    }
+}
+
+#[test]
+fn tokio_0_1_manifest() {
+    assert_eq!(
+        find("tokio-0.1").unwrap().manifest(),
+        r#"[package]
+name = "doc-test-tokio-0-1"
+version = "0.1.0"
+edition = "2018"
+publish = false
+
+# Keep the crate out of any workspace it is generated in.
+[workspace]
+
+[dependencies]
+tokio = "0.1"
+futures = "0.1"
+bytes = "0.4"
+"#
+    );
+}
+
+#[test]
+fn tokio_0_2_manifest() {
+    let manifest = find("tokio-0.2").unwrap().manifest();
+
+    assert!(manifest.contains("name = \"doc-test-tokio-0-2\"\n"));
+    assert!(manifest.ends_with(
+        "[dependencies]\n\
+         tokio = { version = \"0.2\", features = [\"full\"] }\n\
+         futures = \"0.3\"\n\
+         bytes = \"0.5\"\n"
+    ));
+}
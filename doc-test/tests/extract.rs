@@ -0,0 +1,86 @@
+use doc_test::gen::{extract, files};
+use extract::{extract, key, Cache, VERSION};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The markdown files of the tutorial, as a fixture tree too large for one
+/// thread.
+fn tutorial() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../content/tokio");
+    let (files, _) = files::markdown_files(&files::content_dir(&root).unwrap());
+    assert!(files.len() > 1);
+    files
+}
+
+#[test]
+fn second_run_uses_the_cache() {
+    let files = tutorial();
+    let dir = tempfile::tempdir().unwrap();
+
+    let first = Cache::new(dir.path());
+    let extracted = first.get_all(&files);
+    assert_eq!(first.extractions(), files.len());
+
+    let second = Cache::new(dir.path());
+    assert_eq!(second.get_all(&files), extracted);
+    assert_eq!(second.extractions(), 0);
+}
+
+#[test]
+fn same_as_serial_extraction() {
+    let files = tutorial();
+    let dir = tempfile::tempdir().unwrap();
+
+    let serial: Vec<_> = files
+        .iter()
+        .map(|path| extract(&fs::read_to_string(path).unwrap()))
+        .collect();
+
+    assert_eq!(Cache::new(dir.path()).get_all(&files), serial);
+}
+
+#[test]
+fn changed_files_are_extracted_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+
+    let before = cache.get("---\ntitle: A\n---\n\n```rust\nfn main() {}\n```\n");
+    let after = cache.get("---\ntitle: A\n---\n\n```rust,no_run\nfn main() {}\n```\n");
+    assert_eq!(cache.extractions(), 2);
+    assert_ne!(before, after);
+
+    cache.get("---\ntitle: A\n---\n\n```rust\nfn main() {}\n```\n");
+    assert_eq!(cache.extractions(), 2);
+}
+
+#[test]
+fn caches_problems_too() {
+    let raw = "no front matter\n\n```\nunlabelled\n```\n\n```rust\nunclosed\n";
+    let dir = tempfile::tempdir().unwrap();
+
+    let extracted = Cache::new(dir.path()).get(raw);
+    assert!(extracted.front_matter.is_some());
+    assert_eq!(extracted.blocks, None);
+
+    let cache = Cache::new(dir.path());
+    assert_eq!(cache.get(raw), extracted);
+    assert_eq!(cache.extractions(), 0);
+}
+
+#[test]
+fn corrupt_entries_are_misses() {
+    let raw = "---\ntitle: A\n---\n";
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(format!("{}.json", key(VERSION, raw))), "{").unwrap();
+
+    let cache = Cache::new(dir.path());
+    assert_eq!(cache.get(raw), extract(raw));
+    assert_eq!(cache.extractions(), 1);
+}
+
+#[test]
+fn keys_depend_on_the_version() {
+    assert_eq!(key(VERSION, "a"), key(VERSION, "a"));
+    assert_ne!(key(VERSION, "a"), key(VERSION, "b"));
+    assert_ne!(key(VERSION, "a"), key(VERSION + 1, "a"));
+}
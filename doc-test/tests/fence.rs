@@ -0,0 +1,264 @@
+use doc_test::gen::fence::{parse, Block, Kind, Unterminated};
+
+fn block(line: usize, fence: &str, info: &str, code: &str) -> Block {
+    Block {
+        line,
+        fence: fence.to_string(),
+        info: info.to_string(),
+        code: code.to_string(),
+    }
+}
+
+#[test]
+fn splits_blocks() {
+    let markdown =
+        "# Title\n\n```rust\nfn main() {}\n```\n\ntext\n\n~~~\nlet x = 1;\n\nlet y = 2;\n~~~\n";
+
+    assert_eq!(
+        parse(markdown).unwrap(),
+        [
+            block(3, "```", "rust", "fn main() {}\n"),
+            block(9, "~~~", "", "let x = 1;\n\nlet y = 2;\n"),
+        ]
+    );
+}
+
+#[test]
+fn inline_backticks_are_not_fences() {
+    let markdown = "Use ```rust``` to start a block.\n``` not `a` fence\n\n```\ncode\n```\n";
+
+    assert_eq!(parse(markdown).unwrap(), [block(4, "```", "", "code\n")]);
+}
+
+#[test]
+fn longer_fence_contains_shorter() {
+    let markdown = "````markdown\n```rust\nfn main() {}\n```\n````\n";
+
+    assert_eq!(
+        parse(markdown).unwrap(),
+        [block(1, "````", "markdown", "```rust\nfn main() {}\n```\n")]
+    );
+}
+
+#[test]
+fn closing_fence_may_be_longer() {
+    // channels.md closes a block this way.
+    let markdown = "```rust\nfn main() {}\n````\n";
+
+    assert_eq!(
+        parse(markdown).unwrap(),
+        [block(1, "```", "rust", "fn main() {}\n")]
+    );
+}
+
+#[test]
+fn info_strings() {
+    let markdown = "```rust,no_run\n```\n```rust,compile_fail,E0373\n```\n```rust ignore\n```\n";
+
+    let blocks = parse(markdown).unwrap();
+    let infos: Vec<_> = blocks.iter().map(|block| &block.info[..]).collect();
+    assert_eq!(
+        infos,
+        ["rust,no_run", "rust,compile_fail,E0373", "rust ignore"]
+    );
+    assert!(blocks.iter().all(Block::is_rust));
+}
+
+#[test]
+fn rust_blocks() {
+    let cases = [
+        ("", true),
+        ("rust", true),
+        ("rust,no_run", true),
+        ("compile_fail", true),
+        ("rust,compile_fail,E0373", true),
+        ("text", false),
+        ("bash", false),
+        ("toml", false),
+        ("rs", false),
+        ("rust,text", false),
+    ];
+
+    for (info, expected) in cases.iter() {
+        assert_eq!(block(1, "```", info, "").is_rust(), *expected, "{:?}", info);
+    }
+}
+
+#[test]
+fn unterminated() {
+    let markdown = "# Title\n\n```rust\nfn main() {}\n\nmore text\n";
+
+    assert_eq!(parse(markdown), Err(Unterminated { line: 3 }));
+}
+
+#[test]
+fn indented_code_is_not_a_fence() {
+    let markdown = "    ```\n    not a fence\n";
+
+    assert_eq!(parse(markdown).unwrap(), []);
+}
+
+#[test]
+fn kinds() {
+    let markdown = "```rust\n```\n```\n```\n```rust,no_run\n```\n```rust,compile_fail,E0373\n```\n\
+                    ```rust,ignore\n```\n```rust,should_panic\n```\n```compile_fail,ignore\n```\n";
+
+    let kinds: Vec<_> = parse(markdown).unwrap().iter().map(Block::kind).collect();
+
+    assert_eq!(
+        kinds,
+        [
+            Kind::Run,
+            Kind::Run,
+            Kind::NoRun,
+            Kind::CompileFail,
+            Kind::Ignore,
+            Kind::Run,
+            Kind::Ignore,
+        ]
+    );
+}
+
+#[test]
+fn requires_features() {
+    let blocks = parse(
+        "```rust,requires=tokio-util\n```\n```rust,no_run,requires=hyper,requires=tokio-util\n```\n\
+         ```rust, no_run\n```\n```requires=\n```\n",
+    )
+    .unwrap();
+
+    assert!(blocks[0].is_rust());
+    assert_eq!(blocks[0].requires(), ["tokio-util"]);
+    assert_eq!(blocks[0].rustdoc_info(), "rust");
+
+    assert!(blocks[1].is_rust());
+    assert_eq!(blocks[1].requires(), ["hyper", "tokio-util"]);
+    assert_eq!(blocks[1].rustdoc_info(), "rust,no_run");
+
+    // Without `requires=`, the info string is left alone.
+    assert!(blocks[2].requires().is_empty());
+    assert_eq!(blocks[2].rustdoc_info(), "rust, no_run");
+
+    // A feature has to be named.
+    assert!(!blocks[3].is_rust());
+}
+
+#[test]
+fn eras() {
+    let blocks = parse(
+        "```rust,era=tokio-0.2\n```\n```rust,no_run,requires=hyper,era=tokio-0.1\n```\n\
+         ```rust\n```\n",
+    )
+    .unwrap();
+
+    assert_eq!(blocks[0].era(), Some("tokio-0.2"));
+    assert_eq!(blocks[0].rustdoc_info(), "rust");
+
+    assert_eq!(blocks[1].era(), Some("tokio-0.1"));
+    assert_eq!(blocks[1].requires(), ["hyper"]);
+    assert_eq!(blocks[1].rustdoc_info(), "rust,no_run");
+
+    assert_eq!(blocks[2].era(), None);
+}
+
+#[test]
+fn allowed_lints() {
+    let blocks =
+        parse("```rust,no_run,allow=clippy::never_loop,allow=unused\n```\n```rust\n```\n").unwrap();
+
+    assert!(blocks[0].is_rust());
+    assert_eq!(blocks[0].allows(), ["clippy::never_loop", "unused"]);
+    assert_eq!(blocks[0].rustdoc_info(), "rust,no_run");
+
+    assert!(blocks[1].allows().is_empty());
+}
+
+#[test]
+fn needs_server() {
+    let blocks = parse(
+        "```rust,needs-server\n```\n```rust,no_run,needs-server\n```\n\
+         ```rust,needs-server,compile_fail\n```\n```rust\n```\n",
+    )
+    .unwrap();
+
+    assert!(blocks[0].is_rust());
+    assert!(blocks[0].needs_server());
+    assert_eq!(blocks[0].kind(), Kind::NoRun);
+    assert_eq!(blocks[0].rustdoc_info(), "rust,no_run");
+
+    assert_eq!(blocks[1].rustdoc_info(), "rust,no_run");
+
+    assert_eq!(blocks[2].kind(), Kind::CompileFail);
+    assert_eq!(blocks[2].rustdoc_info(), "rust,compile_fail,no_run");
+
+    assert!(!blocks[3].needs_server());
+}
+
+#[test]
+fn fragments() {
+    let blocks = parse("```rust,fragment,no_run\n```\n```rust\n```\n").unwrap();
+
+    assert!(blocks[0].is_rust());
+    assert!(blocks[0].is_fragment());
+    assert_eq!(blocks[0].rustdoc_info(), "rust,no_run");
+
+    assert!(!blocks[1].is_fragment());
+}
+
+fn fixture(name: &str) -> Vec<Block> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/fence")
+        .join(name);
+    parse(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn tilde_fences() {
+    assert_eq!(
+        fixture("tilde.md"),
+        [
+            block(3, "~~~", "rust", "let s = \"```\";\n"),
+            block(7, "~~~~", "rust,no_run", "~~~\nnested\n~~~\n"),
+        ]
+    );
+}
+
+#[test]
+fn fences_in_list_items() {
+    assert_eq!(
+        fixture("list.md"),
+        [
+            block(3, "```", "rust", "fn main() {\n    println!(\"hi\");\n}\n"),
+            block(13, "```", "rust,no_run", "let x = 1;\n"),
+        ]
+    );
+}
+
+#[test]
+fn fences_in_block_quotes() {
+    assert_eq!(
+        fixture("blockquote.md"),
+        [block(
+            3,
+            "```",
+            "rust",
+            "let quoted = true;\n\nassert!(quoted);\n"
+        )]
+    );
+}
+
+#[test]
+fn fences_in_lists_in_block_quotes() {
+    assert_eq!(
+        fixture("nested.md"),
+        [block(5, "~~~", "rust", "let deep = 3;\n    indented();\n")]
+    );
+}
+
+#[test]
+fn unterminated_in_a_container() {
+    // The quote ends, and the block with it.
+    let markdown = "> ```rust\n> fn main() {}\n\nafter\n";
+
+    assert_eq!(parse(markdown), Err(Unterminated { line: 1 }));
+}
@@ -0,0 +1,192 @@
+use doc_test::gen::files;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn touch(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+}
+
+#[test]
+fn finds_markdown_files_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+
+    touch(&root.join("tutorial/spawning.md"));
+    touch(&root.join("glossary.md"));
+    touch(&root.join("tutorial/notes.txt"));
+    touch(&root.join("topics/deep/nested.md"));
+
+    assert_eq!(
+        files::markdown_files(&root),
+        (
+            vec![
+                root.join("glossary.md"),
+                root.join("topics/deep/nested.md"),
+                root.join("tutorial/spawning.md"),
+            ],
+            vec![]
+        )
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn skips_broken_symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+
+    touch(&root.join("glossary.md"));
+    std::os::unix::fs::symlink(root.join("gone.md"), root.join("broken.md")).unwrap();
+
+    let (found, skipped) = files::markdown_files(&root);
+
+    assert_eq!(found, [root.join("glossary.md")]);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].path, root.join("broken.md"));
+    assert!(
+        skipped[0].to_string().starts_with(&format!(
+            "skipping `{}`: ",
+            root.join("broken.md").display()
+        )),
+        "{}",
+        skipped[0]
+    );
+}
+
+#[test]
+fn finds_content_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir(root.join("content")).unwrap();
+    fs::create_dir(root.join("doc-test")).unwrap();
+    touch(&root.join("README.md"));
+
+    assert_eq!(
+        files::content_dir(&root.join("doc-test/../content")),
+        Ok(root.join("content"))
+    );
+
+    // Missing, or not a directory.
+    for missing in ["blog", "README.md"].iter() {
+        let path = root.join(missing);
+        let err = files::content_dir(&path).unwrap_err();
+
+        assert_eq!(err, files::Missing { path: path.clone() });
+        assert!(
+            err.to_string().starts_with(&format!(
+                "content directory `{}` not found; ",
+                path.display()
+            )),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn reruns_on_content_and_build_script_changes() {
+    let files = [
+        PathBuf::from("/content/tokio/glossary.md"),
+        PathBuf::from("/content/tokio/tutorial/spawning.md"),
+    ];
+    let lines = files::rerun_lines(
+        Path::new("/doc-test"),
+        &[
+            PathBuf::from("/content/tokio"),
+            PathBuf::from("/content/blog"),
+        ],
+        &files,
+    );
+
+    // The build script prints exactly these lines.
+    assert_eq!(
+        lines,
+        [
+            "cargo:rerun-if-changed=/doc-test/build.rs",
+            "cargo:rerun-if-changed=/doc-test/src/gen",
+            "cargo:rerun-if-changed=/content/tokio",
+            "cargo:rerun-if-changed=/content/blog",
+            "cargo:rerun-if-changed=/content/tokio/glossary.md",
+            "cargo:rerun-if-changed=/content/tokio/tutorial/spawning.md",
+        ]
+    );
+}
+
+#[test]
+fn filter_matching() {
+    let rel = Path::new("tutorial/spawning.md");
+
+    // Substrings.
+    assert!(files::matches("spawning", rel));
+    assert!(files::matches("tutorial/", rel));
+    assert!(!files::matches("select", rel));
+
+    // Globs match the whole path, and `*` spans directories.
+    assert!(files::matches("tutorial/*.md", rel));
+    assert!(files::matches("*spawn*", rel));
+    assert!(files::matches("*.md", rel));
+    assert!(!files::matches("spawn*", rel));
+    assert!(!files::matches("*.txt", rel));
+
+    // Windows paths are compared with forward slashes.
+    assert!(files::matches(
+        "tutorial/spawning",
+        Path::new("tutorial\\spawning.md")
+    ));
+}
+
+#[test]
+fn filter_files() {
+    let root = PathBuf::from("/content/tokio");
+    let all = || {
+        vec![
+            root.join("glossary.md"),
+            root.join("tutorial/select.md"),
+            root.join("tutorial/spawning.md"),
+        ]
+    };
+
+    assert_eq!(
+        files::filter(all(), &root, "tutorial/s*"),
+        Ok(vec![
+            root.join("tutorial/select.md"),
+            root.join("tutorial/spawning.md"),
+        ])
+    );
+
+    // The root itself is not part of what is matched.
+    assert_eq!(
+        files::filter(all(), &root, "tokio"),
+        Err(files::NoMatch {
+            filter: "tokio".to_string(),
+            total: 3,
+        })
+    );
+    assert_eq!(
+        files::filter(all(), &root, "tokio")
+            .unwrap_err()
+            .to_string(),
+        "DOC_TEST_FILTER=\"tokio\" matches none of the 3 markdown files"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn finds_symlinked_files_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+
+    touch(&root.join("tutorial/spawning.md"));
+    touch(&root.join("glossary.md"));
+    std::os::unix::fs::symlink(root.join("tutorial"), root.join("chapters")).unwrap();
+    std::os::unix::fs::symlink(root.join("glossary.md"), root.join("terms.md")).unwrap();
+
+    assert_eq!(
+        files::markdown_files(&root),
+        (
+            vec![root.join("glossary.md"), root.join("tutorial/spawning.md")],
+            vec![]
+        )
+    );
+}
@@ -0,0 +1,20 @@
+import * as api from "../lib/api";
+import Page from "../lib/page";
+
+const menu = {
+  tokio: {
+    title: "Tokio",
+    nested: {
+      tutorial: {
+        nested: ["setup", "renamed"],
+      },
+      glossary: {},
+      api: {
+        title: "API documentation",
+        href: "https://docs.rs/tokio",
+      },
+    },
+  },
+};
+
+export default Page;
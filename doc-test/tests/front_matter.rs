@@ -0,0 +1,71 @@
+use doc_test::gen::{clean, fence, front_matter};
+use front_matter::{check, Error};
+
+#[test]
+fn valid_front_matter() {
+    let markdown = "---\ntitle: \"Async in depth\"\nmenu: tutorial\n---\n\n# Futures\n";
+
+    assert_eq!(check(markdown), Ok("Async in depth".to_string()));
+}
+
+#[test]
+fn missing_front_matter() {
+    assert_eq!(check("# Futures\n"), Err(Error::Missing));
+    assert_eq!(check(""), Err(Error::Missing));
+}
+
+#[test]
+fn unterminated_front_matter() {
+    let markdown = "---\ntitle: Tokio\n\n# Futures\n";
+
+    assert_eq!(check(markdown), Err(Error::Unterminated));
+    assert_eq!(
+        Error::Unterminated.to_string(),
+        "front matter is never closed with `---`"
+    );
+}
+
+#[test]
+fn title_is_required() {
+    assert_eq!(check("---\n---\n"), Err(Error::NoTitle));
+    assert_eq!(
+        check("---\ndate: \"2021-07-09\"\n---\n"),
+        Err(Error::NoTitle)
+    );
+    assert_eq!(check("---\ntitle: [1, 2]\n---\n"), Err(Error::NoTitle));
+}
+
+#[test]
+fn invalid_yaml() {
+    let err = check("---\ntitle: \"unclosed\n---\n").unwrap_err();
+    assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+
+    assert_eq!(
+        check("---\n- title\n---\n"),
+        Err(Error::Invalid("expected a mapping".to_string()))
+    );
+}
+
+#[test]
+fn fence_inside_front_matter() {
+    let markdown = "\
+---
+title: Fences
+example: |
+  ```rust
+  fn main() {}
+---
+
+```rust
+fn real() {}
+```
+";
+
+    assert_eq!(check(markdown), Ok("Fences".to_string()));
+
+    // Once cleaned, only the block after the front matter is left.
+    let blocks = fence::parse(&clean::clean(markdown)).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].line, 8);
+    assert_eq!(blocks[0].code, "fn real() {}\n");
+}
@@ -0,0 +1,102 @@
+//! Runs the generator over the content directory in `tests/fixtures/gen`,
+//! the way the build script does, and compares the result with the
+//! snapshots in `tests/snapshots`.
+//!
+//! `UPDATE_SNAPSHOTS=1 cargo test --test gen` rewrites the snapshots after an
+//! intended change to the output.
+
+use doc_test::gen::level::{Level, Mode};
+use doc_test::gen::{extract, files, paths};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// What the content directory is called in the snapshots.
+const PLACEHOLDER: &str = "$CONTENT";
+
+fn fixture() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gen/content")
+}
+
+/// Render the blocks of the content directory `content` in `mode`, with the
+/// paths into it made relative to `PLACEHOLDER`.
+fn generate(content: &Path, mode: Mode<'_>) -> String {
+    let content = files::content_dir(content).unwrap();
+    let (found, skipped) = files::markdown_files(&content);
+    assert!(skipped.is_empty(), "{:?}", skipped);
+
+    let mut level = Level::new();
+    for path in found {
+        let raw = fs::read_to_string(&path).unwrap();
+        let rel = path.strip_prefix(&content).unwrap();
+        let parts: Vec<_> = rel.iter().map(|part| part.to_str().unwrap()).collect();
+
+        level.insert(
+            path.clone(),
+            path.clone(),
+            &parts,
+            extract::extract(&raw).source,
+        );
+    }
+
+    let code = level.render(mode).unwrap();
+    syn::parse_file(&code).unwrap();
+
+    paths::replace_root(&code, &content, PLACEHOLDER)
+}
+
+/// Compare `actual` with the snapshot `name`, or rewrite it when
+/// `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        expected == actual,
+        "{} is out of date; rerun with UPDATE_SNAPSHOTS=1 if the change is \
+         intended. The output is:\n{}",
+        path.display(),
+        actual
+    );
+}
+
+#[test]
+fn renders_blocks() {
+    assert_snapshot("doctests.rs", &generate(&fixture(), Mode::Blocks));
+}
+
+#[test]
+fn renders_includes() {
+    assert_snapshot("include.rs", &generate(&fixture(), Mode::Include));
+}
+
+#[test]
+fn renders_snippets() {
+    assert_snapshot("snippets.rs", &generate(&fixture(), Mode::Snippets));
+}
+
+/// Copy the fixture somewhere else: nothing in the output may depend on
+/// where the content is.
+#[test]
+fn output_does_not_depend_on_the_location() {
+    let tmp = tempfile::tempdir().unwrap();
+    let content = tmp.path().join("somewhere").join("content");
+
+    let (found, _) = files::markdown_files(&fixture());
+    for path in found {
+        let dst = content.join(path.strip_prefix(fixture()).unwrap());
+        fs::create_dir_all(dst.parent().unwrap()).unwrap();
+        fs::copy(&path, dst).unwrap();
+    }
+
+    for mode in [Mode::Blocks, Mode::Include, Mode::Snippets].iter() {
+        assert_eq!(generate(&content, *mode), generate(&fixture(), *mode));
+    }
+}
@@ -0,0 +1,214 @@
+use doc_test::gen::fence::parse as blocks;
+use doc_test::gen::ignored::{check, hash, parse, Exception, Ignored, Invalid, Problem};
+
+fn exception(path: &str, hash: &str) -> Exception {
+    Exception {
+        path: path.to_string(),
+        hash: hash.to_string(),
+        reason: "because".to_string(),
+    }
+}
+
+/// The `ignore` blocks of `markdown`, as found by the build.
+fn ignored(path: &str, markdown: &str) -> Vec<Ignored> {
+    blocks(markdown)
+        .unwrap()
+        .into_iter()
+        .filter(|block| block.info.contains("ignore"))
+        .map(|block| Ignored {
+            path: path.to_string(),
+            line: block.line,
+            hash: hash(&block.code),
+        })
+        .collect()
+}
+
+#[test]
+fn hashes_are_stable() {
+    // Written in `ignored.toml`, so it must not change between builds or
+    // Rust releases.
+    assert_eq!(hash(""), "cbf29ce484222325");
+    assert_eq!(hash("let x = 1;\n"), hash("let x = 1;   \r\n"));
+    assert_ne!(hash("let x = 1;\n"), hash("let x = 2;\n"));
+}
+
+#[test]
+fn parses_entries() {
+    let toml = r#"# Comment.
+
+[[ignored]]
+path = "tokio/a.md"
+hash = "0123456789abcdef" # The first block.
+reason = "Needs \"the network\"."
+
+[[ignored]]
+reason = 'Literal \strings.'
+hash = "fedcba9876543210"
+path = "tokio/b.md"
+"#;
+
+    assert_eq!(
+        parse(toml),
+        Ok(vec![
+            Exception {
+                path: "tokio/a.md".to_string(),
+                hash: "0123456789abcdef".to_string(),
+                reason: "Needs \"the network\".".to_string(),
+            },
+            Exception {
+                path: "tokio/b.md".to_string(),
+                hash: "fedcba9876543210".to_string(),
+                reason: r"Literal \strings.".to_string(),
+            },
+        ])
+    );
+    assert_eq!(parse("# Nothing yet.\n"), Ok(vec![]));
+}
+
+#[test]
+fn reports_invalid_entries() {
+    let cases = [
+        (
+            "path = \"a.md\"\n",
+            1,
+            "expected `[[ignored]]` before the first key",
+        ),
+        (
+            "[[ignored]]\npath = \"a.md\"\nhash = \"0\"\n",
+            1,
+            "the entry has no `reason`",
+        ),
+        (
+            "[[ignored]]\npath = \"a.md\"\npath = \"b.md\"\n",
+            3,
+            "`path` is set twice",
+        ),
+        (
+            "[[ignored]]\nline = \"3\"\n",
+            2,
+            "unknown key `line`; expected `path`, `hash` or `reason`",
+        ),
+        ("[[ignored]]\npath = a.md\n", 2, "expected a string"),
+        (
+            "[[ignored]]\npath = \"a.md\n",
+            2,
+            "the string is never closed",
+        ),
+        (
+            "[[ignored]]\npath = \"a.md\" \"b.md\"\n",
+            2,
+            "unexpected `\"b.md\"` after the string",
+        ),
+        (
+            "[ignored]\n",
+            1,
+            "expected `[[ignored]]` or `key = \"value\"`",
+        ),
+    ];
+
+    for (toml, line, reason) in cases.iter() {
+        assert_eq!(
+            parse(toml),
+            Err(Invalid {
+                line: *line,
+                reason: reason.to_string(),
+            }),
+            "{:?}",
+            toml
+        );
+    }
+}
+
+const PAGE: &str = "```rust,ignore\nlet a = 1;\n```\n\n```rust\nlet b = 2;\n```\n";
+
+#[test]
+fn listed_blocks_pass() {
+    let found = ignored("tokio/a.md", PAGE);
+    let exceptions = [exception("tokio/a.md", &found[0].hash)];
+
+    assert_eq!(check(&exceptions, &["tokio/a.md".to_string()], &found), []);
+}
+
+#[test]
+fn reports_unlisted_blocks() {
+    let found = ignored("tokio/a.md", PAGE);
+
+    assert_eq!(
+        check(&[], &["tokio/a.md".to_string()], &found),
+        [Problem::Unlisted(found[0].clone())]
+    );
+
+    // The same code in another file needs its own entry.
+    let exceptions = [exception("tokio/b.md", &found[0].hash)];
+    assert_eq!(
+        check(&exceptions, &["tokio/a.md".to_string()], &found),
+        [Problem::Unlisted(found[0].clone())]
+    );
+}
+
+#[test]
+fn entries_survive_blocks_moving() {
+    let exceptions = [exception(
+        "tokio/a.md",
+        &ignored("tokio/a.md", PAGE)[0].hash,
+    )];
+
+    let moved = format!("```rust\nfn first() {{}}\n```\n\n{}", PAGE);
+    let found = ignored("tokio/a.md", &moved);
+    assert_eq!(found[0].line, 5);
+
+    assert_eq!(check(&exceptions, &["tokio/a.md".to_string()], &found), []);
+}
+
+#[test]
+fn reports_stale_entries() {
+    let exceptions = [
+        exception("tokio/a.md", &ignored("tokio/a.md", PAGE)[0].hash),
+        exception("blog/old.md", "0123456789abcdef"),
+    ];
+
+    // The block changed, so its entry is stale and the new code unlisted.
+    let changed = PAGE.replace("let a = 1;", "let a = 3;");
+    let found = ignored("tokio/a.md", &changed);
+
+    // `blog/old.md` wasn't processed, so its entry is left alone.
+    assert_eq!(
+        check(&exceptions, &["tokio/a.md".to_string()], &found),
+        [
+            Problem::Unlisted(found[0].clone()),
+            Problem::Stale(exceptions[0].clone()),
+        ]
+    );
+
+    // Without any `ignore` block left.
+    let fixed = PAGE.replace("rust,ignore", "rust");
+    assert_eq!(
+        check(
+            &exceptions,
+            &["tokio/a.md".to_string()],
+            &ignored("tokio/a.md", &fixed)
+        ),
+        [Problem::Stale(exceptions[0].clone())]
+    );
+}
+
+#[test]
+fn problems_say_what_to_do() {
+    let block = Ignored {
+        path: "tokio/a.md".to_string(),
+        line: 3,
+        hash: "0123456789abcdef".to_string(),
+    };
+
+    assert_eq!(
+        Problem::Unlisted(block).to_string(),
+        "tokio/a.md:3: `ignore` block without an entry; make it compile, or add this to \
+         ignored.toml with the reason it can't:\n\n\
+         [[ignored]]\npath = \"tokio/a.md\"\nhash = \"0123456789abcdef\"\nreason = \"...\"\n"
+    );
+    assert_eq!(
+        Problem::Stale(exception("tokio/a.md", "0123456789abcdef")).to_string(),
+        "tokio/a.md: stale exception: no `ignore` block has the hash 0123456789abcdef; \
+         remove the entry from ignored.toml, or update the hash if the block changed"
+    );
+}
@@ -0,0 +1,42 @@
+use doc_test::gen::{fence, labels};
+
+fn unlabelled(markdown: &str) -> Vec<usize> {
+    labels::unlabelled(markdown, &fence::parse(markdown).unwrap())
+}
+
+#[test]
+fn labelled_blocks_pass() {
+    let markdown =
+        "```rust\nfn main() {}\n```\n\n```text\noutput\n```\n\n~~~toml\n[dependencies]\n~~~\n";
+
+    assert!(unlabelled(markdown).is_empty());
+}
+
+#[test]
+fn reports_unlabelled_blocks() {
+    let markdown = "# Title\n\n```\nfn main() {}\n```\n\n```rust\n```\n\n~~~\nlet x = 1;\n~~~\n";
+
+    assert_eq!(unlabelled(markdown), [3, 10]);
+}
+
+#[test]
+fn allowlisted_blocks_pass() {
+    let markdown = "\
+<!-- doc-test: allow-unlabelled -->
+
+```
++------+     +------+
+| task | --> | task |
++------+     +------+
+```
+
+<!-- doc-test: allow-unlabelled -->
+Some text in between.
+
+~~~
+not allowed
+~~~
+";
+
+    assert_eq!(unlabelled(markdown), [12]);
+}
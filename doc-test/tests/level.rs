@@ -0,0 +1,572 @@
+// Only what `level` needs from `fence` is used here.
+
+use doc_test::gen::level::{sanitize_ident, Error, Level, Mode};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILES: &[&str] = &[
+    "tokio/tutorial/spawning.md",
+    "tokio/glossary.md",
+    "tokio/topics/bridging.md",
+    "tokio/tutorial/async.md",
+    "tokio/topics/tracing.md",
+    "tokio/tutorial/hello-tokio.md",
+];
+
+fn generate(order: &[usize]) -> String {
+    let mut level = Level::new();
+
+    for &i in order {
+        let rel: Vec<_> = FILES[i].split('/').collect();
+        level.insert(
+            PathBuf::from("/content").join(FILES[i]),
+            PathBuf::from("/content").join(FILES[i]),
+            &rel,
+            String::new(),
+        );
+    }
+
+    level.render(Mode::Include).unwrap()
+}
+
+#[test]
+fn output_does_not_depend_on_insertion_order() {
+    let expected = generate(&[0, 1, 2, 3, 4, 5]);
+
+    for order in [[5, 4, 3, 2, 1, 0], [3, 0, 5, 1, 4, 2], [1, 3, 5, 0, 2, 4]].iter() {
+        assert_eq!(generate(order), expected);
+    }
+}
+
+#[test]
+fn modules_and_files_are_sorted() {
+    let expected = r#"pub mod tokio {
+    pub mod topics {
+        #[doc = include_str!("/content/tokio/topics/bridging.md")]
+        pub fn bridging_md() {}
+        #[doc = include_str!("/content/tokio/topics/tracing.md")]
+        pub fn tracing_md() {}
+    }
+    pub mod tutorial {
+        #[doc = include_str!("/content/tokio/tutorial/async.md")]
+        pub fn async_md() {}
+        #[doc = include_str!("/content/tokio/tutorial/hello-tokio.md")]
+        pub fn hello_tokio_md() {}
+        #[doc = include_str!("/content/tokio/tutorial/spawning.md")]
+        pub fn spawning_md() {}
+    }
+    #[doc = include_str!("/content/tokio/glossary.md")]
+    pub fn glossary_md() {}
+}
+"#;
+
+    assert_eq!(generate(&[3, 0, 5, 1, 4, 2]), expected);
+}
+
+#[test]
+fn sanitizes_names() {
+    let cases = [
+        ("spawning", "spawning"),
+        ("hello-tokio", "hello_tokio"),
+        ("v1.0-notes", "v1_0_notes"),
+        ("1-getting-started", "_1_getting_started"),
+        ("2021-update_md", "_2021_update_md"),
+        (
+            "2019-12-mio-v0.7-alpha.1_md",
+            "_2019_12_mio_v0_7_alpha_1_md",
+        ),
+        ("async", "async_"),
+        ("self", "self_"),
+        ("Self", "Self_"),
+        ("try", "try_"),
+        ("async_md", "async_md"),
+        ("with space", "with_space"),
+        ("", "_"),
+    ];
+
+    for (name, expected) in cases.iter() {
+        assert_eq!(sanitize_ident(name), *expected, "{:?}", name);
+    }
+}
+
+#[test]
+fn sanitizes_modules_and_fns() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/1-getting-started/2021-update.md"),
+        PathBuf::from("/content/1-getting-started/2021-update.md"),
+        &["1-getting-started", "2021-update.md"],
+        String::new(),
+    );
+    level.insert(
+        PathBuf::from("/content/mod/type.md"),
+        PathBuf::from("/content/mod/type.md"),
+        &["mod", "type.md"],
+        String::new(),
+    );
+
+    let expected = r#"pub mod _1_getting_started {
+    #[doc = include_str!("/content/1-getting-started/2021-update.md")]
+    pub fn _2021_update_md() {}
+}
+pub mod mod_ {
+    #[doc = include_str!("/content/mod/type.md")]
+    pub fn type_md() {}
+}
+"#;
+
+    assert_eq!(level.render(Mode::Include).unwrap(), expected);
+}
+
+#[test]
+fn reports_duplicate_fns() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/hello-tokio.md"),
+        PathBuf::from("/content/hello-tokio.md"),
+        &["hello-tokio.md"],
+        String::new(),
+    );
+    level.insert(
+        PathBuf::from("/content/hello_tokio.md"),
+        PathBuf::from("/content/hello_tokio.md"),
+        &["hello_tokio.md"],
+        String::new(),
+    );
+
+    assert_eq!(
+        level.render(Mode::Include),
+        Err(Error::Duplicate {
+            ident: "hello_tokio_md".to_string(),
+            first: "/content/hello-tokio.md".to_string(),
+            second: "/content/hello_tokio.md".to_string(),
+        })
+    );
+}
+
+#[test]
+fn reports_duplicate_nested_fns() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/tokio/topics/graceful-shutdown.md"),
+        PathBuf::from("/out/tokio/topics/graceful-shutdown.md"),
+        &["tokio", "topics", "graceful-shutdown.md"],
+        "```rust\nfn main() {}\n```\n".to_string(),
+    );
+    level.insert(
+        PathBuf::from("/content/tokio/topics/graceful_shutdown.md"),
+        PathBuf::from("/out/tokio/topics/graceful_shutdown.md"),
+        &["tokio", "topics", "graceful_shutdown.md"],
+        "```rust\nfn main() {}\n```\n".to_string(),
+    );
+
+    for mode in [Mode::Include, Mode::Blocks] {
+        let err = level.render(mode).unwrap_err().to_string();
+        assert!(
+            err.contains("/content/tokio/topics/graceful-shutdown.md"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("/content/tokio/topics/graceful_shutdown.md"),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn reports_duplicate_modules() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/a-b/x.md"),
+        PathBuf::from("/content/a-b/x.md"),
+        &["a-b", "x.md"],
+        String::new(),
+    );
+    level.insert(
+        PathBuf::from("/content/a.b/y.md"),
+        PathBuf::from("/content/a.b/y.md"),
+        &["a.b", "y.md"],
+        String::new(),
+    );
+
+    let err = level.render(Mode::Include).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`/content/a-b` and `/content/a.b` would both generate an item named `a_b`; \
+         rename one of them"
+    );
+}
+
+const CHAPTER: &str = r#"# Spawning
+
+```rust
+fn one() {}
+```
+
+Some text with ```inline``` code.
+
+```text
+not rust
+```
+
+```rust,no_run
+fn three() {}
+```
+"#;
+
+#[test]
+fn one_item_per_rust_block() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/spawning.md"),
+        PathBuf::from("/content/spawning.md"),
+        &["spawning.md"],
+        CHAPTER.to_string(),
+    );
+
+    let expected = r#"/// From /content/spawning.md:3
+///
+#[doc = "```rust\nfn one() {}\n```"]
+pub fn spawning_block_1_md() {}
+/// From /content/spawning.md:13
+///
+#[doc = "```rust,no_run\nfn three() {}\n```"]
+pub fn spawning_block_3_md() {}
+"#;
+
+    assert_eq!(level.render(Mode::Blocks).unwrap(), expected);
+}
+
+const ERAS: &str = r#"# Streams
+
+```rust
+fn current() {}
+```
+
+```rust,era=tokio-0.2
+fn old() {}
+```
+"#;
+
+#[test]
+fn routes_blocks_by_era() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/streams.md"),
+        PathBuf::from("/content/streams.md"),
+        &["streams.md"],
+        ERAS.to_string(),
+    );
+
+    assert_eq!(
+        level.render(Mode::Blocks).unwrap(),
+        r#"/// From /content/streams.md:3
+///
+#[doc = "```rust\nfn current() {}\n```"]
+pub fn streams_block_1_md() {}
+"#
+    );
+
+    // The numbering is the same in every crate.
+    assert_eq!(
+        level.render(Mode::Era("tokio-0.2")).unwrap(),
+        r#"/// From /content/streams.md:7
+///
+#[doc = "```rust\nfn old() {}\n```"]
+pub fn streams_block_2_md() {}
+"#
+    );
+
+    assert_eq!(level.render(Mode::Era("tokio-0.1")).unwrap(), "");
+}
+
+const SNIPPETS: &str = r#"# Shared state
+
+```rust
+# use std::sync::Mutex;
+let data = Mutex::new(0);
+```
+
+```rust,compile_fail
+let data: i32 = "no";
+```
+
+```rust,no_run,allow=clippy::await_holding_lock
+async fn f() {}
+fn main() {}
+```
+"#;
+
+#[test]
+fn snippets_are_modules() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/shared-state.md"),
+        PathBuf::from("/content/shared-state.md"),
+        &["shared-state.md"],
+        SNIPPETS.to_string(),
+    );
+
+    let expected = r#"/// From /content/shared-state.md:3
+pub mod shared_state_block_1_md {
+fn main() {
+use std::sync::Mutex;
+let data = Mutex::new(0);
+}
+}
+/// From /content/shared-state.md:12
+#[allow(clippy::await_holding_lock)]
+pub mod shared_state_block_3_md {
+async fn f() {}
+fn main() {}
+}
+"#;
+
+    assert_eq!(level.render(Mode::Snippets).unwrap(), expected);
+}
+
+const CLIENT: &str = r#"# Client
+
+```rust,needs-server
+use mini_redis::client;
+
+#[tokio::main]
+async fn main() {
+    client::connect("127.0.0.1:6379").await.unwrap();
+}
+```
+
+```rust
+let not_a_client = 1;
+```
+"#;
+
+#[test]
+fn server_blocks_are_tests() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/client.md"),
+        PathBuf::from("/content/client.md"),
+        &["client.md"],
+        CLIENT.to_string(),
+    );
+
+    let expected = r#"/// From /content/client.md:3
+pub mod client_block_1_md {
+use mini_redis::client;
+
+#[tokio::main]
+async fn main() {
+    client::connect(DOC_TEST_REDIS_ADDR.get()).await.unwrap();
+}
+
+static DOC_TEST_REDIS_ADDR: doc_test::redis::Addr = doc_test::redis::Addr::new();
+
+#[test]
+fn runs() {
+    doc_test::redis::run(&DOC_TEST_REDIS_ADDR, main);
+}
+}
+"#;
+
+    assert_eq!(level.render(Mode::Server).unwrap(), expected);
+}
+
+#[test]
+fn reports_server_blocks_without_the_address() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/client.md"),
+        PathBuf::from("/content/client.md"),
+        &["client.md"],
+        "```rust,needs-server\nfn main() {}\n```\n".to_string(),
+    );
+
+    assert_eq!(
+        level.render(Mode::Server),
+        Err(Error::Directive {
+            path: "/content/client.md".to_string(),
+            line: 1,
+            reason: "the block needs a server, but never uses \"127.0.0.1:6379\"".to_string(),
+        })
+    );
+}
+
+#[test]
+fn reports_unterminated_block() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/broken.md"),
+        PathBuf::from("/content/broken.md"),
+        &["broken.md"],
+        "text\n\n```rust\nfn main() {}\n".to_string(),
+    );
+
+    let err = level.render(Mode::Blocks).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "/content/broken.md:3: code block is never closed"
+    );
+}
+
+#[test]
+fn includes_the_cleaned_copy() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/tokio/glossary.md"),
+        PathBuf::from("/out/content/tokio/glossary.md"),
+        &["glossary.md"],
+        String::new(),
+    );
+
+    assert_eq!(
+        level.render(Mode::Include).unwrap(),
+        "#[doc = include_str!(\"/out/content/tokio/glossary.md\")]\npub fn glossary_md() {}\n"
+    );
+}
+
+/// The string passed to each `include_str!` in `code`, which must parse as
+/// Rust.
+fn included(code: &str) -> Vec<String> {
+    let file = syn::parse_file(code).unwrap();
+    let mut paths = vec![];
+
+    for item in file.items {
+        let syn::Item::Fn(item) = item else { continue };
+
+        for attr in item.attrs {
+            let syn::Meta::NameValue(doc) = attr.meta else {
+                continue;
+            };
+            let syn::Expr::Macro(include) = doc.value else {
+                continue;
+            };
+            let lit: syn::LitStr = include.mac.parse_body().unwrap();
+            paths.push(lit.value());
+        }
+    }
+
+    paths
+}
+
+#[test]
+fn windows_paths_generate_valid_code() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from(r"\\?\C:\tokio\content\tokio\new.md"),
+        PathBuf::from(r"\\?\C:\target\out\tokio\new.md"),
+        &["new.md"],
+        "```rust\nfn main() {}\n```\n".to_string(),
+    );
+    level.insert(
+        PathBuf::from(r"C:\tokio\content\tokio\tutorial.md"),
+        PathBuf::from(r"C:\target\out\tokio\tutorial.md"),
+        &["tutorial.md"],
+        String::new(),
+    );
+
+    let code = level.render(Mode::Include).unwrap();
+    assert_eq!(
+        included(&code),
+        [
+            "C:/target/out/tokio/tutorial.md",
+            "C:/target/out/tokio/new.md"
+        ]
+    );
+
+    let code = level.render(Mode::Blocks).unwrap();
+    syn::parse_file(&code).unwrap();
+    assert!(code.contains("/// From C:/tokio/content/tokio/new.md:1\n"));
+}
+
+#[test]
+fn applies_preludes() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/tokio/spawning.md"),
+        PathBuf::from("/content/tokio/spawning.md"),
+        &["spawning.md"],
+        "<!-- doc-test: prelude=tokio_main -->\n```rust\nlet x = 1;\n```\n".to_string(),
+    );
+
+    let code = level.render(Mode::Blocks).unwrap();
+    assert!(
+        code.contains(
+            r##"#[doc = "```rust\n# #[tokio::main]\n# async fn main() {\nlet x = 1;\n# }\n```"]"##
+        ),
+        "{}",
+        code
+    );
+
+    // The whole file is included as is.
+    let code = level.render(Mode::Include).unwrap();
+    assert!(!code.contains("tokio::main"));
+}
+
+#[test]
+fn reports_invalid_preludes() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/tokio/spawning.md"),
+        PathBuf::from("/content/tokio/spawning.md"),
+        &["spawning.md"],
+        "text\n<!-- doc-test: prelude=main -->\n```rust\n```\n".to_string(),
+    );
+
+    assert_eq!(
+        level.render(Mode::Blocks).unwrap_err().to_string(),
+        "/content/tokio/spawning.md:2: unknown prelude `main`, expected one of tokio_main, \
+         mini_redis_client, no_main"
+    );
+}
+
+#[test]
+fn gates_blocks_on_features() {
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/tokio/framing.md"),
+        PathBuf::from("/content/tokio/framing.md"),
+        &["framing.md"],
+        "```rust,no_run,requires=tokio-util\nuse tokio_util::codec::LinesCodec;\n```\n".to_string(),
+    );
+
+    let expected = r#"/// From /content/tokio/framing.md:1
+///
+#[doc = "```rust,no_run\nuse tokio_util::codec::LinesCodec;\n```"]
+#[cfg(feature = "tokio-util")]
+pub fn framing_block_1_md() {}
+"#;
+
+    let code = level.render(Mode::Blocks).unwrap();
+    assert_eq!(code, expected);
+    syn::parse_file(&code).unwrap();
+}
+
+#[test]
+fn continued_blocks_are_one_unit() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/units/delay.md");
+    let mut level = Level::new();
+    level.insert(
+        PathBuf::from("/content/delay.md"),
+        PathBuf::from("/content/delay.md"),
+        &["delay.md"],
+        fs::read_to_string(path).unwrap(),
+    );
+
+    let code = level.render(Mode::Snippets).unwrap();
+    syn::parse_file(&code).unwrap();
+    assert!(code.starts_with(
+        "/// From /content/delay.md:9\npub mod delay_block_1_md {\nfn main() {\nuse std::time::Instant;\n"
+    ));
+    assert!(code
+        .contains("/// From /content/delay.md:49\npub mod delay_block_6_md {\nfn main() {}\n}\n"));
+    assert_eq!(code.matches("pub mod").count(), 2);
+
+    let code = level.render(Mode::Blocks).unwrap();
+    syn::parse_file(&code).unwrap();
+    assert!(code.contains("pub fn delay_block_1_md() {}"));
+    assert!(code.contains("pub fn delay_block_6_md() {}"));
+    assert_eq!(code.matches("pub fn").count(), 2);
+}
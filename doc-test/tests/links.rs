@@ -0,0 +1,422 @@
+//! Checks the links between the pages of the website.
+//!
+//! Every link in `content/` is resolved like a browser would from the page
+//! it is on. Links to the site must reach a page, or a file in `public/`,
+//! and a `#fragment` must be the id of one of the page's headings. Links to
+//! other sites are only checked with `ONLINE=1`, as that needs the network.
+
+use doc_test::gen::{clean, files, paths};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Pages that aren't generated from `content/`.
+const ROUTES: &[&str] = &["/", "/blog"];
+
+/// A link on a page.
+#[derive(Debug, PartialEq)]
+struct Link {
+    line: usize,
+    dest: String,
+}
+
+/// What a page links to, and the ids of its headings.
+#[derive(Debug, Default)]
+struct Page {
+    links: Vec<Link>,
+    anchors: HashSet<String>,
+}
+
+/// Where a link goes.
+#[derive(Debug, PartialEq)]
+enum Target {
+    /// A path on the website, and the fragment if there is one.
+    Site {
+        path: String,
+        fragment: Option<String>,
+    },
+
+    /// Another website, or a scheme like `mailto:`.
+    External(String),
+}
+
+/// The ids the website gives headings, the way `github-slugger` makes them:
+/// lowercase, punctuation removed, and a number appended to repeats.
+#[derive(Default)]
+struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    fn slug(&mut self, text: &str) -> String {
+        let original = slug(text);
+        let mut slug = original.clone();
+
+        while self.occurrences.contains_key(&slug) {
+            let n = self.occurrences.get_mut(&original).unwrap();
+            *n += 1;
+            slug = format!("{}-{}", original, n);
+        }
+
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// The slug of a heading, without making it unique.
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|&c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// The links and heading ids of a markdown page.
+fn parse(markdown: &str) -> Page {
+    // Front matter and comments aren't rendered, and the front matter would
+    // read as a heading.
+    let markdown = clean::clean(markdown);
+    let line = |offset: usize| markdown[..offset].matches('\n').count() + 1;
+
+    let mut page = Page::default();
+    let mut slugger = Slugger::default();
+    let mut heading: Option<String> = None;
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    for (event, range) in Parser::new_ext(&markdown, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link { dest_url, .. })
+            | Event::Start(Tag::Image { dest_url, .. }) => {
+                page.links.push(Link {
+                    line: line(range.start),
+                    dest: dest_url.to_string(),
+                });
+            }
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = heading.take() {
+                    page.anchors.insert(slugger.slug(text.trim()));
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = &mut heading {
+                    heading.push_str(&text);
+                }
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for (offset, dest) in attributes(&html, &["href", "src"]) {
+                    page.links.push(Link {
+                        line: line(range.start + offset),
+                        dest,
+                    });
+                }
+
+                // Like `<a name="pin">`, for footnotes.
+                for (_, anchor) in attributes(&html, &["id", "name"]) {
+                    page.anchors.insert(anchor);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    page
+}
+
+/// The values of the attributes called `names` in some HTML, and where
+/// they start.
+fn attributes(html: &str, names: &[&str]) -> Vec<(usize, String)> {
+    let mut values = vec![];
+
+    for name in names {
+        let attr = format!(" {}=\"", name);
+        for (start, _) in html.match_indices(&attr) {
+            let value = &html[start + attr.len()..];
+            if let Some(end) = value.find('"') {
+                values.push((start, value[..end].to_string()));
+            }
+        }
+    }
+
+    values.sort();
+    values
+}
+
+/// The path of the page generated from the markdown file at `rel`, relative
+/// to `content/`.
+fn url(rel: &str) -> String {
+    let path = rel.strip_suffix(".md").unwrap_or(rel);
+    let path = path.strip_suffix("/index").unwrap_or(path);
+    format!("/{}", path)
+}
+
+/// Resolve `dest` from the page at `page`, like a browser would.
+fn resolve(page: &str, dest: &str) -> Target {
+    let scheme = dest.split_once(':').map(|(scheme, _)| scheme);
+    if dest.starts_with("//")
+        || scheme.is_some_and(|scheme| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        })
+    {
+        return Target::External(dest.to_string());
+    }
+
+    let (dest, fragment) = match dest.split_once('#') {
+        Some((dest, fragment)) => (dest, Some(fragment.to_string())),
+        None => (dest, None),
+    };
+    let dest = dest.split('?').next().unwrap();
+
+    let joined = if dest.is_empty() {
+        page.to_string()
+    } else if dest.starts_with('/') {
+        dest.to_string()
+    } else {
+        // Relative to the "directory" of the page, which is everything up
+        // to the last `/`.
+        let dir = &page[..page.rfind('/').unwrap() + 1];
+        format!("{}{}", dir, dest)
+    };
+
+    let mut parts: Vec<&str> = vec![];
+    for part in joined.split('/').skip(1) {
+        match part {
+            "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    // A trailing slash names the same page.
+    while parts.last() == Some(&"") {
+        parts.pop();
+    }
+
+    Target::Site {
+        path: format!("/{}", parts.join("/")),
+        fragment,
+    }
+}
+
+/// Check every link in `content`, with the static files in `public`.
+/// Returns the broken links as `path:line: message`, and the external links.
+fn check(content: &Path, public: &Path) -> (Vec<String>, BTreeSet<String>) {
+    let content = files::content_dir(content).unwrap();
+    let (found, _) = files::markdown_files(&content);
+
+    let mut pages = HashMap::new();
+    for path in &found {
+        let rel = paths::normalize(path.strip_prefix(&content).unwrap());
+        let page = parse(&fs::read_to_string(path).unwrap());
+        pages.insert(url(&rel), (rel, page));
+    }
+
+    let mut broken = vec![];
+    let mut external = BTreeSet::new();
+
+    let mut urls: Vec<_> = pages.keys().collect();
+    urls.sort();
+
+    for url in urls {
+        let (rel, page) = &pages[url];
+
+        for link in &page.links {
+            let (path, fragment) = match resolve(url, &link.dest) {
+                Target::External(dest) => {
+                    external.insert(dest);
+                    continue;
+                }
+                Target::Site { path, fragment } => (path, fragment),
+            };
+
+            let error = match pages.get(&path) {
+                Some((_, target)) => match fragment {
+                    Some(fragment)
+                        if !fragment.is_empty() && !target.anchors.contains(&fragment) =>
+                    {
+                        Some(format!("`{}` has no heading `#{}`", path, fragment))
+                    }
+                    _ => None,
+                },
+                None if ROUTES.contains(&&path[..]) => None,
+                None if public.join(&path[1..]).is_file() => None,
+                None => Some(format!("`{}` isn't a page or a file", path)),
+            };
+
+            if let Some(error) = error {
+                broken.push(format!("{}:{}: {}: {}", rel, link.line, link.dest, error));
+            }
+        }
+    }
+
+    (broken, external)
+}
+
+/// Whether `url` answers without an error status.
+fn reachable(url: &str) -> bool {
+    // Some servers don't implement HEAD, so the page is fetched.
+    Command::new("curl")
+        .args(["--silent", "--location", "--fail", "--max-time", "30"])
+        .args(["--output", if cfg!(windows) { "NUL" } else { "/dev/null" }])
+        .arg(url)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn slugs() {
+    assert_eq!(slug("Hello Tokio"), "hello-tokio");
+    assert_eq!(slug("What is `async`?"), "what-is-async");
+    assert_eq!(slug("Send + Sync, and 'static"), "send--sync-and-static");
+    assert_eq!(slug("snake_case & kebab-case"), "snake_case--kebab-case");
+    assert_eq!(slug("Ünïcode"), "ünïcode");
+}
+
+#[test]
+fn repeated_headings() {
+    let mut slugger = Slugger::default();
+
+    assert_eq!(slugger.slug("Example"), "example");
+    assert_eq!(slugger.slug("Example"), "example-1");
+    assert_eq!(slugger.slug("Example"), "example-2");
+    assert_eq!(slugger.slug("Example 1"), "example-1-1");
+}
+
+#[test]
+fn page_urls() {
+    assert_eq!(
+        url("tokio/tutorial/spawning.md"),
+        "/tokio/tutorial/spawning"
+    );
+    assert_eq!(url("tokio/tutorial/index.md"), "/tokio/tutorial");
+    assert_eq!(url("blog/2020-12-tokio-1-0.md"), "/blog/2020-12-tokio-1-0");
+}
+
+#[test]
+fn resolves_links() {
+    let site = |path: &str, fragment: Option<&str>| Target::Site {
+        path: path.to_string(),
+        fragment: fragment.map(str::to_string),
+    };
+    let page = "/tokio/tutorial/spawning";
+
+    assert_eq!(
+        resolve(page, "/tokio/glossary"),
+        site("/tokio/glossary", None)
+    );
+    assert_eq!(
+        resolve(page, "shared-state"),
+        site("/tokio/tutorial/shared-state", None)
+    );
+    assert_eq!(
+        resolve(page, "./channels/"),
+        site("/tokio/tutorial/channels", None)
+    );
+    assert_eq!(
+        resolve(page, "../glossary#task"),
+        site("/tokio/glossary", Some("task"))
+    );
+    assert_eq!(resolve(page, "#tasks"), site(page, Some("tasks")));
+    assert_eq!(resolve(page, "/img/a.png?v=2"), site("/img/a.png", None));
+
+    // An index page has no trailing slash, so its links resolve from its
+    // parent.
+    assert_eq!(
+        resolve("/tokio/tutorial", "spawning"),
+        site("/tokio/spawning", None)
+    );
+
+    for dest in [
+        "https://docs.rs/tokio",
+        "mailto:team@tokio.rs",
+        "//tokio.rs",
+    ] {
+        assert_eq!(resolve(page, dest), Target::External(dest.to_string()));
+    }
+}
+
+#[test]
+fn parses_links_and_headings() {
+    let page = parse(
+        "---\ntitle: Page\n---\n\n# Intro\n\nSee [the glossary](/tokio/glossary#task) and\n\
+         [a post][post].\n\n## The `select!` macro\n\n<a href=\"#intro\">top</a> <a name=\"note\">1</a>\n\n\
+         ```rust\n// [not a link](/nowhere)\n```\n\n[post]: /blog/post\n",
+    );
+
+    assert_eq!(
+        page.links,
+        [
+            Link {
+                line: 7,
+                dest: "/tokio/glossary#task".to_string()
+            },
+            Link {
+                line: 8,
+                dest: "/blog/post".to_string()
+            },
+            Link {
+                line: 12,
+                dest: "#intro".to_string()
+            },
+        ]
+    );
+
+    let mut anchors: Vec<_> = page.anchors.iter().map(String::as_str).collect();
+    anchors.sort_unstable();
+    assert_eq!(anchors, ["intro", "note", "the-select-macro"]);
+}
+
+#[test]
+fn reports_broken_links() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/links");
+    let (broken, external) = check(&fixtures.join("content"), &fixtures.join("public"));
+
+    assert_eq!(
+        broken,
+        [
+            "tokio/tutorial/index.md:9: spawning: `/tokio/spawning` isn't a page or a file",
+            "tokio/tutorial/spawning.md:8: /tokio/tutorial/shared-state: \
+             `/tokio/tutorial/shared-state` isn't a page or a file",
+            "tokio/tutorial/spawning.md:10: channels#missing: \
+             `/tokio/tutorial/channels` has no heading `#missing`",
+            "tokio/tutorial/spawning.md:12: /img/missing.png: `/img/missing.png` isn't a page or a file",
+        ]
+    );
+    assert_eq!(
+        external.into_iter().collect::<Vec<_>>(),
+        ["https://docs.rs/tokio"]
+    );
+}
+
+#[test]
+fn content_links_resolve() {
+    let home = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (broken, external) = check(&home.join("../content"), &home.join("../public"));
+
+    let mut errors = broken;
+
+    if env::var("ONLINE").as_deref() == Ok("1") {
+        for url in external.iter().filter(|url| url.starts_with("http")) {
+            if !reachable(url) {
+                errors.push(format!("{}: unreachable", url));
+            }
+        }
+    }
+
+    assert!(
+        errors.is_empty(),
+        "{} broken links:\n    {}",
+        errors.len(),
+        errors.join("\n    ")
+    );
+}
@@ -0,0 +1,326 @@
+//! Checks the navigation against the pages in `content/`.
+//!
+//! The menu is the `menu` object in `pages/[...slug].jsx`, listing the pages
+//! in order; front matter has no say in it. Every page the menu lists must
+//! have a markdown file, and every markdown file under a root of the menu
+//! must be listed, or no link on the website leads to it.
+
+use doc_test::gen::{files, paths};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The part of a JavaScript object literal the menu is written in.
+#[derive(Debug, PartialEq)]
+enum Value {
+    Str(String),
+    List(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a JavaScript value: strings, arrays, and objects with identifiers
+/// or strings as keys. Trailing commas and comments are fine.
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                self.pos += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn eat(&mut self, token: char) -> bool {
+        self.skip_space();
+        if self.rest().starts_with(token) {
+            self.pos += token.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: char) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", token)))
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        let line = self.src[..self.pos].lines().count().max(1);
+        format!("line {}: {}", line, msg)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_space();
+        match self.rest().chars().next() {
+            Some('"') | Some('\'') => self.string().map(Value::Str),
+            Some('[') => self.list(),
+            Some('{') => self.object(),
+            _ => Err(self.error("expected a string, an array or an object")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.rest().chars().next().unwrap();
+        let rest = &self.rest()[1..];
+        match rest.find([quote, '\\', '\n']) {
+            Some(end) if rest[end..].starts_with(quote) => {
+                self.pos += end + 2;
+                Ok(rest[..end].to_string())
+            }
+            _ => Err(self.error("expected a string without escapes")),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        self.skip_space();
+        let rest = self.rest();
+        if rest.starts_with(['"', '\'']) {
+            return self.string();
+        }
+
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a key"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn list(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut items = vec![];
+        while !self.eat(']') {
+            items.push(self.value()?);
+            if !self.eat(',') {
+                self.expect(']')?;
+                break;
+            }
+        }
+        Ok(Value::List(items))
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut entries = vec![];
+        while !self.eat('}') {
+            let key = self.key()?;
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(Value::Object(entries))
+    }
+}
+
+/// The value of `const menu = ...` in the page source `jsx`.
+fn menu(jsx: &str) -> Result<Value, String> {
+    let start = jsx.find("const menu =").ok_or("no `const menu = ...`")?;
+    let mut reader = Reader {
+        src: jsx,
+        pos: start + "const menu =".len(),
+    };
+    reader.value()
+}
+
+/// The paths of the pages in `menu`, the way `collectPaths` in `lib/api.js`
+/// lists them: every entry without an `href`, and its `nested` entries.
+fn pages(menu: &Value, prefix: &str, out: &mut Vec<String>) -> Result<(), String> {
+    let entries = match menu {
+        Value::Object(entries) => entries,
+        _ => return Err(format!("`{}` isn't an object", prefix)),
+    };
+
+    for (key, entry) in entries {
+        let path = format!("{}/{}", prefix, key);
+        if entry.get("href").is_none() {
+            out.push(path.clone());
+        }
+
+        match entry.get("nested") {
+            Some(Value::List(children)) => {
+                for child in children {
+                    match child {
+                        Value::Str(child) => out.push(format!("{}/{}", path, child)),
+                        _ => return Err(format!("`{}.nested` lists a non-string", path)),
+                    }
+                }
+            }
+            Some(nested) => pages(nested, &path, out)?,
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The markdown file of the page at `path`, the way `loadPage` in
+/// `lib/api.js` finds it.
+fn page_file(content: &Path, path: &str) -> Option<PathBuf> {
+    let files = [
+        content.join(format!("{}.md", &path[1..])),
+        content.join(&path[1..]).join("index.md"),
+    ];
+    files.iter().find(|file| file.is_file()).cloned()
+}
+
+/// The pages the menu in `jsx` lists without a markdown file, and the
+/// markdown files under the menu's roots it doesn't list.
+fn check(jsx: &Path, content: &Path) -> (Vec<String>, Vec<String>) {
+    let menu = menu(&fs::read_to_string(jsx).unwrap())
+        .unwrap_or_else(|err| panic!("{}: {}", jsx.display(), err));
+    let mut listed = vec![];
+    pages(&menu, "", &mut listed).unwrap_or_else(|err| panic!("{}: {}", jsx.display(), err));
+
+    let content = files::content_dir(content).unwrap();
+
+    let mut missing = vec![];
+    let mut used = BTreeSet::new();
+    for path in &listed {
+        match page_file(&content, path) {
+            Some(file) => {
+                used.insert(file);
+            }
+            None => missing.push(path.clone()),
+        }
+    }
+
+    let mut orphans = vec![];
+    if let Value::Object(roots) = &menu {
+        for (root, _) in roots {
+            let (found, _) = files::markdown_files(&content.join(root));
+            for file in found.into_iter().filter(|file| !used.contains(file)) {
+                orphans.push(paths::normalize(file.strip_prefix(&content).unwrap()));
+            }
+        }
+    }
+    orphans.sort();
+
+    (missing, orphans)
+}
+
+#[test]
+fn parses_object_literals() {
+    let jsx = r#"
+import * as api from "../lib/api";
+
+const menu = {
+  // The first part.
+  tokio: {
+    title: 'Tokio',
+    nested: {
+      "hello-tokio": {},
+      tutorial: { nested: ["setup", "io",], },
+      /* Not a page. */
+      api: { title: "API", href: "https://docs.rs/tokio" },
+    },
+  },
+};
+"#;
+
+    let menu = menu(jsx).unwrap();
+    let mut listed = vec![];
+    pages(&menu, "", &mut listed).unwrap();
+
+    assert_eq!(
+        listed,
+        [
+            "/tokio",
+            "/tokio/hello-tokio",
+            "/tokio/tutorial",
+            "/tokio/tutorial/setup",
+            "/tokio/tutorial/io",
+        ]
+    );
+    assert_eq!(
+        menu.get("tokio").unwrap().get("title"),
+        Some(&Value::Str("Tokio".to_string()))
+    );
+}
+
+#[test]
+fn reports_syntax_errors() {
+    assert_eq!(
+        menu("const menu = {\n  tokio: [\"a\" \"b\"],\n};"),
+        Err("line 2: expected `]`".to_string())
+    );
+    assert_eq!(
+        menu("const menu = { tokio: {} "),
+        Err("line 1: expected `}`".to_string())
+    );
+    assert_eq!(
+        menu("const other = {};"),
+        Err("no `const menu = ...`".to_string())
+    );
+}
+
+#[test]
+fn reports_missing_and_orphan_pages() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/nav");
+    let (missing, orphans) = check(
+        &fixtures.join("pages/[...slug].jsx"),
+        &fixtures.join("content"),
+    );
+
+    assert_eq!(missing, ["/tokio/tutorial/renamed"]);
+    assert_eq!(orphans, ["tokio/tutorial/orphan.md"]);
+}
+
+#[test]
+fn menu_lists_every_page() {
+    let home = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (missing, orphans) = check(
+        &home.join("../pages/[...slug].jsx"),
+        &home.join("../content"),
+    );
+
+    let mut errors = vec![];
+    for path in &missing {
+        errors.push(format!(
+            "{}: listed in the menu, but there is no page",
+            path
+        ));
+    }
+    for file in &orphans {
+        errors.push(format!("{}: not listed in the menu", file));
+    }
+
+    assert!(
+        errors.is_empty(),
+        "the menu in `pages/[...slug].jsx` is out of date:\n    {}",
+        errors.join("\n    ")
+    );
+}
@@ -0,0 +1,66 @@
+use doc_test::gen::paths::{normalize, replace_root};
+use std::path::Path;
+
+#[test]
+fn unix_paths_are_unchanged() {
+    assert_eq!(
+        normalize(Path::new("/root/content/tokio/tutorial/spawning.md")),
+        "/root/content/tokio/tutorial/spawning.md"
+    );
+}
+
+#[test]
+fn backslashes_become_slashes() {
+    assert_eq!(
+        normalize(Path::new(r"C:\tokio\content\tutorial\new.md")),
+        "C:/tokio/content/tutorial/new.md"
+    );
+}
+
+#[test]
+fn verbatim_prefix_is_removed() {
+    assert_eq!(
+        normalize(Path::new(r"\\?\C:\tokio\content\tutorial\new.md")),
+        "C:/tokio/content/tutorial/new.md"
+    );
+    assert_eq!(
+        normalize(Path::new(r"\\?\UNC\server\share\content\tutorial.md")),
+        "//server/share/content/tutorial.md"
+    );
+}
+
+#[test]
+fn roots_are_replaced() {
+    let code = "/// From /tmp/.tmpX1/content/tokio/a.md:3\n\
+                #[doc = include_str!(\"/tmp/.tmpX1/content/tokio/a.md\")]\n";
+
+    assert_eq!(
+        replace_root(code, Path::new("/tmp/.tmpX1/content"), "$CONTENT"),
+        "/// From $CONTENT/tokio/a.md:3\n\
+         #[doc = include_str!(\"$CONTENT/tokio/a.md\")]\n"
+    );
+    assert_eq!(
+        replace_root(code, Path::new("/tmp/.tmpX1/content/"), "$CONTENT"),
+        replace_root(code, Path::new("/tmp/.tmpX1/content"), "$CONTENT")
+    );
+}
+
+#[test]
+fn only_whole_roots_are_replaced() {
+    let code = "/content2/a.md /content/a.md /other/content/a.md";
+
+    assert_eq!(
+        replace_root(code, Path::new("/content"), "$CONTENT"),
+        "/content2/a.md $CONTENT/a.md /other/content/a.md"
+    );
+}
+
+#[test]
+fn windows_roots_are_replaced() {
+    let code = "/// From C:/tokio/content/tutorial/new.md:1\n";
+
+    assert_eq!(
+        replace_root(code, Path::new(r"\\?\C:\tokio\content"), "$CONTENT"),
+        "/// From $CONTENT/tutorial/new.md:1\n"
+    );
+}
@@ -0,0 +1,174 @@
+use doc_test::gen::{fence, prelude};
+use prelude::{assign, Invalid, TEMPLATES};
+
+/// The code rustdoc compiles, and the code it shows, for `code` wrapped with
+/// the template called `name`.
+fn wrap(name: &str, code: &str) -> (String, String) {
+    let template = TEMPLATES.iter().find(|t| t.name == name).unwrap();
+    let wrapped = template.wrap(code);
+
+    let mut compiled = String::new();
+    let mut shown = String::new();
+
+    for line in wrapped.lines() {
+        if line == "#" {
+            compiled.push('\n');
+        } else if let Some(hidden) = line.strip_prefix("# ") {
+            compiled.push_str(hidden);
+            compiled.push('\n');
+        } else {
+            compiled.push_str(line);
+            compiled.push('\n');
+            shown.push_str(line);
+            shown.push('\n');
+        }
+    }
+
+    (compiled, shown)
+}
+
+/// The names of the templates applied to each block of `markdown`.
+fn templates(markdown: &str) -> Result<Vec<Option<&'static str>>, Invalid> {
+    let blocks = fence::parse(markdown).unwrap();
+    let assigned = assign(markdown, &blocks)?;
+    Ok(assigned.iter().map(|t| t.map(|t| t.name)).collect())
+}
+
+#[test]
+fn templates_compile_and_hide_themselves() {
+    let cases = [
+        (
+            "tokio_main",
+            "let listener = tokio::net::TcpListener::bind(\"127.0.0.1:0\").await.unwrap();\n",
+        ),
+        (
+            "mini_redis_client",
+            "client.set(\"hello\", \"world\".into()).await?;\nlet result = client.get(\"hello\").await?;\n",
+        ),
+        (
+            "no_main",
+            "loop {\n    tokio::task::yield_now().await;\n}\n",
+        ),
+    ];
+
+    for (name, code) in cases.iter() {
+        let (compiled, shown) = wrap(name, code);
+
+        // Every template ends up with a `main`, so rustdoc doesn't add one.
+        let file = syn::parse_file(&compiled).unwrap_or_else(|err| panic!("{}: {}", name, err));
+        assert!(
+            file.items
+                .iter()
+                .any(|item| matches!(item, syn::Item::Fn(f) if f.sig.ident == "main")),
+            "{}",
+            name
+        );
+
+        assert_eq!(shown, *code, "{}", name);
+    }
+
+    assert_eq!(TEMPLATES.len(), cases.len());
+}
+
+#[test]
+fn block_directive_applies_to_the_next_block() {
+    let markdown = "\
+<!-- doc-test: prelude=tokio_main -->
+
+```rust
+tokio::task::yield_now().await;
+```
+
+```rust
+fn main() {}
+```
+";
+
+    assert_eq!(templates(markdown), Ok(vec![Some("tokio_main"), None]));
+}
+
+#[test]
+fn file_directive_applies_to_every_rust_block() {
+    let markdown = "\
+<!-- doc-test: file-prelude=tokio_main -->
+
+```rust
+tokio::task::yield_now().await;
+```
+
+```text
+output
+```
+
+<!-- doc-test: prelude=no_main -->
+```rust
+loop {}
+```
+";
+
+    assert_eq!(
+        templates(markdown),
+        Ok(vec![Some("tokio_main"), None, Some("no_main")])
+    );
+}
+
+#[test]
+fn directives_in_code_are_ignored() {
+    let markdown = "```html\n<!-- doc-test: prelude=nope -->\n```\n";
+
+    assert_eq!(templates(markdown), Ok(vec![None]));
+}
+
+#[test]
+fn invalid_directives() {
+    let unknown = "<!-- doc-test: prelude=tokio -->\n```rust\n```\n";
+    assert_eq!(
+        templates(unknown),
+        Err(Invalid {
+            line: 1,
+            reason:
+                "unknown prelude `tokio`, expected one of tokio_main, mini_redis_client, no_main"
+                    .to_string(),
+        })
+    );
+
+    let dangling = "<!-- doc-test: prelude=tokio_main -->\n\nSome text.\n\n```rust\n```\n";
+    assert_eq!(
+        templates(dangling),
+        Err(Invalid {
+            line: 1,
+            reason: "a prelude directive must come right before a code block".to_string(),
+        })
+    );
+
+    let at_end = "```rust\n```\n<!-- doc-test: prelude=tokio_main -->\n";
+    assert_eq!(templates(at_end).unwrap_err().line, 3);
+
+    let on_text = "<!-- doc-test: prelude=tokio_main -->\n```text\n```\n";
+    assert_eq!(
+        templates(on_text),
+        Err(Invalid {
+            line: 2,
+            reason: "a prelude can only be used on a Rust code block".to_string(),
+        })
+    );
+
+    let directive = "<!-- doc-test: skip -->\n```rust\n```\n";
+    assert_eq!(
+        templates(directive).unwrap_err().reason,
+        "unknown directive `skip`"
+    );
+}
+
+#[test]
+fn other_directives_are_skipped() {
+    let markdown = "\
+<!-- doc-test: prelude=tokio_main -->
+<!-- doc-test: allow-unlabelled -->
+```
+tokio::task::yield_now().await;
+```
+";
+
+    assert_eq!(templates(markdown), Ok(vec![Some("tokio_main")]));
+}
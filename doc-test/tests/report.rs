@@ -0,0 +1,53 @@
+use serde_json::Value;
+
+/// Tutorial pages without any Rust in them.
+const NO_CODE: &[&str] = &["tokio/tutorial/index.md", "tokio/tutorial/setup.md"];
+
+fn report() -> Value {
+    let report = include_str!(concat!(env!("OUT_DIR"), "/report.json"));
+    serde_json::from_str(report).unwrap()
+}
+
+#[test]
+fn every_chapter_compiles_something() {
+    let report = report();
+    let files = report["files"].as_array().unwrap();
+
+    let chapters: Vec<_> = files
+        .iter()
+        .filter(|file| {
+            let path = file["path"].as_str().unwrap();
+            path.starts_with("tokio/tutorial/") && !NO_CODE.contains(&path)
+        })
+        .collect();
+
+    // A filtered build only has some of the chapters.
+    if std::env::var_os("DOC_TEST_FILTER").is_none() {
+        assert!(chapters.len() >= 10, "{} chapters", chapters.len());
+    }
+
+    for chapter in chapters {
+        let compiled = chapter["run"].as_u64().unwrap() + chapter["no_run"].as_u64().unwrap();
+        assert!(
+            compiled > 0,
+            "{} has no Rust block that is compiled: {}",
+            chapter["path"],
+            chapter
+        );
+    }
+}
+
+#[test]
+fn counts_are_consistent() {
+    for file in report()["files"].as_array().unwrap() {
+        let count = |key: &str| file[key].as_u64().unwrap();
+
+        assert_eq!(
+            count("rust"),
+            count("run") + count("no_run") + count("compile_fail") + count("ignore"),
+            "{}",
+            file
+        );
+        assert!(count("rust") <= count("blocks"), "{}", file);
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/runner");
+
+    Command::new(env!("CARGO_BIN_EXE_doc-test-runner"))
+        .arg("--content")
+        .arg(&content)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn reports_the_broken_block() {
+    let output = run(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+
+    // The failing block is named up front, by file and line.
+    let failed = stdout.find("failed:\n    tokio/broken.md:13\n");
+    let table = stdout.find("page");
+    assert!(failed.is_some() && failed < table, "{}", stdout);
+
+    assert!(
+        stdout.contains("tokio/broken.md      7  ok\n"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("tokio/broken.md     13  FAILED\n"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("tokio/good.md        5  ok\n"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("2 passed, 1 failed, 0 ignored"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn filters_pages() {
+    let output = run(&["--filter", "*/good.md"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(!stdout.contains("broken.md"), "{}", stdout);
+    assert!(
+        stdout.contains("1 passed, 0 failed, 0 ignored"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn rejects_unknown_arguments() {
+    let output = Command::new(env!("CARGO_BIN_EXE_doc-test-runner"))
+        .arg("--fast")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("unexpected argument `--fast`"));
+}
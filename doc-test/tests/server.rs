@@ -0,0 +1,65 @@
+//! Runs the code blocks marked `needs-server` against a mini-redis server of
+//! their own, and tests how they are turned into tests.
+
+use doc_test::gen::server::{self, NoAddr};
+use doc_test::redis::{self, Addr, Server};
+
+/// The blocks, each with a `runs` test. They are complete programs, which
+/// may well leave some of the code they show unused.
+#[allow(unused, clippy::needless_doctest_main)]
+mod blocks {
+    include!(concat!(env!("OUT_DIR"), "/server.rs"));
+}
+
+#[test]
+fn substitutes_the_address() {
+    let code = "let a = client::connect(\"127.0.0.1:6379\").await?;\n\
+                let b = TcpStream::connect(\"127.0.0.1:6379\"); // \"127.0.0.1:6379\"\n";
+
+    assert_eq!(
+        server::substitute(code).unwrap(),
+        "let a = client::connect(DOC_TEST_REDIS_ADDR.get()).await?;\n\
+         let b = TcpStream::connect(DOC_TEST_REDIS_ADDR.get()); // \"127.0.0.1:6379\"\n"
+    );
+}
+
+#[test]
+fn leaves_other_addresses() {
+    let code = "client::connect(\"127.0.0.1:63790\");\nclient::connect(\"localhost:6379\");\n";
+    assert_eq!(server::substitute(code), Err(NoAddr));
+
+    // Only in a comment, so nothing connects to it.
+    assert_eq!(server::substitute("// \"127.0.0.1:6379\"\n"), Err(NoAddr));
+}
+
+#[test]
+fn servers_are_independent() {
+    let a = Server::start();
+    let b = Server::start();
+    assert_ne!(a.addr(), b.addr());
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut a = mini_redis::client::connect(a.addr()).await.unwrap();
+        let mut b = mini_redis::client::connect(b.addr()).await.unwrap();
+
+        a.set("hello", "world".into()).await.unwrap();
+        assert_eq!(a.get("hello").await.unwrap(), Some("world".into()));
+        assert_eq!(b.get("hello").await.unwrap(), None);
+    });
+}
+
+static ADDR: Addr = Addr::new();
+
+#[tokio::main]
+async fn fails() -> mini_redis::Result<()> {
+    let mut client = mini_redis::client::connect(ADDR.get()).await?;
+    client.publish("nobody", "listening".into()).await?;
+    Err("listening to nobody".into())
+}
+
+#[test]
+#[should_panic(expected = "listening to nobody")]
+fn reports_errors_from_main() {
+    redis::run(&ADDR, fails);
+}
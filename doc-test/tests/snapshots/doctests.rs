@@ -0,0 +1,35 @@
+pub mod tokio {
+    pub mod getting_started {
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:7
+        ///
+        #[doc = "```rust\n# fn main() {\nlet a = 1;\n# let _ = a;\n# }\n```"]
+        pub fn hello_tokio_block_1_md() {}
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:16
+        ///
+        #[doc = "```rust\nfn main() {}\n```"]
+        #[cfg(feature = "blog")]
+        pub fn hello_tokio_block_2_md() {}
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:22
+        ///
+        #[doc = "```rust,compile_fail\nlet a: i32 = \"no\";\n```"]
+        pub fn hello_tokio_block_3_md() {}
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:31
+        ///
+        #[doc = "```rust,no_run\nfn main() {\n    loop {\n        break;\n    }\n}\n```"]
+        pub fn hello_tokio_block_5_md() {}
+        /// From $CONTENT/tokio/getting-started/spawning.md:5
+        ///
+        #[doc = "```rust\nstruct Task;\nfn main() {\n    let _ = Task;\n}\n```"]
+        pub fn spawning_block_1_md() {}
+    }
+    pub mod topics {
+        /// From $CONTENT/tokio/topics/bridging.md:5
+        ///
+        #[doc = "```rust,ignore\nlet rt = tokio::runtime::Runtime::new()?;\n```"]
+        pub fn bridging_block_1_md() {}
+    }
+    /// From $CONTENT/tokio/index.md:7
+    ///
+    #[doc = "```rust\nfn main() {}\n```"]
+    pub fn index_block_1_md() {}
+}
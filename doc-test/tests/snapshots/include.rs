@@ -0,0 +1,14 @@
+pub mod tokio {
+    pub mod getting_started {
+        #[doc = include_str!("$CONTENT/tokio/getting-started/hello-tokio.md")]
+        pub fn hello_tokio_md() {}
+        #[doc = include_str!("$CONTENT/tokio/getting-started/spawning.md")]
+        pub fn spawning_md() {}
+    }
+    pub mod topics {
+        #[doc = include_str!("$CONTENT/tokio/topics/bridging.md")]
+        pub fn bridging_md() {}
+    }
+    #[doc = include_str!("$CONTENT/tokio/index.md")]
+    pub fn index_md() {}
+}
@@ -0,0 +1,38 @@
+pub mod tokio {
+    pub mod getting_started {
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:7
+        pub mod hello_tokio_block_1_md {
+fn main() {
+let a = 1;
+let _ = a;
+}
+        }
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:16
+        #[cfg(feature = "blog")]
+        pub mod hello_tokio_block_2_md {
+fn main() {}
+        }
+        /// From $CONTENT/tokio/getting-started/hello-tokio.md:31
+        #[allow(clippy::never_loop)]
+        pub mod hello_tokio_block_5_md {
+fn main() {
+    loop {
+        break;
+    }
+}
+        }
+        /// From $CONTENT/tokio/getting-started/spawning.md:5
+        pub mod spawning_block_1_md {
+struct Task;
+fn main() {
+    let _ = Task;
+}
+        }
+    }
+    pub mod topics {
+    }
+    /// From $CONTENT/tokio/index.md:7
+    pub mod index_block_1_md {
+fn main() {}
+    }
+}
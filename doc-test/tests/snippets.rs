@@ -0,0 +1,294 @@
+//! Checks that listings taken from `tutorial-code` still match it.
+//!
+//! A comment like
+//!
+//! ```text
+//! <!-- snippet: tutorial-code/spawning/src/bin/chapter.rs#process -->
+//! ```
+//!
+//! right before a code block says the block is the region of that file
+//! between `// [start: process]` and `// [end: process]`. Lines hidden from
+//! the reader with `# ` are left out of the comparison.
+
+use doc_test::gen::{fence, files, paths};
+use std::fs;
+use std::path::Path;
+
+/// A code block that should match a region of a source file.
+#[derive(Debug, PartialEq)]
+struct Reference {
+    /// Line of the code block's opening fence.
+    line: usize,
+
+    /// The source file, relative to the repository.
+    file: String,
+    region: String,
+
+    /// The visible lines of the code block.
+    code: String,
+}
+
+/// Every code block in `markdown` with a `snippet:` comment before it.
+fn references(markdown: &str) -> Result<Vec<Reference>, String> {
+    let blocks =
+        fence::parse(markdown).map_err(|err| format!("line {}: unclosed block", err.line))?;
+    let lines: Vec<_> = markdown.lines().collect();
+    let mut refs = vec![];
+
+    for (i, line) in lines.iter().enumerate() {
+        let target = match line
+            .trim()
+            .strip_prefix("<!-- snippet:")
+            .and_then(|rest| rest.strip_suffix("-->"))
+        {
+            Some(target) => target.trim(),
+            None => continue,
+        };
+
+        let (file, region) = target
+            .split_once('#')
+            .ok_or_else(|| format!("line {}: expected `path#region`, got `{}`", i + 1, target))?;
+
+        // The block must follow, with nothing but blank lines in between.
+        let next = (i + 1..lines.len())
+            .find(|&j| !lines[j].trim().is_empty())
+            .map(|j| j + 1);
+        let block = blocks
+            .iter()
+            .find(|block| Some(block.line) == next)
+            .ok_or_else(|| {
+                format!(
+                    "line {}: snippet comment isn't followed by a code block",
+                    i + 1
+                )
+            })?;
+
+        refs.push(Reference {
+            line: block.line,
+            file: file.to_string(),
+            region: region.to_string(),
+            code: visible(&block.code),
+        });
+    }
+
+    Ok(refs)
+}
+
+/// `code` without the lines rustdoc hides.
+fn visible(code: &str) -> String {
+    let mut dst = String::new();
+
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+        if trimmed == "#" || trimmed.starts_with("# ") {
+            continue;
+        }
+        dst.push_str(line);
+        dst.push('\n');
+    }
+
+    dst
+}
+
+/// The lines of `source` between the `name` markers, without their common
+/// indentation. Markers of other regions inside of it are left out.
+fn extract(source: &str, name: &str) -> Result<String, String> {
+    let start = format!("// [start: {}]", name);
+    let end = format!("// [end: {}]", name);
+
+    let mut lines = source.lines().skip_while(|line| line.trim() != start);
+    if lines.next().is_none() {
+        return Err(format!("no `{}` marker", start));
+    }
+
+    let mut region = vec![];
+    let mut closed = false;
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed == end {
+            closed = true;
+            break;
+        }
+        if trimmed.starts_with("// [start: ") || trimmed.starts_with("// [end: ") {
+            continue;
+        }
+        region.push(line);
+    }
+
+    if !closed {
+        return Err(format!("no `{}` marker", end));
+    }
+
+    let indent = region
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut dst = String::new();
+    for line in region {
+        dst.push_str(line.get(indent..).unwrap_or(""));
+        dst.push('\n');
+    }
+    Ok(dst)
+}
+
+/// A line diff from `expected` to `actual`, `-` and `+` marking the lines
+/// only in one of them.
+fn diff(expected: &str, actual: &str) -> String {
+    let a: Vec<_> = expected.lines().collect();
+    let b: Vec<_> = actual.lines().collect();
+
+    // Length of the longest common subsequence of `a[i..]` and `b[j..]`.
+    let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut dst = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            dst.push_str(&format!("  {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            dst.push_str(&format!("+ {}\n", b[j]));
+            j += 1;
+        } else {
+            dst.push_str(&format!("- {}\n", a[i]));
+            i += 1;
+        }
+    }
+    dst
+}
+
+#[test]
+fn extracts_regions() {
+    let source = "\
+fn before() {}
+
+// [start: outer]
+mod outer {
+    // [start: inner]
+    fn inner() {}
+    // [end: inner]
+}
+// [end: outer]
+";
+
+    assert_eq!(
+        extract(source, "outer").unwrap(),
+        "mod outer {\n    fn inner() {}\n}\n"
+    );
+    assert_eq!(extract(source, "inner").unwrap(), "fn inner() {}\n");
+}
+
+#[test]
+fn missing_markers() {
+    let source = "// [start: open]\nfn main() {}\n";
+
+    assert_eq!(
+        extract(source, "nope"),
+        Err("no `// [start: nope]` marker".to_string())
+    );
+    assert_eq!(
+        extract(source, "open"),
+        Err("no `// [end: open]` marker".to_string())
+    );
+}
+
+#[test]
+fn finds_references() {
+    let markdown = "\
+Text.
+
+<!-- snippet: tutorial-code/spawning/src/bin/chapter.rs#main -->
+
+```rust
+# fn hidden() {}
+fn main() {}
+```
+
+```rust
+fn other() {}
+```
+";
+
+    assert_eq!(
+        references(markdown),
+        Ok(vec![Reference {
+            line: 5,
+            file: "tutorial-code/spawning/src/bin/chapter.rs".to_string(),
+            region: "main".to_string(),
+            code: "fn main() {}\n".to_string(),
+        }])
+    );
+
+    let dangling = "<!-- snippet: a.rs#main -->\nText.\n";
+    assert_eq!(
+        references(dangling),
+        Err("line 1: snippet comment isn't followed by a code block".to_string())
+    );
+}
+
+#[test]
+fn diffs_lines() {
+    assert_eq!(diff("a\nb\nc\n", "a\nx\nc\n"), "  a\n+ x\n- b\n  c\n");
+}
+
+#[test]
+fn snippets_match_tutorial_code() {
+    let repo = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let root = repo.join("content").canonicalize().unwrap();
+
+    let mut checked = 0;
+    let mut failures = String::new();
+
+    for path in files::markdown_files(&root).0 {
+        let markdown = fs::read_to_string(&path).unwrap();
+        let name = paths::normalize(path.strip_prefix(&root).unwrap());
+
+        let refs = match references(&markdown) {
+            Ok(refs) => refs,
+            Err(err) => {
+                failures.push_str(&format!("{}: {}\n", name, err));
+                continue;
+            }
+        };
+
+        for reference in refs {
+            checked += 1;
+
+            let expected = fs::read_to_string(repo.join(&reference.file))
+                .map_err(|err| err.to_string())
+                .and_then(|source| extract(&source, &reference.region));
+
+            match expected {
+                Ok(expected) if expected == reference.code => {}
+                Ok(expected) => failures.push_str(&format!(
+                    "{}:{} differs from {}#{}:\n{}\n",
+                    name,
+                    reference.line,
+                    reference.file,
+                    reference.region,
+                    diff(&expected, &reference.code)
+                )),
+                Err(err) => failures.push_str(&format!(
+                    "{}:{}: {}#{}: {}\n",
+                    name, reference.line, reference.file, reference.region, err
+                )),
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures);
+    assert!(checked > 0, "no snippet comments found");
+}
@@ -0,0 +1,35 @@
+use doc_test::gen::standalone::{items, unhide, visible};
+
+#[test]
+fn shows_hidden_lines() {
+    assert_eq!(
+        unhide(
+            "# use std::io;\n#\nlet x = 1;\n    # indented\n##[derive(Debug)]\n#[derive(Debug)]\n"
+        ),
+        "use std::io;\n\nlet x = 1;\nindented\n#[derive(Debug)]\n#[derive(Debug)]\n"
+    );
+}
+
+#[test]
+fn removes_hidden_lines() {
+    assert_eq!(
+        visible(
+            "# use std::io;\n#\nlet x = 1;\n    # indented\n##[derive(Debug)]\n#[derive(Debug)]\n"
+        ),
+        "let x = 1;\n#[derive(Debug)]\n#[derive(Debug)]\n"
+    );
+}
+
+#[test]
+fn wraps_statements_in_main() {
+    assert_eq!(items("let x = 1;\n"), "fn main() {\nlet x = 1;\n}\n");
+}
+
+#[test]
+fn keeps_programs_with_main() {
+    let code = "# use std::io;\n#[tokio::main]\nasync fn main() {}\n";
+    assert_eq!(
+        items(code),
+        "use std::io;\n#[tokio::main]\nasync fn main() {}\n"
+    );
+}
@@ -0,0 +1,105 @@
+use doc_test::gen::{fence, summary};
+use summary::{Summary, TooManyIgnored};
+
+fn summarize(markdown: &str) -> Summary {
+    Summary::new("tutorial.md", &fence::parse(markdown).unwrap())
+}
+
+#[test]
+fn counts_each_kind() {
+    let markdown = "\
+```rust
+fn main() {}
+```
+
+```rust,no_run
+#[tokio::main]
+async fn main() {}
+```
+
+```rust,compile_fail,E0373
+let v = vec![1];
+std::thread::spawn(|| println!(\"{:?}\", v));
+```
+
+```rust,ignore
+let partial = ...;
+```
+
+```text
+not rust
+```
+";
+
+    let summary = summarize(markdown);
+
+    assert_eq!(
+        summary,
+        Summary {
+            path: "tutorial.md".to_string(),
+            blocks: 5,
+            annotations: ["no_run", "compile_fail", "E0373", "ignore"]
+                .iter()
+                .map(|attr| attr.to_string())
+                .collect(),
+            run: 1,
+            no_run: 1,
+            compile_fail: 1,
+            ignore: 1,
+        }
+    );
+    assert_eq!(
+        summary.to_string(),
+        "tutorial.md: 1 run, 1 no_run, 1 compile_fail, 1 ignore"
+    );
+}
+
+#[test]
+fn ignore_fraction() {
+    let markdown = "```rust\n```\n```rust,ignore\n```\n```rust,ignore\n```\n```text\n```\n";
+    let summary = summarize(markdown);
+
+    // Two of the three Rust blocks; the text block doesn't count.
+    assert_eq!(summary.check(1.0), Ok(()));
+    assert_eq!(summary.check(0.7), Ok(()));
+    assert_eq!(
+        summary.check(0.5),
+        Err(TooManyIgnored {
+            path: "tutorial.md".to_string(),
+            ignored: 2,
+            total: 3,
+            max: 0.5,
+        })
+    );
+    assert_eq!(
+        summary.check(0.5).unwrap_err().to_string(),
+        "tutorial.md: 2 of 3 Rust blocks are `ignore`, more than the allowed 50%"
+    );
+}
+
+#[test]
+fn no_rust_blocks_pass() {
+    let summary = summarize("# Title\n\n```bash\ncargo run\n```\n");
+
+    assert_eq!(summary.total(), 0);
+    assert_eq!(summary.check(0.0), Ok(()));
+}
+
+#[test]
+fn json() {
+    let markdown = "```rust,no_run\n```\n```\n```\n```text\n```\n";
+
+    assert_eq!(
+        summarize(markdown).to_json(),
+        serde_json::json!({
+            "path": "tutorial.md",
+            "blocks": 3,
+            "rust": 2,
+            "run": 1,
+            "no_run": 1,
+            "compile_fail": 0,
+            "ignore": 0,
+            "annotations": ["no_run"],
+        })
+    );
+}
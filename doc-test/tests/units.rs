@@ -0,0 +1,87 @@
+use doc_test::gen::{fence, units};
+use fence::parse;
+use std::fs;
+use std::path::Path;
+use units::{code, group};
+
+fn fixture() -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/units/delay.md");
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn groups_continued_blocks() {
+    let markdown = fixture();
+    let blocks = parse(&markdown).unwrap();
+
+    // The `text` block in the middle stays on its own.
+    assert_eq!(
+        group(&markdown, &blocks).unwrap(),
+        [vec![0, 1, 3, 4], vec![2], vec![5]]
+    );
+}
+
+#[test]
+fn concatenates_code() {
+    let markdown = fixture();
+    let blocks = parse(&markdown).unwrap();
+    let units = group(&markdown, &blocks).unwrap();
+
+    let code = code(&blocks, &units[0]);
+    assert_eq!(
+        code,
+        "use std::time::Instant;\n\nstruct Delay {\n    when: Instant,\n}\n\
+         impl Delay {\n    fn new(when: Instant) -> Delay {\n        Delay { when }\n    }\n\
+         \x20   fn is_elapsed(&self) -> bool {\n        Instant::now() >= self.when\n    }\n\
+         }\n"
+    );
+
+    // Together, the blocks are one valid listing; the impl alone isn't.
+    syn::parse_file(&code).unwrap();
+    assert!(syn::parse_file(&blocks[1].code).is_err());
+}
+
+#[test]
+fn blocks_without_directive_are_alone() {
+    let markdown = "```rust\nfn a() {}\n```\n\n```rust\nfn b() {}\n```\n";
+    let blocks = parse(markdown).unwrap();
+
+    assert_eq!(group(markdown, &blocks).unwrap(), [vec![0], vec![1]]);
+}
+
+#[test]
+fn directive_must_be_right_before_the_block() {
+    let markdown = "```rust\nfn a() {}\n```\n\n<!-- doc-test: continues-previous -->\n\ntext\n\n\
+                    ```rust\nfn b() {}\n```\n";
+    let blocks = parse(markdown).unwrap();
+
+    assert_eq!(group(markdown, &blocks).unwrap(), [vec![0], vec![1]]);
+}
+
+#[test]
+fn nothing_to_continue() {
+    let markdown =
+        "```text\nout\n```\n\n<!-- doc-test: continues-previous -->\n```rust\nfn a() {}\n```\n";
+    let blocks = parse(markdown).unwrap();
+
+    let err = group(markdown, &blocks).unwrap_err();
+    assert_eq!(err.line, 5);
+    assert_eq!(
+        err.reason,
+        "there is no Rust code block before this one to continue"
+    );
+}
+
+#[test]
+fn only_rust_continues() {
+    let markdown =
+        "```rust\nfn a() {}\n```\n<!-- doc-test: continues-previous -->\n```text\nout\n```\n";
+    let blocks = parse(markdown).unwrap();
+
+    let err = group(markdown, &blocks).unwrap_err();
+    assert_eq!(err.line, 4);
+    assert_eq!(
+        err.reason,
+        "only a Rust code block can continue the previous one"
+    );
+}
@@ -0,0 +1,138 @@
+//! Checks the code the reader sees, without the hidden lines.
+//!
+//! The whole code of a block, hidden lines included, is compiled by its
+//! doctest. That doesn't catch hidden lines that hide the wrong thing, like
+//! the closing brace of a function whose opening brace is shown: the doctest
+//! compiles, but what the reader sees makes no sense. So the visible code
+//! has to parse on its own, as items, statements, or the items of an `impl`,
+//! unless the block is marked `fragment`.
+
+use doc_test::gen::fence::{Block, Kind};
+use doc_test::gen::{extract, files, paths, standalone, units};
+use std::fs;
+use std::path::Path;
+
+/// Why `code` doesn't parse as any of the things a block may show, or
+/// `None` if it does.
+fn unparsable(code: &str) -> Option<String> {
+    let err = match syn::parse_file(code) {
+        Ok(_) => return None,
+        Err(err) => err,
+    };
+
+    // Statements, and an expression at the end.
+    if syn::parse_str::<syn::Block>(&format!("{{\n{}\n}}", code)).is_ok() {
+        return None;
+    }
+
+    // Methods, shown without the `impl` they are in.
+    if syn::parse_str::<syn::ItemImpl>(&format!("impl T {{\n{}\n}}", code)).is_ok() {
+        return None;
+    }
+
+    // The error from parsing it as a file is the one that reads best, unless
+    // the code can't even be split into tokens.
+    let err = err.to_string();
+    if err == "cannot parse string into token stream" {
+        Some("unbalanced delimiters, or a literal that is never closed".to_string())
+    } else {
+        Some(err)
+    }
+}
+
+/// The blocks of `markdown` whose visible code doesn't parse, as `line:
+/// reason`.
+fn check_file(markdown: &str) -> Vec<String> {
+    let extracted = extract::extract(markdown);
+    let blocks = match extracted.blocks {
+        Some(blocks) => blocks,
+        // `build.rs` reports it.
+        None => return vec![],
+    };
+    let units = match units::group(&extracted.source, &blocks) {
+        Ok(units) => units,
+        Err(_) => return vec![],
+    };
+
+    let mut errors = vec![];
+
+    for unit in &units {
+        let first: &Block = &blocks[unit[0]];
+        let checked = |block: &Block| {
+            block.is_rust()
+                && !block.is_fragment()
+                && !matches!(block.kind(), Kind::CompileFail | Kind::Ignore)
+        };
+        if !unit.iter().all(|&i| checked(&blocks[i])) {
+            continue;
+        }
+
+        let code = standalone::visible(&units::code(&blocks, unit));
+        if let Some(reason) = unparsable(&code) {
+            errors.push(format!("{}: {}", first.line, reason));
+        }
+    }
+
+    errors
+}
+
+/// The blocks under the `roots` of `content` whose visible code doesn't
+/// parse.
+fn check(content: &Path, roots: &[&str]) -> Vec<String> {
+    let content = files::content_dir(content).unwrap();
+
+    let mut found = vec![];
+    for root in roots {
+        found.extend(files::markdown_files(&content.join(root)).0);
+    }
+
+    let mut errors = vec![];
+    for path in found {
+        let rel = paths::normalize(path.strip_prefix(&content).unwrap());
+        for error in check_file(&fs::read_to_string(&path).unwrap()) {
+            errors.push(format!("{}:{}", rel, error));
+        }
+    }
+
+    errors
+}
+
+#[test]
+fn parses_items_statements_and_methods() {
+    assert_eq!(unparsable("use std::io;\n\nfn main() {}\n"), None);
+    assert_eq!(unparsable("let x = 1;\nx + 1\n"), None);
+    assert_eq!(
+        unparsable("fn poll(&mut self) -> Poll<()> {\n    Poll::Ready(())\n}\n"),
+        None
+    );
+    assert!(unparsable("fn main() {\n    let x = 1;\n").is_some());
+}
+
+#[test]
+fn reports_broken_blocks() {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visible");
+
+    assert_eq!(
+        check(&content, &["tokio"]),
+        ["tokio/hidden.md:8: unbalanced delimiters, or a literal that is never closed"]
+    );
+}
+
+#[test]
+fn visible_code_parses() {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("../content");
+    // Like the doctests, blog posts are only checked with the `blog` feature.
+    let roots: &[&str] = if cfg!(feature = "blog") {
+        &["tokio", "blog"]
+    } else {
+        &["tokio"]
+    };
+    let errors = check(&content, roots);
+
+    assert!(
+        errors.is_empty(),
+        "the code shown doesn't parse without its hidden lines; fix the hidden lines, or mark \
+         blocks showing part of something with ```rust,fragment:\n    {}",
+        errors.join("\n    ")
+    );
+}
@@ -0,0 +1,94 @@
+//! A channel whose sender makes room, when it is full, by dropping the
+//! oldest item in it.
+//!
+//! `mpsc` has no such thing: only the receiver can take items out. So both
+//! ends share the receiver, behind a mutex. The sender takes it only when
+//! the channel is full, to receive the oldest item and drop it, and the
+//! receiver only while receiving.
+//!
+//! The sender only has a `Weak` reference to it, so that dropping the
+//! `Receiver` still drops the `mpsc::Receiver`, closing the channel.
+
+use std::sync::{Arc, Weak};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Mutex;
+
+/// A channel holding up to `capacity` items.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let rx = Receiver::from(rx);
+
+    let tx = Sender {
+        tx,
+        rx: Arc::downgrade(&rx.rx),
+    };
+    (tx, rx)
+}
+
+/// The only sender of a channel.
+#[derive(Debug)]
+pub struct Sender<T> {
+    tx: mpsc::Sender<T>,
+    rx: Weak<Mutex<mpsc::Receiver<T>>>,
+}
+
+/// The receiver of a channel, shared with its sender.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    rx: Arc<Mutex<mpsc::Receiver<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send `item`, dropping the oldest item in the channel if it is full.
+    ///
+    /// Returns the dropped item, if any. If the receiver is gone, `item` is
+    /// dropped too, as nobody would ever get it.
+    pub async fn send(&self, item: T) -> Option<T> {
+        let item = match self.tx.try_send(item) {
+            Ok(()) => return None,
+            Err(TrySendError::Full(item)) => item,
+            Err(TrySendError::Closed(item)) => return Some(item),
+        };
+
+        // The receiver was there when the channel was found full, but may
+        // have been dropped since.
+        let rx = match self.rx.upgrade() {
+            Some(rx) => rx,
+            None => return Some(item),
+        };
+        let mut rx = rx.lock().await;
+
+        // The receiver may have made room while we waited for the lock.
+        let item = match self.tx.try_send(item) {
+            Ok(()) => return None,
+            Err(TrySendError::Full(item)) => item,
+            Err(TrySendError::Closed(item)) => return Some(item),
+        };
+
+        // Holding the lock, nothing else receives, and with the channel
+        // full there is something to. `Sender` isn't `Clone`, so nothing
+        // else sends either, and the room made is still there.
+        let oldest = rx.try_recv().ok();
+        if let Err(err) = self.tx.try_send(item) {
+            unreachable!("no room after receiving: {}", err);
+        }
+        oldest
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receive the oldest item in the channel, or `None` once it is empty
+    /// and the sender is gone.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.rx.lock().await.recv().await
+    }
+}
+
+/// A plain `mpsc` receiver, for a channel nothing else receives from.
+impl<T> From<mpsc::Receiver<T>> for Receiver<T> {
+    fn from(rx: mpsc::Receiver<T>) -> Receiver<T> {
+        Receiver {
+            rx: Arc::new(Mutex::new(rx)),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! A producer faster than its consumer, and what to do about it.
+//!
+//! The two are connected by a bounded `mpsc` channel, which is full most of
+//! the time, as the consumer can't keep up. What the producer does then is
+//! its `Strategy`:
+//!
+//! * `Wait` for room, with `send().await`. Nothing is lost, but the
+//!   producer slows down to the pace of the consumer: that is backpressure.
+//! * `DropNewest`: `try_send` fails, and the item it was given is dropped.
+//!   The producer keeps its pace, and the consumer gets the oldest items.
+//! * `DropOldest`: make room by receiving the oldest item in the channel,
+//!   and dropping it; see `drop_oldest`. The consumer gets the newest
+//!   items, which is what is wanted for readings of a sensor, say.
+//!
+//! With an unbounded channel there is no choice to make, and the items the
+//! consumer hasn't got to pile up in memory instead.
+
+pub mod drop_oldest;
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::{self, Instant};
+
+/// What the producer does when the channel is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    Wait,
+    DropNewest,
+    DropOldest,
+}
+
+/// How much there is to do, and how fast each side does it.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    /// How many items the producer makes.
+    pub items: u64,
+
+    /// The producer makes an item every `produce_every`.
+    pub produce_every: Duration,
+
+    /// The consumer takes `consume_each` to process an item.
+    pub consume_each: Duration,
+
+    /// How many items the channel holds.
+    pub capacity: usize,
+}
+
+/// The counters of a run. Every item produced is either delivered or
+/// dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub produced: u64,
+    pub delivered: u64,
+    pub dropped: u64,
+
+    /// The last item the consumer got, items being numbered from 0.
+    pub last_delivered: Option<u64>,
+
+    /// How long the producer took to make every item.
+    pub producing: Duration,
+
+    /// How long until the consumer processed the last item it got.
+    pub total: Duration,
+}
+
+/// Run a producer and a consumer through `workload`, the producer using
+/// `strategy` when the channel is full.
+pub async fn run(strategy: Strategy, workload: &Workload) -> Report {
+    let start = Instant::now();
+
+    let (items, rx) = match strategy {
+        Strategy::Wait => {
+            let (tx, rx) = mpsc::channel(workload.capacity);
+            (Sender::Wait(tx), rx.into())
+        }
+        Strategy::DropNewest => {
+            let (tx, rx) = mpsc::channel(workload.capacity);
+            (Sender::DropNewest(tx), rx.into())
+        }
+        Strategy::DropOldest => {
+            let (tx, rx) = drop_oldest::channel(workload.capacity);
+            (Sender::DropOldest(tx), rx)
+        }
+    };
+
+    let producer = tokio::spawn(produce(items, workload.clone()));
+    let consumer = tokio::spawn(consume(rx, workload.consume_each));
+
+    let (produced, dropped) = producer.await.unwrap();
+    let producing = start.elapsed();
+    let (delivered, last_delivered) = consumer.await.unwrap();
+
+    Report {
+        produced,
+        delivered,
+        dropped,
+        last_delivered,
+        producing,
+        total: start.elapsed(),
+    }
+}
+
+/// The sending end of the channel, for each strategy.
+enum Sender {
+    Wait(mpsc::Sender<u64>),
+    DropNewest(mpsc::Sender<u64>),
+    DropOldest(drop_oldest::Sender<u64>),
+}
+
+/// Make the items of `workload`, numbered from 0, and send them to
+/// `items`. Returns how many were made, and how many of them dropped.
+async fn produce(items: Sender, workload: Workload) -> (u64, u64) {
+    let mut ticks = time::interval(workload.produce_every);
+    let mut dropped = 0;
+
+    for item in 0..workload.items {
+        ticks.tick().await;
+
+        // Sending only fails if the consumer is gone, and it never leaves
+        // before the producer.
+        match &items {
+            Sender::Wait(tx) => tx.send(item).await.unwrap(),
+            Sender::DropNewest(tx) => match tx.try_send(item) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => dropped += 1,
+                Err(TrySendError::Closed(_)) => unreachable!("the consumer left first"),
+            },
+            Sender::DropOldest(tx) => {
+                if tx.send(item).await.is_some() {
+                    dropped += 1;
+                }
+            }
+        }
+    }
+
+    (workload.items, dropped)
+}
+
+/// Process the items of `rx`, taking `each` per item, until the producer is
+/// done. Returns how many there were, and the last one.
+async fn consume(mut rx: drop_oldest::Receiver<u64>, each: Duration) -> (u64, Option<u64>) {
+    let mut delivered = 0;
+    let mut last = None;
+
+    while let Some(item) = rx.recv().await {
+        time::sleep(each).await;
+        delivered += 1;
+        last = Some(item);
+    }
+
+    (delivered, last)
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strategy, String> {
+        match s {
+            "wait" => Ok(Strategy::Wait),
+            "drop-newest" => Ok(Strategy::DropNewest),
+            "drop-oldest" => Ok(Strategy::DropOldest),
+            _ => Err(format!(
+                "unknown strategy {:?}; expected wait, drop-newest or drop-oldest",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "produced {} in {:?}, delivered {}, dropped {}; done after {:?}",
+            self.produced, self.producing, self.delivered, self.dropped, self.total
+        )
+    }
+}
@@ -0,0 +1,30 @@
+use backpressure::{Strategy, Workload};
+use std::env;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    // Items come three times as fast as they are processed.
+    let workload = Workload {
+        items: 300,
+        produce_every: Duration::from_millis(1),
+        consume_each: Duration::from_millis(3),
+        capacity: 16,
+    };
+
+    let strategies = match env::args().nth(1) {
+        Some(strategy) => match strategy.parse() {
+            Ok(strategy) => vec![strategy],
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        },
+        None => vec![Strategy::Wait, Strategy::DropNewest, Strategy::DropOldest],
+    };
+
+    for strategy in strategies {
+        let report = backpressure::run(strategy, &workload).await;
+        println!("{:?}: {}", strategy, report);
+    }
+}
@@ -0,0 +1,24 @@
+use backpressure::drop_oldest;
+
+#[tokio::test]
+async fn drops_the_oldest_item_when_full() {
+    let (tx, mut rx) = drop_oldest::channel(2);
+
+    assert_eq!(tx.send(1).await, None);
+    assert_eq!(tx.send(2).await, None);
+    assert_eq!(tx.send(3).await, Some(1));
+    assert_eq!(tx.send(4).await, Some(2));
+    drop(tx);
+
+    assert_eq!(rx.recv().await, Some(3));
+    assert_eq!(rx.recv().await, Some(4));
+    assert_eq!(rx.recv().await, None);
+}
+
+#[tokio::test]
+async fn drops_items_nobody_will_receive() {
+    let (tx, rx) = drop_oldest::channel(2);
+    drop(rx);
+
+    assert_eq!(tx.send(1).await, Some(1));
+}
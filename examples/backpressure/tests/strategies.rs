@@ -0,0 +1,79 @@
+use backpressure::{run, Report, Strategy, Workload};
+use std::time::Duration;
+
+const PRODUCE_EVERY: Duration = Duration::from_millis(1);
+const CONSUME_EACH: Duration = Duration::from_millis(3);
+
+/// Items come three times as fast as they are processed.
+fn workload() -> Workload {
+    Workload {
+        items: 300,
+        produce_every: PRODUCE_EVERY,
+        consume_each: CONSUME_EACH,
+        capacity: 16,
+    }
+}
+
+fn assert_accounted_for(report: &Report) {
+    assert_eq!(report.produced, 300);
+    assert_eq!(
+        report.delivered + report.dropped,
+        report.produced,
+        "{:?}",
+        report
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_drops_nothing_but_takes_as_long_as_the_consumer() {
+    let report = run(Strategy::Wait, &workload()).await;
+
+    assert_accounted_for(&report);
+    assert_eq!(report.dropped, 0);
+    assert_eq!(report.last_delivered, Some(299));
+
+    // The consumer sets the pace of both.
+    assert_eq!(report.total, CONSUME_EACH * 300);
+    assert!(report.producing > PRODUCE_EVERY * 300 * 2, "{:?}", report);
+}
+
+/// The producer is done on time, and the consumer gets an item every
+/// `CONSUME_EACH` while it runs, then the ones left in the channel: 100,
+/// then 16.
+fn assert_on_time(report: &Report) {
+    assert_accounted_for(report);
+    assert_eq!(report.producing, PRODUCE_EVERY * 299);
+    assert_eq!(report.delivered, 100 + 16);
+    assert_eq!(report.dropped, 300 - 116);
+    assert_eq!(report.total, PRODUCE_EVERY * 300 + CONSUME_EACH * 16);
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropping_the_newest_keeps_the_pace_and_loses_the_latest_items() {
+    let report = run(Strategy::DropNewest, &workload()).await;
+
+    assert_on_time(&report);
+    assert!(report.last_delivered < Some(299), "{:?}", report);
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropping_the_oldest_keeps_the_pace_and_delivers_the_latest_item() {
+    let report = run(Strategy::DropOldest, &workload()).await;
+
+    assert_on_time(&report);
+    assert_eq!(report.last_delivered, Some(299));
+}
+
+#[tokio::test(start_paused = true)]
+async fn nothing_is_dropped_when_the_consumer_keeps_up() {
+    let workload = Workload {
+        consume_each: PRODUCE_EVERY / 2,
+        ..workload()
+    };
+
+    for strategy in [Strategy::Wait, Strategy::DropNewest, Strategy::DropOldest].iter() {
+        let report = run(*strategy, &workload).await;
+        assert_eq!(report.delivered, 300, "{:?}: {:?}", strategy, report);
+        assert_eq!(report.producing, PRODUCE_EVERY * 299);
+    }
+}
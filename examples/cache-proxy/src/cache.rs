@@ -0,0 +1,96 @@
+//! Values kept for a while.
+//!
+//! A value read from upstream may be outdated by the time it is cached, if a
+//! `SET` of the same key went through in the meantime. Every key has a
+//! version, bumped by `insert`: `fill` only caches a value read at the
+//! current version.
+
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+pub struct Cache {
+    ttl: Duration,
+    // A std mutex: it is never held across an `.await`.
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    entries: HashMap<String, Entry>,
+
+    /// How many times each key was inserted. Kept after the entry expires,
+    /// so that a fill started before then still sees the key changed.
+    versions: HashMap<String, u64>,
+}
+
+struct Entry {
+    value: Bytes,
+    expires_at: Instant,
+}
+
+impl Cache {
+    /// A cache keeping values for `ttl`.
+    pub fn new(ttl: Duration) -> Cache {
+        Cache {
+            ttl,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                versions: HashMap::new(),
+            }),
+        }
+    }
+
+    /// The value of `key`, unless it expired.
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let entries = &mut self.inner.lock().unwrap().entries;
+
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// The version of `key`, to give `fill` with a value read after this.
+    pub fn version(&self, key: &str) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner.versions.get(key).copied().unwrap_or(0)
+    }
+
+    /// Keep `value` for the cache's TTL, or for `expire` if that's sooner,
+    /// like when the value itself expires then. This is a new version of
+    /// `key`.
+    pub fn insert(&self, key: &str, value: Bytes, expire: Option<Duration>) {
+        let entry = self.entry(value, expire);
+        let mut inner = self.inner.lock().unwrap();
+
+        *inner.versions.entry(key.to_string()).or_insert(0) += 1;
+        inner.entries.insert(key.to_string(), entry);
+    }
+
+    /// Keep `value`, read from upstream at `version`, for the cache's TTL.
+    /// Returns `false`, and keeps nothing, if `key` was inserted since.
+    pub fn fill(&self, key: &str, value: Bytes, version: u64) -> bool {
+        let entry = self.entry(value, None);
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.versions.get(key).copied().unwrap_or(0) != version {
+            return false;
+        }
+        inner.entries.insert(key.to_string(), entry);
+        true
+    }
+
+    fn entry(&self, value: Bytes, expire: Option<Duration>) -> Entry {
+        let ttl = expire.map_or(self.ttl, |expire| expire.min(self.ttl));
+        Entry {
+            value,
+            expires_at: Instant::now() + ttl,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! A caching proxy in front of a mini-redis server.
+//!
+//! Clients speak the redis protocol to the proxy, as they would to the
+//! server. A `GET` is answered from an in-memory cache when it can be;
+//! otherwise it goes to the upstream server, over one of a few connections
+//! the clients share, and the value is cached for next time, unless a `SET`
+//! of the key went through in the meantime. A `SET` goes to the upstream
+//! server first, and is only cached once it's stored there.
+//!
+//! When the upstream server fails, the client gets the error in an error
+//! frame, and its connection to the proxy goes on.
+
+pub mod cache;
+pub mod pool;
+
+use bytes::Bytes;
+use cache::Cache;
+use mini_redis::{Command, Connection, Frame};
+use pool::Pool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+pub struct Proxy {
+    cache: Cache,
+    pool: Pool,
+    upstream_gets: AtomicUsize,
+}
+
+impl Proxy {
+    /// A proxy caching values for `ttl`, and sending what it can't answer
+    /// over `pool`.
+    pub fn new(pool: Pool, ttl: Duration) -> Proxy {
+        Proxy {
+            cache: Cache::new(ttl),
+            pool,
+            upstream_gets: AtomicUsize::new(0),
+        }
+    }
+
+    /// How many `GET`s went to the upstream server so far.
+    pub fn upstream_gets(&self) -> usize {
+        self.upstream_gets.load(Ordering::Relaxed)
+    }
+
+    pub async fn get(&self, key: &str) -> mini_redis::Result<Option<Bytes>> {
+        if let Some(value) = self.cache.get(key) {
+            return Ok(Some(value));
+        }
+
+        // Taken before reading upstream: a `SET` of the key from now on
+        // makes the value read outdated.
+        let version = self.cache.version(key);
+
+        self.upstream_gets.fetch_add(1, Ordering::Relaxed);
+        let value = self.pool.get(key).await?;
+
+        // A missing key isn't cached: it may be set by someone else than
+        // a client of this proxy.
+        if let Some(value) = &value {
+            self.cache.fill(key, value.clone(), version);
+        }
+
+        Ok(value)
+    }
+
+    pub async fn set(
+        &self,
+        key: &str,
+        value: Bytes,
+        expire: Option<Duration>,
+    ) -> mini_redis::Result<()> {
+        self.pool.set(key, value.clone(), expire).await?;
+        self.cache.insert(key, value, expire);
+
+        Ok(())
+    }
+}
+
+/// How long `run` waits after a failed `accept` before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Answer the clients of `listener` with `proxy`, forever.
+///
+/// A failed accept, such as one running out of file descriptors, is logged,
+/// and retried after a short pause.
+pub async fn run(listener: TcpListener, proxy: Arc<Proxy>) {
+    loop {
+        let (socket, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                eprintln!("failed to accept a connection: {}", err);
+                time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let proxy = proxy.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = process(socket, &proxy).await {
+                eprintln!("{}: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Answer the commands of `socket` until the client closes it.
+async fn process(socket: TcpStream, proxy: &Proxy) -> mini_redis::Result<()> {
+    let mut connection = Connection::new(socket);
+
+    while let Some(frame) = connection.read_frame().await? {
+        let response = match Command::from_frame(frame) {
+            Ok(Command::Get(cmd)) => match proxy.get(cmd.key()).await {
+                Ok(Some(value)) => Frame::Bulk(value),
+                Ok(None) => Frame::Null,
+                Err(err) => upstream_error(err),
+            },
+            Ok(Command::Set(cmd)) => {
+                match proxy
+                    .set(cmd.key(), cmd.value().clone(), cmd.expire())
+                    .await
+                {
+                    Ok(()) => Frame::Simple("OK".to_string()),
+                    Err(err) => upstream_error(err),
+                }
+            }
+            Ok(_) => Frame::Error("ERR only GET and SET are supported".to_string()),
+            Err(err) => Frame::Error(format!("ERR {}", err)),
+        };
+
+        connection.write_frame(&response).await?;
+    }
+
+    Ok(())
+}
+
+fn upstream_error(err: mini_redis::Error) -> Frame {
+    Frame::Error(format!("ERR upstream: {}", err))
+}
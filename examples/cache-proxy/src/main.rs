@@ -0,0 +1,32 @@
+use cache_proxy::pool::Pool;
+use cache_proxy::Proxy;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// How many connections to the upstream server the clients share.
+const POOL_SIZE: usize = 4;
+
+/// How long a value is served from the cache.
+const TTL: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    // `cache-proxy 127.0.0.1:6380 127.0.0.1:6379` caches the server on
+    // port 6379 for the clients of port 6380.
+    let mut args = env::args().skip(1);
+    let listen = args.next().unwrap_or_else(|| "127.0.0.1:6380".to_string());
+    let upstream = args
+        .next()
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string())
+        .parse()?;
+
+    let pool = Pool::connect(upstream, POOL_SIZE).await?;
+    let listener = TcpListener::bind(&listen).await?;
+    println!("caching {} on {}", upstream, listen);
+
+    cache_proxy::run(listener, Arc::new(Proxy::new(pool, TTL))).await;
+
+    Ok(())
+}
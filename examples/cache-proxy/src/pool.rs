@@ -0,0 +1,60 @@
+//! Connections to the upstream server, shared by the clients.
+
+use bytes::Bytes;
+use mini_redis::client::{self, Client};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// A fixed set of connections, handed out in turn.
+///
+/// A connection is used by one request at a time, so a request waits for
+/// its turn when the connection it gets is busy. A connection the server
+/// closed stays broken, failing every request sent over it.
+pub struct Pool {
+    // A Tokio mutex: a connection is locked across the request.
+    clients: Vec<Mutex<Client>>,
+    next: AtomicUsize,
+}
+
+impl Pool {
+    /// Open `size` connections to the server at `addr`.
+    pub async fn connect(addr: SocketAddr, size: usize) -> mini_redis::Result<Pool> {
+        assert!(size > 0, "a pool needs at least one connection");
+
+        let mut clients = Vec::with_capacity(size);
+        for _ in 0..size {
+            clients.push(Mutex::new(client::connect(addr).await?));
+        }
+
+        Ok(Pool {
+            clients,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// The next connection, round-robin.
+    async fn checkout(&self) -> MutexGuard<'_, Client> {
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        self.clients[i].lock().await
+    }
+
+    pub async fn get(&self, key: &str) -> mini_redis::Result<Option<Bytes>> {
+        self.checkout().await.get(key).await
+    }
+
+    pub async fn set(
+        &self,
+        key: &str,
+        value: Bytes,
+        expire: Option<Duration>,
+    ) -> mini_redis::Result<()> {
+        let mut client = self.checkout().await;
+
+        match expire {
+            Some(expire) => client.set_expires(key, value, expire).await,
+            None => client.set(key, value).await,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use bytes::Bytes;
+use cache_proxy::cache::Cache;
+use std::time::Duration;
+use tokio::time;
+
+#[tokio::test(start_paused = true)]
+async fn values_expire_after_the_ttl() {
+    let cache = Cache::new(Duration::from_secs(10));
+    cache.insert("hello", Bytes::from("world"), None);
+
+    time::advance(Duration::from_secs(9)).await;
+    assert_eq!(cache.get("hello"), Some(Bytes::from("world")));
+
+    time::advance(Duration::from_secs(1)).await;
+    assert_eq!(cache.get("hello"), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn values_expiring_sooner_are_dropped_sooner() {
+    let cache = Cache::new(Duration::from_secs(10));
+    cache.insert("soon", Bytes::from("1"), Some(Duration::from_secs(2)));
+    cache.insert("late", Bytes::from("2"), Some(Duration::from_secs(60)));
+
+    time::advance(Duration::from_secs(2)).await;
+    assert_eq!(cache.get("soon"), None);
+    assert_eq!(cache.get("late"), Some(Bytes::from("2")));
+
+    // Never longer than the cache's own TTL.
+    time::advance(Duration::from_secs(8)).await;
+    assert_eq!(cache.get("late"), None);
+}
+
+#[tokio::test]
+async fn fills_only_the_version_they_read() {
+    let cache = Cache::new(Duration::from_secs(10));
+
+    let version = cache.version("key");
+    assert!(cache.fill("key", Bytes::from("old"), version));
+
+    // A fill that read upstream before this insert is outdated.
+    let version = cache.version("key");
+    cache.insert("key", Bytes::from("new"), None);
+    assert!(!cache.fill("key", Bytes::from("old"), version));
+    assert_eq!(cache.get("key"), Some(Bytes::from("new")));
+}
@@ -0,0 +1,187 @@
+use bytes::Bytes;
+use cache_proxy::pool::Pool;
+use cache_proxy::{run, Proxy};
+use mini_redis::{client, Command, Connection, Frame};
+use std::future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+const TTL: Duration = Duration::from_secs(60);
+
+/// A mini-redis server.
+async fn upstream() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mini_redis::server::run(listener, future::pending::<()>()));
+    addr
+}
+
+/// An upstream holding a single value, `old` to begin with. A `GET` is
+/// answered with the value at the time it came in, but only after telling
+/// `got` about it, and once `release` is notified.
+async fn slow_upstream(got: Arc<Notify>, release: Arc<Notify>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let value = Arc::new(Mutex::new(Bytes::from("old")));
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (got, release, value) = (got.clone(), release.clone(), value.clone());
+
+            tokio::spawn(async move {
+                let mut connection = Connection::new(socket);
+                while let Some(frame) = connection.read_frame().await.unwrap() {
+                    let response = match Command::from_frame(frame).unwrap() {
+                        Command::Get(_) => {
+                            let value = value.lock().unwrap().clone();
+                            got.notify_one();
+                            release.notified().await;
+                            Frame::Bulk(value)
+                        }
+                        Command::Set(cmd) => {
+                            *value.lock().unwrap() = cmd.value().clone();
+                            Frame::Simple("OK".to_string())
+                        }
+                        cmd => panic!("unexpected {:?}", cmd),
+                    };
+                    connection.write_frame(&response).await.unwrap();
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+/// A proxy in front of `upstream`, with `size` connections to it.
+async fn proxy(upstream: SocketAddr, size: usize) -> (SocketAddr, Arc<Proxy>) {
+    let pool = Pool::connect(upstream, size).await.unwrap();
+    let proxy = Arc::new(Proxy::new(pool, TTL));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(run(listener, proxy.clone()));
+
+    (addr, proxy)
+}
+
+#[tokio::test]
+async fn second_get_is_served_from_the_cache() {
+    let upstream = upstream().await;
+    client::connect(upstream)
+        .await
+        .unwrap()
+        .set("hello", "world".into())
+        .await
+        .unwrap();
+
+    let (addr, proxy) = proxy(upstream, 2).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert_eq!(client.get("hello").await.unwrap(), Some("world".into()));
+    assert_eq!(proxy.upstream_gets(), 1);
+
+    assert_eq!(client.get("hello").await.unwrap(), Some("world".into()));
+    assert_eq!(proxy.upstream_gets(), 1);
+}
+
+#[tokio::test]
+async fn missing_keys_are_not_cached() {
+    let upstream = upstream().await;
+    let (addr, proxy) = proxy(upstream, 2).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert_eq!(client.get("missing").await.unwrap(), None);
+    assert_eq!(client.get("missing").await.unwrap(), None);
+    assert_eq!(proxy.upstream_gets(), 2);
+}
+
+#[tokio::test]
+async fn set_writes_through() {
+    let upstream = upstream().await;
+    let (addr, proxy) = proxy(upstream, 2).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    // Stored upstream...
+    let mut direct = client::connect(upstream).await.unwrap();
+    assert_eq!(direct.get("hello").await.unwrap(), Some("world".into()));
+
+    // ...and cached.
+    assert_eq!(client.get("hello").await.unwrap(), Some("world".into()));
+    assert_eq!(proxy.upstream_gets(), 0);
+}
+
+#[tokio::test]
+async fn clients_share_the_pool() {
+    let upstream = upstream().await;
+    let (addr, _proxy) = proxy(upstream, 2).await;
+
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            tokio::spawn(async move {
+                let mut client = client::connect(addr).await.unwrap();
+                let key = format!("key-{}", i);
+                let value = Bytes::from(format!("value-{}", i));
+
+                client.set(&key, value.clone()).await.unwrap();
+                assert_eq!(client.get(&key).await.unwrap(), Some(value));
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn upstream_errors_reach_the_client() {
+    // An upstream closing every connection it accepts.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            drop(listener.accept().await.unwrap());
+        }
+    });
+
+    let (addr, _proxy) = proxy(upstream, 1).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let err = client.get("hello").await.unwrap_err();
+    assert!(err.to_string().starts_with("ERR upstream: "), "{}", err);
+
+    // The connection to the proxy is still there.
+    let err = client.set("hello", "world".into()).await.unwrap_err();
+    assert!(err.to_string().starts_with("ERR upstream: "), "{}", err);
+}
+
+#[tokio::test]
+async fn a_set_during_a_get_is_not_overwritten() {
+    let (got, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+    let upstream = slow_upstream(got.clone(), release.clone()).await;
+    let (addr, proxy) = proxy(upstream, 2).await;
+
+    // The `GET` misses the cache, and reads `old` upstream...
+    let get = tokio::spawn(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        client.get("key").await.unwrap()
+    });
+    got.notified().await;
+
+    // ...then a `SET` goes through before the `GET` is answered.
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("key", "new".into()).await.unwrap();
+    release.notify_one();
+    assert_eq!(get.await.unwrap(), Some("old".into()));
+
+    // The outdated value wasn't cached over the new one.
+    assert_eq!(client.get("key").await.unwrap(), Some("new".into()));
+    assert_eq!(proxy.upstream_gets(), 1);
+}
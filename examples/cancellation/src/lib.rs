@@ -0,0 +1,107 @@
+//! Cancelling a tree of tasks with `CancellationToken`.
+//!
+//! The workers are spawned in groups. Each group has a child token of a
+//! root token, and each worker of the group selects on it against its
+//! work. Cancelling a group's token stops its workers only; cancelling the
+//! root stops every group, as a child token is cancelled with its parent.
+//!
+//! Unlike aborting its task, cancelling a worker lets it finish on its
+//! own: it completes the step it's in, then cleans up, which may take
+//! `.await`s of its own.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+/// How long a step of work takes.
+pub const STEP: Duration = Duration::from_millis(100);
+
+/// How long cleaning up takes.
+pub const CLEANUP: Duration = Duration::from_millis(10);
+
+/// What the workers of a group did.
+#[derive(Debug, Default)]
+pub struct Stats {
+    steps: AtomicUsize,
+    cleaned_up: AtomicUsize,
+}
+
+impl Stats {
+    /// The steps of work done.
+    pub fn steps(&self) -> usize {
+        self.steps.load(Ordering::SeqCst)
+    }
+
+    /// The workers that cleaned up.
+    pub fn cleaned_up(&self) -> usize {
+        self.cleaned_up.load(Ordering::SeqCst)
+    }
+}
+
+/// Workers sharing a token.
+pub struct Group {
+    token: CancellationToken,
+    stats: Arc<Stats>,
+    workers: Vec<JoinHandle<()>>,
+
+    // Cancels the workers when the group is dropped, so they don't go on
+    // unattended when whoever owns the group returns early, or panics.
+    _guard: DropGuard,
+}
+
+impl Group {
+    /// Spawn `workers` workers, stopped by a child token of `parent`.
+    pub fn spawn(parent: &CancellationToken, workers: usize) -> Group {
+        let token = parent.child_token();
+        let stats = Arc::new(Stats::default());
+
+        let workers = (0..workers)
+            .map(|_| tokio::spawn(worker(token.clone(), stats.clone())))
+            .collect();
+
+        Group {
+            _guard: token.clone().drop_guard(),
+            token,
+            stats,
+            workers,
+        }
+    }
+
+    /// The token stopping the group's workers.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn stats(&self) -> &Arc<Stats> {
+        &self.stats
+    }
+
+    /// Wait for the workers to stop, once the group's token is cancelled,
+    /// and return what they did.
+    pub async fn join(mut self) -> Arc<Stats> {
+        for worker in self.workers.drain(..) {
+            worker.await.expect("worker panicked");
+        }
+
+        self.stats.clone()
+    }
+}
+
+/// Do steps of work until `token` is cancelled, then clean up.
+async fn worker(token: CancellationToken, stats: Arc<Stats>) {
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = time::sleep(STEP) => {
+                stats.steps.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    // Flushing buffers, saying goodbye to a peer...
+    time::sleep(CLEANUP).await;
+    stats.cleaned_up.fetch_add(1, Ordering::SeqCst);
+}
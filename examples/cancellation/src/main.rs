@@ -0,0 +1,33 @@
+use cancellation::Group;
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() {
+    let root = CancellationToken::new();
+    let first = Group::spawn(&root, 3);
+    let second = Group::spawn(&root, 3);
+
+    time::sleep(Duration::from_millis(350)).await;
+
+    // A subtree: the second group goes on.
+    first.token().cancel();
+    let stats = first.join().await;
+    println!(
+        "first group: {} steps, {} workers cleaned up",
+        stats.steps(),
+        stats.cleaned_up()
+    );
+
+    time::sleep(Duration::from_millis(350)).await;
+
+    // The whole tree.
+    root.cancel();
+    let stats = second.join().await;
+    println!(
+        "second group: {} steps, {} workers cleaned up",
+        stats.steps(),
+        stats.cleaned_up()
+    );
+}
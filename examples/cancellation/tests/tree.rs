@@ -0,0 +1,87 @@
+use cancellation::{Group, CLEANUP, STEP};
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// Long enough for every worker to do a step, or to clean up.
+async fn a_step() {
+    time::sleep(STEP + Duration::from_millis(1)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_a_group_leaves_the_others_alone() {
+    let root = CancellationToken::new();
+    let first = Group::spawn(&root, 3);
+    let second = Group::spawn(&root, 2);
+
+    a_step().await;
+    first.token().cancel();
+    let first = first.join().await;
+    assert_eq!(first.cleaned_up(), 3);
+
+    // The second group goes on.
+    assert!(!second.token().is_cancelled());
+    assert!(!root.is_cancelled());
+    let steps = second.stats().steps();
+    a_step().await;
+    assert_eq!(second.stats().steps(), steps + 2);
+    assert_eq!(second.stats().cleaned_up(), 0);
+
+    // And the first one did nothing more.
+    assert_eq!(first.steps(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_the_root_stops_every_group() {
+    let root = CancellationToken::new();
+    let groups = vec![
+        Group::spawn(&root, 3),
+        Group::spawn(&root, 1),
+        Group::spawn(&root, 4),
+    ];
+
+    a_step().await;
+    root.cancel();
+
+    for (group, workers) in groups.into_iter().zip([3, 1, 4].iter()) {
+        assert!(group.token().is_cancelled());
+
+        let stats = time::timeout(Duration::from_secs(1), group.join())
+            .await
+            .expect("a group didn't stop");
+        assert_eq!(stats.cleaned_up(), *workers);
+        assert_eq!(stats.steps(), *workers);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn workers_clean_up_before_stopping() {
+    let root = CancellationToken::new();
+    let group = Group::spawn(&root, 2);
+
+    group.token().cancel();
+    let start = time::Instant::now();
+    let stats = group.join().await;
+
+    assert_eq!(start.elapsed(), CLEANUP);
+    assert_eq!(stats.cleaned_up(), 2);
+    assert_eq!(stats.steps(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropping_a_group_cancels_it() {
+    let root = CancellationToken::new();
+    let group = Group::spawn(&root, 3);
+    let token = group.token().clone();
+    let stats = group.stats().clone();
+    let sibling = Group::spawn(&root, 1);
+
+    drop(group);
+    assert!(token.is_cancelled());
+    assert!(!sibling.token().is_cancelled());
+
+    // The workers weren't aborted: they still clean up.
+    a_step().await;
+    assert_eq!(stats.cleaned_up(), 3);
+    assert_eq!(stats.steps(), 0);
+}
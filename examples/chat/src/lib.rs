@@ -0,0 +1,149 @@
+//! A chat server: every line a client sends goes to all the other clients,
+//! prefixed with the address of the sender.
+//!
+//! The connections share a `broadcast` channel. Each one's task waits on
+//! both its socket and its receiver with `select!`, sending what the client
+//! writes into the channel and writing what comes out of it back to the
+//! client. A client that reads too slowly falls behind the channel; it is
+//! told how many messages it missed, and carries on from the oldest one
+//! still there.
+//!
+//! When `shutdown` completes, every connection is closed, and `run` returns
+//! once their tasks are done.
+
+use futures::{SinkExt, StreamExt};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinSet;
+use tokio::time;
+use tokio_util::codec::{Framed, LinesCodec, LinesCodecError};
+
+/// How many messages the channel keeps for the clients that haven't read
+/// them yet.
+pub const CAPACITY: usize = 64;
+
+/// The longest line a client may send, newline excluded.
+pub const MAX_LINE: usize = 8 * 1024;
+
+/// A line for the clients, and who it is from, so it isn't sent back to them.
+#[derive(Debug, Clone)]
+struct Message {
+    from: SocketAddr,
+    line: String,
+}
+
+/// How long `run` waits after a failed `accept` before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Run the chat for the clients of `listener` until `shutdown` completes.
+///
+/// A failed accept, such as one running out of file descriptors, is logged,
+/// and retried after a short pause.
+pub async fn run(listener: TcpListener, shutdown: impl Future) {
+    let (messages, _) = broadcast::channel(CAPACITY);
+
+    // Nothing is ever sent: dropping `notify` is what tells the connections
+    // to close.
+    let (notify, closing) = watch::channel(());
+
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(shutdown);
+
+    loop {
+        let res = tokio::select! {
+            res = listener.accept() => res,
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = &mut shutdown => break,
+        };
+
+        let (socket, addr) = match res {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                eprintln!("failed to accept a connection: {}", err);
+                tokio::select! {
+                    _ = time::sleep(ACCEPT_BACKOFF) => continue,
+                    _ = &mut shutdown => break,
+                }
+            }
+        };
+        let messages = messages.clone();
+        let closing = closing.clone();
+
+        connections.spawn(async move {
+            if let Err(err) = process(socket, addr, messages, closing).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+
+    drop(listener);
+    drop(notify);
+
+    while connections.join_next().await.is_some() {}
+}
+
+/// Chat with the client at `addr` until it leaves, or the server closes.
+async fn process(
+    socket: TcpStream,
+    addr: SocketAddr,
+    messages: broadcast::Sender<Message>,
+    mut closing: watch::Receiver<()>,
+) -> Result<(), LinesCodecError> {
+    let mut lines = Framed::new(socket, LinesCodec::new_with_max_length(MAX_LINE));
+
+    // Subscribe before announcing, so that once the others know about the
+    // client, it gets everything they send.
+    let mut received = messages.subscribe();
+    announce(&messages, addr, "joined");
+
+    let res = loop {
+        tokio::select! {
+            // Checked first: once the server is shutting down, the goodbyes
+            // of the other clients aren't relayed anymore.
+            biased;
+
+            // The server is shutting down.
+            _ = closing.changed() => break Ok(()),
+            line = lines.next() => match line {
+                Some(Ok(line)) => {
+                    let line = format!("{}: {}", addr, line);
+                    // An error only means nobody else is connected.
+                    let _ = messages.send(Message { from: addr, line });
+                }
+                Some(Err(err)) => break Err(err),
+                None => break Ok(()),
+            },
+            message = received.recv() => match message {
+                Ok(message) if message.from == addr => {}
+                Ok(message) => {
+                    if let Err(err) = lines.send(message.line).await {
+                        break Err(err);
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    let notice = format!("* you missed {} messages", missed);
+                    if let Err(err) = lines.send(notice).await {
+                        break Err(err);
+                    }
+                }
+                // `messages` is one of the senders.
+                Err(RecvError::Closed) => unreachable!(),
+            },
+        }
+    };
+
+    announce(&messages, addr, "left");
+
+    res
+}
+
+/// Tell the other clients that the client at `addr` did `what`.
+fn announce(messages: &broadcast::Sender<Message>, addr: SocketAddr, what: &str) {
+    let line = format!("* {} {}", addr, what);
+    let _ = messages.send(Message { from: addr, line });
+}
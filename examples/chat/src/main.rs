@@ -0,0 +1,13 @@
+use tokio::net::TcpListener;
+use tokio::signal;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try it with a few `nc 127.0.0.1 12345`.
+    let listener = TcpListener::bind("127.0.0.1:12345").await?;
+
+    chat::run(listener, signal::ctrl_c()).await;
+    println!("shut down");
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use chat::run;
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::time;
+use tokio_util::codec::{Framed, LinesCodec};
+
+struct Client {
+    addr: SocketAddr,
+    lines: Framed<TcpStream, LinesCodec>,
+}
+
+impl Client {
+    async fn connect(server: SocketAddr) -> Client {
+        let socket = TcpStream::connect(server).await.unwrap();
+        Client {
+            addr: socket.local_addr().unwrap(),
+            lines: Framed::new(socket, LinesCodec::new()),
+        }
+    }
+
+    async fn send(&mut self, line: &str) {
+        self.lines.send(line).await.unwrap();
+    }
+
+    /// The next line from the server, or `None` once it closed the
+    /// connection.
+    async fn next(&mut self) -> Option<String> {
+        time::timeout(Duration::from_secs(5), self.lines.next())
+            .await
+            .expect("nothing came from the server")
+            .map(Result::unwrap)
+    }
+}
+
+#[tokio::test]
+async fn lines_go_to_everyone_else() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = listener.local_addr().unwrap();
+
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let chat = tokio::spawn(run(listener, signal));
+
+    // Each client waits for the announcement of the next one, so everybody
+    // has joined before the first message.
+    let mut a = Client::connect(server).await;
+    let mut b = Client::connect(server).await;
+    assert_eq!(a.next().await.unwrap(), format!("* {} joined", b.addr));
+    let mut c = Client::connect(server).await;
+    assert_eq!(a.next().await.unwrap(), format!("* {} joined", c.addr));
+    assert_eq!(b.next().await.unwrap(), format!("* {} joined", c.addr));
+
+    a.send("hello").await;
+    let hello = format!("{}: hello", a.addr);
+    assert_eq!(b.next().await.unwrap(), hello);
+    assert_eq!(c.next().await.unwrap(), hello);
+
+    // If `a` got its own line back, it would come before this one.
+    b.send("hi").await;
+    let hi = format!("{}: hi", b.addr);
+    assert_eq!(a.next().await.unwrap(), hi);
+    assert_eq!(c.next().await.unwrap(), hi);
+
+    let left = format!("* {} left", c.addr);
+    drop(c);
+    assert_eq!(a.next().await.unwrap(), left);
+    assert_eq!(b.next().await.unwrap(), left);
+
+    shutdown.send(()).unwrap();
+
+    // Every connection is closed.
+    assert_eq!(a.next().await, None);
+    assert_eq!(b.next().await, None);
+
+    time::timeout(Duration::from_secs(5), chat)
+        .await
+        .expect("the server didn't stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stops_without_clients() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    time::timeout(Duration::from_secs(5), run(listener, async {}))
+        .await
+        .expect("the server didn't stop");
+}
@@ -0,0 +1,82 @@
+//! The settings of the service, and the file they are read from.
+//!
+//! The file has a `key = value` setting per line, and `#` comments:
+//!
+//! ```text
+//! # Greetings per second, for each worker.
+//! rate = 2
+//! greeting = Hello, world!
+//! ```
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Greetings per second; never 0.
+    pub rate: u32,
+    pub greeting: String,
+}
+
+/// A line of the configuration that isn't a setting.
+#[derive(Debug, PartialEq)]
+pub struct Invalid {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            rate: 1,
+            greeting: "Hello".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// The settings of `text`, the others keeping their default.
+    pub fn parse(text: &str) -> Result<Config, Invalid> {
+        let mut config = Config::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            config.set(line).map_err(|reason| Invalid {
+                line: i + 1,
+                reason,
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    /// Apply the setting `line`, a `key = value`.
+    pub fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(format!("expected `key = value`, got `{}`", line)),
+        };
+
+        match key {
+            "rate" => match value.parse() {
+                Ok(rate) if rate > 0 => self.rate = rate,
+                _ => return Err(format!("`rate` is a number above 0, not `{}`", value)),
+            },
+            "greeting" => self.greeting = value.to_string(),
+            _ => return Err(format!("unknown setting `{}`", key)),
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for Invalid {}
@@ -0,0 +1,71 @@
+//! Sending a new configuration when asked to.
+
+use crate::config::Config;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tokio::sync::watch;
+use tokio::{fs, time};
+
+/// Check the modification time of the file at `path` every `period`, and
+/// send its configuration each time it changes.
+///
+/// A configuration that can't be read or parsed is reported, and the one
+/// in use kept.
+pub async fn watch_file(path: &Path, config: &watch::Sender<Config>, period: Duration) {
+    let mut interval = time::interval(period);
+    let mut last_modified: Option<SystemTime> = None;
+
+    loop {
+        interval.tick().await;
+
+        // A file that is missing for now may be written later.
+        let modified = match fs::metadata(path).await.and_then(|meta| meta.modified()) {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+        if last_modified == Some(modified) {
+            continue;
+        }
+        last_modified = Some(modified);
+
+        let text = match fs::read_to_string(path).await {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                continue;
+            }
+        };
+        match Config::parse(&text) {
+            // Only wakes the workers up if something changed.
+            Ok(new) => {
+                config.send_if_modified(|current| {
+                    let modified = *current != new;
+                    *current = new;
+                    modified
+                });
+            }
+            Err(err) => eprintln!("{}: {}", path.display(), err),
+        }
+    }
+}
+
+/// Apply each line of `input`, a `key = value` setting, until its end.
+pub async fn watch_lines<R>(input: R, config: &watch::Sender<Config>)
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut lines = input.lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut res = Ok(());
+        config.send_modify(|config| res = config.set(line.trim()));
+        if let Err(err) = res {
+            eprintln!("{}", err);
+        }
+    }
+}
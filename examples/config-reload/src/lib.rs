@@ -0,0 +1,48 @@
+//! A service reloading its configuration without restarting.
+//!
+//! The configuration is in a `watch` channel. Workers read it at the start
+//! of every iteration, and wait on `changed()` along with their sleep, so
+//! a new configuration takes effect as soon as it's sent. The `control`
+//! module sends one when the configuration file changes, or when a setting
+//! is typed on stdin.
+
+pub mod config;
+pub mod control;
+
+use config::Config;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time;
+
+/// Send greetings to `out`, as many per second and with the text the
+/// configuration says, until either channel is closed.
+///
+/// The last configuration sent is always used before stopping, even when
+/// its sender is dropped right after.
+pub async fn worker(name: String, mut config: watch::Receiver<Config>, out: mpsc::Sender<String>) {
+    loop {
+        // `borrow_and_update`, not `borrow`: this marks the value as seen,
+        // so `changed` below only returns for a value sent after it. With
+        // `borrow`, a value sent before the first iteration would wake the
+        // worker up once more, for nothing.
+        let (greeting, rate) = {
+            let config = config.borrow_and_update();
+            (config.greeting.clone(), config.rate)
+        };
+
+        if out.send(format!("{}: {}", name, greeting)).await.is_err() {
+            return;
+        }
+
+        tokio::select! {
+            _ = time::sleep(Duration::from_secs(1) / rate) => {}
+            res = config.changed() => {
+                // Only once the last value was seen: the loop goes on
+                // otherwise, and uses it.
+                if res.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use config_reload::config::Config;
+use config_reload::{control, worker};
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{self, BufReader};
+use tokio::sync::{mpsc, watch};
+
+/// How often the configuration file is checked for changes.
+const POLL: Duration = Duration::from_millis(500);
+
+#[tokio::main]
+async fn main() {
+    // `config-reload settings.conf`; edit the file, or type a setting like
+    // `greeting = Hi` on stdin, while it runs.
+    let path = PathBuf::from(
+        env::args()
+            .nth(1)
+            .unwrap_or_else(|| "config.txt".to_string()),
+    );
+
+    let (config, receiver) = watch::channel(Config::default());
+    let (out, mut greetings) = mpsc::channel(16);
+
+    for i in 0..2 {
+        tokio::spawn(worker(
+            format!("worker {}", i),
+            receiver.clone(),
+            out.clone(),
+        ));
+    }
+    drop(out);
+
+    tokio::spawn(async move {
+        while let Some(greeting) = greetings.recv().await {
+            println!("{}", greeting);
+        }
+    });
+
+    tokio::join!(
+        control::watch_file(&path, &config, POLL),
+        control::watch_lines(BufReader::new(io::stdin()), &config),
+    );
+}
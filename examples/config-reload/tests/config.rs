@@ -0,0 +1,38 @@
+use config_reload::config::{Config, Invalid};
+
+#[test]
+fn parses_settings() {
+    let config = Config::parse("# Faster.\nrate = 5\n\ngreeting = Hello, world!\n").unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            rate: 5,
+            greeting: "Hello, world!".to_string(),
+        }
+    );
+}
+
+#[test]
+fn missing_settings_keep_their_default() {
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+}
+
+#[test]
+fn rejects_invalid_lines() {
+    assert_eq!(
+        Config::parse("rate = 2\nrate = 0\n"),
+        Err(Invalid {
+            line: 2,
+            reason: "`rate` is a number above 0, not `0`".to_string(),
+        })
+    );
+    assert_eq!(
+        Config::parse("\n\ncolor = blue").unwrap_err().to_string(),
+        "line 3: unknown setting `color`"
+    );
+    assert_eq!(
+        Config::parse("greeting").unwrap_err().reason,
+        "expected `key = value`, got `greeting`"
+    );
+}
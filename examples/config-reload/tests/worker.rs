@@ -0,0 +1,112 @@
+//! The workers, with configurations sent straight into the channel.
+
+use config_reload::config::Config;
+use config_reload::worker;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+struct Greetings {
+    start: Instant,
+    rx: mpsc::Receiver<String>,
+}
+
+impl Greetings {
+    /// The next greeting, and when it came in milliseconds, or `None` once
+    /// every worker stopped.
+    async fn next(&mut self) -> Option<(String, u128)> {
+        let greeting = self.rx.recv().await?;
+        Some((greeting, self.start.elapsed().as_millis()))
+    }
+}
+
+/// Start `workers` workers, named after their number.
+fn spawn(config: &watch::Receiver<Config>, workers: usize) -> Greetings {
+    let (out, rx) = mpsc::channel(16);
+    for i in 0..workers {
+        tokio::spawn(worker(i.to_string(), config.clone(), out.clone()));
+    }
+
+    Greetings {
+        start: Instant::now(),
+        rx,
+    }
+}
+
+fn greeting(text: &str) -> Config {
+    Config {
+        greeting: text.to_string(),
+        ..Config::default()
+    }
+}
+
+fn some(greeting: &str, at: u128) -> Option<(String, u128)> {
+    Some((greeting.to_string(), at))
+}
+
+#[tokio::test(start_paused = true)]
+async fn picks_up_a_new_greeting() {
+    let (config, rx) = watch::channel(greeting("Hello"));
+    let mut greetings = spawn(&rx, 1);
+
+    assert_eq!(greetings.next().await, some("0: Hello", 0));
+
+    // Taken into account at once, without waiting for the next second.
+    config.send(greeting("Hi")).unwrap();
+    assert_eq!(greetings.next().await, some("0: Hi", 0));
+    assert_eq!(greetings.next().await, some("0: Hi", 1000));
+}
+
+#[tokio::test(start_paused = true)]
+async fn picks_up_a_new_rate() {
+    let (config, rx) = watch::channel(Config::default());
+    let mut greetings = spawn(&rx, 1);
+
+    assert_eq!(greetings.next().await, some("0: Hello", 0));
+    assert_eq!(greetings.next().await, some("0: Hello", 1000));
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    config.send_modify(|config| config.rate = 10);
+
+    assert_eq!(greetings.next().await, some("0: Hello", 1500));
+    assert_eq!(greetings.next().await, some("0: Hello", 1600));
+    assert_eq!(greetings.next().await, some("0: Hello", 1700));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_last_configuration_is_not_missed() {
+    let (config, rx) = watch::channel(greeting("Hello"));
+    let mut greetings = spawn(&rx, 3);
+    drop(rx);
+
+    let mut first = vec![];
+    for _ in 0..3 {
+        first.push(greetings.next().await.unwrap().0);
+    }
+    first.sort();
+    assert_eq!(first, ["0: Hello", "1: Hello", "2: Hello"]);
+
+    // Sent, and gone right after.
+    config.send(greeting("Goodbye")).unwrap();
+    drop(config);
+
+    let mut last = vec![];
+    while let Some((greeting, _)) = greetings.next().await {
+        last.push(greeting);
+    }
+    last.sort();
+    assert_eq!(last, ["0: Goodbye", "1: Goodbye", "2: Goodbye"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_configuration_already_used_does_not_wake_the_worker() {
+    let (config, rx) = watch::channel(greeting("Hello"));
+    let mut greetings = spawn(&rx, 1);
+
+    // Sent before the worker's first iteration, which uses it: there's no
+    // change left to wake up for.
+    config.send(greeting("Hi")).unwrap();
+
+    assert_eq!(greetings.next().await, some("0: Hi", 0));
+    assert_eq!(greetings.next().await, some("0: Hi", 1000));
+}
@@ -0,0 +1,241 @@
+//! A pool of connections to a mini-redis server, shared by many tasks.
+//!
+//! `Pool::get` checks a connection out, and the `Conn` it returns puts the
+//! connection back when dropped, for the next task to use. At most
+//! `max_size` connections are checked out at once, counted by a
+//! `Semaphore`: once they all are, `get` waits for one to come back, rather
+//! than failing. Connections are only opened when a task needs one and none
+//! is idle.
+//!
+//! A connection idle for a while may have been closed by the server in the
+//! meantime, so it is checked before being handed out again, and replaced
+//! if it doesn't answer. mini-redis has no `PING`, so the check is a `GET`
+//! of a key nobody sets. A task finding a connection broken otherwise can
+//! `discard` it, for the pool to open another next time.
+//!
+//! A request cancelled half way, by a timeout or a `select!`, may leave its
+//! answer on the connection, where the next request would read it. So a
+//! `Conn` dropped while a request is in flight is closed instead of being
+//! returned.
+
+use bytes::Bytes;
+use mini_redis::client::{self, Client};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{self, Instant};
+
+/// The key the health check gets.
+const PING_KEY: &str = "__connection_pool_ping";
+
+/// How long the health check waits for an answer.
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Connections to the server at one address. Clones share the same
+/// connections.
+#[derive(Clone)]
+pub struct Pool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    addr: SocketAddr,
+    max_idle: Duration,
+
+    /// One permit per connection that may be checked out.
+    permits: Arc<Semaphore>,
+
+    /// The connections not checked out, the most recently used last.
+    idle: Mutex<Vec<Idle>>,
+
+    in_use: AtomicUsize,
+    high_water: AtomicUsize,
+    opened: AtomicU64,
+    replaced: AtomicU64,
+}
+
+struct Idle {
+    client: Client,
+    since: Instant,
+}
+
+/// A connection checked out of a `Pool`, sending the same requests as a
+/// `Client`. Dropping it returns the connection to the pool.
+pub struct Conn {
+    // `None` once discarded.
+    client: Option<Client>,
+
+    // Set while a request waits for its answer.
+    in_flight: bool,
+    shared: Arc<Shared>,
+
+    // Dropped after `drop` puts the connection back, so that a task woken
+    // by the permit finds it there, instead of opening another.
+    _permit: OwnedSemaphorePermit,
+}
+
+/// The counters of a pool at some point.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    /// Connections checked out.
+    pub in_use: usize,
+
+    /// The most connections ever checked out at once.
+    pub high_water: usize,
+
+    /// Connections opened so far, including replacements.
+    pub opened: u64,
+
+    /// Connections that failed the health check, and were replaced.
+    pub replaced: u64,
+}
+
+impl Pool {
+    /// A pool of up to `max_size` connections to `addr`, checking those
+    /// idle for longer than `max_idle` before handing them out. No
+    /// connection is opened yet.
+    pub fn new(addr: SocketAddr, max_size: usize, max_idle: Duration) -> Pool {
+        assert!(max_size > 0, "a pool needs at least one connection");
+
+        Pool {
+            shared: Arc::new(Shared {
+                addr,
+                max_idle,
+                permits: Arc::new(Semaphore::new(max_size)),
+                idle: Mutex::new(Vec::with_capacity(max_size)),
+                in_use: AtomicUsize::new(0),
+                high_water: AtomicUsize::new(0),
+                opened: AtomicU64::new(0),
+                replaced: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Check out a connection, waiting for one to be returned if they are
+    /// all checked out. Fails if a connection has to be opened, and can't.
+    pub async fn get(&self) -> mini_redis::Result<Conn> {
+        let permit = self
+            .shared
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed");
+
+        // The most recently used is the least likely to have gone stale.
+        let idle = self.shared.idle.lock().unwrap().pop();
+
+        let client = match idle {
+            Some(idle) if idle.since.elapsed() < self.shared.max_idle => idle.client,
+            Some(mut idle) => {
+                if ping(&mut idle.client).await {
+                    idle.client
+                } else {
+                    self.shared.replaced.fetch_add(1, Ordering::Relaxed);
+                    self.connect().await?
+                }
+            }
+            None => self.connect().await?,
+        };
+
+        let in_use = self.shared.in_use.fetch_add(1, Ordering::Relaxed) + 1;
+        self.shared.high_water.fetch_max(in_use, Ordering::Relaxed);
+
+        Ok(Conn {
+            client: Some(client),
+            in_flight: false,
+            shared: self.shared.clone(),
+            _permit: permit,
+        })
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            in_use: self.shared.in_use.load(Ordering::Relaxed),
+            high_water: self.shared.high_water.load(Ordering::Relaxed),
+            opened: self.shared.opened.load(Ordering::Relaxed),
+            replaced: self.shared.replaced.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn connect(&self) -> mini_redis::Result<Client> {
+        let client = client::connect(self.shared.addr).await?;
+        self.shared.opened.fetch_add(1, Ordering::Relaxed);
+        Ok(client)
+    }
+}
+
+/// Whether `client` still gets answers from the server.
+async fn ping(client: &mut Client) -> bool {
+    matches!(
+        time::timeout(PING_TIMEOUT, client.get(PING_KEY)).await,
+        Ok(Ok(_))
+    )
+}
+
+impl Conn {
+    /// Get the value of `key`, like `Client::get`.
+    pub async fn get(&mut self, key: &str) -> mini_redis::Result<Option<Bytes>> {
+        let res = self.start().get(key).await;
+        self.in_flight = false;
+        res
+    }
+
+    /// Set `key` to `value`, like `Client::set`.
+    pub async fn set(&mut self, key: &str, value: Bytes) -> mini_redis::Result<()> {
+        let res = self.start().set(key, value).await;
+        self.in_flight = false;
+        res
+    }
+
+    /// Set `key` to `value` for `expiration`, like `Client::set_expires`.
+    pub async fn set_expires(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        expiration: Duration,
+    ) -> mini_redis::Result<()> {
+        let res = self.start().set_expires(key, value, expiration).await;
+        self.in_flight = false;
+        res
+    }
+
+    /// Publish `message` on `channel`, like `Client::publish`.
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> mini_redis::Result<u64> {
+        let res = self.start().publish(channel, message).await;
+        self.in_flight = false;
+        res
+    }
+
+    /// Close the connection rather than return it to the pool, after it
+    /// failed. The pool opens another when one is next needed.
+    pub fn discard(mut self) {
+        self.client = None;
+    }
+
+    /// The client, marked as waiting for an answer until the caller clears
+    /// `in_flight`.
+    fn start(&mut self) -> &mut Client {
+        self.in_flight = true;
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl Drop for Conn {
+    fn drop(&mut self) {
+        match self.client.take() {
+            // Dropped mid-request, the connection may still get an answer
+            // meant for this request, so it is closed.
+            Some(client) if !self.in_flight => {
+                self.shared.idle.lock().unwrap().push(Idle {
+                    client,
+                    since: Instant::now(),
+                });
+            }
+            _ => {}
+        }
+        self.shared.in_use.fetch_sub(1, Ordering::Relaxed);
+    }
+}
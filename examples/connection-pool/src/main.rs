@@ -0,0 +1,40 @@
+use connection_pool::Pool;
+use std::env;
+use std::time::Duration;
+
+/// How many connections the tasks share.
+const MAX_SIZE: usize = 4;
+
+/// How long a connection may sit idle before it is checked.
+const MAX_IDLE: Duration = Duration::from_secs(30);
+
+/// How many tasks use the pool.
+const TASKS: usize = 32;
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    // Start `mini-redis-server` first, or pass the address of another.
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string())
+        .parse()?;
+    let pool = Pool::new(addr, MAX_SIZE, MAX_IDLE);
+
+    let mut tasks = vec![];
+    for i in 0..TASKS {
+        let pool = pool.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut conn = pool.get().await?;
+            let key = format!("key-{}", i);
+            conn.set(&key, format!("value-{}", i).into()).await?;
+            conn.get(&key).await
+        }));
+    }
+
+    for task in tasks {
+        println!("{:?}", task.await??);
+    }
+    println!("{:?}", pool.stats());
+
+    Ok(())
+}
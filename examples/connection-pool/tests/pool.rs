@@ -0,0 +1,162 @@
+use connection_pool::{Pool, Stats};
+use std::future;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+const MAX_IDLE: Duration = Duration::from_millis(50);
+
+/// A mini-redis server on `addr`, or a port of its own, running until the
+/// sender is dropped.
+async fn server(addr: Option<SocketAddr>) -> (SocketAddr, oneshot::Sender<()>, JoinHandle<()>) {
+    let addr = addr.unwrap_or_else(|| "127.0.0.1:0".parse().unwrap());
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (stop, stopped) = oneshot::channel::<()>();
+    let task = tokio::spawn(async move {
+        mini_redis::server::run(listener, stopped).await.unwrap();
+    });
+    (addr, stop, task)
+}
+
+#[tokio::test]
+async fn connections_are_opened_when_needed_and_reused() {
+    let (addr, _stop, _) = server(None).await;
+    let pool = Pool::new(addr, 4, MAX_IDLE);
+    assert_eq!(pool.stats().opened, 0);
+
+    for i in 0..3 {
+        let mut conn = pool.get().await.unwrap();
+        conn.set("count", i.to_string().into()).await.unwrap();
+    }
+
+    assert_eq!(
+        pool.stats(),
+        Stats {
+            in_use: 0,
+            high_water: 1,
+            opened: 1,
+            replaced: 0,
+        }
+    );
+}
+
+#[tokio::test]
+async fn checkouts_never_exceed_the_max() {
+    let (addr, _stop, _) = server(None).await;
+    let pool = Pool::new(addr, 3, MAX_IDLE);
+
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let mut conn = pool.get().await.unwrap();
+                let key = format!("key-{}", i);
+                conn.set(&key, "value".into()).await.unwrap();
+                time::sleep(Duration::from_millis(10)).await;
+                assert_eq!(conn.get(&key).await.unwrap(), Some("value".into()));
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let stats = pool.stats();
+    assert_eq!(stats.high_water, 3);
+    assert_eq!(stats.opened, 3);
+    assert_eq!(stats.in_use, 0);
+}
+
+#[tokio::test]
+async fn get_waits_while_the_pool_is_exhausted() {
+    let (addr, _stop, _) = server(None).await;
+    let pool = Pool::new(addr, 1, MAX_IDLE);
+
+    let conn = pool.get().await.unwrap();
+
+    let waiting = {
+        let pool = pool.clone();
+        tokio::spawn(async move { pool.get().await.map(drop).map_err(|e| e.to_string()) })
+    };
+    time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished());
+
+    drop(conn);
+    time::timeout(Duration::from_secs(5), waiting)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(pool.stats().opened, 1);
+}
+
+#[tokio::test]
+async fn a_connection_broken_by_a_restart_is_replaced() {
+    let (addr, stop, task) = server(None).await;
+    let pool = Pool::new(addr, 1, MAX_IDLE);
+
+    pool.get()
+        .await
+        .unwrap()
+        .set("hello", "world".into())
+        .await
+        .unwrap();
+
+    // Stopping the server closes the connection idle in the pool.
+    drop(stop);
+    task.await.unwrap();
+    let (_, _stop, _) = server(Some(addr)).await;
+
+    time::sleep(MAX_IDLE * 2).await;
+    let mut conn = pool.get().await.unwrap();
+
+    // The data went with the old server, but the connection works.
+    assert_eq!(conn.get("hello").await.unwrap(), None);
+    drop(conn);
+
+    let stats = pool.stats();
+    assert_eq!(stats.opened, 2);
+    assert_eq!(stats.replaced, 1);
+}
+
+#[tokio::test]
+async fn a_discarded_connection_is_not_reused() {
+    let (addr, _stop, _) = server(None).await;
+    let pool = Pool::new(addr, 1, MAX_IDLE);
+
+    pool.get().await.unwrap().discard();
+    let mut conn = pool.get().await.unwrap();
+    conn.set("hello", "world".into()).await.unwrap();
+
+    assert_eq!(pool.stats().opened, 2);
+    assert_eq!(pool.stats().replaced, 0);
+}
+
+#[tokio::test]
+async fn a_connection_dropped_mid_request_is_not_reused() {
+    let (addr, _stop, _) = server(None).await;
+    let pool = Pool::new(addr, 1, MAX_IDLE);
+
+    let mut conn = pool.get().await.unwrap();
+    conn.set("first", "1".into()).await.unwrap();
+    conn.set("second", "2".into()).await.unwrap();
+
+    // The request is sent on the first poll, and cancelled right after,
+    // before the server can answer.
+    tokio::select! {
+        biased;
+        res = conn.get("first") => panic!("answered in one poll: {:?}", res),
+        _ = future::ready(()) => {}
+    }
+    drop(conn);
+
+    // The answer to the cancelled request isn't read in place of this one.
+    let mut conn = pool.get().await.unwrap();
+    assert_eq!(conn.get("second").await.unwrap(), Some("2".into()));
+    assert_eq!(pool.stats().opened, 2);
+}
@@ -0,0 +1,152 @@
+//! A crawler: pages fetched by a pool of workers, the links found on them
+//! fetched in turn, until there is nothing left to fetch.
+//!
+//! The dispatcher owns the set of pages seen so far, and feeds the new ones
+//! to the workers through a channel. The workers send back the links they
+//! found. Knowing when to stop is the tricky part: the queue being empty
+//! isn't enough, as a page being fetched may add to it. So the dispatcher
+//! counts the pages sent and not answered yet, and once there are none,
+//! drops its end of the queue. Every worker's `recv` then returns `None`,
+//! and it stops.
+//!
+//! The pool bounds how many pages are fetched at once; a `Semaphore`,
+//! which may be shared with other users of the site, bounds how many
+//! requests it gets at once.
+
+pub mod site;
+
+use std::collections::{BTreeSet, HashSet};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex, Semaphore};
+
+/// What was found.
+#[derive(Debug, Default, PartialEq)]
+pub struct Crawl {
+    /// The pages fetched.
+    pub visited: BTreeSet<String>,
+    /// The pages linked to, but missing.
+    pub missing: BTreeSet<String>,
+    /// The pages that couldn't be fetched.
+    pub failed: BTreeSet<String>,
+}
+
+/// A page fetched by a worker.
+struct Fetched {
+    path: String,
+    links: io::Result<Option<Vec<String>>>,
+}
+
+/// Crawl the site at `site` from the page `start`, with `workers` workers,
+/// sending at most as many requests at once as `requests` has permits.
+pub async fn crawl(
+    site: SocketAddr,
+    start: &str,
+    workers: usize,
+    requests: Arc<Semaphore>,
+) -> Crawl {
+    // Unbounded: the dispatcher must never wait on the workers while they
+    // wait on it to take their results. The pages of the site bound it
+    // anyway.
+    let (queue, pages) = mpsc::unbounded_channel::<String>();
+    let (results, mut fetched) = mpsc::channel::<Fetched>(workers);
+
+    // The workers share the receiving end.
+    let pages = Arc::new(Mutex::new(pages));
+    let workers: Vec<_> = (0..workers)
+        .map(|_| {
+            let pages = pages.clone();
+            let results = results.clone();
+            let requests = requests.clone();
+            tokio::spawn(worker(site, pages, results, requests))
+        })
+        .collect();
+    drop(results);
+
+    let mut seen = HashSet::new();
+    let mut crawl = Crawl::default();
+
+    seen.insert(start.to_string());
+    queue.send(start.to_string()).unwrap();
+    let mut pending = 1;
+
+    while pending > 0 {
+        let Fetched { path, links } = fetched.recv().await.expect("the workers stopped");
+        pending -= 1;
+
+        match links {
+            Ok(Some(links)) => {
+                for link in links {
+                    if seen.insert(link.clone()) {
+                        queue.send(link).unwrap();
+                        pending += 1;
+                    }
+                }
+                crawl.visited.insert(path);
+            }
+            Ok(None) => {
+                crawl.missing.insert(path);
+            }
+            Err(_) => {
+                crawl.failed.insert(path);
+            }
+        }
+    }
+
+    // Nothing is queued or being fetched: the workers can stop.
+    drop(queue);
+    for worker in workers {
+        worker.await.unwrap();
+    }
+
+    crawl
+}
+
+async fn worker(
+    site: SocketAddr,
+    pages: Arc<Mutex<mpsc::UnboundedReceiver<String>>>,
+    results: mpsc::Sender<Fetched>,
+    requests: Arc<Semaphore>,
+) {
+    loop {
+        // The lock is only held while waiting for a page, not while
+        // fetching it.
+        let path = match pages.lock().await.recv().await {
+            Some(path) => path,
+            None => return,
+        };
+
+        let links = {
+            let _permit = requests.acquire().await.unwrap();
+            fetch(site, &path).await
+        };
+
+        if results.send(Fetched { path, links }).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// The links of the page at `path`, or `None` if there is no such page.
+pub async fn fetch(site: SocketAddr, path: &str) -> io::Result<Option<Vec<String>>> {
+    let mut socket = BufReader::new(TcpStream::connect(site).await?);
+    socket
+        .write_all(format!("GET {}\n", path).as_bytes())
+        .await?;
+
+    let mut line = String::new();
+    socket.read_line(&mut line).await?;
+
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some("OK") => Ok(Some(words.map(str::to_string).collect())),
+        Some("NOT_FOUND") => Ok(None),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected answer {:?}", line),
+        )),
+    }
+}
@@ -0,0 +1,40 @@
+use crawler::site::Site;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// How many pages the site has.
+const PAGES: usize = 100;
+
+const WORKERS: usize = 8;
+
+/// How many requests the site gets at most at once.
+const REQUESTS: usize = 4;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Every page links to a few others, and the graph has cycles.
+    let mut site = Site {
+        latency: Duration::from_millis(50),
+        ..Site::default()
+    };
+    for i in 0..PAGES {
+        let links = [(i * 7 + 1) % PAGES, (i * 13 + 5) % PAGES, (i + 1) % PAGES];
+        let links: Vec<_> = links.iter().map(|link| format!("/{}", link)).collect();
+        site.pages.insert(format!("/{}", i), links);
+    }
+
+    let (addr, stats) = site.spawn().await?;
+
+    let start = Instant::now();
+    let crawl = crawler::crawl(addr, "/0", WORKERS, Arc::new(Semaphore::new(REQUESTS))).await;
+
+    println!(
+        "visited {} pages in {:?}, with at most {} requests at once",
+        crawl.visited.len(),
+        start.elapsed(),
+        stats.max_in_flight()
+    );
+
+    Ok(())
+}
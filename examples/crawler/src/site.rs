@@ -0,0 +1,99 @@
+//! A site to crawl, simulated in-process.
+//!
+//! Each request is a line, `GET <path>`. The answer is a line too: `OK`
+//! followed by the links of the page, or `NOT_FOUND`. It takes a random
+//! time, up to the site's latency.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+/// The pages of a site, and the paths they link to.
+#[derive(Debug, Clone, Default)]
+pub struct Site {
+    pub pages: HashMap<String, Vec<String>>,
+    pub latency: Duration,
+}
+
+/// What a running site saw.
+#[derive(Debug, Default)]
+pub struct Stats {
+    hits: Mutex<HashMap<String, usize>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl Stats {
+    /// How many times the page at `path` was requested.
+    pub fn hits(&self, path: &str) -> usize {
+        self.hits.lock().unwrap().get(path).copied().unwrap_or(0)
+    }
+
+    /// The most requests answered at once.
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+impl Site {
+    /// Add a page linking to `links`.
+    pub fn page(mut self, path: &str, links: &[&str]) -> Site {
+        let links = links.iter().map(|link| link.to_string()).collect();
+        self.pages.insert(path.to_string(), links);
+        self
+    }
+
+    /// Answer on a free port of localhost, in the background.
+    pub async fn spawn(self) -> io::Result<(SocketAddr, Arc<Stats>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let stats = Arc::new(Stats::default());
+
+        let site = Arc::new(self);
+        let served = stats.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let site = site.clone();
+                let stats = served.clone();
+                tokio::spawn(async move {
+                    let _ = site.answer(socket, &stats).await;
+                });
+            }
+        });
+
+        Ok((addr, stats))
+    }
+
+    async fn answer(&self, socket: TcpStream, stats: &Stats) -> io::Result<()> {
+        let mut socket = BufReader::new(socket);
+        let mut request = String::new();
+        socket.read_line(&mut request).await?;
+
+        let path = match request.trim_end().strip_prefix("GET ") {
+            Some(path) => path.to_string(),
+            None => return Ok(()),
+        };
+        *stats.hits.lock().unwrap().entry(path.clone()).or_insert(0) += 1;
+
+        let in_flight = stats.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        stats.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+        let latency = fastrand::u64(0..=self.latency.as_millis() as u64);
+        time::sleep(Duration::from_millis(latency)).await;
+
+        let answer = match self.pages.get(&path) {
+            Some(links) if links.is_empty() => "OK\n".to_string(),
+            Some(links) => format!("OK {}\n", links.join(" ")),
+            None => "NOT_FOUND\n".to_string(),
+        };
+        stats.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        socket.write_all(answer.as_bytes()).await
+    }
+}
@@ -0,0 +1,110 @@
+use crawler::crawl;
+use crawler::site::Site;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time;
+
+/// Crawl, failing if it never finishes.
+async fn crawl_within(
+    addr: std::net::SocketAddr,
+    workers: usize,
+    requests: usize,
+) -> crawler::Crawl {
+    let requests = Arc::new(Semaphore::new(requests));
+    time::timeout(Duration::from_secs(10), crawl(addr, "/", workers, requests))
+        .await
+        .expect("the crawl never finished")
+}
+
+fn site() -> Site {
+    Site {
+        latency: Duration::from_millis(5),
+        ..Site::default()
+    }
+}
+
+#[tokio::test]
+async fn visits_every_page_once() {
+    // With cycles, and a page linked to from several others.
+    let (addr, stats) = site()
+        .page("/", &["/a", "/b"])
+        .page("/a", &["/b", "/c", "/"])
+        .page("/b", &["/a", "/c"])
+        .page("/c", &["/c", "/d"])
+        .page("/d", &[])
+        .page("/unlinked", &["/"])
+        .spawn()
+        .await
+        .unwrap();
+
+    let crawl = crawl_within(addr, 4, 2).await;
+
+    let visited: Vec<_> = crawl.visited.iter().map(String::as_str).collect();
+    assert_eq!(visited, ["/", "/a", "/b", "/c", "/d"]);
+    assert!(crawl.missing.is_empty());
+    assert!(crawl.failed.is_empty());
+
+    for path in visited {
+        assert_eq!(stats.hits(path), 1, "{}", path);
+    }
+    assert_eq!(stats.hits("/unlinked"), 0);
+}
+
+#[tokio::test]
+async fn reports_missing_pages() {
+    let (addr, stats) = site()
+        .page("/", &["/gone", "/here"])
+        .page("/here", &["/gone"])
+        .spawn()
+        .await
+        .unwrap();
+
+    let crawl = crawl_within(addr, 2, 2).await;
+
+    assert_eq!(crawl.visited.len(), 2);
+    assert_eq!(crawl.missing.iter().collect::<Vec<_>>(), ["/gone"]);
+    assert_eq!(stats.hits("/gone"), 1);
+}
+
+#[tokio::test]
+async fn stops_after_a_single_page() {
+    let (addr, _) = site().page("/", &[]).spawn().await.unwrap();
+
+    let crawl = crawl_within(addr, 4, 4).await;
+
+    assert_eq!(crawl.visited.len(), 1);
+}
+
+#[tokio::test]
+async fn bounds_the_requests_in_flight() {
+    let links: Vec<_> = (0..40).map(|i| format!("/{}", i)).collect();
+    let links: Vec<_> = links.iter().map(String::as_str).collect();
+    let mut site = Site {
+        latency: Duration::from_millis(10),
+        ..Site::default()
+    }
+    .page("/", &links);
+    for link in &links {
+        site = site.page(link, &[]);
+    }
+    let (addr, stats) = site.spawn().await.unwrap();
+
+    // More workers than requests allowed.
+    let crawl = crawl_within(addr, 10, 3).await;
+
+    assert_eq!(crawl.visited.len(), 41);
+    assert!(stats.max_in_flight() <= 3, "{}", stats.max_in_flight());
+}
+
+#[tokio::test]
+async fn unreachable_site_fails_the_start() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let crawl = crawl_within(addr, 2, 2).await;
+
+    assert!(crawl.visited.is_empty());
+    assert_eq!(crawl.failed.iter().collect::<Vec<_>>(), ["/"]);
+}
@@ -0,0 +1,28 @@
+//! The line-echo protocol: every line comes back.
+
+use crate::listener::Accepted;
+use crate::Error;
+use futures::{SinkExt, StreamExt};
+use tokio_util::codec::{Framed, LinesCodec};
+
+/// The longest line a client may send, newline excluded.
+pub const MAX_LINE: usize = 8 * 1024;
+
+/// Echo the lines of `conn` until the client closes it, or the server
+/// shuts down.
+pub async fn handle(conn: Accepted) -> Result<(), Error> {
+    let mut lines = Framed::new(conn.socket, LinesCodec::new_with_max_length(MAX_LINE));
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next() => match line {
+                Some(line) => line?,
+                None => return Ok(()),
+            },
+            _ = conn.shutdown.cancelled() => return Ok(()),
+        };
+
+        lines.send(line).await?;
+        conn.stats.request();
+    }
+}
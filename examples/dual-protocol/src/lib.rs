@@ -0,0 +1,55 @@
+//! One server, two protocols: line-echo on one port, and the `GET` and
+//! `SET` of mini-redis on another.
+//!
+//! The accept loop, the task of each connection, counting them, and
+//! waiting for them on shutdown are the same for both, so they are written
+//! once, in `listener`. What differs is the `Handler` of each protocol, an
+//! async function taking the connection. Both listeners count into the same
+//! `Metrics`, and stop on the same `CancellationToken`.
+
+pub mod echo;
+pub mod listener;
+pub mod metrics;
+pub mod redis;
+
+use listener::Listener;
+use metrics::Metrics;
+use std::io;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// An error from a connection.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Serve line-echo on `echo`, and mini-redis on `redis`, until `shutdown` is
+/// cancelled. Returns once the connections of both are closed.
+///
+/// If accepting on either listener fails, `shutdown` is cancelled, so the
+/// other stops as well.
+pub async fn run(
+    echo: TcpListener,
+    redis: TcpListener,
+    metrics: Arc<Metrics>,
+    shutdown: CancellationToken,
+) -> io::Result<()> {
+    let db = crate::redis::Db::default();
+
+    let echo = Listener::new(echo, crate::echo::handle, metrics.register("echo"));
+    let redis = Listener::new(
+        redis,
+        move |conn| crate::redis::handle(conn, db.clone()),
+        metrics.register("redis"),
+    );
+
+    let stop_all = |res: io::Result<()>| {
+        shutdown.cancel();
+        res
+    };
+    let (echo, redis) = tokio::join!(
+        async { stop_all(echo.run(shutdown.clone()).await) },
+        async { stop_all(redis.run(shutdown.clone()).await) },
+    );
+
+    echo.and(redis)
+}
@@ -0,0 +1,128 @@
+//! What every listener does, whatever its protocol: accepting connections,
+//! running a handler for each in a task, counting them, and draining them
+//! on shutdown.
+
+use crate::metrics::Stats;
+use crate::Error;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+/// A connection, and what its handler needs besides.
+#[derive(Debug)]
+pub struct Accepted {
+    pub socket: TcpStream,
+    pub addr: SocketAddr,
+
+    /// Cancelled when the server shuts down. The handler should then finish
+    /// the request it is on, if any, and return.
+    pub shutdown: CancellationToken,
+
+    /// The counters of the listener, for the handler to count requests.
+    pub stats: Arc<Stats>,
+}
+
+/// Handles the connections of a protocol.
+///
+/// Any function or closure taking an `Accepted` and returning a suitable
+/// future is one.
+pub trait Handler: Send + Sync + 'static {
+    type Future: Future<Output = Result<(), Error>> + Send + 'static;
+
+    fn handle(&self, conn: Accepted) -> Self::Future;
+}
+
+impl<F, Fut> Handler for F
+where
+    F: Fn(Accepted) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    type Future = Fut;
+
+    fn handle(&self, conn: Accepted) -> Fut {
+        self(conn)
+    }
+}
+
+/// A `TcpListener`, and the handler of its connections.
+#[derive(Debug)]
+pub struct Listener<H> {
+    listener: TcpListener,
+    handler: H,
+    stats: Arc<Stats>,
+}
+
+impl<H: Handler> Listener<H> {
+    /// Counting into `stats`, likely from `Metrics::register`.
+    pub fn new(listener: TcpListener, handler: H, stats: Arc<Stats>) -> Listener<H> {
+        Listener {
+            listener,
+            handler,
+            stats,
+        }
+    }
+
+    /// Accept connections until `shutdown` is cancelled, or accepting
+    /// fails, then wait for the connections still open to close.
+    pub async fn run(self, shutdown: CancellationToken) -> io::Result<()> {
+        let mut connections = JoinSet::new();
+
+        let res = loop {
+            tokio::select! {
+                res = self.listener.accept() => {
+                    let (socket, addr) = match res {
+                        Ok(accepted) => accepted,
+                        Err(err) => break Err(err),
+                    };
+                    let conn = Accepted {
+                        socket,
+                        addr,
+                        shutdown: shutdown.clone(),
+                        stats: self.stats.clone(),
+                    };
+                    let future = self.handler.handle(conn);
+                    spawn_connection(&mut connections, &self.stats, addr, future);
+                }
+                // Forget about the connections that are done, so the set
+                // only holds the ones open.
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                _ = shutdown.cancelled() => break Ok(()),
+            }
+        };
+
+        // Stop accepting before waiting, so new clients are refused rather
+        // than left hanging.
+        drop(self.listener);
+
+        while connections.join_next().await.is_some() {}
+
+        res
+    }
+}
+
+/// Run `future`, handling the connection from `addr`, in a task of
+/// `connections`, counting it in `stats`.
+pub fn spawn_connection<F>(
+    connections: &mut JoinSet<()>,
+    stats: &Arc<Stats>,
+    addr: SocketAddr,
+    future: F,
+) where
+    F: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    stats.opened();
+
+    let stats = stats.clone();
+    connections.spawn(async move {
+        let res = future.await;
+        stats.closed(res.is_ok());
+
+        if let Err(err) = res {
+            eprintln!("{} connection from {} failed: {}", stats.name(), addr, err);
+        }
+    });
+}
@@ -0,0 +1,27 @@
+use dual_protocol::metrics::Metrics;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::signal;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try them with `nc 127.0.0.1 12345`, and the mini-redis CLI.
+    let echo = TcpListener::bind("127.0.0.1:12345").await?;
+    let redis = TcpListener::bind("127.0.0.1:6379").await?;
+    println!("line-echo on 127.0.0.1:12345, mini-redis on 127.0.0.1:6379");
+
+    let metrics = Arc::new(Metrics::new());
+    let shutdown = CancellationToken::new();
+
+    let stop = shutdown.clone();
+    tokio::spawn(async move {
+        let _ = signal::ctrl_c().await;
+        println!("shutting down");
+        stop.cancel();
+    });
+
+    let res = dual_protocol::run(echo, redis, metrics.clone(), shutdown).await;
+    print!("{}", metrics);
+    res
+}
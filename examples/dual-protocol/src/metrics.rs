@@ -0,0 +1,117 @@
+//! Counters shared by the listeners, one set per protocol.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The counters of every protocol, by name.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    protocols: Mutex<BTreeMap<&'static str, Arc<Stats>>>,
+}
+
+/// The counters of one protocol.
+#[derive(Debug)]
+pub struct Stats {
+    name: &'static str,
+    accepted: AtomicU64,
+    open: AtomicU64,
+    failed: AtomicU64,
+    requests: AtomicU64,
+}
+
+/// The counters of a protocol at some point.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Snapshot {
+    /// Connections accepted so far.
+    pub accepted: u64,
+
+    /// Connections still open.
+    pub open: u64,
+
+    /// Connections closed by an error.
+    pub failed: u64,
+
+    /// Requests answered, over all connections.
+    pub requests: u64,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    /// The counters of the protocol `name`, created the first time.
+    pub fn register(&self, name: &'static str) -> Arc<Stats> {
+        self.protocols
+            .lock()
+            .unwrap()
+            .entry(name)
+            .or_insert_with(|| {
+                Arc::new(Stats {
+                    name,
+                    accepted: AtomicU64::new(0),
+                    open: AtomicU64::new(0),
+                    failed: AtomicU64::new(0),
+                    requests: AtomicU64::new(0),
+                })
+            })
+            .clone()
+    }
+
+    /// The counters of every protocol registered.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, Snapshot> {
+        self.protocols
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (*name, stats.snapshot()))
+            .collect()
+    }
+}
+
+impl Stats {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Count a request answered.
+    pub fn request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn opened(&self) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        self.open.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn closed(&self, ok: bool) {
+        self.open.fetch_sub(1, Ordering::Relaxed);
+        if !ok {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            open: self.open.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, snapshot) in self.snapshot() {
+            writeln!(
+                fmt,
+                "{}: {} connections ({} open, {} failed), {} requests",
+                name, snapshot.accepted, snapshot.open, snapshot.failed, snapshot.requests
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! The `GET` and `SET` commands of mini-redis, on a shared map.
+
+use crate::listener::Accepted;
+use crate::Error;
+use bytes::Bytes;
+use mini_redis::{Command, Connection, Frame};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The keys and values, shared by every connection.
+pub type Db = Arc<Mutex<HashMap<String, Bytes>>>;
+
+/// Answer the commands of `conn` from `db`, until the client closes it, or
+/// the server shuts down.
+pub async fn handle(conn: Accepted, db: Db) -> Result<(), Error> {
+    let mut connection = Connection::new(conn.socket);
+
+    loop {
+        let frame = tokio::select! {
+            frame = connection.read_frame() => match frame? {
+                Some(frame) => frame,
+                None => return Ok(()),
+            },
+            _ = conn.shutdown.cancelled() => return Ok(()),
+        };
+
+        let response = match Command::from_frame(frame) {
+            Ok(Command::Get(cmd)) => match db.lock().unwrap().get(cmd.key()) {
+                Some(value) => Frame::Bulk(value.clone()),
+                None => Frame::Null,
+            },
+            Ok(Command::Set(cmd)) => {
+                let mut db = db.lock().unwrap();
+                db.insert(cmd.key().to_string(), cmd.value().clone());
+                Frame::Simple("OK".to_string())
+            }
+            Ok(_) => Frame::Error("ERR only GET and SET are supported".to_string()),
+            Err(err) => Frame::Error(format!("ERR {}", err)),
+        };
+
+        connection.write_frame(&response).await?;
+        conn.stats.request();
+    }
+}
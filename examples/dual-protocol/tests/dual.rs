@@ -0,0 +1,147 @@
+use bytes::Bytes;
+use dual_protocol::listener::{Accepted, Listener};
+use dual_protocol::metrics::{Metrics, Snapshot};
+use futures::{SinkExt, StreamExt};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+use tokio::task::{self, JoinHandle};
+use tokio::time;
+use tokio_util::codec::{Framed, LinesCodec};
+use tokio_util::sync::CancellationToken;
+
+struct Server {
+    echo: SocketAddr,
+    redis: SocketAddr,
+    metrics: Arc<Metrics>,
+    shutdown: CancellationToken,
+    task: JoinHandle<io::Result<()>>,
+}
+
+async fn server() -> Server {
+    let echo = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let redis = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let (echo_addr, redis_addr) = (echo.local_addr().unwrap(), redis.local_addr().unwrap());
+
+    let metrics = Arc::new(Metrics::new());
+    let shutdown = CancellationToken::new();
+    let task = tokio::spawn(dual_protocol::run(
+        echo,
+        redis,
+        metrics.clone(),
+        shutdown.clone(),
+    ));
+
+    Server {
+        echo: echo_addr,
+        redis: redis_addr,
+        metrics,
+        shutdown,
+        task,
+    }
+}
+
+async fn echo_client(addr: SocketAddr) -> Framed<TcpStream, LinesCodec> {
+    Framed::new(TcpStream::connect(addr).await.unwrap(), LinesCodec::new())
+}
+
+#[tokio::test]
+async fn serves_both_protocols_at_once() {
+    let server = server().await;
+
+    let echo = async {
+        let mut lines = echo_client(server.echo).await;
+        for i in 0..10 {
+            lines.send(format!("line {}", i)).await.unwrap();
+            assert_eq!(lines.next().await.unwrap().unwrap(), format!("line {}", i));
+        }
+    };
+    let redis = async {
+        let mut client = mini_redis::client::connect(server.redis).await.unwrap();
+        for i in 0..5 {
+            let key = format!("key {}", i);
+            client.set(&key, Bytes::from(i.to_string())).await.unwrap();
+            let value = client.get(&key).await.unwrap();
+            assert_eq!(value, Some(Bytes::from(i.to_string())));
+        }
+    };
+    tokio::join!(echo, redis);
+
+    let metrics = server.metrics.snapshot();
+    assert_eq!(metrics["echo"].accepted, 1);
+    assert_eq!(metrics["echo"].requests, 10);
+    assert_eq!(metrics["redis"].accepted, 1);
+    assert_eq!(metrics["redis"].requests, 10);
+}
+
+#[tokio::test]
+async fn shutdown_closes_the_connections_of_both() {
+    let server = server().await;
+
+    let mut lines = echo_client(server.echo).await;
+    lines.send("hello").await.unwrap();
+    assert_eq!(lines.next().await.unwrap().unwrap(), "hello");
+
+    let mut client = mini_redis::client::connect(server.redis).await.unwrap();
+    client.set("key", Bytes::from("value")).await.unwrap();
+
+    server.shutdown.cancel();
+    server.task.await.unwrap().unwrap();
+
+    // Both connections are closed, and both listeners too.
+    assert!(lines.next().await.is_none());
+    assert!(client.get("key").await.is_err());
+    assert!(TcpStream::connect(server.echo).await.is_err());
+    assert!(TcpStream::connect(server.redis).await.is_err());
+
+    let metrics = server.metrics.snapshot();
+    let expected = Snapshot {
+        accepted: 1,
+        open: 0,
+        failed: 0,
+        requests: 1,
+    };
+    assert_eq!(metrics["echo"], expected);
+    assert_eq!(metrics["redis"], expected);
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_handlers_to_finish() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let metrics = Metrics::new();
+
+    // A handler that finishes when told to, whether the server is shutting
+    // down or not.
+    let done = Arc::new(Notify::new());
+    let handler = {
+        let done = done.clone();
+        move |_conn: Accepted| {
+            let done = done.clone();
+            async move {
+                done.notified().await;
+                Ok(())
+            }
+        }
+    };
+
+    let shutdown = CancellationToken::new();
+    let listener = Listener::new(listener, handler, metrics.register("slow"));
+    let task = tokio::spawn(listener.run(shutdown.clone()));
+
+    let _socket = TcpStream::connect(addr).await.unwrap();
+    while metrics.snapshot()["slow"].open == 0 {
+        task::yield_now().await;
+    }
+
+    shutdown.cancel();
+    time::sleep(Duration::from_millis(50)).await;
+    assert!(!task.is_finished());
+
+    done.notify_one();
+    task.await.unwrap().unwrap();
+    assert_eq!(metrics.snapshot()["slow"].open, 0);
+}
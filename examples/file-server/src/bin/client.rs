@@ -0,0 +1,30 @@
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+#[tokio::main]
+async fn main() {
+    // `client 127.0.0.1:8080 some/file copy` downloads `some/file` into
+    // `copy`.
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.len() != 3 {
+        eprintln!("usage: file-server-client <server> <file> <destination>");
+        process::exit(2);
+    }
+
+    let addr = match args[0].parse() {
+        Ok(addr) => addr,
+        Err(err) => {
+            eprintln!("{}: {}", args[0], err);
+            process::exit(2);
+        }
+    };
+
+    match file_server::client::download(addr, &args[1], &PathBuf::from(&args[2])).await {
+        Ok(len) => println!("{} bytes, checksum verified", len),
+        Err(err) => {
+            eprintln!("downloading {} failed: {}", args[1], err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::env;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // `file-server 127.0.0.1:8080 some/dir` serves the files of `some/dir`.
+    let mut args = env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    let root = PathBuf::from(args.next().unwrap_or_else(|| ".".to_string()));
+
+    let listener = TcpListener::bind(&addr).await?;
+    println!("serving {} on {}", root.display(), addr);
+
+    file_server::server::serve(listener, root).await
+}
@@ -0,0 +1,93 @@
+//! Downloading files.
+
+use crate::{Checksum, CHUNK, ERROR, FILE};
+use bytes::BytesMut;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The server couldn't send the file.
+    Server(String),
+    /// The contents didn't match the checksum.
+    Corrupted,
+}
+
+/// Download the file `name` from the server at `addr` into a file at `dst`,
+/// and return its length.
+///
+/// Should the download fail, what was written of `dst` is left there.
+pub async fn download(addr: SocketAddr, name: &str, dst: &Path) -> Result<u64, Error> {
+    let mut socket = BufReader::new(TcpStream::connect(addr).await?);
+    socket
+        .get_mut()
+        .write_all(format!("{}\n", name).as_bytes())
+        .await?;
+
+    let tag = socket.read_u8().await?;
+    let len = socket.read_u32().await?;
+
+    match tag {
+        FILE => {}
+        ERROR => {
+            let mut message = vec![0; len as usize];
+            socket.read_exact(&mut message).await?;
+            return Err(Error::Server(
+                String::from_utf8_lossy(&message).into_owned(),
+            ));
+        }
+        _ => {
+            let err = io::Error::new(io::ErrorKind::InvalidData, "unknown answer");
+            return Err(Error::Io(err));
+        }
+    }
+
+    let mut file = File::create(dst).await?;
+    let mut checksum = Checksum::new();
+    let mut buf = BytesMut::with_capacity(CHUNK);
+    let mut left = u64::from(len);
+
+    while left > 0 {
+        buf.clear();
+        // Only this file's bytes: the checksum comes right after them.
+        let n = (&mut socket).take(left).read_buf(&mut buf).await?;
+        if n == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        left -= n as u64;
+
+        checksum.update(&buf);
+        file.write_all(&buf).await?;
+    }
+    file.flush().await?;
+
+    if socket.read_u64().await? != checksum.finish() {
+        return Err(Error::Corrupted);
+    }
+
+    Ok(u64::from(len))
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => err.fmt(fmt),
+            Error::Server(message) => write!(fmt, "the server says: {}", message),
+            Error::Corrupted => "the contents don't match their checksum".fmt(fmt),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
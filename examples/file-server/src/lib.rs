@@ -0,0 +1,56 @@
+//! A file server: a client sends the name of a file, the server streams it
+//! back.
+//!
+//! The request is the name on a line. The answer starts with a tag byte
+//! and a 4-byte big-endian length:
+//!
+//! * `+`: the file follows, that many bytes of it, then the checksum of
+//!   the contents, 8 bytes.
+//! * `-`: an error message follows, that many bytes of it.
+//!
+//! The server reads the file in chunks into the same buffer, so serving a
+//! large file takes no more memory than serving a small one. The client
+//! writes the contents to a file as they arrive, and checks them against
+//! the checksum at the end.
+
+pub mod client;
+pub mod server;
+
+/// The tag of an answer with a file.
+pub const FILE: u8 = b'+';
+
+/// The tag of an answer with an error.
+pub const ERROR: u8 = b'-';
+
+/// How much of a file is read or written at once.
+pub const CHUNK: usize = 64 * 1024;
+
+/// The longest file name a client may send.
+pub const MAX_NAME: usize = 1024;
+
+/// FNV-1a, 64 bits: quick, and good enough to catch a corrupted transfer.
+#[derive(Debug, Clone, Copy)]
+pub struct Checksum(u64);
+
+impl Checksum {
+    pub fn new() -> Checksum {
+        Checksum(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Checksum {
+        Checksum::new()
+    }
+}
@@ -0,0 +1,125 @@
+//! Serving the files of a directory.
+
+use crate::{Checksum, CHUNK, ERROR, FILE, MAX_NAME};
+use bytes::BytesMut;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Serve the files of `root` to the clients of `listener`, until accepting
+/// fails.
+pub async fn serve(listener: TcpListener, root: PathBuf) -> io::Result<()> {
+    let root = Arc::new(root);
+
+    loop {
+        let (socket, addr) = listener.accept().await?;
+        let root = root.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = answer(socket, &root).await {
+                eprintln!("{}: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Answer the one request of `socket`.
+async fn answer(socket: TcpStream, root: &Path) -> io::Result<()> {
+    let mut socket = BufReader::new(socket);
+
+    let mut name = String::new();
+    (&mut socket)
+        .take(MAX_NAME as u64 + 1)
+        .read_line(&mut name)
+        .await?;
+    let name = name.trim_end_matches(&['\r', '\n'][..]);
+    if name.len() > MAX_NAME {
+        return send_error(&mut socket, "file name too long").await;
+    }
+
+    let path = match resolve(root, name) {
+        Some(path) => path,
+        None => return send_error(&mut socket, "invalid file name").await,
+    };
+
+    // The length goes first, so it must be known before sending anything.
+    let (file, len) = match open(&path).await {
+        Ok((file, len)) if len <= u64::from(u32::MAX) => (file, len as u32),
+        Ok(_) => return send_error(&mut socket, "file too large").await,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return send_error(&mut socket, "file not found").await
+        }
+        Err(err) => return send_error(&mut socket, &err.to_string()).await,
+    };
+
+    socket.write_u8(FILE).await?;
+    socket.write_u32(len).await?;
+    let checksum = send_contents(file, len, socket.get_mut()).await?;
+    socket.write_u64(checksum).await?;
+    socket.flush().await
+}
+
+/// The file at `path`, and its length.
+async fn open(path: &Path) -> io::Result<(File, u64)> {
+    let file = File::open(path).await?;
+    let meta = file.metadata().await?;
+
+    // A directory opens fine, but can't be read.
+    if !meta.is_file() {
+        return Err(io::Error::other("not a file"));
+    }
+
+    Ok((file, meta.len()))
+}
+
+/// Send `len` bytes of `file`, and return their checksum.
+async fn send_contents(mut file: File, len: u32, socket: &mut TcpStream) -> io::Result<u64> {
+    let mut checksum = Checksum::new();
+    let mut buf = BytesMut::with_capacity(CHUNK);
+    let mut left = u64::from(len);
+
+    while left > 0 {
+        // `read_buf` appends to what is already there: clearing the buffer
+        // keeps its capacity, so each chunk reuses the same memory.
+        buf.clear();
+        if file.read_buf(&mut buf).await? == 0 {
+            // The file got shorter since its length was sent: there's no
+            // way to tell the client but to close the connection.
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        // It may have grown, too: only the length sent goes out.
+        buf.truncate(left.min(buf.len() as u64) as usize);
+        left -= buf.len() as u64;
+
+        checksum.update(&buf);
+        socket.write_all(&buf).await?;
+    }
+
+    Ok(checksum.finish())
+}
+
+async fn send_error(socket: &mut BufReader<TcpStream>, message: &str) -> io::Result<()> {
+    socket.write_u8(ERROR).await?;
+    socket.write_u32(message.len() as u32).await?;
+    socket.write_all(message.as_bytes()).await?;
+    socket.flush().await
+}
+
+/// The path of the file `name` in `root`, unless `name` tries to get out
+/// of it, with `..` or an absolute path.
+fn resolve(root: &Path, name: &str) -> Option<PathBuf> {
+    let name = Path::new(name);
+    let inside = name
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+
+    if name.as_os_str().is_empty() || !inside {
+        return None;
+    }
+
+    Some(root.join(name))
+}
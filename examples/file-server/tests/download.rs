@@ -0,0 +1,142 @@
+use file_server::client::{download, Error};
+use file_server::server::serve;
+use file_server::Checksum;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+/// A server for the files of `root`.
+async fn server(root: &Path) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener, root.to_path_buf()));
+    addr
+}
+
+/// `len` bytes that aren't all the same, so that mixing up chunks shows.
+fn contents(len: usize, seed: u8) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i % 251) as u8 ^ seed.wrapping_mul((i / 251) as u8))
+        .collect()
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut checksum = Checksum::new();
+    checksum.update(bytes);
+    checksum.finish()
+}
+
+#[tokio::test]
+async fn downloads_a_large_file() {
+    let served = TempDir::new().unwrap();
+    let received = TempDir::new().unwrap();
+    // Many chunks, and a last one that isn't full.
+    let original = contents(5 * 1024 * 1024 + 123, 7);
+    fs::write(served.path().join("large.bin"), &original).unwrap();
+
+    let addr = server(served.path()).await;
+    let dst = received.path().join("copy.bin");
+    let len = download(addr, "large.bin", &dst).await.unwrap();
+
+    assert_eq!(len, original.len() as u64);
+    let copy = fs::read(&dst).unwrap();
+    assert_eq!(checksum(&copy), checksum(&original));
+    assert!(copy == original);
+}
+
+#[tokio::test]
+async fn downloads_an_empty_file() {
+    let served = TempDir::new().unwrap();
+    let received = TempDir::new().unwrap();
+    fs::write(served.path().join("empty"), b"").unwrap();
+
+    let addr = server(served.path()).await;
+    let dst = received.path().join("empty");
+
+    assert_eq!(download(addr, "empty", &dst).await.unwrap(), 0);
+    assert_eq!(fs::read(&dst).unwrap(), b"");
+}
+
+#[tokio::test]
+async fn missing_files_are_an_error() {
+    let served = TempDir::new().unwrap();
+    let received = TempDir::new().unwrap();
+    let addr = server(served.path()).await;
+    let dst = received.path().join("copy");
+
+    match download(addr, "missing.txt", &dst).await {
+        Err(Error::Server(message)) => assert_eq!(message, "file not found"),
+        res => panic!("{:?}", res),
+    }
+    // Nothing was created.
+    assert!(!dst.exists());
+}
+
+#[tokio::test]
+async fn names_outside_the_directory_are_refused() {
+    let root = TempDir::new().unwrap();
+    let served = root.path().join("served");
+    fs::create_dir(&served).unwrap();
+    fs::write(root.path().join("secret"), b"secret").unwrap();
+
+    let addr = server(&served).await;
+    let dst = root.path().join("copy");
+
+    for name in ["../secret", "/etc/passwd", ""].iter() {
+        match download(addr, name, &dst).await {
+            Err(Error::Server(message)) => assert_eq!(message, "invalid file name"),
+            res => panic!("{}: {:?}", name, res),
+        }
+    }
+}
+
+#[tokio::test]
+async fn long_names_are_refused() {
+    let served = TempDir::new().unwrap();
+    let addr = server(served.path()).await;
+    let name = "a".repeat(file_server::MAX_NAME + 1);
+
+    match download(addr, &name, &served.path().join("copy")).await {
+        Err(Error::Server(message)) => assert_eq!(message, "file name too long"),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn directories_are_not_files() {
+    let served = TempDir::new().unwrap();
+    fs::create_dir(served.path().join("dir")).unwrap();
+    let addr = server(served.path()).await;
+
+    match download(addr, "dir", &served.path().join("copy")).await {
+        Err(Error::Server(message)) => assert_eq!(message, "not a file"),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn concurrent_downloads() {
+    let served = TempDir::new().unwrap();
+    let received = TempDir::new().unwrap();
+    let first = contents(2 * 1024 * 1024, 3);
+    let second = contents(3 * 1024 * 1024 + 17, 5);
+    fs::write(served.path().join("first"), &first).unwrap();
+    fs::write(served.path().join("second"), &second).unwrap();
+
+    let addr = server(served.path()).await;
+    let (first_dst, second_dst) = (
+        received.path().join("first"),
+        received.path().join("second"),
+    );
+    let (a, b) = tokio::join!(
+        download(addr, "first", &first_dst),
+        download(addr, "second", &second_dst),
+    );
+    a.unwrap();
+    b.unwrap();
+
+    assert!(fs::read(&first_dst).unwrap() == first);
+    assert!(fs::read(&second_dst).unwrap() == second);
+}
@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Generates the messages, and the server and client of `Greeter`, into
+    // `OUT_DIR`, where `tonic::include_proto!` finds them.
+    tonic_build::compile_protos("proto/hello.proto")?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use grpc_hello::pb::greeter_client::GreeterClient;
+use grpc_hello::pb::{HelloRequest, HellosRequest};
+use std::env;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:50051".to_string());
+    let mut client = GreeterClient::connect(format!("http://{}", addr)).await?;
+
+    let reply = client
+        .say_hello(HelloRequest {
+            name: "Tokio".to_string(),
+        })
+        .await?;
+    println!("{}", reply.into_inner().message);
+
+    let mut replies = client
+        .say_hellos(HellosRequest {
+            name: "Tokio".to_string(),
+            count: 3,
+        })
+        .await?
+        .into_inner();
+    while let Some(reply) = replies.message().await? {
+        println!("{}", reply.message);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use std::env;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:50051".to_string());
+    let listener = TcpListener::bind(&addr).await?;
+    println!("listening on {}", addr);
+
+    grpc_hello::serve(listener).await?;
+    Ok(())
+}
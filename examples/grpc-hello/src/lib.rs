@@ -0,0 +1,94 @@
+//! A gRPC greeter with tonic, on top of Tokio.
+//!
+//! The service is described in `proto/hello.proto`. At build time,
+//! tonic-build turns it into Rust: a struct for each message, a
+//! `greeter_server::Greeter` trait for the server to implement, and a
+//! `greeter_client::GreeterClient` to call it with. Underneath, tonic
+//! serves HTTP/2 with hyper, on Tokio's `TcpListener`.
+//!
+//! `SayHellos` streams its replies: the handler spawns a task sending them
+//! into an `mpsc` channel, and returns the receiver as the stream, wrapped
+//! in a `ReceiverStream`.
+
+pub mod pb {
+    tonic::include_proto!("hello");
+}
+
+use pb::greeter_server::{Greeter, GreeterServer};
+use pb::{HelloReply, HelloRequest, HellosRequest};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+/// How long the greeter thinks about each greeting.
+pub const THINKING: Duration = Duration::from_millis(10);
+
+/// The most greetings `SayHellos` makes for one request.
+pub const MAX_COUNT: u32 = 100;
+
+#[derive(Debug, Default)]
+pub struct MyGreeter;
+
+#[tonic::async_trait]
+impl Greeter for MyGreeter {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let name = request.into_inner().name;
+        if name.is_empty() {
+            return Err(Status::invalid_argument("a name is needed"));
+        }
+
+        // Like a handler waiting on a database: the task yields, and the
+        // runtime serves other requests in the meantime.
+        time::sleep(THINKING).await;
+
+        Ok(Response::new(HelloReply {
+            message: format!("Hello, {}!", name),
+        }))
+    }
+
+    type SayHellosStream = ReceiverStream<Result<HelloReply, Status>>;
+
+    async fn say_hellos(
+        &self,
+        request: Request<HellosRequest>,
+    ) -> Result<Response<Self::SayHellosStream>, Status> {
+        let HellosRequest { name, count } = request.into_inner();
+        if count > MAX_COUNT {
+            return Err(Status::invalid_argument(format!(
+                "up to {} greetings at once",
+                MAX_COUNT
+            )));
+        }
+
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            for i in 1..=count {
+                time::sleep(THINKING).await;
+                let reply = HelloReply {
+                    message: format!("Hello, {}! ({} of {})", name, i, count),
+                };
+                // The client went away.
+                if tx.send(Ok(reply)).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serve `MyGreeter` on `listener`.
+pub async fn serve(listener: TcpListener) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(GreeterServer::new(MyGreeter))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
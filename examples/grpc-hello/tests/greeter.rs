@@ -0,0 +1,83 @@
+use grpc_hello::pb::greeter_client::GreeterClient;
+use grpc_hello::pb::{HelloRequest, HellosRequest};
+use grpc_hello::MAX_COUNT;
+use tokio::net::TcpListener;
+use tonic::transport::Channel;
+use tonic::Code;
+
+/// A client of a server on a free port.
+async fn client() -> GreeterClient<Channel> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(grpc_hello::serve(listener));
+
+    GreeterClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn says_hello() {
+    let mut client = client().await;
+
+    let reply = client
+        .say_hello(HelloRequest {
+            name: "Ferris".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(reply.into_inner().message, "Hello, Ferris!");
+}
+
+#[tokio::test]
+async fn refuses_empty_names() {
+    let mut client = client().await;
+
+    let status = client
+        .say_hello(HelloRequest {
+            name: String::new(),
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn streams_greetings() {
+    let mut client = client().await;
+
+    let mut replies = client
+        .say_hellos(HellosRequest {
+            name: "Ferris".to_string(),
+            count: 5,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut messages = vec![];
+    while let Some(reply) = replies.message().await.unwrap() {
+        messages.push(reply.message);
+    }
+
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[0], "Hello, Ferris! (1 of 5)");
+    assert_eq!(messages[4], "Hello, Ferris! (5 of 5)");
+}
+
+#[tokio::test]
+async fn refuses_too_many_greetings() {
+    let mut client = client().await;
+
+    let status = client
+        .say_hellos(HellosRequest {
+            name: "Ferris".to_string(),
+            count: MAX_COUNT + 1,
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,52 @@
+//! A server greeting every client with `Hello!`, then closing the
+//! connection.
+//!
+//! Each connection is handled by a task of its own, kept in a `JoinSet`.
+//! When `shutdown` completes, the server stops accepting connections, but
+//! waits for the tasks still greeting their clients before `run` returns.
+
+use std::future::Future;
+use std::io;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+
+/// What every client gets.
+pub const GREETING: &[u8] = b"Hello!\n";
+
+/// Greet the clients of `listener` until `shutdown` completes.
+pub async fn run(listener: TcpListener, shutdown: impl Future) -> io::Result<()> {
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            res = listener.accept() => {
+                let (socket, addr) = res?;
+                connections.spawn(async move {
+                    if let Err(err) = greet(socket).await {
+                        eprintln!("failed to greet {}: {}", addr, err);
+                    }
+                });
+            }
+            // Forget about the connections that are done, so the set only
+            // holds the ones in flight.
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    // Stop accepting before waiting, so new clients are refused rather
+    // than left hanging.
+    drop(listener);
+
+    while connections.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+async fn greet(mut socket: TcpStream) -> io::Result<()> {
+    socket.write_all(GREETING).await?;
+    socket.shutdown().await
+}
@@ -0,0 +1,44 @@
+use hello_server::{run, GREETING};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::time;
+
+#[tokio::test]
+async fn greets_until_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let server = tokio::spawn(run(listener, signal));
+
+    for _ in 0..2 {
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut greeting = vec![];
+        socket.read_to_end(&mut greeting).await.unwrap();
+        assert_eq!(greeting, GREETING);
+    }
+
+    shutdown.send(()).unwrap();
+
+    time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("the server didn't stop")
+        .unwrap()
+        .unwrap();
+
+    // Nobody listens anymore.
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn stops_without_clients() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+    // A shutdown that is already complete.
+    time::timeout(Duration::from_secs(5), run(listener, async {}))
+        .await
+        .expect("the server didn't stop")
+        .unwrap();
+}
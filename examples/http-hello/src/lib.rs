@@ -0,0 +1,196 @@
+//! An HTTP/1.0 server written directly on a `TcpStream`, answering every
+//! request with a greeting.
+//!
+//! What hyper does for you, done by hand, only just enough of it: the
+//! request head is read into a `BytesMut` until it ends with a blank line,
+//! whichever reads it arrives in, and httparse makes sense of it. Only the
+//! method and the path are used. The response says how long its body is
+//! with `Content-Length`, and the connection is closed after it, as
+//! HTTP/1.0 does by default.
+
+use bytes::BytesMut;
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time;
+
+/// The longest request head the server reads. A client sending more gets a
+/// `431 Request Header Fields Too Large`.
+pub const MAX_HEAD: usize = 8 * 1024;
+
+/// How many headers a request may have.
+pub const MAX_HEADERS: usize = 32;
+
+/// How long the server goes on reading from a client after answering it;
+/// see `linger`.
+pub const LINGER: Duration = Duration::from_secs(1);
+
+/// How long `serve` waits after a failed `accept` before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The parts of a request the server uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+}
+
+/// A response, with a body of plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub reason: &'static str,
+    pub body: String,
+}
+
+/// Accept connections on `listener` forever, answering each in a task of
+/// its own. A failed accept, such as one running out of file descriptors, is
+/// logged, and retried after a short pause.
+pub async fn serve(listener: TcpListener) {
+    loop {
+        let (socket, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                eprintln!("failed to accept a connection: {}", err);
+                time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            if let Err(err) = handle(socket).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Read one request from `io`, answer it, and close the connection.
+pub async fn handle<T>(mut io: T) -> io::Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let response = match read_request(&mut io).await? {
+        Ok(request) => hello(&request),
+        Err(response) => response,
+    };
+
+    io.write_all(&response.to_bytes()).await?;
+    io.shutdown().await?;
+
+    linger(&mut io).await;
+    Ok(())
+}
+
+/// The greeting for `request`.
+pub fn hello(request: &Request) -> Response {
+    Response {
+        status: 200,
+        reason: "OK",
+        body: format!(
+            "Hello! You asked for {} {}.\n",
+            request.method, request.path
+        ),
+    }
+}
+
+/// Read the head of a request from `io`, or the response refusing it if it
+/// is malformed or too long.
+///
+/// An `Err` is for the connection itself, like the client closing it
+/// before the end of the head.
+pub async fn read_request<T>(io: &mut T) -> io::Result<Result<Request, Response>>
+where
+    T: AsyncRead + Unpin,
+{
+    let mut buf = BytesMut::with_capacity(1024);
+
+    loop {
+        if io.read_buf(&mut buf).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        // Anything past `MAX_HEAD` doesn't matter: if the head isn't over
+        // by then, it is too long.
+        let head = &buf[..buf.len().min(MAX_HEAD)];
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut request = httparse::Request::new(&mut headers);
+
+        match request.parse(head) {
+            Ok(httparse::Status::Complete(_)) => {
+                // A complete request has both.
+                return Ok(Ok(Request {
+                    method: request.method.unwrap().to_string(),
+                    path: request.path.unwrap().to_string(),
+                }));
+            }
+            Ok(httparse::Status::Partial) if head.len() == MAX_HEAD => {
+                return Ok(Err(too_large()));
+            }
+            // The rest of the head is yet to come.
+            Ok(httparse::Status::Partial) => {}
+            Err(httparse::Error::TooManyHeaders) => return Ok(Err(too_large())),
+            Err(err) => {
+                return Ok(Err(Response {
+                    status: 400,
+                    reason: "Bad Request",
+                    body: format!("{}\n", err),
+                }))
+            }
+        }
+    }
+}
+
+fn too_large() -> Response {
+    Response {
+        status: 431,
+        reason: "Request Header Fields Too Large",
+        body: format!(
+            "the request head may be up to {} bytes, with up to {} headers\n",
+            MAX_HEAD, MAX_HEADERS
+        ),
+    }
+}
+
+/// Read and discard what the client still sends, until it closes its end,
+/// or for `LINGER` at most.
+///
+/// Closing a socket with data left unread makes the kernel reset the
+/// connection, rather than close it, and a reset can reach the client
+/// before it read the response, losing it. The data could be a second
+/// request, or the rest of a head that was too long.
+async fn linger<T>(io: &mut T)
+where
+    T: AsyncRead + Unpin,
+{
+    let mut buf = [0; 1024];
+    let _ = time::timeout(LINGER, async {
+        while let Ok(n) = io.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+        }
+    })
+    .await;
+}
+
+impl Response {
+    /// The response as written on the connection.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "HTTP/1.0 {} {}\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            self.status,
+            self.reason,
+            self.body.len(),
+            self.body
+        )
+        .into_bytes()
+    }
+}
@@ -0,0 +1,15 @@
+use std::env;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try it with `curl -i http://127.0.0.1:3000/hello`.
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:3000".to_string());
+    let listener = TcpListener::bind(&addr).await?;
+    println!("listening on {}", addr);
+
+    http_hello::serve(listener).await;
+    Ok(())
+}
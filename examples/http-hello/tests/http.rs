@@ -0,0 +1,131 @@
+use http_hello::{serve, MAX_HEAD, MAX_HEADERS};
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task;
+
+async fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener));
+    addr
+}
+
+/// Send `request` in one write, and read everything until the server
+/// closes the connection.
+async fn exchange(request: &[u8]) -> String {
+    let mut socket = TcpStream::connect(server().await).await.unwrap();
+    socket.write_all(request).await.unwrap();
+    read_all(&mut socket).await
+}
+
+async fn read_all(socket: &mut TcpStream) -> String {
+    let mut response = String::new();
+    socket.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+/// The status line of `response`.
+fn status(response: &str) -> &str {
+    response.lines().next().unwrap()
+}
+
+#[tokio::test]
+async fn greets() {
+    let response = exchange(b"GET /hello HTTP/1.0\r\nHost: example\r\n\r\n").await;
+
+    let body = "Hello! You asked for GET /hello.\n";
+    assert_eq!(
+        response,
+        format!(
+            "HTTP/1.0 200 OK\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            body.len(),
+            body
+        )
+    );
+}
+
+#[tokio::test]
+async fn reads_a_request_split_byte_by_byte() {
+    let mut socket = TcpStream::connect(server().await).await.unwrap();
+    socket.set_nodelay(true).unwrap();
+
+    for byte in b"POST /split HTTP/1.0\r\nHost: example\r\n\r\n".iter() {
+        socket.write_all(&[*byte]).await.unwrap();
+        task::yield_now().await;
+    }
+
+    let response = read_all(&mut socket).await;
+    assert_eq!(status(&response), "HTTP/1.0 200 OK");
+    assert!(
+        response.ends_with("You asked for POST /split.\n"),
+        "{}",
+        response
+    );
+}
+
+#[tokio::test]
+async fn answers_one_request_per_connection() {
+    let mut socket = TcpStream::connect(server().await).await.unwrap();
+
+    socket
+        .write_all(b"GET /first HTTP/1.0\r\n\r\nGET /second HTTP/1.0\r\n\r\n")
+        .await
+        .unwrap();
+
+    let response = read_all(&mut socket).await;
+    assert_eq!(response.matches("HTTP/1.0").count(), 1, "{}", response);
+    assert!(response.ends_with("GET /first.\n"), "{}", response);
+
+    // The server is done with the connection: another request gets nothing
+    // back, whether the write goes through or not.
+    let _ = socket.write_all(b"GET /third HTTP/1.0\r\n\r\n").await;
+    let mut rest = vec![];
+    match socket.read_to_end(&mut rest).await {
+        Ok(_) => assert!(rest.is_empty()),
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::ConnectionReset),
+    }
+}
+
+#[tokio::test]
+async fn refuses_malformed_requests() {
+    let response = exchange(b"GET\x00/ HTTP/1.0\r\n\r\n").await;
+    assert_eq!(status(&response), "HTTP/1.0 400 Bad Request");
+
+    let response = exchange(b"GET / HTTP/1.0\r\nno colon\r\n\r\n").await;
+    assert_eq!(status(&response), "HTTP/1.0 400 Bad Request");
+}
+
+#[tokio::test]
+async fn refuses_heads_that_are_too_long() {
+    let mut request = b"GET / HTTP/1.0\r\nX-Padding: ".to_vec();
+    request.resize(2 * MAX_HEAD, b'a');
+    request.extend_from_slice(b"\r\n\r\n");
+
+    let response = exchange(&request).await;
+    assert_eq!(
+        status(&response),
+        "HTTP/1.0 431 Request Header Fields Too Large"
+    );
+}
+
+#[tokio::test]
+async fn refuses_too_many_headers() {
+    let mut request = "GET / HTTP/1.0\r\n".to_string();
+    for i in 0..=MAX_HEADERS {
+        request.push_str(&format!("X-{}: {}\r\n", i, i));
+    }
+    request.push_str("\r\n");
+
+    let response = exchange(request.as_bytes()).await;
+    assert_eq!(
+        status(&response),
+        "HTTP/1.0 431 Request Header Fields Too Large"
+    );
+}
@@ -0,0 +1,20 @@
+use length_delimited::manual::Connection;
+use tokio::net::TcpStream;
+
+/// Send each argument to the server, and print what comes back.
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let socket = TcpStream::connect("127.0.0.1:12345").await?;
+    let mut connection = Connection::new(socket);
+
+    for message in std::env::args().skip(1) {
+        connection.write_frame(message.as_bytes()).await?;
+
+        match connection.read_frame().await? {
+            Some(reply) => println!("{}", String::from_utf8_lossy(&reply)),
+            None => break,
+        }
+    }
+
+    Ok(())
+}
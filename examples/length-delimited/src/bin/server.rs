@@ -0,0 +1,9 @@
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:12345").await?;
+    println!("listening on 127.0.0.1:12345");
+
+    length_delimited::codec::serve(listener).await
+}
@@ -0,0 +1,67 @@
+//! Framing with `LengthDelimitedCodec`.
+
+use crate::{HEADER, MAX_FRAME};
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+/// `io` as a stream of messages and a sink for them.
+///
+/// A message longer than `MAX_FRAME` is an `InvalidData` error, whether it
+/// is read or written.
+pub fn framed<T: AsyncRead + AsyncWrite>(io: T) -> Framed<T, LengthDelimitedCodec> {
+    let codec = LengthDelimitedCodec::builder()
+        .length_field_length(HEADER)
+        .max_frame_length(MAX_FRAME)
+        .new_codec();
+
+    Framed::new(io, codec)
+}
+
+/// Send every message of the clients of `listener` back to them.
+pub async fn serve(listener: TcpListener) -> io::Result<()> {
+    loop {
+        let (socket, addr) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            if let Err(err) = echo(socket).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Send every message read from `socket` back, until it is closed.
+pub async fn echo(socket: TcpStream) -> io::Result<()> {
+    let mut frames = framed(socket);
+
+    while let Some(frame) = frames.next().await {
+        frames.send(frame?.freeze()).await?;
+    }
+
+    Ok(())
+}
+
+/// Send `messages` over `io` and wait for the reply to each, in order.
+pub async fn exchange<T>(io: T, messages: Vec<Bytes>) -> io::Result<Vec<Bytes>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut frames = framed(io);
+    let mut replies = vec![];
+
+    for message in messages {
+        frames.send(message).await?;
+
+        match frames.next().await {
+            Some(reply) => replies.push(reply?.freeze()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    Ok(replies)
+}
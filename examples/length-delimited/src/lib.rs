@@ -0,0 +1,17 @@
+//! Messages framed with a length prefix: a big-endian `u32` holding the
+//! length of the message, then the message.
+//!
+//! `codec` lets `LengthDelimitedCodec` do the framing. `manual` does it by
+//! hand, the way the framing chapter of the tutorial builds its
+//! `Connection`. Both write the same bytes, so either side can use either.
+
+pub mod codec;
+pub mod manual;
+
+/// The longest message either side accepts. The length prefix is read
+/// before the message, so without a limit, a peer could have us allocate
+/// up to 4 GiB by sending four bytes.
+pub const MAX_FRAME: usize = 64 * 1024;
+
+/// How many bytes the length prefix takes.
+pub const HEADER: usize = 4;
@@ -0,0 +1,95 @@
+//! Framing by hand, like the `Connection` of the tutorial.
+//!
+//! Reads go into a `BytesMut` until it holds a whole frame. A read may
+//! return part of a frame, or several frames at once, so the buffer is
+//! checked for a frame before every read, and what is left of it after a
+//! frame is kept for the next one.
+
+use crate::{HEADER, MAX_FRAME};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+/// Reads and writes length-prefixed frames on `stream`.
+#[derive(Debug)]
+pub struct Connection<S> {
+    stream: BufWriter<S>,
+    buffer: BytesMut,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    pub fn new(stream: S) -> Connection<S> {
+        Connection {
+            stream: BufWriter::new(stream),
+            buffer: BytesMut::with_capacity(4 * 1024),
+        }
+    }
+
+    /// Read the next frame. `None` means the peer closed the connection
+    /// between two frames; closing it in the middle of one is an error.
+    pub async fn read_frame(&mut self) -> io::Result<Option<Bytes>> {
+        loop {
+            if let Some(frame) = parse_frame(&mut self.buffer)? {
+                return Ok(Some(frame));
+            }
+
+            if self.stream.read_buf(&mut self.buffer).await? == 0 {
+                return if self.buffer.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::ConnectionReset,
+                        "connection reset by peer",
+                    ))
+                };
+            }
+        }
+    }
+
+    /// Write `frame`, and flush it.
+    pub async fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        if frame.len() > MAX_FRAME {
+            return Err(too_large(frame.len()));
+        }
+
+        let mut header = [0; HEADER];
+        (&mut header[..]).put_u32(frame.len() as u32);
+
+        self.stream.write_all(&header).await?;
+        self.stream.write_all(frame).await?;
+        self.stream.flush().await
+    }
+}
+
+/// Take the first frame out of `buffer`, if it holds a whole one.
+///
+/// Once the header is in, the buffer is grown to fit the whole frame, so
+/// the reads that follow don't have to grow it bit by bit.
+pub fn parse_frame(buffer: &mut BytesMut) -> io::Result<Option<Bytes>> {
+    if buffer.len() < HEADER {
+        return Ok(None);
+    }
+
+    // Peek at the length; the header stays in the buffer until the whole
+    // frame is there.
+    let len = (&buffer[..HEADER]).get_u32() as usize;
+    if len > MAX_FRAME {
+        return Err(too_large(len));
+    }
+
+    if buffer.len() < HEADER + len {
+        buffer.reserve(HEADER + len - buffer.len());
+        return Ok(None);
+    }
+
+    buffer.advance(HEADER);
+    Ok(Some(buffer.split_to(len).freeze()))
+}
+
+fn too_large(len: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("frame of {} bytes is larger than {} bytes", len, MAX_FRAME),
+    )
+}
@@ -0,0 +1,91 @@
+use bytes::Bytes;
+use futures::StreamExt;
+use length_delimited::codec::{exchange, framed, serve};
+use length_delimited::manual::Connection;
+use length_delimited::MAX_FRAME;
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{duplex, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task;
+
+fn frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = (message.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(message);
+    frame
+}
+
+async fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener));
+    addr
+}
+
+#[tokio::test]
+async fn frame_split_over_three_writes() {
+    let (client, mut server) = duplex(1024);
+    let mut frames = framed(client);
+
+    let bytes = frame(b"hello world");
+    let reader = tokio::spawn(async move { frames.next().await });
+
+    for part in [&bytes[..2], &bytes[2..7], &bytes[7..]].iter() {
+        server.write_all(part).await.unwrap();
+        task::yield_now().await;
+    }
+
+    let frame = reader.await.unwrap().unwrap().unwrap();
+    assert_eq!(&frame[..], b"hello world");
+}
+
+#[tokio::test]
+async fn two_frames_in_one_write() {
+    let (client, mut server) = duplex(1024);
+    let mut frames = framed(client);
+
+    let mut bytes = frame(b"first");
+    bytes.extend(frame(b"second"));
+    server.write_all(&bytes).await.unwrap();
+    drop(server);
+
+    assert_eq!(&frames.next().await.unwrap().unwrap()[..], b"first");
+    assert_eq!(&frames.next().await.unwrap().unwrap()[..], b"second");
+    assert!(frames.next().await.is_none());
+}
+
+#[tokio::test]
+async fn oversized_frames_are_errors() {
+    let (client, mut server) = duplex(1024);
+    let mut frames = framed(client);
+
+    server
+        .write_all(&((MAX_FRAME + 1) as u32).to_be_bytes())
+        .await
+        .unwrap();
+
+    let err = frames.next().await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn echoes_messages() {
+    let socket = TcpStream::connect(server().await).await.unwrap();
+
+    let messages = vec![Bytes::from("hello"), Bytes::new(), Bytes::from("world")];
+    let replies = exchange(socket, messages.clone()).await.unwrap();
+
+    assert_eq!(replies, messages);
+}
+
+#[tokio::test]
+async fn both_framings_agree() {
+    let socket = TcpStream::connect(server().await).await.unwrap();
+    let mut connection = Connection::new(socket);
+
+    connection.write_frame(b"by hand").await.unwrap();
+    assert_eq!(
+        connection.read_frame().await.unwrap().as_deref(),
+        Some(&b"by hand"[..])
+    );
+}
@@ -0,0 +1,131 @@
+use bytes::BytesMut;
+use length_delimited::manual::{parse_frame, Connection};
+use length_delimited::MAX_FRAME;
+use std::io;
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+use tokio::task;
+
+/// `message` with its length prefix.
+fn frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = (message.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(message);
+    frame
+}
+
+#[tokio::test]
+async fn frame_split_over_three_writes() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    let bytes = frame(b"hello world");
+    let reader = tokio::spawn(async move { connection.read_frame().await });
+
+    // Half of the header, the rest of it and some of the message, then the
+    // rest of the message.
+    for part in [&bytes[..2], &bytes[2..7], &bytes[7..]].iter() {
+        server.write_all(part).await.unwrap();
+        server.flush().await.unwrap();
+        task::yield_now().await;
+    }
+
+    let frame = reader.await.unwrap().unwrap();
+    assert_eq!(frame.as_deref(), Some(&b"hello world"[..]));
+}
+
+#[tokio::test]
+async fn two_frames_in_one_write() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    let mut bytes = frame(b"first");
+    bytes.extend(frame(b"second"));
+    server.write_all(&bytes).await.unwrap();
+    drop(server);
+
+    assert_eq!(
+        connection.read_frame().await.unwrap().as_deref(),
+        Some(&b"first"[..])
+    );
+    assert_eq!(
+        connection.read_frame().await.unwrap().as_deref(),
+        Some(&b"second"[..])
+    );
+    assert_eq!(connection.read_frame().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn empty_frames() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    server.write_all(&frame(b"")).await.unwrap();
+    assert_eq!(
+        connection.read_frame().await.unwrap().as_deref(),
+        Some(&b""[..])
+    );
+}
+
+#[tokio::test]
+async fn oversized_frames_are_errors() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    // Only the header: the frame is refused before any of it is read.
+    let header = ((MAX_FRAME + 1) as u32).to_be_bytes();
+    server.write_all(&header).await.unwrap();
+
+    let err = connection.read_frame().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = connection
+        .write_frame(&vec![0; MAX_FRAME + 1])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn closing_mid_frame_is_an_error() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    server.write_all(&frame(b"hello")[..6]).await.unwrap();
+    drop(server);
+
+    let err = connection.read_frame().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+}
+
+#[tokio::test]
+async fn writes_length_prefixed_frames() {
+    let (client, mut server) = duplex(1024);
+    let mut connection = Connection::new(client);
+
+    connection.write_frame(b"hello").await.unwrap();
+    drop(connection);
+
+    let mut written = vec![];
+    server.read_to_end(&mut written).await.unwrap();
+    assert_eq!(written, frame(b"hello"));
+}
+
+#[test]
+fn parse_waits_for_whole_frames() {
+    let bytes = frame(b"hello");
+    let mut buffer = BytesMut::new();
+
+    for &byte in &bytes[..bytes.len() - 1] {
+        buffer.extend_from_slice(&[byte]);
+        assert_eq!(parse_frame(&mut buffer).unwrap(), None);
+    }
+
+    // Room for the rest was made once the header was in.
+    assert!(buffer.capacity() >= bytes.len());
+
+    buffer.extend_from_slice(&bytes[bytes.len() - 1..]);
+    assert_eq!(
+        parse_frame(&mut buffer).unwrap().as_deref(),
+        Some(&b"hello"[..])
+    );
+    assert!(buffer.is_empty());
+}
@@ -0,0 +1,181 @@
+//! Keepalives: finding out that a client is gone when its connection can't
+//! tell.
+//!
+//! A client that vanishes without closing its connection, because its
+//! machine lost power or a NAT forgot about it, leaves the server waiting
+//! for a line that never comes. So when a client has been quiet for a while,
+//! the server sends it a `PING` line, which it has to answer with `PONG`. A
+//! client that misses two `PONG`s in a row is disconnected.
+//!
+//! `PING` and `PONG` are part of the protocol from then on: the server
+//! doesn't pass a `PONG` to its service, and `Client` doesn't pass a `PING`
+//! to the application.
+
+use crate::{Error, MAX_LINE};
+use futures::{SinkExt, StreamExt};
+use std::future;
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio::time::{self, MissedTickBehavior};
+use tokio_util::codec::{Framed, LinesCodec};
+use tower::Service;
+
+/// The line the server sends to a quiet client.
+pub const PING: &str = "PING";
+
+/// The line a client answers a `PING` with.
+pub const PONG: &str = "PONG";
+
+/// How many `PING`s in a row may go unanswered before the server gives up
+/// on the client.
+pub const MISSED_PONGS: u32 = 2;
+
+/// Like `crate::process`, but sending the client a `PING` every `period` it
+/// is quiet, and closing the connection when it misses `MISSED_PONGS`
+/// `PONG`s in a row.
+///
+/// The first tick of an interval is right away, so the client is pinged as
+/// soon as it connects, and one that never answers is disconnected after
+/// `MISSED_PONGS` periods. Any line from the client shows it is still there,
+/// not only a `PONG`.
+pub async fn process<T, S>(io: T, mut service: S, period: Duration) -> Result<(), Error>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    S: Service<String, Response = String>,
+    S::Error: Into<Error>,
+{
+    let mut lines = Framed::new(io, LinesCodec::new_with_max_length(MAX_LINE));
+
+    let mut ticks = time::interval(period);
+    // After a slow request, ping once rather than for every period missed.
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // Whether the last `PING` is still unanswered, and how many before it
+    // were.
+    let mut pinged = false;
+    let mut missed = 0;
+
+    loop {
+        tokio::select! {
+            line = lines.next() => {
+                let line = match line {
+                    Some(line) => line?,
+                    None => return Ok(()),
+                };
+
+                pinged = false;
+                missed = 0;
+                ticks.reset();
+
+                if line == PONG {
+                    continue;
+                }
+
+                future::poll_fn(|cx| service.poll_ready(cx))
+                    .await
+                    .map_err(Into::into)?;
+                let response = service.call(line).await.map_err(Into::into)?;
+
+                lines.send(response).await?;
+            }
+            _ = ticks.tick() => {
+                if pinged {
+                    missed += 1;
+                    if missed == MISSED_PONGS {
+                        let msg = format!("no {} to the last {} {}s", PONG, MISSED_PONGS, PING);
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, msg).into());
+                    }
+                }
+
+                lines.send(PING).await?;
+                pinged = true;
+            }
+        }
+    }
+}
+
+/// The client end of a connection with keepalives, answering `PING`s on its
+/// own.
+///
+/// A task reads the connection, so `PING`s are answered even while the
+/// application does something else. Other lines are passed on to `recv`,
+/// through a channel holding up to `BUFFER` of them: an application that
+/// doesn't read them stops the task too, and the server then rightly finds
+/// that it isn't answering.
+#[derive(Debug)]
+pub struct Client {
+    lines: mpsc::Receiver<Result<String, Error>>,
+    send: mpsc::Sender<String>,
+}
+
+/// How many lines the server sent `Client` holds until they are received.
+pub const BUFFER: usize = 32;
+
+impl Client {
+    /// Start answering `PING`s on `io`.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn new<T>(io: T) -> Client
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (lines_tx, lines) = mpsc::channel(BUFFER);
+        let (send, send_rx) = mpsc::channel(BUFFER);
+
+        tokio::spawn(async move {
+            if let Err(err) = run(io, lines_tx.clone(), send_rx).await {
+                let _ = lines_tx.send(Err(err)).await;
+            }
+        });
+
+        Client { lines, send }
+    }
+
+    /// Send `line` to the server.
+    pub async fn send(&self, line: impl Into<String>) -> Result<(), Error> {
+        self.send
+            .send(line.into())
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe).into())
+    }
+
+    /// The next line from the server that isn't a `PING`, or `None` once
+    /// the server has closed the connection.
+    pub async fn recv(&mut self) -> Option<Result<String, Error>> {
+        self.lines.recv().await
+    }
+}
+
+/// Read the lines of `io` into `lines`, answering `PING`s, and write the
+/// ones from `send`.
+async fn run<T>(
+    io: T,
+    lines: mpsc::Sender<Result<String, Error>>,
+    mut send: mpsc::Receiver<String>,
+) -> Result<(), Error>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    let framed = Framed::new(io, LinesCodec::new_with_max_length(MAX_LINE));
+    tokio::pin!(framed);
+
+    loop {
+        tokio::select! {
+            line = framed.next() => match line {
+                Some(line) => {
+                    let line = line?;
+                    if line == PING {
+                        framed.send(PONG).await?;
+                    } else if lines.send(Ok(line)).await.is_err() {
+                        // The `Client` is gone.
+                        return Ok(());
+                    }
+                }
+                None => return Ok(()),
+            },
+            Some(line) = send.recv() => framed.send(line).await?,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! A line-echo server: every line a client sends comes back to it.
+//!
+//! The socket is turned into a stream of lines and a sink for them by
+//! `Framed` with a `LinesCodec`, so the server never sees a partial line.
+//! What to answer is up to a `tower::Service`, one per connection, made by
+//! `make_service` for the address of the client. `Echo` answers each line
+//! with itself.
+//!
+//! With `serve_with_keepalive`, clients that went away without closing their
+//! connection are found out too; see `keepalive`.
+
+pub mod keepalive;
+
+use futures::{SinkExt, StreamExt};
+use std::convert::Infallible;
+use std::future::{self, Ready};
+use std::io;
+use std::net::SocketAddr;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{Framed, LinesCodec};
+use tower::Service;
+
+/// An error from a connection, or from the service answering it.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// The longest line a client may send, newline excluded. Without a limit, a
+/// client that never sends a newline would have the server buffer forever.
+pub const MAX_LINE: usize = 8 * 1024;
+
+/// Accept connections on `listener` until accepting fails, answering each
+/// with a service made by `make_service`.
+///
+/// A connection that fails, or whose service does, is closed on its own;
+/// the others go on.
+pub async fn serve<M, S>(listener: TcpListener, make_service: M) -> io::Result<()>
+where
+    M: Service<SocketAddr, Response = S>,
+    M::Error: Into<Error>,
+    S: Service<String, Response = String> + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send,
+{
+    accept(listener, make_service, None).await
+}
+
+/// Like `serve`, but pinging clients that are quiet for `period`, with
+/// `keepalive::process`.
+pub async fn serve_with_keepalive<M, S>(
+    listener: TcpListener,
+    make_service: M,
+    period: Duration,
+) -> io::Result<()>
+where
+    M: Service<SocketAddr, Response = S>,
+    M::Error: Into<Error>,
+    S: Service<String, Response = String> + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send,
+{
+    accept(listener, make_service, Some(period)).await
+}
+
+/// The loop of `serve`, with keepalives every `keepalive` if set.
+async fn accept<M, S>(
+    listener: TcpListener,
+    mut make_service: M,
+    keepalive: Option<Duration>,
+) -> io::Result<()>
+where
+    M: Service<SocketAddr, Response = S>,
+    M::Error: Into<Error>,
+    S: Service<String, Response = String> + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send,
+{
+    loop {
+        let (socket, addr) = listener.accept().await?;
+
+        let service = match make(&mut make_service, addr).await {
+            Ok(service) => service,
+            Err(err) => {
+                eprintln!("no service for {}: {}", addr, err);
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            let res = match keepalive {
+                Some(period) => keepalive::process(socket, service, period).await,
+                None => process(socket, service).await,
+            };
+            if let Err(err) = res {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Ask `make_service` for the service of the client at `addr`.
+async fn make<M, S>(make_service: &mut M, addr: SocketAddr) -> Result<S, Error>
+where
+    M: Service<SocketAddr, Response = S>,
+    M::Error: Into<Error>,
+{
+    future::poll_fn(|cx| make_service.poll_ready(cx))
+        .await
+        .map_err(Into::into)?;
+    make_service.call(addr).await.map_err(Into::into)
+}
+
+/// Answer the lines of `socket` with `service`, in order, until the client
+/// closes the connection.
+pub async fn process<S>(socket: TcpStream, mut service: S) -> Result<(), Error>
+where
+    S: Service<String, Response = String>,
+    S::Error: Into<Error>,
+{
+    let mut lines = Framed::new(socket, LinesCodec::new_with_max_length(MAX_LINE));
+
+    while let Some(line) = lines.next().await {
+        let line = line?;
+
+        // A service may not be ready for the next request yet, like one
+        // limiting how many it handles at once.
+        future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(Into::into)?;
+        let response = service.call(line).await.map_err(Into::into)?;
+
+        lines.send(response).await?;
+    }
+
+    Ok(())
+}
+
+/// Answers each line with itself.
+#[derive(Debug, Clone, Default)]
+pub struct Echo;
+
+impl Service<String> for Echo {
+    type Response = String;
+    type Error = Infallible;
+    type Future = Ready<Result<String, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, line: String) -> Self::Future {
+        future::ready(Ok(line))
+    }
+}
+
+/// Makes an `Echo` for every client.
+#[derive(Debug, Clone, Default)]
+pub struct MakeEcho;
+
+impl Service<SocketAddr> for MakeEcho {
+    type Response = Echo;
+    type Error = Infallible;
+    type Future = Ready<Result<Echo, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _addr: SocketAddr) -> Self::Future {
+        future::ready(Ok(Echo))
+    }
+}
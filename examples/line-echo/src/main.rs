@@ -0,0 +1,31 @@
+use line_echo::MakeEcho;
+use std::env;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try it with `nc 127.0.0.1 12345`. With a number of seconds after the
+    // address, quiet clients get a `PING` that often, and have to answer
+    // `PONG` to stay connected.
+    let mut args = env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:12345".to_string());
+    let keepalive = match args.next() {
+        Some(secs) => match secs.parse() {
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(err) => {
+                eprintln!("invalid keepalive period {:?}: {}", secs, err);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let listener = TcpListener::bind(&addr).await?;
+    println!("listening on {}", addr);
+
+    match keepalive {
+        Some(period) => line_echo::serve_with_keepalive(listener, MakeEcho, period).await,
+        None => line_echo::serve(listener, MakeEcho).await,
+    }
+}
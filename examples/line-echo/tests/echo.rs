@@ -0,0 +1,74 @@
+use line_echo::{serve, MakeEcho, MAX_LINE};
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+async fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener, MakeEcho));
+    addr
+}
+
+#[tokio::test]
+async fn echoes_lines() {
+    let mut socket = TcpStream::connect(server().await).await.unwrap();
+
+    socket.write_all(b"hello\nworld\n").await.unwrap();
+
+    let mut lines = BufReader::new(socket).lines();
+    assert_eq!(lines.next_line().await.unwrap().unwrap(), "hello");
+    assert_eq!(lines.next_line().await.unwrap().unwrap(), "world");
+}
+
+#[tokio::test]
+async fn waits_for_the_rest_of_a_line() {
+    let socket = TcpStream::connect(server().await).await.unwrap();
+    let (rd, mut wr) = socket.into_split();
+    let mut lines = BufReader::new(rd).lines();
+
+    wr.write_all(b"hel").await.unwrap();
+
+    // Nothing comes back for half a line.
+    let early = time::timeout(Duration::from_millis(100), lines.next_line()).await;
+    assert!(early.is_err(), "{:?}", early);
+
+    wr.write_all(b"lo\n").await.unwrap();
+    assert_eq!(lines.next_line().await.unwrap().unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn clients_are_independent() {
+    let addr = server().await;
+    let mut a = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    let mut b = BufReader::new(TcpStream::connect(addr).await.unwrap());
+
+    a.write_all(b"from a\n").await.unwrap();
+    b.write_all(b"from b\n").await.unwrap();
+
+    let mut line = String::new();
+    b.read_line(&mut line).await.unwrap();
+    assert_eq!(line, "from b\n");
+
+    line.clear();
+    a.read_line(&mut line).await.unwrap();
+    assert_eq!(line, "from a\n");
+}
+
+#[tokio::test]
+async fn closes_connections_with_overlong_lines() {
+    let mut socket = TcpStream::connect(server().await).await.unwrap();
+
+    socket.write_all(&vec![b'a'; MAX_LINE + 1]).await.unwrap();
+
+    // The server may close the connection before reading all of it, which
+    // resets it.
+    let mut rest = vec![];
+    match socket.read_to_end(&mut rest).await {
+        Ok(_) => assert!(rest.is_empty()),
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::ConnectionReset),
+    }
+}
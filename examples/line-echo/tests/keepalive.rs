@@ -0,0 +1,107 @@
+use futures::{SinkExt, StreamExt};
+use line_echo::keepalive::{self, Client, PING, PONG};
+use line_echo::{serve_with_keepalive, Echo, MakeEcho};
+use std::io;
+use std::time::Duration;
+use tokio::io::DuplexStream;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+use tokio_util::codec::{Framed, LinesCodec};
+
+const PERIOD: Duration = Duration::from_secs(10);
+
+/// The client end of a connection to an echo server with keepalives, and
+/// the task of the server end.
+fn connect() -> (DuplexStream, JoinHandle<Result<(), line_echo::Error>>) {
+    let (client, server) = tokio::io::duplex(1024);
+    let task = tokio::spawn(keepalive::process(server, Echo, PERIOD));
+    (client, task)
+}
+
+#[tokio::test(start_paused = true)]
+async fn silent_clients_are_disconnected_after_two_periods() {
+    let start = Instant::now();
+    let (client, task) = connect();
+    let mut lines = Framed::new(client, LinesCodec::new());
+
+    // Pinged on connecting, and again after a period without an answer.
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+    assert_eq!(start.elapsed(), PERIOD);
+
+    assert!(lines.next().await.is_none());
+    assert_eq!(start.elapsed(), PERIOD * 2);
+
+    let err = task.await.unwrap().unwrap_err();
+    let err = err.downcast::<io::Error>().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
+
+#[tokio::test(start_paused = true)]
+async fn answering_clients_stay_connected() {
+    let (io, task) = connect();
+    let mut client = Client::new(io);
+
+    time::sleep(PERIOD * 10).await;
+    assert!(!task.is_finished());
+
+    client.send("still here").await.unwrap();
+    assert_eq!(client.recv().await.unwrap().unwrap(), "still here");
+}
+
+#[tokio::test(start_paused = true)]
+async fn any_line_counts_as_an_answer() {
+    let start = Instant::now();
+    let (client, task) = connect();
+    let mut lines = Framed::new(client, LinesCodec::new());
+
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+
+    // Not a `PONG`, but the client is clearly there; the next `PING` is a
+    // period after it.
+    time::sleep(PERIOD / 2).await;
+    lines.send("hello").await.unwrap();
+    assert_eq!(lines.next().await.unwrap().unwrap(), "hello");
+
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+    assert_eq!(start.elapsed(), PERIOD / 2 + PERIOD);
+
+    lines.send(PONG).await.unwrap();
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+    assert_eq!(start.elapsed(), PERIOD / 2 + PERIOD * 2);
+    assert!(!task.is_finished());
+}
+
+#[tokio::test(start_paused = true)]
+async fn pongs_are_not_echoed() {
+    let (client, _task) = connect();
+    let mut lines = Framed::new(client, LinesCodec::new());
+
+    assert_eq!(lines.next().await.unwrap().unwrap(), PING);
+    lines.send(PONG).await.unwrap();
+    lines.send("after").await.unwrap();
+
+    assert_eq!(lines.next().await.unwrap().unwrap(), "after");
+}
+
+#[tokio::test]
+async fn client_passes_other_lines_on() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_with_keepalive(
+        listener,
+        MakeEcho,
+        Duration::from_millis(10),
+    ));
+
+    let mut client = Client::new(TcpStream::connect(addr).await.unwrap());
+
+    for i in 0..5 {
+        let line = format!("line {}", i);
+        client.send(line.clone()).await.unwrap();
+        assert_eq!(client.recv().await.unwrap().unwrap(), line);
+        time::sleep(Duration::from_millis(20)).await;
+    }
+}
@@ -0,0 +1,141 @@
+//! The client: requests from any number of tasks over one connection.
+//!
+//! A request gets the next id, and a oneshot channel for its response is
+//! put in a map under that id before the request is sent. A task reading
+//! the connection, the demultiplexer, takes the channel of each response
+//! out of the map and sends the response on it.
+//!
+//! If the connection dies, the demultiplexer fails the requests still in
+//! the map, and marks it closed so that no new ones wait for a response
+//! that can't come.
+
+use crate::{Codec, Frame};
+use bytes::Bytes;
+use futures::stream::SplitStream;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::codec::Framed;
+
+/// How many requests wait for the writer before callers wait too.
+const REQUESTS: usize = 64;
+
+/// A connection to a server, shared by reference among the tasks making
+/// requests.
+#[derive(Debug)]
+pub struct Client {
+    next_id: AtomicU32,
+    pending: Arc<Mutex<Pending>>,
+    requests: mpsc::Sender<Frame>,
+}
+
+/// The requests waiting for a response.
+#[derive(Debug, Default)]
+struct Pending {
+    waiting: HashMap<u32, oneshot::Sender<io::Result<Bytes>>>,
+
+    /// Why the connection is closed, once it is.
+    closed: Option<(io::ErrorKind, String)>,
+}
+
+impl Client {
+    /// Start a client on `io`.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn new<T>(io: T) -> Client
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (mut sink, responses) = Framed::new(io, Codec::new()).split();
+        let (requests, mut rx) = mpsc::channel(REQUESTS);
+        let pending = Arc::new(Mutex::new(Pending::default()));
+
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if let Err(err) = sink.send(frame).await {
+                    eprintln!("sending a request failed: {}", err);
+                    return;
+                }
+            }
+            // The client is gone: tell the server there are no more
+            // requests, so it closes the connection once it answered the
+            // ones it has.
+            let _ = sink.close().await;
+        });
+
+        tokio::spawn(demux(responses, pending.clone()));
+
+        Client {
+            next_id: AtomicU32::new(0),
+            pending,
+            requests,
+        }
+    }
+
+    /// Send a request with `body`, and wait for the response.
+    ///
+    /// Fails if the connection dies before the response comes.
+    pub async fn request(&self, body: Bytes) -> io::Result<Bytes> {
+        // Ids are reused after 2^32 requests, long after the first ones
+        // were answered.
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if let Some((kind, reason)) = &pending.closed {
+                return Err(io::Error::new(*kind, reason.clone()));
+            }
+            pending.waiting.insert(id, tx);
+        }
+
+        if self.requests.send(Frame { id, body }).await.is_err() {
+            self.pending.lock().unwrap().waiting.remove(&id);
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the connection is closed",
+            ));
+        }
+
+        // The demultiplexer always sends something before dropping `tx`.
+        rx.await.unwrap()
+    }
+}
+
+/// Route each response read from `responses` to the request waiting for
+/// it, then fail those left when the connection closes.
+async fn demux<T>(mut responses: SplitStream<Framed<T, Codec>>, pending: Arc<Mutex<Pending>>)
+where
+    T: AsyncRead + AsyncWrite,
+{
+    let closed = loop {
+        let Frame { id, body } = match responses.next().await {
+            Some(Ok(frame)) => frame,
+            Some(Err(err)) => break (err.kind(), err.to_string()),
+            None => {
+                break (
+                    io::ErrorKind::UnexpectedEof,
+                    "the server closed the connection".to_string(),
+                )
+            }
+        };
+
+        match pending.lock().unwrap().waiting.remove(&id) {
+            // The request may have been dropped, and its receiver with it.
+            Some(tx) => {
+                let _ = tx.send(Ok(body));
+            }
+            None => eprintln!("response to unknown request {}", id),
+        }
+    };
+
+    let mut pending = pending.lock().unwrap();
+    for (_, tx) in pending.waiting.drain() {
+        let _ = tx.send(Err(io::Error::new(closed.0, closed.1.clone())));
+    }
+    pending.closed = Some(closed);
+}
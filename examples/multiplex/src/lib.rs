@@ -0,0 +1,89 @@
+//! Many requests in flight on one connection, answered in any order.
+//!
+//! With one request at a time, or responses in the order of the requests,
+//! the client knows which request a response is for. Here the server
+//! answers each request as soon as it can, so a slow request doesn't hold
+//! up the quick ones behind it, and every frame carries the id of the
+//! request it belongs to.
+//!
+//! A frame is length-delimited, as in `length-delimited`: a big-endian
+//! `u32` length, then that many bytes. The first four bytes are the id, as
+//! a big-endian `u32`, and the rest is the body.
+
+pub mod client;
+pub mod server;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::io;
+use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
+
+/// The longest frame either side accepts, id included.
+pub const MAX_FRAME: usize = 64 * 1024;
+
+/// How many bytes the id takes.
+pub const ID: usize = 4;
+
+/// A request, or the response to the request with the same id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub id: u32,
+    pub body: Bytes,
+}
+
+/// Reads and writes `Frame`s, on top of `LengthDelimitedCodec`.
+#[derive(Debug)]
+pub struct Codec {
+    frames: LengthDelimitedCodec,
+}
+
+impl Codec {
+    pub fn new() -> Codec {
+        Codec {
+            frames: LengthDelimitedCodec::builder()
+                .max_frame_length(MAX_FRAME)
+                .new_codec(),
+        }
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Codec {
+        Codec::new()
+    }
+}
+
+impl Decoder for Codec {
+    type Item = Frame;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Frame>> {
+        let mut frame = match self.frames.decode(src)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        if frame.len() < ID {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame too short for an id",
+            ));
+        }
+
+        Ok(Some(Frame {
+            id: frame.get_u32(),
+            body: frame.freeze(),
+        }))
+    }
+}
+
+impl Encoder<Frame> for Codec {
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> io::Result<()> {
+        let mut bytes = BytesMut::with_capacity(ID + frame.body.len());
+        bytes.put_u32(frame.id);
+        bytes.put(frame.body);
+
+        self.frames.encode(bytes.freeze(), dst)
+    }
+}
@@ -0,0 +1,32 @@
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, StreamExt};
+use multiplex::client::Client;
+use multiplex::server;
+use tokio::net::{TcpListener, TcpStream};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(server::serve(listener, server::slow_uppercase));
+
+    let client = Client::new(TcpStream::connect(addr).await?);
+
+    // All ten are in flight at once, on the one connection, and the
+    // responses are printed as they come.
+    let mut responses: FuturesUnordered<_> = (0..10)
+        .map(|i| {
+            let client = &client;
+            async move {
+                let body = format!("request {}", i);
+                (body.clone(), client.request(Bytes::from(body)).await)
+            }
+        })
+        .collect();
+
+    while let Some((request, response)) = responses.next().await {
+        println!("{:?} -> {:?}", request, response?);
+    }
+
+    Ok(())
+}
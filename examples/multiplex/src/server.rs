@@ -0,0 +1,93 @@
+//! The server: a task per request, and one writing the responses.
+
+use crate::{Codec, Frame};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use std::future::Future;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_util::codec::Framed;
+
+/// How many responses wait for the writer before request tasks wait too.
+const RESPONSES: usize = 64;
+
+/// Accept connections on `listener` until accepting fails, answering each
+/// request with `handler`.
+pub async fn serve<H, F>(listener: TcpListener, handler: H) -> io::Result<()>
+where
+    H: Fn(Bytes) -> F + Send + Sync + 'static,
+    F: Future<Output = Bytes> + Send + 'static,
+{
+    let handler = Arc::new(handler);
+
+    loop {
+        let (socket, addr) = listener.accept().await?;
+        let handler = handler.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = process(socket, handler).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Answer the requests read from `io` with `handler`, each in a task of its
+/// own, until the client closes its end.
+///
+/// Responses are written as they are ready, whatever the order of the
+/// requests. Once the client is done, the connection stays open until the
+/// requests in flight are answered.
+pub async fn process<T, H, F>(io: T, handler: Arc<H>) -> io::Result<()>
+where
+    T: AsyncRead + AsyncWrite + Send + 'static,
+    H: Fn(Bytes) -> F + Send + Sync + 'static,
+    F: Future<Output = Bytes> + Send + 'static,
+{
+    let (mut sink, mut requests) = Framed::new(io, Codec::new()).split();
+    let (responses, mut rx) = mpsc::channel(RESPONSES);
+
+    // The sink is written by one task only, so responses don't need a lock
+    // around it.
+    let writer = tokio::spawn(async move {
+        while let Some(frame) = rx.recv().await {
+            sink.send(frame).await?;
+        }
+        Ok::<_, io::Error>(())
+    });
+
+    while let Some(request) = requests.next().await {
+        let Frame { id, body } = request?;
+        let handler = handler.clone();
+        let responses = responses.clone();
+
+        tokio::spawn(async move {
+            let body = handler(body).await;
+            // Only fails if the writer failed, which it reports.
+            let _ = responses.send(Frame { id, body }).await;
+        });
+    }
+
+    // The writer stops once the last request task has sent its response,
+    // and dropped its sender.
+    drop(responses);
+    match writer.await {
+        Ok(res) => res,
+        Err(err) => Err(io::Error::other(err)),
+    }
+}
+
+/// How long `slow_uppercase` may take.
+pub const MAX_DELAY: Duration = Duration::from_millis(100);
+
+/// A handler answering each request with its body, uppercased, after a
+/// random delay of up to `MAX_DELAY`, so that responses come out of order.
+pub async fn slow_uppercase(body: Bytes) -> Bytes {
+    time::sleep(MAX_DELAY.mul_f64(fastrand::f64())).await;
+    Bytes::from(body.to_ascii_uppercase())
+}
@@ -0,0 +1,109 @@
+use bytes::Bytes;
+use futures::future;
+use futures::{SinkExt, StreamExt};
+use multiplex::client::Client;
+use multiplex::{server, Codec, Frame};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+use tokio_util::codec::Framed;
+
+/// Answers with the body after as many milliseconds as it says.
+async fn wait_and_echo(body: Bytes) -> Bytes {
+    let ms = std::str::from_utf8(&body).unwrap().parse().unwrap();
+    time::sleep(Duration::from_millis(ms)).await;
+    body
+}
+
+#[tokio::test]
+async fn concurrent_requests_get_their_own_responses() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server::serve(listener, server::slow_uppercase));
+
+    let client = Client::new(TcpStream::connect(addr).await.unwrap());
+
+    let requests = (0..100).map(|i| {
+        let client = &client;
+        async move {
+            let body = format!("request {}", i);
+            let response = client.request(Bytes::from(body.clone())).await.unwrap();
+            assert_eq!(response, body.to_uppercase());
+        }
+    });
+    future::join_all(requests).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn responses_come_as_they_are_ready() {
+    let (io, server) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(server::process(server, Arc::new(wait_and_echo)));
+    let client = Client::new(io);
+
+    // The first request takes longest, and is answered last.
+    let answered = Mutex::new(vec![]);
+    let requests = (0..100u64).map(|i| {
+        let (client, answered) = (&client, &answered);
+        async move {
+            let body = Bytes::from((1000 - i * 10).to_string());
+            let response = client.request(body.clone()).await.unwrap();
+            assert_eq!(response, body);
+            answered.lock().unwrap().push(i);
+        }
+    });
+    future::join_all(requests).await;
+
+    let expected: Vec<u64> = (0..100).rev().collect();
+    assert_eq!(answered.into_inner().unwrap(), expected);
+}
+
+#[tokio::test]
+async fn pending_requests_fail_when_the_connection_dies() {
+    let (io, server) = tokio::io::duplex(64 * 1024);
+    let client = Client::new(io);
+
+    // A server reading three requests, then dropping the connection
+    // without answering.
+    let server = tokio::spawn(async move {
+        let mut frames = Framed::new(server, Codec::new());
+        for _ in 0..3 {
+            frames.next().await.unwrap().unwrap();
+        }
+    });
+
+    let requests = (0..3).map(|i| client.request(Bytes::from(vec![i])));
+    let (responses, _) = tokio::join!(future::join_all(requests), server);
+
+    for response in responses {
+        assert_eq!(response.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    // Later requests fail right away.
+    let err = client.request(Bytes::new()).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn server_answers_in_flight_requests_after_the_client_is_done() {
+    let (io, server) = tokio::io::duplex(64 * 1024);
+    let server = tokio::spawn(server::process(server, Arc::new(wait_and_echo)));
+
+    let mut frames = Framed::new(io, Codec::new());
+    frames
+        .send(Frame {
+            id: 7,
+            body: Bytes::from("50"),
+        })
+        .await
+        .unwrap();
+    // No more requests.
+    SinkExt::<Frame>::close(&mut frames).await.unwrap();
+
+    let response = frames.next().await.unwrap().unwrap();
+    assert_eq!(response.id, 7);
+    assert_eq!(response.body, "50");
+    assert!(frames.next().await.is_none());
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,72 @@
+//! The child processes of the examples and tests, in the `process` binary
+//! itself: `process child` runs `run` with the arguments that follow.
+//!
+//! Unlike a shell command, this runs wherever the binary was built.
+
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// What the child does.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    /// Exit after echoing that many lines, without reading the rest.
+    pub stop_after: Option<usize>,
+    /// Exit with this code.
+    pub exit_code: i32,
+    /// Hang instead of reading anything.
+    pub hang: bool,
+}
+
+impl Args {
+    /// Parse `--stop-after <lines>`, `--exit-code <code>` and `--hang`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--stop-after" => parsed.stop_after = Some(number(&arg, args.next())?),
+                "--exit-code" => parsed.exit_code = number(&arg, args.next())?,
+                "--hang" => parsed.hang = true,
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// The value of the argument `name`.
+fn number<T: FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a number", name))
+}
+
+/// Echo stdin to stdout, a line at a time, then exit as `args` says.
+pub fn run(args: Args) -> ! {
+    if args.hang {
+        loop {
+            thread::sleep(Duration::from_secs(60));
+        }
+    }
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    for (i, line) in stdin.lock().lines().enumerate() {
+        if args.stop_after == Some(i) {
+            break;
+        }
+
+        let line = line.expect("reading stdin");
+        writeln!(stdout, "{}", line).expect("writing stdout");
+    }
+
+    stdout.flush().expect("writing stdout");
+    process::exit(args.exit_code);
+}
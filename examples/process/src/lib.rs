@@ -0,0 +1,103 @@
+//! Piping lines through a child process with `tokio::process`.
+//!
+//! Writing all the input before reading any output only works for small
+//! amounts: once the pipe from the child is full, it stops reading its
+//! input until its output is read, while the parent stops reading its
+//! output until it took all the input. So the input is written and the
+//! output read at once, with `try_join!`.
+//!
+//! A child may also exit before reading all its input. Writing to it then
+//! fails with a broken pipe, which only means the rest of the input isn't
+//! needed.
+
+pub mod child;
+
+use std::io;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::process::{ChildStdin, ChildStdout, Command};
+use tokio::time;
+
+/// What came out of a child process.
+#[derive(Debug)]
+pub struct Output {
+    /// Its output, a line at a time.
+    pub lines: Vec<String>,
+    pub status: ExitStatus,
+    /// Whether all the input was written, rather than the child exiting
+    /// before reading it.
+    pub input_written: bool,
+}
+
+/// Run `command`, with `input` written to it a line at a time, and return
+/// its output once it exits.
+///
+/// Should it take longer than `limit` altogether, it is killed, and the
+/// error is `TimedOut`.
+pub async fn pipe(command: &mut Command, input: &[String], limit: Duration) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        // Should this future be dropped, the child goes with it.
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+
+    let run = async {
+        let (input_written, lines) = tokio::try_join!(write(stdin, input), read(stdout))?;
+        let status = child.wait().await?;
+
+        Ok(Output {
+            lines,
+            status,
+            input_written,
+        })
+    };
+
+    match time::timeout(limit, run).await {
+        Ok(res) => res,
+        Err(_) => {
+            child.kill().await?;
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("the child didn't exit within {:?}", limit),
+            ))
+        }
+    }
+}
+
+/// Write `input` to the child, and close its stdin. Returns whether all of
+/// it was written before the child closed its end.
+async fn write(stdin: ChildStdin, input: &[String]) -> io::Result<bool> {
+    let mut stdin = BufWriter::new(stdin);
+
+    let res = async {
+        for line in input {
+            stdin.write_all(line.as_bytes()).await?;
+            stdin.write_all(b"\n").await?;
+        }
+        stdin.flush().await
+    }
+    .await;
+
+    // Dropping `stdin` closes it: the child sees the end of its input.
+    match res {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+async fn read(stdout: ChildStdout) -> io::Result<Vec<String>> {
+    let mut lines = BufReader::new(stdout).lines();
+    let mut output = vec![];
+
+    while let Some(line) = lines.next_line().await? {
+        output.push(line);
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,55 @@
+use process::child::{self, Args};
+use std::env;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long the child may take.
+const LIMIT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() == Some("child") {
+        match Args::parse(args) {
+            Ok(args) => child::run(args),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    tokio::runtime::Runtime::new().unwrap().block_on(demo());
+}
+
+/// A command turning lines to upper case.
+#[cfg(unix)]
+fn upper_case() -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", "tr a-z A-Z"]);
+    command
+}
+
+/// Without `sh`: this binary echoing the lines as they are.
+#[cfg(not(unix))]
+fn upper_case() -> Command {
+    let mut command = Command::new(env::current_exe().unwrap());
+    command.arg("child");
+    command
+}
+
+async fn demo() {
+    let input: Vec<_> = ["hello", "from", "tokio::process"]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+    match process::pipe(&mut upper_case(), &input, LIMIT).await {
+        Ok(output) => {
+            for line in &output.lines {
+                println!("{}", line);
+            }
+            println!("({})", output.status);
+        }
+        Err(err) => eprintln!("running the child failed: {}", err),
+    }
+}
@@ -0,0 +1,107 @@
+use process::pipe;
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+const LIMIT: Duration = Duration::from_secs(30);
+
+/// The `process` binary as a child echoing its input, with `args`.
+fn child(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_process"));
+    command.arg("child").args(args);
+    command
+}
+
+fn lines(count: usize, width: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| format!("{:0width$}", i, width = width))
+        .collect()
+}
+
+#[tokio::test]
+async fn echoes_the_input() {
+    let input = lines(3, 1);
+
+    let output = pipe(&mut child(&[]), &input, LIMIT).await.unwrap();
+
+    assert_eq!(output.lines, input);
+    assert!(output.status.success());
+    assert!(output.input_written);
+}
+
+#[tokio::test]
+async fn output_larger_than_the_pipe_does_not_deadlock() {
+    // About 10MB each way, far more than a pipe holds.
+    let input = lines(100_000, 100);
+
+    let output = pipe(&mut child(&[]), &input, LIMIT).await.unwrap();
+
+    assert_eq!(output.lines.len(), input.len());
+    assert!(output.lines == input);
+    assert!(output.input_written);
+}
+
+#[tokio::test]
+async fn child_exiting_early_is_not_an_error() {
+    // The child stops reading long before the input is all written.
+    let input = lines(100_000, 100);
+
+    let output = pipe(&mut child(&["--stop-after", "10"]), &input, LIMIT)
+        .await
+        .unwrap();
+
+    assert_eq!(output.lines, &input[..10]);
+    assert!(output.status.success());
+    assert!(!output.input_written);
+}
+
+#[tokio::test]
+async fn collects_the_exit_status() {
+    let output = pipe(&mut child(&["--exit-code", "3"]), &lines(2, 1), LIMIT)
+        .await
+        .unwrap();
+
+    assert_eq!(output.lines.len(), 2);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[tokio::test]
+async fn kills_a_child_taking_too_long() {
+    let start = Instant::now();
+
+    let err = pipe(
+        &mut child(&["--hang"]),
+        &lines(3, 1),
+        Duration::from_millis(200),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn parses_the_child_arguments() {
+    use process::child::Args;
+
+    let args = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]), Ok(Args::default()));
+    assert_eq!(
+        args(&["--stop-after", "4", "--exit-code", "1"]),
+        Ok(Args {
+            stop_after: Some(4),
+            exit_code: 1,
+            hang: false,
+        })
+    );
+    assert_eq!(
+        args(&["--stop-after"]),
+        Err("--stop-after needs a number".to_string())
+    );
+    assert_eq!(
+        args(&["--loud"]),
+        Err("unknown argument `--loud`".to_string())
+    );
+}
@@ -0,0 +1,53 @@
+//! A TCP proxy: each connection accepted is forwarded to a target address.
+//!
+//! `copy_bidirectional` moves the bytes both ways at once. When one side
+//! is done sending, the other side's write half is shut down, while the
+//! bytes going the other way keep flowing: a client may close its end and
+//! still get the rest of the reply. Should either side fail, both
+//! connections are dropped, so the client never waits on an upstream that
+//! is gone.
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::copy_bidirectional;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+/// How long connecting to the target may take before the client is given
+/// up on.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Forward the connections of `listener` to `target`, until accepting
+/// fails.
+pub async fn run(listener: TcpListener, target: SocketAddr) -> io::Result<()> {
+    loop {
+        let (inbound, addr) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            match proxy(inbound, target).await {
+                Ok((sent, received)) => println!(
+                    "{}: {} bytes sent to {}, {} bytes received",
+                    addr, sent, target, received
+                ),
+                Err(err) => eprintln!("{}: proxying to {} failed: {}", addr, target, err),
+            }
+        });
+    }
+}
+
+/// Forward `inbound` to `target` until both are done, returning how many
+/// bytes went to the target, and how many came back.
+pub async fn proxy(mut inbound: TcpStream, target: SocketAddr) -> io::Result<(u64, u64)> {
+    let mut outbound = match time::timeout(CONNECT_TIMEOUT, TcpStream::connect(target)).await {
+        Ok(res) => res?,
+        Err(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "connecting to the target timed out",
+            ))
+        }
+    };
+
+    copy_bidirectional(&mut inbound, &mut outbound).await
+}
@@ -0,0 +1,20 @@
+use std::env;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // `proxy 127.0.0.1:8081 127.0.0.1:8080` forwards port 8081 to port 8080.
+    let mut args = env::args().skip(1);
+    let listen = args.next().unwrap_or_else(|| "127.0.0.1:8081".to_string());
+    let target = args
+        .next()
+        .unwrap_or_else(|| "127.0.0.1:8080".to_string())
+        .parse()?;
+
+    let listener = TcpListener::bind(&listen).await?;
+    println!("forwarding {} to {}", listen, target);
+
+    proxy::run(listener, target).await?;
+
+    Ok(())
+}
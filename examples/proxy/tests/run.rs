@@ -0,0 +1,136 @@
+use proxy::run;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+/// A proxy in front of `target`.
+async fn proxy(target: SocketAddr) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(run(listener, target));
+    addr
+}
+
+/// An upstream echoing everything back, closing its end once the client
+/// closed its own.
+async fn echo() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let (mut rd, mut wr) = socket.split();
+                tokio::io::copy(&mut rd, &mut wr).await.unwrap();
+                wr.shutdown().await.unwrap();
+            });
+        }
+    });
+
+    addr
+}
+
+/// Read until the end of `socket`, or the proxy resetting it.
+async fn read_until_closed(socket: &mut TcpStream) -> Vec<u8> {
+    let mut received = vec![];
+    let res = time::timeout(Duration::from_secs(5), socket.read_to_end(&mut received))
+        .await
+        .expect("the connection was never closed");
+
+    match res {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::ConnectionReset => {}
+        Err(err) => panic!("{}", err),
+    }
+
+    received
+}
+
+#[tokio::test]
+async fn round_trips() {
+    let addr = proxy(echo().await).await;
+    let socket = TcpStream::connect(addr).await.unwrap();
+    let (mut rd, mut wr) = socket.into_split();
+
+    // More than any buffer on the way holds, so both directions have to
+    // make progress at the same time.
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| i as u8).collect();
+
+    let writer = {
+        let data = data.clone();
+        tokio::spawn(async move {
+            wr.write_all(&data).await.unwrap();
+            wr.shutdown().await.unwrap();
+        })
+    };
+
+    let mut received = vec![];
+    time::timeout(Duration::from_secs(5), rd.read_to_end(&mut received))
+        .await
+        .expect("the echo never ended")
+        .unwrap();
+
+    writer.await.unwrap();
+    assert_eq!(received.len(), data.len());
+    assert!(received == data);
+}
+
+#[tokio::test]
+async fn replies_after_the_client_is_done_sending() {
+    // Answers once it has read everything.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        socket.read_to_end(&mut request).await.unwrap();
+        let reply = format!("got {} bytes", request.len());
+        socket.write_all(reply.as_bytes()).await.unwrap();
+    });
+
+    let mut socket = TcpStream::connect(proxy(target).await).await.unwrap();
+    socket.write_all(b"hello").await.unwrap();
+    socket.shutdown().await.unwrap();
+
+    assert_eq!(read_until_closed(&mut socket).await, b"got 5 bytes");
+}
+
+#[tokio::test]
+async fn closes_the_client_when_the_upstream_dies() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = listener.local_addr().unwrap();
+    let upstream = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        socket.write_all(b"partial").await.unwrap();
+        // Stuck in the middle of the transfer.
+        std::future::pending::<()>().await;
+    });
+
+    let mut socket = TcpStream::connect(proxy(target).await).await.unwrap();
+    let mut partial = [0; 7];
+    socket.read_exact(&mut partial).await.unwrap();
+    assert_eq!(&partial, b"partial");
+
+    // Dropping the task drops its socket, as if the process had been killed.
+    upstream.abort();
+
+    // The client hasn't closed its end, yet it isn't left hanging.
+    assert_eq!(read_until_closed(&mut socket).await, b"");
+}
+
+#[tokio::test]
+async fn closes_the_client_when_the_upstream_is_down() {
+    // An address nothing listens on anymore.
+    let target = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    let mut socket = TcpStream::connect(proxy(target).await).await.unwrap();
+
+    assert_eq!(read_until_closed(&mut socket).await, b"");
+}
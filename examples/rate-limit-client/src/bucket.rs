@@ -0,0 +1,86 @@
+//! A token bucket, refilled by an `Interval`.
+//!
+//! Each request takes a token from the bucket, waiting for one if it is
+//! empty. A token is added every period, up to `burst` of them: a client
+//! that was idle can then make `burst` requests at once, but never more
+//! than that.
+//!
+//! The bucket is behind a `tokio::sync::Mutex`, which hands the lock out in
+//! the order it was asked for. So callers waiting for tokens get them in
+//! the order they came: only the one holding the lock waits for the next
+//! tick, and the others wait for the lock.
+
+use futures::FutureExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{self, Instant, Interval};
+
+/// A token bucket, shared by its clones.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: u32,
+    burst: u32,
+    refill: Interval,
+}
+
+impl TokenBucket {
+    /// A full bucket letting `rate` requests through per second, and up to
+    /// `burst` at once.
+    ///
+    /// # Panics
+    ///
+    /// If `rate` or `burst` is 0, or if not called from within a Tokio
+    /// runtime.
+    pub fn new(rate: u32, burst: u32) -> TokenBucket {
+        assert!(rate > 0, "the rate must be positive");
+        assert!(burst > 0, "the burst must be at least one request");
+
+        let period = Duration::from_secs(1) / rate;
+        TokenBucket {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                burst,
+                // The first tick of `interval` is right away, but the bucket
+                // is full to begin with.
+                refill: time::interval_at(Instant::now() + period, period),
+            })),
+        }
+    }
+
+    /// Wait for a token, and take it.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+
+        bucket.add_missed();
+        if bucket.tokens == 0 {
+            bucket.refill.tick().await;
+            bucket.tokens += 1;
+        }
+        bucket.tokens -= 1;
+    }
+}
+
+impl Bucket {
+    /// Add the tokens of the ticks that went by since the bucket was last
+    /// used.
+    ///
+    /// An `Interval` remembers the ticks it missed, and returns them right
+    /// away, one per call to `tick`. Only `burst` of them count: once the
+    /// bucket is full, the interval is reset, so that the next token comes a
+    /// period after a token is taken, not earlier.
+    fn add_missed(&mut self) {
+        while self.tokens < self.burst {
+            match self.refill.tick().now_or_never() {
+                Some(_) => self.tokens += 1,
+                None => return,
+            }
+        }
+        self.refill.reset();
+    }
+}
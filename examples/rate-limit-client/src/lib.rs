@@ -0,0 +1,83 @@
+//! A client making many requests to an API that allows only so many per
+//! second, in two ways.
+//!
+//! * `bucket::TokenBucket`, written here: each request waits for a token,
+//!   and tokens come at the rate allowed.
+//! * tower's `RateLimit`, wrapping the client as a `Service`. It counts
+//!   requests in windows of time: `Rate::new(n, per)` lets `n` through,
+//!   then waits for `per` to be over since the first of them. With `n` set
+//!   to 1 and `per` to the period between requests, as `rate_limit` does,
+//!   it paces requests the same way as a bucket holding one token.
+//!
+//! Either way, the requests are all started at once, in tasks of their own,
+//! and the limit decides when each goes out.
+
+pub mod bucket;
+pub mod upstream;
+
+use bucket::TokenBucket;
+use std::future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tower::limit::rate::{Rate, RateLimit};
+use tower::Service;
+use upstream::Upstream;
+
+/// Send `requests` to the upstream at `addr`, each taking a token from
+/// `bucket` first. Returns the answers, in the order of the requests.
+pub async fn with_bucket(
+    bucket: &TokenBucket,
+    addr: SocketAddr,
+    requests: Vec<String>,
+) -> Vec<io::Result<String>> {
+    let mut tasks = vec![];
+    for request in requests {
+        let bucket = bucket.clone();
+        tasks.push(tokio::spawn(async move {
+            bucket.acquire().await;
+            upstream::call(addr, &request).await
+        }));
+    }
+
+    let mut answers = vec![];
+    for task in tasks {
+        answers.push(task.await.unwrap());
+    }
+    answers
+}
+
+/// `service`, letting `rate` requests through per second, one at a time.
+pub fn rate_limit<S>(service: S, rate: u32) -> RateLimit<S> {
+    let period = Duration::from_secs(1) / rate;
+    RateLimit::new(service, Rate::new(1, period))
+}
+
+/// Send `requests` to the upstream at `addr` through `rate_limit`. Returns
+/// the answers, in the order of the requests.
+pub async fn with_tower(
+    rate: u32,
+    addr: SocketAddr,
+    requests: Vec<String>,
+) -> Vec<io::Result<String>> {
+    let mut service = rate_limit(Upstream { addr }, rate);
+
+    // `RateLimit` isn't ready until the next request may go, so waiting for
+    // it to be ready is what paces the calls. A call is only the start of
+    // a request; the response is waited for in a task, so the next call
+    // needn't wait for it.
+    let mut tasks = vec![];
+    for request in requests {
+        if let Err(err) = future::poll_fn(|cx| service.poll_ready(cx)).await {
+            tasks.push(tokio::spawn(future::ready(Err(err))));
+            continue;
+        }
+        tasks.push(tokio::spawn(service.call(request)));
+    }
+
+    let mut answers = vec![];
+    for task in tasks {
+        answers.push(task.await.unwrap());
+    }
+    answers
+}
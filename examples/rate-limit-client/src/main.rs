@@ -0,0 +1,29 @@
+use rate_limit_client::bucket::TokenBucket;
+use rate_limit_client::{upstream, with_bucket, with_tower};
+use tokio::time::Instant;
+
+/// Requests per second the upstream allows.
+const RATE: u32 = 5;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let requests: Vec<String> = (0..15).map(|i| format!("GET /items/{}", i)).collect();
+
+    let (addr, log) = upstream::spawn().await?;
+    let start = Instant::now();
+    with_bucket(&TokenBucket::new(RATE, 1), addr, requests.clone()).await;
+    println!("token bucket:");
+    for time in log.times() {
+        println!("  {:?}", time - start);
+    }
+
+    let (addr, log) = upstream::spawn().await?;
+    let start = Instant::now();
+    with_tower(RATE, addr, requests).await;
+    println!("tower RateLimit:");
+    for time in log.times() {
+        println!("  {:?}", time - start);
+    }
+
+    Ok(())
+}
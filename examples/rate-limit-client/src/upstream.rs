@@ -0,0 +1,99 @@
+//! An API to call, simulated in-process, and the client calling it.
+//!
+//! Each request is a connection with a line on it, answered with `OK` and
+//! the number of the request. The upstream keeps when each request came,
+//! so the pace of a client can be checked.
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Instant;
+use tower::Service;
+
+/// When the requests to a running upstream came.
+#[derive(Debug, Default)]
+pub struct Log {
+    times: Mutex<Vec<Instant>>,
+}
+
+impl Log {
+    /// The time of every request so far, in order.
+    pub fn times(&self) -> Vec<Instant> {
+        self.times.lock().unwrap().clone()
+    }
+}
+
+/// Answer requests on a free port of localhost, in the background.
+pub async fn spawn() -> io::Result<(SocketAddr, Arc<Log>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let log = Arc::new(Log::default());
+
+    let served = log.clone();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let log = served.clone();
+            tokio::spawn(async move {
+                let _ = answer(socket, &log).await;
+            });
+        }
+    });
+
+    Ok((addr, log))
+}
+
+async fn answer(socket: TcpStream, log: &Log) -> io::Result<()> {
+    let mut socket = BufReader::new(socket);
+    let mut request = String::new();
+    socket.read_line(&mut request).await?;
+
+    let n = {
+        let mut times = log.times.lock().unwrap();
+        times.push(Instant::now());
+        times.len()
+    };
+
+    socket
+        .get_mut()
+        .write_all(format!("OK {}\n", n).as_bytes())
+        .await
+}
+
+/// Send `request` to the upstream at `addr`, and read the answer.
+pub async fn call(addr: SocketAddr, request: &str) -> io::Result<String> {
+    let mut socket = BufReader::new(TcpStream::connect(addr).await?);
+    socket
+        .get_mut()
+        .write_all(format!("{}\n", request).as_bytes())
+        .await?;
+
+    let mut answer = String::new();
+    socket.read_line(&mut answer).await?;
+    Ok(answer.trim_end().to_string())
+}
+
+/// `call` as a `tower::Service`, so tower middleware can wrap it.
+#[derive(Debug, Clone)]
+pub struct Upstream {
+    pub addr: SocketAddr,
+}
+
+impl Service<String> for Upstream {
+    type Response = String;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<String>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: String) -> Self::Future {
+        let addr = self.addr;
+        Box::pin(async move { call(addr, &request).await })
+    }
+}
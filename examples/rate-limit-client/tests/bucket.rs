@@ -0,0 +1,147 @@
+use rate_limit_client::bucket::TokenBucket;
+use rate_limit_client::rate_limit;
+use std::future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{self, Instant};
+use tower::Service;
+
+const RATE: u32 = 5;
+const PERIOD: Duration = Duration::from_millis(200);
+
+/// How many of `times` fall in each second after `start`.
+fn per_second(start: Instant, times: &[Instant]) -> Vec<usize> {
+    let mut counts = vec![];
+    for time in times {
+        let second = (*time - start).as_secs() as usize;
+        if counts.len() <= second {
+            counts.resize(second + 1, 0);
+        }
+        counts[second] += 1;
+    }
+    counts
+}
+
+/// Have `n` tasks take a token from `bucket` at once, and return when each
+/// got it, in the order they did.
+async fn acquire_all(bucket: &TokenBucket, n: usize) -> Vec<(usize, Instant)> {
+    let acquired = Arc::new(Mutex::new(vec![]));
+
+    let mut tasks = vec![];
+    for i in 0..n {
+        let (bucket, acquired) = (bucket.clone(), acquired.clone());
+        tasks.push(tokio::spawn(async move {
+            bucket.acquire().await;
+            acquired.lock().unwrap().push((i, Instant::now()));
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let acquired = acquired.lock().unwrap().clone();
+    acquired
+}
+
+#[tokio::test(start_paused = true)]
+async fn lets_the_rate_through_each_second() {
+    let start = Instant::now();
+    let bucket = TokenBucket::new(RATE, 1);
+
+    let acquired = acquire_all(&bucket, 20).await;
+    let times: Vec<_> = acquired.iter().map(|(_, time)| *time).collect();
+
+    assert_eq!(per_second(start, &times), [5, 5, 5, 5]);
+    for (i, time) in times.iter().enumerate() {
+        assert_eq!(*time - start, PERIOD * i as u32);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_callers_go_in_the_order_they_came() {
+    let bucket = TokenBucket::new(RATE, 2);
+
+    let acquired = acquire_all(&bucket, 10).await;
+    let order: Vec<_> = acquired.iter().map(|(i, _)| *i).collect();
+
+    assert_eq!(order, (0..10).collect::<Vec<_>>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn bursts_after_being_idle() {
+    let bucket = TokenBucket::new(RATE, 3);
+
+    // Full to begin with.
+    let start = Instant::now();
+    for _ in 0..3 {
+        bucket.acquire().await;
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // Idle for long enough to get many more tokens than the burst.
+    time::sleep(Duration::from_secs(10)).await;
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        bucket.acquire().await;
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    bucket.acquire().await;
+    assert_eq!(start.elapsed(), PERIOD);
+}
+
+#[tokio::test(start_paused = true)]
+async fn refills_a_period_after_a_token_is_taken_from_a_full_bucket() {
+    let bucket = TokenBucket::new(RATE, 1);
+    time::sleep(PERIOD * 3 / 2).await;
+
+    let start = Instant::now();
+    bucket.acquire().await;
+    bucket.acquire().await;
+    assert_eq!(start.elapsed(), PERIOD);
+}
+
+/// Records when it is called.
+struct Record(Arc<Mutex<Vec<Instant>>>);
+
+impl Service<()> for Record {
+    type Response = ();
+    type Error = ();
+    type Future = future::Ready<Result<(), ()>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: ()) -> Self::Future {
+        self.0.lock().unwrap().push(Instant::now());
+        future::ready(Ok(()))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn tower_paces_requests_like_a_bucket_of_one_token() {
+    let start = Instant::now();
+    let called = Arc::new(Mutex::new(vec![]));
+    let mut service = rate_limit(Record(called.clone()), RATE);
+
+    for _ in 0..12 {
+        future::poll_fn(|cx| service.poll_ready(cx)).await.unwrap();
+        service.call(()).await.unwrap();
+    }
+    let tower: Vec<_> = called
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|time| *time - start)
+        .collect();
+
+    let start = Instant::now();
+    let bucket = TokenBucket::new(RATE, 1);
+    let acquired = acquire_all(&bucket, 12).await;
+    let bucket: Vec<_> = acquired.iter().map(|(_, time)| *time - start).collect();
+
+    assert_eq!(tower, bucket);
+}
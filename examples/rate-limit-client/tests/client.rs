@@ -0,0 +1,64 @@
+//! The client against an upstream over TCP, in real time: with paused time,
+//! the clock may jump ahead while a connection is being made, so when
+//! requests arrive isn't exact. The pace itself is checked exactly in
+//! `tests/bucket.rs`.
+
+use rate_limit_client::bucket::TokenBucket;
+use rate_limit_client::{upstream, with_bucket, with_tower};
+use std::io;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const RATE: u32 = 100;
+const PERIOD: Duration = Duration::from_millis(10);
+const REQUESTS: usize = 30;
+
+fn requests() -> Vec<String> {
+    (0..REQUESTS).map(|i| format!("GET /items/{}", i)).collect()
+}
+
+/// The numbers the upstream gave the requests answered by `answers`.
+fn numbers(answers: Vec<io::Result<String>>) -> Vec<usize> {
+    let mut numbers: Vec<usize> = answers
+        .into_iter()
+        .map(|answer| answer.unwrap()["OK ".len()..].parse().unwrap())
+        .collect();
+    numbers.sort_unstable();
+    numbers
+}
+
+#[tokio::test]
+async fn bucket_keeps_to_the_rate() {
+    let (addr, log) = upstream::spawn().await.unwrap();
+    let start = Instant::now();
+
+    let answers = with_bucket(&TokenBucket::new(RATE, 1), addr, requests()).await;
+
+    assert_eq!(numbers(answers), (1..=REQUESTS).collect::<Vec<_>>());
+    let times = log.times();
+    assert!(times[REQUESTS - 1] - start >= PERIOD * (REQUESTS as u32 - 1));
+}
+
+#[tokio::test]
+async fn tower_keeps_to_the_rate() {
+    let (addr, log) = upstream::spawn().await.unwrap();
+    let start = Instant::now();
+
+    let answers = with_tower(RATE, addr, requests()).await;
+
+    assert_eq!(numbers(answers), (1..=REQUESTS).collect::<Vec<_>>());
+    let times = log.times();
+    assert!(times[REQUESTS - 1] - start >= PERIOD * (REQUESTS as u32 - 1));
+}
+
+#[tokio::test]
+async fn bursts_go_out_at_once() {
+    let (addr, log) = upstream::spawn().await.unwrap();
+    let start = Instant::now();
+
+    let answers = with_bucket(&TokenBucket::new(1, 10), addr, requests()[..10].to_vec()).await;
+
+    assert_eq!(numbers(answers), (1..=10).collect::<Vec<_>>());
+    // Far less than the second a single token takes.
+    assert!(log.times()[9] - start < Duration::from_millis(500));
+}
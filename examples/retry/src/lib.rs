@@ -0,0 +1,129 @@
+//! Retrying an operation, with exponential backoff.
+//!
+//! `retry` calls the operation again each time it fails, sleeping longer
+//! and longer in between: twice as long each time, up to a maximum. Part
+//! of each sleep is random, the jitter, so that clients failing together
+//! don't all retry together. Which errors are worth retrying is up to the
+//! `Policy`: some, like a refused connection, may go away; others, like a
+//! malformed request, won't.
+
+pub mod redis;
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::time;
+
+/// Decides whether an error is worth retrying.
+pub trait Retryable<E> {
+    fn is_retryable(&self, err: &E) -> bool;
+}
+
+impl<E, F: Fn(&E) -> bool> Retryable<E> for F {
+    fn is_retryable(&self, err: &E) -> bool {
+        self(err)
+    }
+}
+
+/// Retries every error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnyError;
+
+impl<E> Retryable<E> for AnyError {
+    fn is_retryable(&self, _err: &E) -> bool {
+        true
+    }
+}
+
+/// How to retry.
+#[derive(Debug, Clone)]
+pub struct Policy<R = AnyError> {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: f64,
+    retryable: R,
+}
+
+impl Policy {
+    /// Make up to `max_attempts` attempts, retrying any error.
+    ///
+    /// The first retry is after 100ms, up to 10s, with half of each sleep
+    /// random.
+    pub fn new(max_attempts: u32) -> Policy {
+        assert!(max_attempts > 0, "at least one attempt is needed");
+
+        Policy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: 0.5,
+            retryable: AnyError,
+        }
+    }
+}
+
+impl<R> Policy<R> {
+    /// Sleep `initial` before the first retry, twice as long before each
+    /// next one, but never longer than `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Take up to `jitter` of each sleep off, at random: `0.0` for none,
+    /// `1.0` for anything from no sleep to all of it.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&jitter),
+            "the jitter is a fraction of the sleep"
+        );
+        self.jitter = jitter;
+        self
+    }
+
+    /// Only retry the errors `retryable` accepts.
+    pub fn retry_if<P>(self, retryable: P) -> Policy<P> {
+        Policy {
+            max_attempts: self.max_attempts,
+            initial_backoff: self.initial_backoff,
+            max_backoff: self.max_backoff,
+            jitter: self.jitter,
+            retryable,
+        }
+    }
+
+    /// The sleep after the attempt `attempt` failed, the first one being
+    /// 1, before jitter. An `attempt` of 0 is taken as 1.
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        // `checked_` all the way: a large `attempt` just means `max`.
+        2u32.checked_pow(attempt.saturating_sub(1))
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+/// Call `op` until it succeeds, it fails with an error `policy` says isn't
+/// worth retrying, or it failed as many times as `policy` allows. In the
+/// last two cases, the error is the last one `op` returned.
+pub async fn retry<T, E, R, F, Fut>(policy: &Policy<R>, mut op: F) -> Result<T, E>
+where
+    R: Retryable<E>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut rng = fastrand::Rng::new();
+    let mut attempt = 1;
+
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_attempts && policy.retryable.is_retryable(&err) => {
+                let backoff = policy.backoff_after(attempt);
+                time::sleep(backoff.mul_f64(1.0 - policy.jitter * rng.f64())).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
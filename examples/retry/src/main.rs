@@ -0,0 +1,30 @@
+use retry::Policy;
+use std::future;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time;
+
+/// How long the server takes to start.
+const STARTUP: Duration = Duration::from_secs(1);
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    // A free port, for a server that only starts listening on it later.
+    let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+
+    tokio::spawn(async move {
+        time::sleep(STARTUP).await;
+        let listener = TcpListener::bind(addr).await.unwrap();
+        println!("server listening on {}", addr);
+        mini_redis::server::run(listener, future::pending::<()>()).await
+    });
+
+    let policy = Policy::new(10).backoff(Duration::from_millis(50), Duration::from_secs(1));
+    let mut client = retry::redis::connect(addr, policy).await?;
+    println!("connected to {}", addr);
+
+    client.set("hello", "world".into()).await?;
+    println!("got {:?}", client.get("hello").await?);
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+//! Connecting to a mini-redis server that may not be up yet.
+
+use crate::{retry, Policy};
+use mini_redis::client::{self, Client};
+use std::io;
+use std::net::SocketAddr;
+
+/// Whether connecting failed because nothing listens on the address yet.
+/// Any other error, like an unreachable network, is given up on at once.
+pub fn is_refused(err: &mini_redis::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+/// Connect to `addr`, retrying for as long as `policy` allows while the
+/// connection is refused.
+pub async fn connect(addr: SocketAddr, policy: Policy) -> mini_redis::Result<Client> {
+    let policy = policy.retry_if(is_refused);
+
+    retry(&policy, || client::connect(addr)).await
+}
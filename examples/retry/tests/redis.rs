@@ -0,0 +1,49 @@
+use retry::Policy;
+use std::future;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::time;
+
+fn policy() -> Policy {
+    Policy::new(20).backoff(Duration::from_millis(10), Duration::from_millis(50))
+}
+
+#[tokio::test]
+async fn connects_once_the_server_is_up() {
+    // A port nothing listens on, until the server starts.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let start = Instant::now();
+    tokio::spawn(async move {
+        time::sleep(Duration::from_millis(200)).await;
+        let listener = TcpListener::bind(addr).await.unwrap();
+        mini_redis::server::run(listener, future::pending::<()>()).await
+    });
+
+    let mut client = retry::redis::connect(addr, policy()).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(client.get("hello").await.unwrap(), Some("world".into()));
+}
+
+#[tokio::test]
+async fn gives_up_when_the_server_never_starts() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let policy = Policy::new(3).backoff(Duration::from_millis(10), Duration::from_millis(10));
+    let err = match retry::redis::connect(addr, policy).await {
+        Ok(_) => panic!("connected to nothing"),
+        Err(err) => err,
+    };
+
+    assert!(retry::redis::is_refused(&err), "{}", err);
+}
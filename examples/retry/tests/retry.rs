@@ -0,0 +1,124 @@
+use retry::{retry, Policy};
+use std::cell::RefCell;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// An operation failing its first `failures` calls with the number of the
+/// call, and recording when each call was made.
+struct Flaky {
+    failures: usize,
+    calls: RefCell<Vec<Instant>>,
+}
+
+impl Flaky {
+    fn new(failures: usize) -> Flaky {
+        Flaky {
+            failures,
+            calls: RefCell::new(vec![]),
+        }
+    }
+
+    async fn call(&self) -> Result<&'static str, usize> {
+        let mut calls = self.calls.borrow_mut();
+        calls.push(Instant::now());
+
+        if calls.len() <= self.failures {
+            Err(calls.len())
+        } else {
+            Ok("done")
+        }
+    }
+
+    /// When each call was made, in milliseconds after `start`.
+    fn schedule(&self, start: Instant) -> Vec<u128> {
+        self.calls
+            .borrow()
+            .iter()
+            .map(|call| (*call - start).as_millis())
+            .collect()
+    }
+}
+
+fn policy(max_attempts: u32) -> Policy {
+    Policy::new(max_attempts)
+        .backoff(Duration::from_millis(100), Duration::from_secs(1))
+        .jitter(0.0)
+}
+
+#[tokio::test(start_paused = true)]
+async fn backs_off_exponentially() {
+    let flaky = Flaky::new(4);
+    let start = Instant::now();
+
+    let res = retry(&policy(5), || flaky.call()).await;
+
+    assert_eq!(res, Ok("done"));
+    // Sleeping 100ms, 200ms, 400ms and 800ms in between.
+    assert_eq!(flaky.schedule(start), [0, 100, 300, 700, 1500]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn backoff_is_capped() {
+    let flaky = Flaky::new(5);
+    let start = Instant::now();
+    let policy = policy(6).backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+    retry(&policy, || flaky.call()).await.unwrap();
+
+    assert_eq!(flaky.schedule(start), [0, 100, 300, 600, 900, 1200]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn gives_up_after_the_last_attempt() {
+    let flaky = Flaky::new(usize::MAX);
+    let start = Instant::now();
+
+    let res = retry(&policy(3), || flaky.call()).await;
+
+    // The error of the last attempt, with no sleep after it.
+    assert_eq!(res, Err(3));
+    assert_eq!(flaky.schedule(start), [0, 100, 300]);
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+async fn non_retryable_errors_return_at_once() {
+    let flaky = Flaky::new(usize::MAX);
+    let start = Instant::now();
+    // Only the first error is worth retrying.
+    let policy = policy(5).retry_if(|err: &usize| *err < 2);
+
+    let res = retry(&policy, || flaky.call()).await;
+
+    assert_eq!(res, Err(2));
+    assert_eq!(flaky.schedule(start), [0, 100]);
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn jitter_shortens_the_sleeps() {
+    let flaky = Flaky::new(9);
+    let start = Instant::now();
+    let policy = Policy::new(10)
+        .backoff(Duration::from_millis(100), Duration::from_millis(100))
+        .jitter(0.5);
+
+    retry(&policy, || flaky.call()).await.unwrap();
+
+    let schedule = flaky.schedule(start);
+    for pair in schedule.windows(2) {
+        let sleep = pair[1] - pair[0];
+        assert!((50..=100).contains(&sleep), "slept {}ms", sleep);
+    }
+}
+
+#[test]
+fn backoff_does_not_overflow() {
+    let policy = Policy::new(100).backoff(Duration::from_secs(1), Duration::from_secs(60));
+
+    assert_eq!(policy.backoff_after(0), Duration::from_secs(1));
+    assert_eq!(policy.backoff_after(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff_after(6), Duration::from_secs(32));
+    assert_eq!(policy.backoff_after(7), Duration::from_secs(60));
+    assert_eq!(policy.backoff_after(100), Duration::from_secs(60));
+}
@@ -0,0 +1,126 @@
+//! Scatter-gather: the same query sent to several upstreams at once, the
+//! answers gathered as they arrive.
+//!
+//! Each request is a task of a `JoinSet`, with a timeout of its own. Once
+//! enough upstreams answered, a quorum, the requests still going are
+//! aborted, and the answers returned. When so many requests failed that
+//! the quorum can't be met anymore, the others are aborted too, without
+//! waiting for them.
+
+pub mod upstream;
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tokio::time;
+
+/// Fewer upstreams answered than the quorum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoQuorum {
+    pub quorum: usize,
+    pub answered: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+}
+
+/// Send `query` to every upstream of `upstreams`, and return the first
+/// `quorum` answers.
+pub async fn query(
+    upstreams: &[SocketAddr],
+    query: &str,
+    quorum: usize,
+    timeout: Duration,
+) -> Result<Vec<String>, NoQuorum> {
+    let query = query.to_string();
+
+    gather(upstreams.iter().copied(), quorum, timeout, |addr| {
+        ask(addr, query.clone())
+    })
+    .await
+}
+
+/// Call `ask` with each target at once, and return the first `quorum`
+/// answers, in the order they arrived.
+///
+/// Each call gets `timeout` to answer. The calls still going once the
+/// outcome is known are aborted, and dropped by the time this returns.
+pub async fn gather<T, R, F, Fut>(
+    targets: impl IntoIterator<Item = T>,
+    quorum: usize,
+    timeout: Duration,
+    mut ask: F,
+) -> Result<Vec<R>, NoQuorum>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = io::Result<R>> + Send + 'static,
+    R: Send + 'static,
+{
+    let mut requests = JoinSet::new();
+    for target in targets {
+        requests.spawn(time::timeout(timeout, ask(target)));
+    }
+
+    let mut answers = vec![];
+    let mut outcome = NoQuorum {
+        quorum,
+        answered: 0,
+        failed: 0,
+        timed_out: 0,
+    };
+
+    loop {
+        if answers.len() >= quorum {
+            requests.shutdown().await;
+            return Ok(answers);
+        }
+        // Even if every request left answered.
+        if answers.len() + requests.len() < quorum {
+            requests.shutdown().await;
+            outcome.answered = answers.len();
+            return Err(outcome);
+        }
+
+        match requests.join_next().await.expect("a request is left") {
+            Ok(Ok(Ok(answer))) => answers.push(answer),
+            Ok(Ok(Err(_))) => outcome.failed += 1,
+            Ok(Err(_)) => outcome.timed_out += 1,
+            // The request panicked.
+            Err(_) => outcome.failed += 1,
+        }
+    }
+}
+
+/// Send `query` to the upstream at `addr`, and return the line it answers.
+async fn ask(addr: SocketAddr, query: String) -> io::Result<String> {
+    let mut socket = BufReader::new(TcpStream::connect(addr).await?);
+    socket.write_all(query.as_bytes()).await?;
+    socket.write_all(b"\n").await?;
+
+    let mut answer = String::new();
+    if socket.read_line(&mut answer).await? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the upstream closed the connection without answering",
+        ));
+    }
+    answer.truncate(answer.trim_end().len());
+
+    Ok(answer)
+}
+
+impl fmt::Display for NoQuorum {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{} answers of the {} needed; {} failed, {} timed out",
+            self.answered, self.quorum, self.failed, self.timed_out
+        )
+    }
+}
+
+impl std::error::Error for NoQuorum {}
@@ -0,0 +1,47 @@
+use scatter_gather::upstream::Upstream;
+use std::time::Duration;
+
+/// How many answers are enough.
+const QUORUM: usize = 3;
+
+/// How long each upstream has to answer.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Some quick, some slow, one too slow and one broken.
+    let upstreams = [
+        Upstream {
+            delay: Duration::from_millis(50),
+            fails: false,
+        },
+        Upstream {
+            delay: Duration::from_millis(100),
+            fails: true,
+        },
+        Upstream {
+            delay: Duration::from_millis(150),
+            fails: false,
+        },
+        Upstream {
+            delay: Duration::from_millis(300),
+            fails: false,
+        },
+        Upstream {
+            delay: Duration::from_secs(5),
+            fails: false,
+        },
+    ];
+
+    let mut addrs = vec![];
+    for upstream in upstreams.iter() {
+        addrs.push(upstream.spawn().await?);
+    }
+
+    let answers = scatter_gather::query(&addrs, "hello", QUORUM, TIMEOUT).await?;
+    for answer in answers {
+        println!("{}", answer);
+    }
+
+    Ok(())
+}
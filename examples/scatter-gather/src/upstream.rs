@@ -0,0 +1,47 @@
+//! Upstreams to query, simulated in-process.
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::time;
+
+/// How an upstream answers each query: after `delay`, with the query and
+/// its own address, or by closing the connection if it `fails`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Upstream {
+    pub delay: Duration,
+    pub fails: bool,
+}
+
+impl Upstream {
+    /// Listen on a free port of localhost, answering every connection in
+    /// the background.
+    pub async fn spawn(self) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut socket = BufReader::new(socket);
+                    let mut query = String::new();
+                    if socket.read_line(&mut query).await.is_err() {
+                        return;
+                    }
+
+                    time::sleep(self.delay).await;
+                    if self.fails {
+                        return;
+                    }
+
+                    let answer = format!("{} from {}\n", query.trim_end(), addr);
+                    let _ = socket.write_all(answer.as_bytes()).await;
+                });
+            }
+        });
+
+        Ok(addr)
+    }
+}
@@ -0,0 +1,147 @@
+//! The quorum math, with requests simulated by timers in paused time.
+
+use scatter_gather::{gather, NoQuorum};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+/// What a request does after that many milliseconds.
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Answer(u64),
+    Fail(u64),
+}
+
+/// Counts the requests dropped before they were done.
+struct Guard {
+    dropped: Arc<AtomicUsize>,
+    done: bool,
+}
+
+impl Guard {
+    fn done(&mut self) {
+        self.done = true;
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if !self.done {
+            self.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Gather the answers of `targets`, returning them with how many requests
+/// were dropped before they were done, and how long it took.
+async fn run(
+    targets: &[Target],
+    quorum: usize,
+    timeout: Duration,
+) -> (Result<Vec<u64>, NoQuorum>, usize, Duration) {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+
+    let res = gather(targets.iter().copied(), quorum, timeout, |target| {
+        let mut guard = Guard {
+            dropped: dropped.clone(),
+            done: false,
+        };
+
+        async move {
+            let (ms, answers) = match target {
+                Target::Answer(ms) => (ms, true),
+                Target::Fail(ms) => (ms, false),
+            };
+            time::sleep(Duration::from_millis(ms)).await;
+            guard.done();
+
+            if answers {
+                Ok(ms)
+            } else {
+                Err(io::ErrorKind::ConnectionRefused.into())
+            }
+        }
+    })
+    .await;
+
+    (res, dropped.load(Ordering::SeqCst), start.elapsed())
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum_met_early_aborts_the_rest() {
+    use Target::*;
+    let targets = [
+        Answer(30),
+        Answer(10),
+        Answer(2000),
+        Answer(20),
+        Answer(1000),
+    ];
+
+    let (res, dropped, elapsed) = run(&targets, 3, TIMEOUT).await;
+
+    assert_eq!(res, Ok(vec![10, 20, 30]));
+    // The two slow ones, gone by the time `gather` returned.
+    assert_eq!(dropped, 2);
+    assert_eq!(elapsed, Duration::from_millis(30));
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_request_timing_out_is_an_error() {
+    use Target::*;
+    let targets = [Answer(10_000), Answer(20_000), Answer(30_000)];
+
+    let (res, _, elapsed) = run(&targets, 1, Duration::from_secs(1)).await;
+
+    assert_eq!(
+        res,
+        Err(NoQuorum {
+            quorum: 1,
+            answered: 0,
+            failed: 0,
+            timed_out: 3,
+        })
+    );
+    assert_eq!(elapsed, Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn failures_count_against_the_quorum() {
+    use Target::*;
+    let targets = [Answer(10), Fail(20), Answer(30), Fail(40), Answer(50)];
+
+    // Three answers out of five: met once the last one answers.
+    let (res, dropped, elapsed) = run(&targets, 3, TIMEOUT).await;
+    assert_eq!(res, Ok(vec![10, 30, 50]));
+    assert_eq!(dropped, 0);
+    assert_eq!(elapsed, Duration::from_millis(50));
+
+    // Four can't be met after the second failure, and the last request
+    // isn't waited for.
+    let (res, dropped, elapsed) = run(&targets, 4, TIMEOUT).await;
+    assert_eq!(
+        res,
+        Err(NoQuorum {
+            quorum: 4,
+            answered: 2,
+            failed: 2,
+            timed_out: 0,
+        })
+    );
+    assert_eq!(dropped, 1);
+    assert_eq!(elapsed, Duration::from_millis(40));
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum_larger_than_the_targets_fails_at_once() {
+    let (res, dropped, elapsed) = run(&[Target::Answer(10)], 2, TIMEOUT).await;
+
+    assert_eq!(res.unwrap_err().answered, 0);
+    assert_eq!(dropped, 1);
+    assert_eq!(elapsed, Duration::ZERO);
+}
@@ -0,0 +1,70 @@
+use scatter_gather::upstream::Upstream;
+use scatter_gather::{query, NoQuorum};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+async fn spawn(delay_ms: u64, fails: bool) -> SocketAddr {
+    Upstream {
+        delay: Duration::from_millis(delay_ms),
+        fails,
+    }
+    .spawn()
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn gathers_the_quickest_answers() {
+    let quick = [
+        spawn(10, false).await,
+        spawn(10, false).await,
+        spawn(10, false).await,
+    ];
+    let mut upstreams = quick.to_vec();
+    upstreams.push(spawn(0, true).await);
+    upstreams.push(spawn(10_000, false).await);
+
+    let start = Instant::now();
+    let mut answers = query(&upstreams, "hello", 3, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    // Without waiting for the slow one.
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    answers.sort();
+    let mut expected: Vec<_> = quick
+        .iter()
+        .map(|addr| format!("hello from {}", addr))
+        .collect();
+    expected.sort();
+    assert_eq!(answers, expected);
+}
+
+#[tokio::test]
+async fn reports_why_the_quorum_is_not_met() {
+    let upstreams = [
+        spawn(0, false).await,
+        spawn(0, false).await,
+        spawn(0, true).await,
+        spawn(10_000, false).await,
+    ];
+
+    let err = query(&upstreams, "hello", 3, Duration::from_millis(500))
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        NoQuorum {
+            quorum: 3,
+            answered: 2,
+            failed: 1,
+            timed_out: 1,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "2 answers of the 3 needed; 1 failed, 1 timed out"
+    );
+}
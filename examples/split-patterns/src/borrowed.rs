@@ -0,0 +1,48 @@
+//! `TcpStream::split`: borrowed halves, used from one task.
+//!
+//! The halves borrow the socket, so they can't outlive it, nor move into
+//! another task. Instead the task waits for whichever of a line from the
+//! client and a message for it comes first, with `select!`. While it
+//! handles one, the other waits: a slow client holds up reading as well as
+//! writing.
+
+use crate::{lines_error, MAX_LINE};
+use futures::StreamExt;
+use std::io;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+pub async fn run(
+    mut socket: TcpStream,
+    incoming: mpsc::Sender<String>,
+    mut outgoing: mpsc::Receiver<String>,
+) -> io::Result<()> {
+    let (rd, mut wr) = socket.split();
+    let mut lines = FramedRead::new(rd, LinesCodec::new_with_max_length(MAX_LINE));
+
+    // Whether `outgoing` is still open.
+    let mut writing = true;
+
+    loop {
+        tokio::select! {
+            line = lines.next() => {
+                let line = match line {
+                    Some(line) => line.map_err(lines_error)?,
+                    None => return Ok(()),
+                };
+                if incoming.send(line).await.is_err() {
+                    return Ok(());
+                }
+            }
+            msg = outgoing.recv(), if writing => match msg {
+                Some(msg) => wr.write_all(format!("{}\n", msg).as_bytes()).await?,
+                None => {
+                    writing = false;
+                    wr.shutdown().await?;
+                }
+            },
+        }
+    }
+}
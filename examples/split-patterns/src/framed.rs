@@ -0,0 +1,37 @@
+//! `Framed::split`: a stream of lines and a sink for them.
+//!
+//! `split`, from `StreamExt`, works on anything that is both a `Stream`
+//! and a `Sink`, such as a `Framed`. Both halves share the `Framed` behind
+//! a lock, taken only while one is polled, so they can move into tasks of
+//! their own, like the halves of `into_split`. Unlike those, they work in
+//! lines rather than bytes.
+
+use crate::{forward, lines_error, MAX_LINE};
+use futures::{SinkExt, StreamExt};
+use std::io;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_util::codec::{Framed, LinesCodec};
+
+pub async fn run(
+    socket: TcpStream,
+    incoming: mpsc::Sender<String>,
+    mut outgoing: mpsc::Receiver<String>,
+) -> io::Result<()> {
+    let framed = Framed::new(socket, LinesCodec::new_with_max_length(MAX_LINE));
+    let (mut sink, lines) = framed.split();
+
+    let writer = tokio::spawn(async move {
+        while let Some(msg) = outgoing.recv().await {
+            sink.send(msg).await.map_err(lines_error)?;
+        }
+        // Closing the sink shuts down the writing side of the socket.
+        sink.close().await.map_err(lines_error)
+    });
+
+    let res = forward(lines, incoming).await;
+
+    // As in `owned`.
+    writer.abort();
+    res
+}
@@ -0,0 +1,113 @@
+//! Reading from and writing to the same socket at once, three ways.
+//!
+//! A chat connection has to read what the client sends while writing what
+//! others said, whenever either comes. Both reading and writing need
+//! `&mut TcpStream`, so doing them at once with the socket itself doesn't
+//! compile:
+//!
+//! ```compile_fail,E0499
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//! use tokio::net::TcpStream;
+//!
+//! async fn chat(mut socket: TcpStream) {
+//!     let mut buf = [0; 1024];
+//!     let read = socket.read(&mut buf);
+//!     let write = socket.write_all(b"hello\n");
+//!     tokio::join!(read, write);
+//! }
+//! ```
+//!
+//! and neither does giving it to two tasks:
+//!
+//! ```compile_fail,E0382
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//! use tokio::net::TcpStream;
+//!
+//! async fn chat(mut socket: TcpStream) {
+//!     tokio::spawn(async move {
+//!         let mut buf = [0; 1024];
+//!         socket.read(&mut buf).await
+//!     });
+//!     tokio::spawn(async move { socket.write_all(b"hello\n").await });
+//! }
+//! ```
+//!
+//! The socket has to be split into a reading half and a writing half. Each
+//! module here does it differently, with the same behavior:
+//!
+//! * `borrowed`: `TcpStream::split` borrows the socket, so the halves stay
+//!   in the task, which uses both from one `select!`. It costs nothing.
+//! * `owned`: `TcpStream::into_split` gives halves that own the socket, so
+//!   they can move into tasks of their own.
+//! * `framed`: `Framed::split`, from `StreamExt`, splits a framed socket
+//!   into a stream of lines and a sink for them, which can move into tasks
+//!   too. They share the socket behind a lock.
+//!
+//! For any socket, `tokio::io::split` does what `into_split` does, with a
+//! lock as well.
+//!
+//! In all three, the lines the client sends go to `incoming`, and the
+//! messages from `outgoing` go to the client, each on a line. The
+//! connection is over when the client closes it. If `outgoing` closes
+//! first, the writing half is shut down, and the client can still send.
+
+pub mod borrowed;
+pub mod framed;
+pub mod owned;
+
+use futures::{Stream, StreamExt};
+use std::io;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_util::codec::LinesCodecError;
+
+/// The longest line a client may send, newline excluded.
+pub const MAX_LINE: usize = 8 * 1024;
+
+/// One of the ways of splitting a socket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Borrowed,
+    Owned,
+    Framed,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 3] = [Pattern::Borrowed, Pattern::Owned, Pattern::Framed];
+
+    /// Run the connection of `socket` with this pattern.
+    pub async fn run(
+        self,
+        socket: TcpStream,
+        incoming: mpsc::Sender<String>,
+        outgoing: mpsc::Receiver<String>,
+    ) -> io::Result<()> {
+        match self {
+            Pattern::Borrowed => borrowed::run(socket, incoming, outgoing).await,
+            Pattern::Owned => owned::run(socket, incoming, outgoing).await,
+            Pattern::Framed => framed::run(socket, incoming, outgoing).await,
+        }
+    }
+}
+
+/// Send the lines of `lines` to `incoming`, until the client closes the
+/// connection, or `incoming` is closed.
+async fn forward<S>(mut lines: S, incoming: mpsc::Sender<String>) -> io::Result<()>
+where
+    S: Stream<Item = Result<String, LinesCodecError>> + Unpin,
+{
+    while let Some(line) = lines.next().await {
+        if incoming.send(line.map_err(lines_error)?).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Turn an error from `LinesCodec` into one of `io`.
+fn lines_error(err: LinesCodecError) -> io::Error {
+    match err {
+        LinesCodecError::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
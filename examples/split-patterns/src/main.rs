@@ -0,0 +1,55 @@
+use split_patterns::Pattern;
+use std::env;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try it with `nc 127.0.0.1 12345`: what you type is answered, and a
+    // tick comes every few seconds whether you type or not.
+    let pattern = match env::args().nth(1).as_deref() {
+        None | Some("borrowed") => Pattern::Borrowed,
+        Some("owned") => Pattern::Owned,
+        Some("framed") => Pattern::Framed,
+        Some(other) => {
+            eprintln!(
+                "unknown pattern {:?}; expected borrowed, owned or framed",
+                other
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:12345").await?;
+    println!("listening on 127.0.0.1:12345, splitting with {:?}", pattern);
+
+    loop {
+        let (socket, addr) = listener.accept().await?;
+        let (incoming_tx, mut incoming) = mpsc::channel(16);
+        let (outgoing, outgoing_rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            if let Err(err) = pattern.run(socket, incoming_tx, outgoing_rx).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut ticks = time::interval(Duration::from_secs(3));
+            loop {
+                let msg = tokio::select! {
+                    line = incoming.recv() => match line {
+                        Some(line) => format!("you said: {}", line),
+                        None => return,
+                    },
+                    _ = ticks.tick() => "tick".to_string(),
+                };
+                if outgoing.send(msg).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,37 @@
+//! `TcpStream::into_split`: owned halves, each in a task of its own.
+//!
+//! The halves own the socket between them, so the writing half can move
+//! into a task, and write as messages come, whatever the reading half is
+//! doing. Dropping the writing half shuts down the writing side of the
+//! socket.
+
+use crate::{forward, MAX_LINE};
+use std::io;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+pub async fn run(
+    socket: TcpStream,
+    incoming: mpsc::Sender<String>,
+    mut outgoing: mpsc::Receiver<String>,
+) -> io::Result<()> {
+    let (rd, mut wr) = socket.into_split();
+
+    let writer = tokio::spawn(async move {
+        while let Some(msg) = outgoing.recv().await {
+            wr.write_all(format!("{}\n", msg).as_bytes()).await?;
+        }
+        wr.shutdown().await
+    });
+
+    let lines = FramedRead::new(rd, LinesCodec::new_with_max_length(MAX_LINE));
+    let res = forward(lines, incoming).await;
+
+    // The client is gone: messages not written yet never will be. A write
+    // that failed did so because the connection is broken, which reading
+    // found out too.
+    writer.abort();
+    res
+}
@@ -0,0 +1,146 @@
+use futures::{SinkExt, StreamExt};
+use split_patterns::{Pattern, MAX_LINE};
+use std::io;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tokio_util::codec::{Framed, LinesCodec};
+
+struct Conn {
+    client: Framed<TcpStream, LinesCodec>,
+    incoming: mpsc::Receiver<String>,
+    outgoing: mpsc::Sender<String>,
+    task: JoinHandle<io::Result<()>>,
+}
+
+/// A connection run with `pattern`, and the client at the other end.
+async fn connect(pattern: Pattern) -> Conn {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (socket, _) = listener.accept().await.unwrap();
+
+    let (incoming_tx, incoming) = mpsc::channel(16);
+    let (outgoing, outgoing_rx) = mpsc::channel(16);
+    let task = tokio::spawn(pattern.run(socket, incoming_tx, outgoing_rx));
+
+    Conn {
+        client: Framed::new(client, LinesCodec::new()),
+        incoming,
+        outgoing,
+        task,
+    }
+}
+
+async fn next_line(client: &mut Framed<TcpStream, LinesCodec>) -> Option<String> {
+    time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("timed out waiting for the server")
+        .map(Result::unwrap)
+}
+
+/// What happened on one connection, to compare across patterns.
+#[derive(Debug, PartialEq)]
+struct Transcript {
+    incoming: Vec<String>,
+    received: Vec<String>,
+}
+
+/// Messages go out before the client sends anything, then both ways at
+/// once, then `outgoing` closes while the client still has lines to send.
+async fn transcript(pattern: Pattern) -> Transcript {
+    let mut conn = connect(pattern).await;
+    let mut received = vec![];
+
+    conn.outgoing.send("welcome".to_string()).await.unwrap();
+    received.push(next_line(&mut conn.client).await.unwrap());
+
+    for i in 0..3 {
+        conn.client.send(format!("ping {}", i)).await.unwrap();
+        let line = conn.incoming.recv().await.unwrap();
+        conn.outgoing
+            .send(format!("you said: {}", line))
+            .await
+            .unwrap();
+        received.push(next_line(&mut conn.client).await.unwrap());
+    }
+
+    drop(conn.outgoing);
+    assert_eq!(next_line(&mut conn.client).await, None, "{:?}", pattern);
+
+    conn.client.send("after").await.unwrap();
+    conn.client.get_mut().shutdown().await.unwrap();
+
+    let mut incoming = vec![];
+    while let Some(line) = conn.incoming.recv().await {
+        incoming.push(line);
+    }
+    conn.task.await.unwrap().unwrap();
+
+    Transcript { incoming, received }
+}
+
+#[tokio::test]
+async fn every_pattern_gives_the_same_transcript() {
+    let expected = Transcript {
+        incoming: vec!["after".to_string()],
+        received: vec![
+            "welcome".to_string(),
+            "you said: ping 0".to_string(),
+            "you said: ping 1".to_string(),
+            "you said: ping 2".to_string(),
+        ],
+    };
+
+    for pattern in Pattern::ALL.iter() {
+        assert_eq!(transcript(*pattern).await, expected, "{:?}", pattern);
+    }
+}
+
+#[tokio::test]
+async fn writes_while_the_client_is_silent() {
+    for pattern in Pattern::ALL.iter() {
+        let mut conn = connect(*pattern).await;
+
+        for i in 0..5 {
+            conn.outgoing.send(format!("tick {}", i)).await.unwrap();
+        }
+        for i in 0..5 {
+            let line = next_line(&mut conn.client).await;
+            assert_eq!(line, Some(format!("tick {}", i)), "{:?}", pattern);
+        }
+
+        drop(conn.client);
+        conn.task.await.unwrap().unwrap();
+        assert_eq!(conn.incoming.recv().await, None);
+    }
+}
+
+#[tokio::test]
+async fn ends_when_incoming_is_closed() {
+    for pattern in Pattern::ALL.iter() {
+        let mut conn = connect(*pattern).await;
+        drop(conn.incoming);
+
+        conn.client.send("nobody listens").await.unwrap();
+        conn.task.await.unwrap().unwrap();
+        assert_eq!(next_line(&mut conn.client).await, None, "{:?}", pattern);
+    }
+}
+
+#[tokio::test]
+async fn a_line_too_long_is_an_error() {
+    for pattern in Pattern::ALL.iter() {
+        let mut conn = connect(*pattern).await;
+
+        let line = "x".repeat(MAX_LINE + 1);
+        conn.client.send(line).await.unwrap();
+
+        let err = conn.task.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", pattern);
+    }
+}
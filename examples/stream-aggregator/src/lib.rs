@@ -0,0 +1,98 @@
+//! Averaging the readings of sensors that come and go.
+//!
+//! Each sensor is a stream of readings. The aggregator merges them with a
+//! `StreamMap`, which yields each reading along with the name of its
+//! sensor, keeps a rolling average per sensor in a `window::Window`, and
+//! publishes the averages of all of them to a `watch` channel every period.
+//! Any number of consumers can then look at the latest averages, without
+//! holding the aggregator up.
+//!
+//! Sensors are added and removed while it runs, through a control channel.
+//! A sensor whose stream ends is removed too: each stream is wrapped in
+//! `StreamNotifyClose`, so its end comes out of the map as a `None` for it.
+//! A removed sensor is gone from the next snapshot, whatever it read before.
+
+pub mod sensor;
+pub mod window;
+
+use sensor::Sensor;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{self, Instant};
+use tokio_stream::{StreamExt, StreamMap, StreamNotifyClose};
+use window::Window;
+
+/// A change to the set of sensors.
+#[derive(Debug)]
+pub enum Control {
+    /// Add a sensor, replacing any of the same name.
+    Add(String, Sensor),
+
+    /// Remove the sensor of this name.
+    Remove(String),
+}
+
+/// The average of each sensor over the window, by name. `None` for a
+/// sensor without any readings in it.
+pub type Snapshot = BTreeMap<String, Option<f64>>;
+
+/// Average the readings of the sensors over `window`, publishing a
+/// `Snapshot` to `snapshots` every `period`, the first after one period.
+///
+/// Returns when every receiver of `snapshots` is dropped, or once `control`
+/// is closed and every sensor has ended, after a last snapshot.
+pub async fn run(
+    window: Duration,
+    period: Duration,
+    mut control: mpsc::Receiver<Control>,
+    snapshots: watch::Sender<Snapshot>,
+) {
+    let mut sensors = StreamMap::new();
+    let mut windows: HashMap<String, Window> = HashMap::new();
+    let mut ticks = time::interval_at(Instant::now() + period, period);
+    let mut controlled = true;
+
+    loop {
+        tokio::select! {
+            // An empty map yields `None`, which disables this branch until
+            // the next time around.
+            Some((name, reading)) = sensors.next() => match reading {
+                Some(value) => {
+                    if let Some(window) = windows.get_mut(&name) {
+                        window.push(Instant::now(), value);
+                    }
+                }
+                None => {
+                    // Ended; the map has already forgotten it.
+                    windows.remove(&name);
+                }
+            },
+            cmd = control.recv(), if controlled => match cmd {
+                Some(Control::Add(name, sensor)) => {
+                    sensors.insert(name.clone(), StreamNotifyClose::new(sensor));
+                    windows.insert(name, Window::new(window));
+                }
+                Some(Control::Remove(name)) => {
+                    sensors.remove(&name);
+                    windows.remove(&name);
+                }
+                None => controlled = false,
+            },
+            _ = ticks.tick() => {
+                let now = Instant::now();
+                let snapshot = windows
+                    .iter_mut()
+                    .map(|(name, window)| (name.clone(), window.average(now)))
+                    .collect();
+
+                if snapshots.send(snapshot).is_err() {
+                    return;
+                }
+                if !controlled && sensors.is_empty() {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+use stream_aggregator::sensor;
+use stream_aggregator::Control;
+use tokio::sync::{mpsc, watch};
+use tokio::time;
+
+/// How far back the averages go.
+const WINDOW: Duration = Duration::from_secs(5);
+
+/// How often they are published.
+const PERIOD: Duration = Duration::from_secs(1);
+
+/// A reading around `base`, give or take `spread`, from the `i`th.
+fn wobble(base: f64, spread: f64, i: u32) -> f64 {
+    base + spread * (f64::from(i) * 0.7).sin()
+}
+
+#[tokio::main]
+async fn main() {
+    let (control, control_rx) = mpsc::channel(8);
+    let (snapshots_tx, mut snapshots) = watch::channel(Default::default());
+    let aggregator = tokio::spawn(stream_aggregator::run(
+        WINDOW,
+        PERIOD,
+        control_rx,
+        snapshots_tx,
+    ));
+
+    // Two sensors from the start, one reading ten times a second and one
+    // every second, which stops after eight readings.
+    let fast = sensor::simulate(
+        Duration::from_millis(100),
+        (0..).map(|i| wobble(20.0, 2.0, i)),
+    );
+    let slow = sensor::simulate(
+        Duration::from_secs(1),
+        (0..8).map(|i| wobble(55.0, 10.0, i)),
+    );
+    control
+        .send(Control::Add("fast".into(), fast))
+        .await
+        .unwrap();
+    control
+        .send(Control::Add("slow".into(), slow))
+        .await
+        .unwrap();
+
+    // A third comes along later, and is taken away again.
+    let control_late = control.clone();
+    tokio::spawn(async move {
+        time::sleep(Duration::from_secs(3)).await;
+        let late = sensor::simulate(
+            Duration::from_millis(300),
+            (0..).map(|i| wobble(-5.0, 1.0, i)),
+        );
+        let _ = control_late.send(Control::Add("late".into(), late)).await;
+
+        time::sleep(Duration::from_secs(6)).await;
+        let _ = control_late.send(Control::Remove("late".into())).await;
+    });
+
+    for _ in 0..12 {
+        if snapshots.changed().await.is_err() {
+            break;
+        }
+        println!("{:.2?}", *snapshots.borrow_and_update());
+    }
+
+    drop(snapshots);
+    aggregator.await.unwrap();
+}
@@ -0,0 +1,39 @@
+//! Sensors, as streams of readings.
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The readings of a sensor. The sensor is gone when the stream ends.
+pub type Sensor = ReceiverStream<f64>;
+
+/// A sensor, and the sender feeding it readings. Dropping the sender ends
+/// the sensor.
+pub fn channel() -> (mpsc::Sender<f64>, Sensor) {
+    let (tx, rx) = mpsc::channel(16);
+    (tx, ReceiverStream::new(rx))
+}
+
+/// A sensor reading `values` in turn, one every `period`, from a task of
+/// its own. It ends after the last value, or when the sensor is dropped.
+pub fn simulate<I>(period: Duration, values: I) -> Sensor
+where
+    I: IntoIterator<Item = f64>,
+    I::IntoIter: Send + 'static,
+{
+    let (tx, sensor) = channel();
+    let mut values = values.into_iter();
+
+    tokio::spawn(async move {
+        let mut ticks = time::interval(period);
+        for value in &mut values {
+            ticks.tick().await;
+            if tx.send(value).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    sensor
+}
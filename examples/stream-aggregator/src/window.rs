@@ -0,0 +1,50 @@
+//! The readings of one sensor over the last so long.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug)]
+pub struct Window {
+    length: Duration,
+
+    /// The readings and when they came, the oldest first.
+    readings: VecDeque<(Instant, f64)>,
+    sum: f64,
+}
+
+impl Window {
+    /// A window over the readings of the last `length`.
+    pub fn new(length: Duration) -> Window {
+        Window {
+            length,
+            readings: VecDeque::new(),
+            sum: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, at: Instant, value: f64) {
+        self.readings.push_back((at, value));
+        self.sum += value;
+    }
+
+    /// The average of the readings of the `length` up to `now`, or `None`
+    /// without any. Older readings are forgotten.
+    pub fn average(&mut self, now: Instant) -> Option<f64> {
+        while let Some(&(at, value)) = self.readings.front() {
+            if now.saturating_duration_since(at) < self.length {
+                break;
+            }
+            self.readings.pop_front();
+            self.sum -= value;
+        }
+
+        if self.readings.is_empty() {
+            // Rather than what rounding left over from the sum.
+            self.sum = 0.0;
+            None
+        } else {
+            Some(self.sum / self.readings.len() as f64)
+        }
+    }
+}
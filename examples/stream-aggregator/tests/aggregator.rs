@@ -0,0 +1,200 @@
+use std::time::Duration;
+use stream_aggregator::sensor;
+use stream_aggregator::{Control, Snapshot};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+const WINDOW: Duration = Duration::from_secs(2);
+const PERIOD: Duration = Duration::from_secs(1);
+
+struct Aggregator {
+    start: Instant,
+    control: mpsc::Sender<Control>,
+    snapshots: watch::Receiver<Snapshot>,
+    task: JoinHandle<()>,
+}
+
+fn aggregator() -> Aggregator {
+    let (control, control_rx) = mpsc::channel(8);
+    let (snapshots_tx, snapshots) = watch::channel(Snapshot::new());
+    let start = Instant::now();
+    let task = tokio::spawn(stream_aggregator::run(
+        WINDOW,
+        PERIOD,
+        control_rx,
+        snapshots_tx,
+    ));
+
+    Aggregator {
+        start,
+        control,
+        snapshots,
+        task,
+    }
+}
+
+impl Aggregator {
+    async fn add(&self, name: &str) -> mpsc::Sender<f64> {
+        let (tx, sensor) = sensor::channel();
+        self.control
+            .send(Control::Add(name.to_string(), sensor))
+            .await
+            .unwrap();
+        tx
+    }
+
+    async fn at(&self, millis: u64) {
+        time::sleep_until(self.start + Duration::from_millis(millis)).await;
+    }
+
+    /// The next snapshot published.
+    async fn next(&mut self) -> Snapshot {
+        self.snapshots.changed().await.unwrap();
+        self.snapshots.borrow_and_update().clone()
+    }
+}
+
+fn snapshot(averages: &[(&str, Option<f64>)]) -> Snapshot {
+    averages
+        .iter()
+        .map(|(name, average)| (name.to_string(), *average))
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn averages_over_the_window() {
+    let mut agg = aggregator();
+    let a = agg.add("a").await;
+    let b = agg.add("b").await;
+
+    agg.at(250).await;
+    a.send(1.0).await.unwrap();
+    b.send(10.0).await.unwrap();
+    agg.at(500).await;
+    a.send(3.0).await.unwrap();
+
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("a", Some(2.0)), ("b", Some(10.0))])
+    );
+    assert_eq!(Instant::now(), agg.start + PERIOD);
+
+    agg.at(1500).await;
+    a.send(5.0).await.unwrap();
+
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("a", Some(3.0)), ("b", Some(10.0))])
+    );
+
+    // The readings before 1s are out of the window.
+    assert_eq!(agg.next().await, snapshot(&[("a", Some(5.0)), ("b", None)]));
+    assert_eq!(agg.next().await, snapshot(&[("a", None), ("b", None)]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_sensor_ending_mid_window_is_gone_from_the_next_snapshot() {
+    let mut agg = aggregator();
+    let a = agg.add("a").await;
+    let b = agg.add("b").await;
+
+    agg.at(250).await;
+    a.send(4.0).await.unwrap();
+    b.send(8.0).await.unwrap();
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("a", Some(4.0)), ("b", Some(8.0))])
+    );
+
+    // `b` still has a reading in the window when it ends.
+    agg.at(1500).await;
+    drop(b);
+
+    assert_eq!(agg.next().await, snapshot(&[("a", Some(4.0))]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn sensors_are_added_and_removed_at_runtime() {
+    let mut agg = aggregator();
+    let a = agg.add("a").await;
+
+    agg.at(250).await;
+    a.send(1.0).await.unwrap();
+    assert_eq!(agg.next().await, snapshot(&[("a", Some(1.0))]));
+
+    agg.at(1250).await;
+    let c = agg.add("c").await;
+    c.send(7.0).await.unwrap();
+    a.send(2.0).await.unwrap();
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("a", Some(1.5)), ("c", Some(7.0))])
+    );
+
+    agg.at(2250).await;
+    agg.control.send(Control::Remove("a".into())).await.unwrap();
+    assert_eq!(agg.next().await, snapshot(&[("c", Some(7.0))]));
+
+    // Readings of a removed sensor go nowhere.
+    assert!(a.send(3.0).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn stops_once_control_is_closed_and_the_sensors_ended() {
+    let mut agg = aggregator();
+    let a = agg.add("a").await;
+    drop(agg.control);
+
+    // Still running, for the sensor left.
+    time::sleep_until(agg.start + Duration::from_millis(250)).await;
+    a.send(1.0).await.unwrap();
+    agg.snapshots.changed().await.unwrap();
+    assert_eq!(
+        *agg.snapshots.borrow_and_update(),
+        snapshot(&[("a", Some(1.0))])
+    );
+
+    drop(a);
+    agg.task.await.unwrap();
+    assert_eq!(*agg.snapshots.borrow_and_update(), Snapshot::new());
+}
+
+#[tokio::test(start_paused = true)]
+async fn stops_when_nobody_watches() {
+    let agg = aggregator();
+    let _a = agg.add("a").await;
+
+    drop(agg.snapshots);
+    agg.task.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn simulated_sensors_read_at_their_own_rates() {
+    let mut agg = aggregator();
+
+    agg.at(100).await;
+    let fast = sensor::simulate(Duration::from_millis(100), vec![2.0; 100]);
+    let slow = sensor::simulate(Duration::from_millis(700), vec![1.0, 3.0, 5.0, 7.0]);
+    agg.control
+        .send(Control::Add("fast".into(), fast))
+        .await
+        .unwrap();
+    agg.control
+        .send(Control::Add("slow".into(), slow))
+        .await
+        .unwrap();
+
+    // `slow` reads at 100 and 800ms...
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("fast", Some(2.0)), ("slow", Some(2.0))])
+    );
+    // ...then at 1500ms...
+    assert_eq!(
+        agg.next().await,
+        snapshot(&[("fast", Some(2.0)), ("slow", Some(3.0))])
+    );
+    // ...and is over after its last, at 2200ms.
+    assert_eq!(agg.next().await, snapshot(&[("fast", Some(2.0))]));
+}
@@ -0,0 +1,52 @@
+//! Racing CPU-bound work against a timeout.
+//!
+//! `is_prime` runs on the blocking thread pool, as a loop this long would
+//! keep a worker thread of the runtime from running other tasks. The timeout
+//! is on the `JoinHandle`: when it fires, the answer is no longer waited for,
+//! but the computation runs to its end anyway, as blocking code can't be
+//! cancelled.
+
+use std::time::Duration;
+use tokio::task;
+use tokio::time;
+
+/// The millionth prime.
+pub const BIG_PRIME: u64 = 15_485_863;
+
+/// How `check` ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Prime,
+    NotPrime,
+    TimedOut,
+}
+
+/// Whether `num` is prime.
+///
+/// Every divisor is tried, not only the ones up to the square root, to have
+/// something slow enough to time out.
+pub fn is_prime(num: u64) -> bool {
+    if num < 2 {
+        return false;
+    }
+    if num < 4 {
+        return true;
+    }
+    if num.is_multiple_of(2) {
+        return false;
+    }
+
+    (3..num).step_by(2).all(|i| !num.is_multiple_of(i))
+}
+
+/// Check whether `num` is prime, giving up after `limit`.
+pub async fn check(num: u64, limit: Duration) -> Outcome {
+    let computation = task::spawn_blocking(move || is_prime(num));
+
+    match time::timeout(limit, computation).await {
+        Ok(Ok(true)) => Outcome::Prime,
+        Ok(Ok(false)) => Outcome::NotPrime,
+        Ok(Err(err)) => panic!("is_prime panicked: {}", err),
+        Err(_elapsed) => Outcome::TimedOut,
+    }
+}
@@ -0,0 +1,11 @@
+use std::time::Duration;
+use timeout::{check, Outcome, BIG_PRIME};
+
+#[tokio::main]
+async fn main() {
+    match check(BIG_PRIME, Duration::from_secs(1)).await {
+        Outcome::Prime => println!("Prime"),
+        Outcome::NotPrime => println!("Not prime"),
+        Outcome::TimedOut => println!("Timed out"),
+    }
+}
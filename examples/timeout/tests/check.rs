@@ -0,0 +1,16 @@
+use std::time::Duration;
+use timeout::{check, Outcome, BIG_PRIME};
+
+#[tokio::test]
+async fn answers_in_time() {
+    assert_eq!(check(97, Duration::from_secs(10)).await, Outcome::Prime);
+    assert_eq!(check(91, Duration::from_secs(10)).await, Outcome::NotPrime);
+}
+
+#[tokio::test]
+async fn times_out() {
+    assert_eq!(
+        check(BIG_PRIME, Duration::from_micros(1)).await,
+        Outcome::TimedOut
+    );
+}
@@ -0,0 +1,22 @@
+use timeout::{is_prime, BIG_PRIME};
+
+#[test]
+fn primes() {
+    for &num in [2, 3, 5, 7, 11, 13, 97, 7919, BIG_PRIME].iter() {
+        assert!(is_prime(num), "{}", num);
+    }
+}
+
+#[test]
+fn composites() {
+    // 15_485_869 is 7 * 2_212_267.
+    for &num in [4, 6, 9, 15, 21, 25, 49, 7917, 15_485_869].iter() {
+        assert!(!is_prime(num), "{}", num);
+    }
+}
+
+#[test]
+fn neither() {
+    assert!(!is_prime(0));
+    assert!(!is_prime(1));
+}
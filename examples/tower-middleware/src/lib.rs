@@ -0,0 +1,43 @@
+//! The echo service of `line-echo`, with middleware around it.
+//!
+//! Middleware is a service wrapping another one: it gets each request
+//! first, and can wait, refuse, or change it before passing it on, and do
+//! the same with the response. tower comes with some; `stack` puts the
+//! service in three layers of it, from the outside in:
+//!
+//! * `log::LogLayer` records how long each request took. It is written by
+//!   hand, to show how.
+//! * `ConcurrencyLimit` lets only so many requests be in flight at once.
+//!   Its clones share the limit, so with a clone of the stack for each
+//!   connection, it holds for all of them together.
+//! * `Timeout` fails the requests the service takes too long for.
+//!
+//! `ServiceBuilder` adds layers in the order they are listed, the first one
+//! outermost.
+
+pub mod log;
+
+use log::{LogLayer, Logged};
+use std::time::Duration;
+use tower::limit::ConcurrencyLimit;
+use tower::timeout::Timeout;
+use tower::ServiceBuilder;
+
+/// `S` in the layers of `stack`.
+pub type Stack<S> = Logged<ConcurrencyLimit<Timeout<S>>>;
+
+/// How long a request may take before it fails.
+pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How many requests may be in flight at once.
+pub const MAX_IN_FLIGHT: usize = 64;
+
+/// Wrap `service` in middleware: logging into `log`, at most
+/// `max_in_flight` requests at once, and each failing after `timeout`.
+pub fn stack<S>(service: S, log: LogLayer, max_in_flight: usize, timeout: Duration) -> Stack<S> {
+    ServiceBuilder::new()
+        .layer(log)
+        .concurrency_limit(max_in_flight)
+        .timeout(timeout)
+        .service(service)
+}
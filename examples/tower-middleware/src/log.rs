@@ -0,0 +1,129 @@
+//! A middleware written by hand, recording how long requests take.
+//!
+//! Middleware takes two types: the `Layer`, which wraps a service, and the
+//! service it wraps it in. `Logged::call` starts a clock, passes the request
+//! on, and wraps the inner service's future in a `ResponseFuture`, which
+//! records an `Entry` when that future completes.
+
+use pin_project_lite::pin_project;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tower::{Layer, Service};
+
+/// A request that got an answer, or failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub request: String,
+
+    /// From the call to the response. Time spent waiting for the service to
+    /// be ready is not included, as that is before the call.
+    pub latency: Duration,
+
+    /// Why the request failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Wraps services in `Logged`, sending the entries of all of them to the
+/// same channel.
+#[derive(Debug, Clone)]
+pub struct LogLayer {
+    entries: mpsc::UnboundedSender<Entry>,
+}
+
+/// A layer logging into a channel, and the receiver of the entries.
+///
+/// The channel is unbounded so that logging never holds up a request; once
+/// the receiver is dropped, entries are discarded.
+pub fn channel() -> (LogLayer, mpsc::UnboundedReceiver<Entry>) {
+    let (entries, rx) = mpsc::unbounded_channel();
+    (LogLayer { entries }, rx)
+}
+
+impl<S> Layer<S> for LogLayer {
+    type Service = Logged<S>;
+
+    fn layer(&self, inner: S) -> Logged<S> {
+        Logged {
+            inner,
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+/// A service logging the requests to `S`.
+#[derive(Debug, Clone)]
+pub struct Logged<S> {
+    inner: S,
+    entries: mpsc::UnboundedSender<Entry>,
+}
+
+impl<S> Service<String> for Logged<S>
+where
+    S: Service<String>,
+    S::Error: fmt::Display,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        // Whether the inner service is ready is for it to say.
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: String) -> Self::Future {
+        let start = Instant::now();
+        let logged = request.clone();
+
+        ResponseFuture {
+            inner: self.inner.call(request),
+            request: Some(logged),
+            start,
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+pin_project! {
+    /// The future of a response from `Logged`.
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        // Taken when the entry is sent.
+        request: Option<String>,
+        start: Instant,
+        entries: mpsc::UnboundedSender<Entry>,
+    }
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: fmt::Display,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T, E>> {
+        let this = self.project();
+
+        let res = match this.inner.poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        if let Some(request) = this.request.take() {
+            let _ = this.entries.send(Entry {
+                request,
+                latency: this.start.elapsed(),
+                error: res.as_ref().err().map(ToString::to_string),
+            });
+        }
+
+        Poll::Ready(res)
+    }
+}
@@ -0,0 +1,33 @@
+use line_echo::Echo;
+use std::env;
+use tokio::net::TcpListener;
+use tower::make::Shared;
+use tower_middleware::{log, MAX_IN_FLIGHT, TIMEOUT};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    // Try it with `nc 127.0.0.1 12345`.
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:12345".to_string());
+    let listener = TcpListener::bind(&addr).await?;
+    println!("listening on {}", addr);
+
+    let (layer, mut entries) = log::channel();
+    tokio::spawn(async move {
+        while let Some(entry) = entries.recv().await {
+            match entry.error {
+                Some(err) => println!(
+                    "{:?} failed after {:?}: {}",
+                    entry.request, entry.latency, err
+                ),
+                None => println!("{:?} took {:?}", entry.request, entry.latency),
+            }
+        }
+    });
+
+    // Each connection gets a clone of the same stack, so they share its
+    // concurrency limit.
+    let service = tower_middleware::stack(Echo, layer, MAX_IN_FLIGHT, TIMEOUT);
+    line_echo::serve(listener, Shared::new(service)).await
+}
@@ -0,0 +1,97 @@
+use futures::future::{self, BoxFuture};
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time;
+use tower::timeout::error::Elapsed;
+use tower::Service;
+use tower_middleware::log::{self, Entry};
+use tower_middleware::{stack, Stack};
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Echoes lines after waiting as many milliseconds as they say.
+#[derive(Clone)]
+struct SlowEcho;
+
+impl Service<String> for SlowEcho {
+    type Response = String;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<String, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, line: String) -> Self::Future {
+        let ms = line.parse().unwrap();
+        Box::pin(async move {
+            time::sleep(Duration::from_millis(ms)).await;
+            Ok(line)
+        })
+    }
+}
+
+/// Wait for `service` to be ready, then call it with `line`.
+async fn call(service: &mut Stack<SlowEcho>, line: &str) -> Result<String, tower::BoxError> {
+    future::poll_fn(|cx| service.poll_ready(cx)).await?;
+    service.call(line.to_string()).await
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_slow_requests() {
+    let (layer, _entries) = log::channel();
+    let mut service = stack(SlowEcho, layer, 8, TIMEOUT);
+
+    assert_eq!(call(&mut service, "500").await.unwrap(), "500");
+
+    let err = call(&mut service, "1500").await.unwrap_err();
+    assert!(err.is::<Elapsed>(), "{}", err);
+}
+
+#[tokio::test(start_paused = true)]
+async fn holds_back_requests_over_the_limit() {
+    let (layer, _entries) = log::channel();
+    let mut a = stack(SlowEcho, layer, 1, TIMEOUT);
+    // Clones share the limit.
+    let mut b = a.clone();
+
+    future::poll_fn(|cx| a.poll_ready(cx)).await.unwrap();
+    let response = a.call("100".to_string());
+
+    let ready = future::poll_fn(|cx| Poll::Ready(b.poll_ready(cx))).await;
+    assert!(ready.is_pending());
+
+    assert_eq!(response.await.unwrap(), "100");
+
+    let ready = future::poll_fn(|cx| Poll::Ready(b.poll_ready(cx))).await;
+    assert!(matches!(ready, Poll::Ready(Ok(()))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn logs_requests() {
+    let (layer, mut entries) = log::channel();
+    let mut service = stack(SlowEcho, layer, 8, TIMEOUT);
+
+    for line in ["200", "2000"].iter() {
+        let _ = call(&mut service, line).await;
+    }
+
+    assert_eq!(
+        entries.recv().await.unwrap(),
+        Entry {
+            request: "200".to_string(),
+            latency: Duration::from_millis(200),
+            error: None,
+        }
+    );
+    assert_eq!(
+        entries.recv().await.unwrap(),
+        Entry {
+            request: "2000".to_string(),
+            latency: TIMEOUT,
+            error: Some(Elapsed::new().to_string()),
+        }
+    );
+    assert!(entries.try_recv().is_err());
+}
@@ -0,0 +1,27 @@
+use std::env;
+use std::time::Duration;
+use udp_echo::client;
+
+/// `udp-echo-client 100` sends 100 datagrams.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let count = match env::args().nth(1) {
+        Some(count) => count.parse()?,
+        None => 10,
+    };
+
+    let summary = client::ping(
+        "127.0.0.1:12345".parse()?,
+        count,
+        Duration::from_millis(500),
+    )
+    .await?;
+    println!(
+        "{} sent, {} echoed, {} lost",
+        summary.sent,
+        summary.echoed,
+        summary.lost()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use std::env;
+use tokio::net::UdpSocket;
+use udp_echo::server;
+
+/// `udp-echo-server framed` uses `UdpFramed`.
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let socket = UdpSocket::bind("127.0.0.1:12345").await?;
+    println!("listening on 127.0.0.1:12345");
+
+    if env::args().nth(1).as_deref() == Some("framed") {
+        server::serve_framed(socket).await
+    } else {
+        server::serve(socket).await
+    }
+}
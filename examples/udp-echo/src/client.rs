@@ -0,0 +1,60 @@
+//! A client sending datagrams to an echo server, and counting the echoes.
+
+use crate::MAX_DATAGRAM;
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::{self, Instant};
+
+/// How many datagrams were sent, and how many came back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub sent: usize,
+    pub echoed: usize,
+}
+
+impl Summary {
+    /// The datagrams that didn't come back, either way.
+    pub fn lost(&self) -> usize {
+        self.sent - self.echoed
+    }
+}
+
+/// The payload of the datagram number `i`, telling it apart from the
+/// others.
+pub fn payload(i: usize) -> Vec<u8> {
+    format!("datagram {}", i).into_bytes()
+}
+
+/// Send `count` datagrams to `server` one after the other, each time waiting
+/// at most `wait` for the echo.
+pub async fn ping(server: SocketAddr, count: usize, wait: Duration) -> io::Result<Summary> {
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    // Only the server's datagrams are received from now on.
+    socket.connect(server).await?;
+
+    let mut buf = vec![0; MAX_DATAGRAM];
+    let mut echoed = 0;
+
+    for i in 0..count {
+        let payload = payload(i);
+        socket.send(&payload).await?;
+
+        // The echo of an earlier datagram may come late, after we gave up
+        // on it. It doesn't count, and doesn't extend the wait either.
+        let deadline = Instant::now() + wait;
+        while let Ok(res) = time::timeout_at(deadline, socket.recv(&mut buf)).await {
+            if buf[..res?] == payload[..] {
+                echoed += 1;
+                break;
+            }
+        }
+    }
+
+    Ok(Summary {
+        sent: count,
+        echoed,
+    })
+}
@@ -0,0 +1,15 @@
+//! Datagrams echoed back to where they came from.
+//!
+//! `server` has two versions of the server: one calling `recv_from` and
+//! `send_to` on the `UdpSocket` itself, and one going through `UdpFramed`,
+//! which makes the socket a stream of datagrams and a sink for them. They
+//! behave the same.
+//!
+//! UDP doesn't promise that a datagram arrives, so the `client` waits for
+//! each echo only so long, and counts the ones that never came.
+
+pub mod client;
+pub mod server;
+
+/// The largest payload a UDP datagram can carry over IPv4.
+pub const MAX_DATAGRAM: usize = 65_507;
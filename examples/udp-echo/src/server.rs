@@ -0,0 +1,35 @@
+//! The echo servers.
+
+use crate::MAX_DATAGRAM;
+
+use futures::{SinkExt, StreamExt};
+use std::io;
+use tokio::net::UdpSocket;
+use tokio_util::codec::BytesCodec;
+use tokio_util::udp::UdpFramed;
+
+/// Send every datagram `socket` receives back to its sender, until the
+/// socket fails.
+pub async fn serve(socket: UdpSocket) -> io::Result<()> {
+    // Each datagram is read whole: a buffer too short for it would have the
+    // rest thrown away.
+    let mut buf = vec![0; MAX_DATAGRAM];
+
+    loop {
+        let (len, addr) = socket.recv_from(&mut buf).await?;
+        socket.send_to(&buf[..len], addr).await?;
+    }
+}
+
+/// Like `serve`, with the datagrams read and written through `UdpFramed`.
+pub async fn serve_framed(socket: UdpSocket) -> io::Result<()> {
+    // `BytesCodec` passes each datagram on as it is.
+    let mut datagrams = UdpFramed::new(socket, BytesCodec::new());
+
+    while let Some(datagram) = datagrams.next().await {
+        let (data, addr) = datagram?;
+        datagrams.send((data.freeze(), addr)).await?;
+    }
+
+    Ok(())
+}
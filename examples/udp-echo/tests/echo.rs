@@ -0,0 +1,94 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time;
+use udp_echo::client::{self, payload, Summary};
+use udp_echo::server::{serve, serve_framed};
+
+/// How long the tests wait for an echo that should come.
+const WAIT: Duration = Duration::from_secs(5);
+
+async fn server<F, S>(serve: S) -> SocketAddr
+where
+    S: FnOnce(UdpSocket) -> F,
+    F: std::future::Future<Output = io::Result<()>> + Send + 'static,
+{
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(serve(socket));
+    addr
+}
+
+/// Send 50 datagrams at once, and check every echo.
+async fn echoes_every_datagram(addr: SocketAddr) {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    for i in 0..50 {
+        socket.send_to(&payload(i), addr).await.unwrap();
+    }
+
+    let mut buf = [0; 64];
+    let mut echoes = vec![];
+    for _ in 0..50 {
+        let (len, from) = time::timeout(WAIT, socket.recv_from(&mut buf))
+            .await
+            .expect("an echo is missing")
+            .unwrap();
+        assert_eq!(from, addr);
+        echoes.push(buf[..len].to_vec());
+    }
+
+    // Nothing on localhost reorders datagrams, but nothing promises it either.
+    echoes.sort();
+    let mut expected: Vec<_> = (0..50).map(payload).collect();
+    expected.sort();
+    assert_eq!(echoes, expected);
+}
+
+#[tokio::test]
+async fn echoes_datagrams() {
+    echoes_every_datagram(server(serve).await).await;
+}
+
+#[tokio::test]
+async fn echoes_datagrams_framed() {
+    echoes_every_datagram(server(serve_framed).await).await;
+}
+
+#[tokio::test]
+async fn counts_echoes() {
+    for addr in [server(serve).await, server(serve_framed).await].iter() {
+        let summary = client::ping(*addr, 50, WAIT).await.unwrap();
+        assert_eq!(
+            summary,
+            Summary {
+                sent: 50,
+                echoed: 50
+            }
+        );
+        assert_eq!(summary.lost(), 0);
+    }
+}
+
+#[tokio::test]
+async fn counts_lost_datagrams() {
+    // Echoes every other datagram.
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut buf = [0; 64];
+        for i in 0.. {
+            let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+            if i % 2 == 0 {
+                socket.send_to(&buf[..len], from).await.unwrap();
+            }
+        }
+    });
+
+    let summary = client::ping(addr, 10, Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert_eq!(summary.echoed, 5);
+    assert_eq!(summary.lost(), 5);
+}
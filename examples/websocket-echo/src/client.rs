@@ -0,0 +1,40 @@
+//! A client sending messages and checking they come back.
+
+use crate::is_data;
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+/// Connect to `url`, send `messages` one at a time, waiting for the echo
+/// of each, then close the connection.
+///
+/// Returns the echoes, in order.
+pub async fn exchange(url: &str, messages: Vec<Message>) -> Result<Vec<Message>, Error> {
+    let (mut ws, _response) = tokio_tungstenite::connect_async(url).await?;
+    let mut echoes = vec![];
+
+    for msg in messages {
+        ws.send(msg).await?;
+
+        loop {
+            match ws.next().await {
+                Some(msg) => {
+                    let msg = msg?;
+                    if is_data(&msg) {
+                        echoes.push(msg);
+                        break;
+                    }
+                }
+                None => return Err(Error::ConnectionClosed),
+            }
+        }
+    }
+
+    // Send a close frame, then read until the server's close frame ends the
+    // stream, so that the handshake is complete.
+    ws.close(None).await?;
+    while let Some(msg) = ws.next().await {
+        msg?;
+    }
+
+    Ok(echoes)
+}
@@ -0,0 +1,49 @@
+//! A WebSocket echo server, with tokio-tungstenite.
+//!
+//! A WebSocket connection starts as an HTTP request asking to upgrade it;
+//! `accept_async` answers that request, and returns the connection as a
+//! stream of messages and a sink for them. The server splits it into the
+//! two, and forwards every text and binary message from one to the other.
+//!
+//! The control messages are taken care of by tungstenite: it answers a ping
+//! with a pong, and a close frame with one of its own, after which the
+//! stream ends. They are still yielded by the stream, so the server skips
+//! them rather than echoing them.
+
+pub mod client;
+
+use futures::{future, StreamExt, TryStreamExt};
+use std::io;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+/// Accept connections on `listener` until accepting fails, echoing the
+/// messages of each in a task of its own.
+pub async fn serve(listener: TcpListener) -> io::Result<()> {
+    loop {
+        let (socket, addr) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            if let Err(err) = echo(socket).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
+
+/// Upgrade `socket` to a WebSocket connection, and send back every text or
+/// binary message until the client closes it.
+pub async fn echo(socket: TcpStream) -> Result<(), Error> {
+    let ws = tokio_tungstenite::accept_async(socket).await?;
+    let (sink, stream) = ws.split();
+
+    stream
+        .try_filter(|msg| future::ready(is_data(msg)))
+        .forward(sink)
+        .await
+}
+
+/// Whether `msg` is one to echo, rather than part of the protocol.
+pub fn is_data(msg: &Message) -> bool {
+    msg.is_text() || msg.is_binary()
+}
@@ -0,0 +1,31 @@
+use std::env;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use websocket_echo::client;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // With an address, serve on it; try it from a browser console with
+    // `new WebSocket("ws://127.0.0.1:12345")`. Without one, serve on a free
+    // port and talk to it.
+    let addr = env::args().nth(1);
+    let listener = TcpListener::bind(addr.as_deref().unwrap_or("127.0.0.1:0")).await?;
+    let local = listener.local_addr()?;
+
+    if addr.is_some() {
+        println!("listening on {}", local);
+        return Ok(websocket_echo::serve(listener).await?);
+    }
+    tokio::spawn(websocket_echo::serve(listener));
+
+    let messages = vec![
+        Message::Text("hello".to_string()),
+        Message::Binary(vec![1, 2, 3]),
+    ];
+    let url = format!("ws://{}", local);
+    for echo in client::exchange(&url, messages).await? {
+        println!("{:?}", echo);
+    }
+
+    Ok(())
+}
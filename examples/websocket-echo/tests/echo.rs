@@ -0,0 +1,80 @@
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use websocket_echo::client::exchange;
+
+/// The URL of a server on a free port.
+async fn server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(websocket_echo::serve(listener));
+    format!("ws://{}", addr)
+}
+
+#[tokio::test]
+async fn echoes_text_and_binary() {
+    let messages = vec![
+        Message::Text("hello".to_string()),
+        Message::Binary(vec![0, 1, 2]),
+        Message::Text("".to_string()),
+    ];
+
+    let echoes = exchange(&server().await, messages.clone()).await.unwrap();
+    assert_eq!(echoes, messages);
+}
+
+#[tokio::test]
+async fn echoes_large_messages() {
+    let large: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let echoes = exchange(&server().await, vec![Message::Binary(large.clone())])
+        .await
+        .unwrap();
+    assert_eq!(echoes, [Message::Binary(large)]);
+}
+
+#[tokio::test]
+async fn answers_pings() {
+    let (mut ws, _) = tokio_tungstenite::connect_async(server().await)
+        .await
+        .unwrap();
+
+    ws.send(Message::Ping(b"are you there".to_vec()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        ws.next().await.unwrap().unwrap(),
+        Message::Pong(b"are you there".to_vec())
+    );
+}
+
+#[tokio::test]
+async fn closes_when_the_client_does() {
+    let (mut ws, _) = tokio_tungstenite::connect_async(server().await)
+        .await
+        .unwrap();
+
+    ws.send(Message::Text("bye".to_string())).await.unwrap();
+    assert_eq!(
+        ws.next().await.unwrap().unwrap(),
+        Message::Text("bye".to_string())
+    );
+
+    ws.close(Some(CloseFrame {
+        code: CloseCode::Normal,
+        reason: "done".into(),
+    }))
+    .await
+    .unwrap();
+
+    // The server answers with a close frame of its own, echoing the code,
+    // and the stream ends.
+    match ws.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Normal),
+        msg => panic!("expected a close frame, got {:?}", msg),
+    }
+    assert!(ws.next().await.is_none());
+}
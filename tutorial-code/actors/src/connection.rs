@@ -0,0 +1,86 @@
+//! An actor owning a stream.
+//!
+//! Each request is a line to send, answered by the next line read. With
+//! the stream owned by a single task, two requests can't interleave their
+//! lines, whichever task sends them.
+
+use std::io;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+struct ConnectionActor<S> {
+    receiver: mpsc::Receiver<Request>,
+    stream: BufReader<S>,
+}
+
+struct Request {
+    line: String,
+    respond_to: oneshot::Sender<io::Result<String>>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> ConnectionActor<S> {
+    async fn run(mut self) {
+        while let Some(request) = self.receiver.recv().await {
+            let res = self.exchange(&request.line).await;
+            let failed = res.is_err();
+            let _ = request.respond_to.send(res);
+
+            // The stream may be halfway through a line: the next reply
+            // couldn't be trusted.
+            if failed {
+                return;
+            }
+        }
+    }
+
+    async fn exchange(&mut self, line: &str) -> io::Result<String> {
+        self.stream.write_all(line.as_bytes()).await?;
+        self.stream.write_all(b"\n").await?;
+
+        let mut reply = String::new();
+        if self.stream.read_line(&mut reply).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        reply.truncate(reply.trim_end_matches(&['\r', '\n'][..]).len());
+
+        Ok(reply)
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnectionHandle {
+    sender: mpsc::Sender<Request>,
+}
+
+impl ConnectionHandle {
+    /// Spawn an actor owning `stream`.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(8);
+        let actor = ConnectionActor {
+            receiver,
+            stream: BufReader::new(stream),
+        };
+        tokio::spawn(actor.run());
+
+        Self { sender }
+    }
+
+    /// Send `line` to the peer, and return its reply.
+    ///
+    /// Once a request fails, the actor stops, and every later one fails
+    /// with `BrokenPipe`.
+    pub async fn request(&self, line: &str) -> io::Result<String> {
+        let (send, recv) = oneshot::channel();
+        let request = Request {
+            line: line.to_string(),
+            respond_to: send,
+        };
+
+        let _ = self.sender.send(request).await;
+        recv.await
+            .unwrap_or_else(|_| Err(io::ErrorKind::BrokenPipe.into()))
+    }
+}
@@ -0,0 +1,18 @@
+//! Actors with Tokio, after Alice Ryhl's "Actors with Tokio".
+//!
+//! An actor is a task owning some state, or a resource like a socket, and
+//! the receiving end of an `mpsc` channel. Everything else talks to it
+//! through a handle holding the sending end: a request is a message
+//! carrying a `oneshot` sender, for the actor to answer on. Handles are
+//! cheap to clone, and once the last one is dropped, the channel closes and
+//! the actor stops.
+//!
+//! * `unique_id`: the basic actor, handing out ids.
+//! * `connection`: an actor owning a stream, so requests to it don't
+//!   interleave.
+//! * `supervisor`: an actor restarted when it panics, a bounded number of
+//!   times.
+
+pub mod connection;
+pub mod supervisor;
+pub mod unique_id;
@@ -0,0 +1,100 @@
+//! An actor restarted when it panics.
+//!
+//! The actor can't own its receiver here: a panic would drop it, closing
+//! the channel for good. The supervisor keeps it instead, behind a Tokio
+//! mutex each run of the actor locks. The guard is dropped while the
+//! panicking task unwinds, and a Tokio mutex isn't poisoned by that, so
+//! the next run picks up the same channel, and the handles keep working.
+//!
+//! The message the actor panicked on is lost, and its requester gets an
+//! error. The actor's state starts over: ids are handed out from 1 again.
+
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+enum ActorMessage {
+    GetUniqueId {
+        respond_to: oneshot::Sender<u32>,
+    },
+
+    /// Stands in for a bug.
+    Panic,
+}
+
+/// How supervising the actor ended.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// Every handle was dropped, after restarting the actor that many
+    /// times.
+    Stopped { restarts: usize },
+
+    /// The actor panicked once more after being restarted `max_restarts`
+    /// times.
+    GaveUp,
+}
+
+async fn run_actor(receiver: Arc<Mutex<mpsc::Receiver<ActorMessage>>>) {
+    let mut receiver = receiver.lock().await;
+    let mut next_id = 0;
+
+    while let Some(msg) = receiver.recv().await {
+        match msg {
+            ActorMessage::GetUniqueId { respond_to } => {
+                next_id += 1;
+                let _ = respond_to.send(next_id);
+            }
+            ActorMessage::Panic => panic!("the actor panicked, as asked"),
+        }
+    }
+}
+
+/// Run the actor, restarting it each time it panics, up to `max_restarts`
+/// times.
+async fn supervise(receiver: mpsc::Receiver<ActorMessage>, max_restarts: usize) -> Outcome {
+    let receiver = Arc::new(Mutex::new(receiver));
+    let mut restarts = 0;
+
+    loop {
+        let actor = tokio::spawn(run_actor(receiver.clone()));
+
+        match actor.await {
+            Ok(()) => return Outcome::Stopped { restarts },
+            Err(err) if err.is_panic() && restarts < max_restarts => restarts += 1,
+            // Out of restarts. Dropping the receiver with this task makes
+            // every request fail from now on.
+            Err(_) => return Outcome::GaveUp,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SupervisedHandle {
+    sender: mpsc::Sender<ActorMessage>,
+}
+
+impl SupervisedHandle {
+    /// Spawn the actor under a supervisor restarting it up to
+    /// `max_restarts` times. The supervisor's task says how it ended.
+    pub fn spawn(max_restarts: usize) -> (Self, JoinHandle<Outcome>) {
+        let (sender, receiver) = mpsc::channel(8);
+        let supervisor = tokio::spawn(supervise(receiver, max_restarts));
+
+        (Self { sender }, supervisor)
+    }
+
+    /// An id, or `None` if the actor is gone for good, or panicked while
+    /// handling this request.
+    pub async fn get_unique_id(&self) -> Option<u32> {
+        let (send, recv) = oneshot::channel();
+        let msg = ActorMessage::GetUniqueId { respond_to: send };
+
+        let _ = self.sender.send(msg).await;
+        recv.await.ok()
+    }
+
+    /// Make the actor panic.
+    pub async fn panic(&self) {
+        let _ = self.sender.send(ActorMessage::Panic).await;
+    }
+}
@@ -0,0 +1,54 @@
+use actors::connection::ConnectionHandle;
+use std::io;
+use tokio::io::{duplex, AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// A peer answering each line with it in upper case.
+fn peer(stream: tokio::io::DuplexStream) {
+    tokio::spawn(async move {
+        let mut stream = BufReader::new(stream);
+        let mut line = String::new();
+
+        while stream.read_line(&mut line).await.unwrap() != 0 {
+            stream
+                .write_all(line.to_uppercase().as_bytes())
+                .await
+                .unwrap();
+            line.clear();
+        }
+    });
+}
+
+#[tokio::test]
+async fn requests_get_their_own_reply() {
+    let (ours, theirs) = duplex(64);
+    peer(theirs);
+    let handle = ConnectionHandle::new(ours);
+
+    // Many tasks at once, each expecting the reply to its own line.
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                let reply = handle.request(&format!("request {}", i)).await.unwrap();
+                assert_eq!(reply, format!("REQUEST {}", i));
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn fails_once_the_peer_is_gone() {
+    let (ours, theirs) = duplex(64);
+    let handle = ConnectionHandle::new(ours);
+    drop(theirs);
+
+    assert!(handle.request("hello").await.is_err());
+
+    // The actor stopped.
+    let err = handle.request("hello").await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
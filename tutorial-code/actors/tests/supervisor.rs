@@ -0,0 +1,42 @@
+use actors::supervisor::{Outcome, SupervisedHandle};
+use std::time::Duration;
+use tokio::time;
+
+#[tokio::test]
+async fn restarts_after_a_panic() {
+    let (handle, supervisor) = SupervisedHandle::spawn(3);
+
+    assert_eq!(handle.get_unique_id().await, Some(1));
+    handle.panic().await;
+
+    // A new actor, on the same channel, with its state started over.
+    assert_eq!(handle.get_unique_id().await, Some(1));
+
+    drop(handle);
+    let outcome = time::timeout(Duration::from_secs(5), supervisor)
+        .await
+        .expect("the supervisor didn't stop")
+        .unwrap();
+    assert_eq!(outcome, Outcome::Stopped { restarts: 1 });
+}
+
+#[tokio::test]
+async fn gives_up_after_the_allowed_restarts() {
+    let (handle, supervisor) = SupervisedHandle::spawn(3);
+
+    for _ in 0..3 {
+        handle.panic().await;
+        // Still there.
+        assert!(handle.get_unique_id().await.is_some());
+    }
+
+    // One panic too many.
+    handle.panic().await;
+    let outcome = time::timeout(Duration::from_secs(5), supervisor)
+        .await
+        .expect("the supervisor didn't stop")
+        .unwrap();
+    assert_eq!(outcome, Outcome::GaveUp);
+
+    assert_eq!(handle.get_unique_id().await, None);
+}
@@ -0,0 +1,47 @@
+use actors::unique_id::MyActorHandle;
+use std::time::Duration;
+use tokio::time;
+
+#[tokio::test]
+async fn answers_requests() {
+    let handle = MyActorHandle::new();
+
+    assert_eq!(handle.get_unique_id().await, 1);
+    assert_eq!(handle.get_unique_id().await, 2);
+}
+
+#[tokio::test]
+async fn clones_share_the_actor() {
+    let handle = MyActorHandle::new();
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let handle = handle.clone();
+            tokio::spawn(async move { handle.get_unique_id().await })
+        })
+        .collect();
+
+    let mut ids = vec![];
+    for task in tasks {
+        ids.push(task.await.unwrap());
+    }
+    ids.sort_unstable();
+
+    assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn stops_when_every_handle_is_dropped() {
+    let (handle, actor) = MyActorHandle::spawn();
+    let clone = handle.clone();
+
+    drop(handle);
+    // One handle is enough to keep it going.
+    assert_eq!(clone.get_unique_id().await, 1);
+
+    drop(clone);
+    time::timeout(Duration::from_secs(5), actor)
+        .await
+        .expect("the actor didn't stop")
+        .unwrap();
+}
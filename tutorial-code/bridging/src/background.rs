@@ -0,0 +1,57 @@
+//! Spawning things on a runtime from sync code, and the other way around.
+
+use crate::blocking_client::BlockingClient;
+
+use bytes::Bytes;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::task;
+use tokio::time;
+
+/// Spawn a task sleeping for each of `delays` on `runtime`, do something
+/// time-consuming for `busy` meanwhile, then wait for all of them. Returns
+/// the tasks in the order they finished, by their index in `delays`.
+///
+/// With a `multi_thread` runtime, the tasks run while this thread is busy.
+/// With a `current_thread` one, they only start once it waits for them with
+/// `block_on`, since that is the only time the runtime runs at all.
+pub fn run_all(runtime: &Runtime, delays: &[Duration], busy: Duration) -> Vec<usize> {
+    let finished = Arc::new(Mutex::new(vec![]));
+
+    let mut handles = Vec::with_capacity(delays.len());
+    for (i, &delay) in delays.iter().enumerate() {
+        let finished = finished.clone();
+        handles.push(runtime.spawn(async move {
+            time::sleep(delay).await;
+            finished.lock().unwrap().push(i);
+        }));
+    }
+
+    // Do something time-consuming while the background tasks execute.
+    thread::sleep(busy);
+
+    // Wait for all of them to complete.
+    for handle in handles {
+        // The `spawn` method returns a `JoinHandle`. A `JoinHandle` is
+        // a future, so we can wait for it using `block_on`.
+        runtime.block_on(handle).unwrap();
+    }
+
+    let finished = finished.lock().unwrap();
+    finished.clone()
+}
+
+/// Use a `BlockingClient` from async code.
+///
+/// Calling `client.get` directly would panic. `block_in_place` tells the
+/// runtime that this thread is about to block, and moves the other tasks
+/// it runs elsewhere first. That takes other threads to move them to: it
+/// panics on a `current_thread` runtime.
+pub async fn get_in_place(
+    client: &mut BlockingClient,
+    key: &str,
+) -> mini_redis::Result<Option<Bytes>> {
+    task::block_in_place(|| client.get(key))
+}
@@ -0,0 +1,46 @@
+//! A synchronous interface to mini-redis.
+
+use bytes::Bytes;
+use mini_redis::client::{self, Client};
+use tokio::net::ToSocketAddrs;
+use tokio::runtime::Runtime;
+
+/// Established connection with a Redis server.
+pub struct BlockingClient {
+    /// The asynchronous `Client`.
+    inner: Client,
+
+    /// A `current_thread` runtime for executing operations on the
+    /// asynchronous client in a blocking manner.
+    rt: Runtime,
+}
+
+/// Connect to the server at `addr`.
+///
+/// Like every method of `BlockingClient`, this panics when called from
+/// async code, as blocking would keep its runtime from running anything
+/// else.
+pub fn connect<T: ToSocketAddrs>(addr: T) -> mini_redis::Result<BlockingClient> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    // Call the asynchronous connect method using the runtime.
+    let inner = rt.block_on(client::connect(addr))?;
+
+    Ok(BlockingClient { inner, rt })
+}
+
+impl BlockingClient {
+    pub fn get(&mut self, key: &str) -> mini_redis::Result<Option<Bytes>> {
+        self.rt.block_on(self.inner.get(key))
+    }
+
+    pub fn set(&mut self, key: &str, value: Bytes) -> mini_redis::Result<()> {
+        self.rt.block_on(self.inner.set(key, value))
+    }
+
+    pub fn publish(&mut self, channel: &str, message: Bytes) -> mini_redis::Result<u64> {
+        self.rt.block_on(self.inner.publish(channel, message))
+    }
+}
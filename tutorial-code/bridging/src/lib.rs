@@ -0,0 +1,11 @@
+//! The ways of calling async code from sync code of the bridging chapter.
+//!
+//! * `blocking_client`: a struct holding a runtime, with sync methods that
+//!   `block_on` the async ones.
+//! * `background`: tasks spawned on a runtime from sync code, and async code
+//!   calling the sync client with `block_in_place`.
+//! * `spawner`: a runtime on a thread of its own, sent jobs over a channel.
+
+pub mod background;
+pub mod blocking_client;
+pub mod spawner;
@@ -0,0 +1,12 @@
+use bridging::blocking_client;
+
+/// No `#[tokio::main]`: the client brings its own runtime.
+fn main() -> mini_redis::Result<()> {
+    let mut client = blocking_client::connect("127.0.0.1:6379")?;
+
+    client.set("hello", "world".into())?;
+    let result = client.get("hello")?;
+    println!("got value from the server; result={:?}", result);
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! A runtime on a thread of its own, sent jobs over a channel.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::thread;
+use tokio::runtime::Builder;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+/// What the runtime thread runs.
+pub type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Sends jobs to the runtime thread.
+///
+/// Dropping it, or calling `shutdown`, closes the channel. The thread then
+/// finishes the jobs it got, and exits.
+pub struct TaskSpawner {
+    spawn: Option<mpsc::Sender<Job>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl TaskSpawner {
+    pub fn new() -> TaskSpawner {
+        // Set up a channel for communicating.
+        let (send, mut recv) = mpsc::channel::<Job>(16);
+
+        // Build the runtime for the new thread.
+        //
+        // The runtime is created before spawning the thread
+        // to more cleanly forward errors if the `unwrap()`
+        // panics.
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+
+        let thread = thread::spawn(move || {
+            rt.block_on(async move {
+                let mut jobs = JoinSet::new();
+
+                while let Some(job) = recv.recv().await {
+                    jobs.spawn(job);
+                }
+
+                // Once all senders have gone out of scope, the `.recv()`
+                // call returns None. Dropping the runtime would drop the
+                // jobs still running, so wait for them first.
+                while jobs.join_next().await.is_some() {}
+            });
+        });
+
+        TaskSpawner {
+            spawn: Some(send),
+            thread: Some(thread),
+        }
+    }
+
+    /// Have the runtime thread run `job`.
+    ///
+    /// Blocks while the channel is full, so it must not be called from
+    /// async code.
+    pub fn spawn_task<F>(&self, job: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let spawn = self.spawn.as_ref().expect("not shut down");
+        match spawn.blocking_send(Box::pin(job)) {
+            Ok(()) => {}
+            Err(_) => panic!("The shared runtime has shut down."),
+        }
+    }
+
+    /// Close the channel, and wait for the runtime thread to finish the
+    /// jobs it got.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        drop(self.spawn.take());
+
+        if let Some(thread) = self.thread.take() {
+            // A job panicking doesn't bring the thread down with it, so
+            // this only fails if the runtime itself did.
+            thread.join().expect("the runtime thread panicked");
+        }
+    }
+}
+
+impl Default for TaskSpawner {
+    fn default() -> TaskSpawner {
+        TaskSpawner::new()
+    }
+}
+
+impl Drop for TaskSpawner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,37 @@
+use bridging::background::run_all;
+use std::time::Duration;
+use tokio::runtime::Builder;
+
+/// Later tasks sleep less, so they finish first if they run in parallel
+/// with the main thread.
+fn delays() -> Vec<Duration> {
+    (0..5)
+        .map(|i| Duration::from_millis(250 - 50 * i))
+        .collect()
+}
+
+#[test]
+fn tasks_run_in_the_background_on_multi_thread() {
+    let rt = Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        run_all(&rt, &delays(), Duration::from_millis(100)),
+        [4, 3, 2, 1, 0]
+    );
+}
+
+#[test]
+fn tasks_wait_for_block_on_with_current_thread() {
+    let rt = Builder::new_current_thread().enable_all().build().unwrap();
+
+    // The tasks only started sleeping once the main thread was done being
+    // busy, and the longest one took its whole delay after that.
+    let start = std::time::Instant::now();
+    let finished = run_all(&rt, &delays(), Duration::from_millis(300));
+    assert!(start.elapsed() >= Duration::from_millis(300 + 250));
+    assert_eq!(finished, [4, 3, 2, 1, 0]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use bridging::background::get_in_place;
+use bridging::blocking_client;
+use tokio::runtime::Builder;
+
+#[test]
+fn gets_what_it_set() {
+    let mut client = blocking_client::connect(common::server()).unwrap();
+
+    assert_eq!(client.get("foo").unwrap(), None);
+    client.set("foo", "bar".into()).unwrap();
+    assert_eq!(client.get("foo").unwrap().as_deref(), Some(&b"bar"[..]));
+
+    // Nobody is subscribed.
+    assert_eq!(client.publish("news", "hello".into()).unwrap(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Cannot start a runtime from within a runtime")]
+fn panics_in_async_code() {
+    let mut client = blocking_client::connect(common::server()).unwrap();
+    let rt = Builder::new_current_thread().enable_all().build().unwrap();
+
+    // Borrowed rather than moved in, so the client's runtime isn't dropped
+    // inside this one while unwinding.
+    let _ = rt.block_on(async { client.get("foo") });
+}
+
+#[test]
+fn works_in_async_code_with_block_in_place() {
+    let mut client = blocking_client::connect(common::server()).unwrap();
+    client.set("foo", "bar".into()).unwrap();
+
+    let rt = Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    let value = rt.block_on(get_in_place(&mut client, "foo")).unwrap();
+
+    assert_eq!(value.as_deref(), Some(&b"bar"[..]));
+}
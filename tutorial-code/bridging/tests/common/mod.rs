@@ -0,0 +1,27 @@
+use std::future;
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+/// Start a mini-redis server on a thread of its own, for the rest of the
+/// test process, and return its address.
+pub fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    listener.set_nonblocking(true).unwrap();
+
+    thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            mini_redis::server::run(listener, future::pending::<()>())
+                .await
+                .unwrap();
+        });
+    });
+
+    addr
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use bridging::spawner::TaskSpawner;
+use mini_redis::client;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn runs_jobs_and_sends_results_back() {
+    let addr = common::server();
+    let spawner = TaskSpawner::new();
+
+    // A channel in the opposite direction, for the answer.
+    let (tx, rx) = mpsc::channel();
+    spawner.spawn_task(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        client.set("foo", "bar".into()).await.unwrap();
+        tx.send(client.get("foo").await.unwrap()).unwrap();
+    });
+
+    let value = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(value.as_deref(), Some(&b"bar"[..]));
+
+    spawner.shutdown();
+}
+
+#[test]
+fn shutdown_waits_for_the_jobs() {
+    let spawner = TaskSpawner::new();
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..10 {
+        let done = done.clone();
+        spawner.spawn_task(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            done.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    spawner.shutdown();
+    assert_eq!(done.load(Ordering::SeqCst), 10);
+}
@@ -0,0 +1,113 @@
+//! The client manager of the channels chapter.
+//!
+//! A single task owns the `mini_redis` client. Other tasks send it commands
+//! over an `mpsc` channel, each with a `oneshot` sender for the response.
+//! The channel is bounded: once `CAPACITY` commands are waiting, senders
+//! wait for the manager to catch up instead of queueing without end.
+
+use mini_redis::client::Client;
+use std::future::Future;
+use tokio::sync::mpsc;
+
+/// How many commands may wait for the manager.
+pub const CAPACITY: usize = 32;
+
+// [start: command]
+use bytes::Bytes;
+use tokio::sync::oneshot;
+
+/// Multiple different commands are multiplexed over a single channel.
+#[derive(Debug)]
+pub enum Command {
+    Get {
+        key: String,
+        resp: Responder<Option<Bytes>>,
+    },
+    Set {
+        key: String,
+        val: Vec<u8>,
+        resp: Responder<()>,
+    },
+}
+
+/// Provided by the requester and used by the manager task to send the command
+/// response back to the requester.
+pub type Responder<T> = oneshot::Sender<mini_redis::Result<T>>;
+// [end: command]
+
+/// The channel the manager receives its commands on.
+pub fn channel() -> (mpsc::Sender<Command>, mpsc::Receiver<Command>) {
+    mpsc::channel(CAPACITY)
+}
+
+/// Run the commands of `rx` with `client`, until every sender is dropped.
+pub async fn manager(mut client: Client, mut rx: mpsc::Receiver<Command>) {
+    // [start: manager]
+    while let Some(cmd) = rx.recv().await {
+        match cmd {
+            Command::Get { key, resp } => {
+                let res = client.get(&key).await;
+                // Ignore errors
+                let _ = resp.send(res);
+            }
+            Command::Set { key, val, resp } => {
+                let res = client.set(&key, val.into()).await;
+                // Ignore errors
+                let _ = resp.send(res);
+            }
+        }
+    }
+    // [end: manager]
+}
+
+/// Like `manager`, but also stops when `shutdown` completes, even if some
+/// senders are left.
+///
+/// The commands still in the channel are dropped with it, and so are their
+/// responders: their requesters get an error instead of a response. So does
+/// the requester of a command the manager is running at the time.
+pub async fn manager_until(client: Client, rx: mpsc::Receiver<Command>, shutdown: impl Future) {
+    tokio::select! {
+        _ = manager(client, rx) => {}
+        _ = shutdown => {}
+    }
+}
+
+/// Ask the manager behind `tx` for the value of `key`.
+pub async fn get(tx: &mpsc::Sender<Command>, key: &str) -> mini_redis::Result<Option<Bytes>> {
+    let (resp, resp_rx) = oneshot::channel();
+    let cmd = Command::Get {
+        key: key.to_string(),
+        resp,
+    };
+
+    request(tx, cmd, resp_rx).await
+}
+
+/// Ask the manager behind `tx` to set `key` to `val`.
+pub async fn set(tx: &mpsc::Sender<Command>, key: &str, val: &[u8]) -> mini_redis::Result<()> {
+    let (resp, resp_rx) = oneshot::channel();
+    let cmd = Command::Set {
+        key: key.to_string(),
+        val: val.to_vec(),
+        resp,
+    };
+
+    request(tx, cmd, resp_rx).await
+}
+
+async fn request<T>(
+    tx: &mpsc::Sender<Command>,
+    cmd: Command,
+    resp_rx: oneshot::Receiver<mini_redis::Result<T>>,
+) -> mini_redis::Result<T> {
+    // Waits while the channel is full.
+    if tx.send(cmd).await.is_err() {
+        return Err("connection task shutdown".into());
+    }
+
+    // The manager drops the responder without answering if it stops first.
+    resp_rx
+        .await
+        .unwrap_or_else(|_| Err("connection task shutdown".into()))
+}
@@ -0,0 +1,106 @@
+use channels::{get, manager, manager_until, set, Command, CAPACITY};
+use mini_redis::client::{self, Client};
+use std::future;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::time;
+
+/// A client of a mini-redis server of its own.
+async fn client() -> Client {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mini_redis::server::run(listener, future::pending::<()>()));
+
+    client::connect(addr).await.unwrap()
+}
+
+#[tokio::test]
+async fn get_sees_what_set_wrote() {
+    let (tx, rx) = channels::channel();
+    let manager = tokio::spawn(manager(client().await, rx));
+
+    let setter = {
+        let tx = tx.clone();
+        tokio::spawn(async move { set(&tx, "foo", b"bar").await.unwrap() })
+    };
+    setter.await.unwrap();
+
+    let getter = tokio::spawn(async move { get(&tx, "foo").await.unwrap() });
+    assert_eq!(getter.await.unwrap().as_deref(), Some(&b"bar"[..]));
+
+    // Both senders went with their tasks.
+    time::timeout(Duration::from_secs(5), manager)
+        .await
+        .expect("the manager didn't stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stops_when_the_senders_are_dropped() {
+    let (tx, rx) = channels::channel();
+    let tx2 = tx.clone();
+    let manager = tokio::spawn(manager(client().await, rx));
+
+    drop(tx);
+    // One sender is enough to keep it going.
+    set(&tx2, "foo", b"bar").await.unwrap();
+
+    drop(tx2);
+    time::timeout(Duration::from_secs(5), manager)
+        .await
+        .expect("the manager didn't stop")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stops_on_shutdown() {
+    let (tx, rx) = channels::channel();
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let manager = tokio::spawn(manager_until(client().await, rx, signal));
+
+    set(&tx, "foo", b"bar").await.unwrap();
+
+    shutdown.send(()).unwrap();
+    time::timeout(Duration::from_secs(5), manager)
+        .await
+        .expect("the manager didn't stop")
+        .unwrap();
+
+    // `tx` is still around, but nobody is listening anymore.
+    assert!(get(&tx, "foo").await.is_err());
+}
+
+#[tokio::test]
+async fn senders_wait_when_the_channel_is_full() {
+    // No manager yet: nothing takes the commands out.
+    let (tx, rx) = channels::channel();
+
+    let mut responses = vec![];
+    for i in 0..CAPACITY {
+        let (resp, resp_rx) = oneshot::channel();
+        let cmd = Command::Set {
+            key: i.to_string(),
+            val: vec![],
+            resp,
+        };
+        tx.try_send(cmd).unwrap();
+        responses.push(resp_rx);
+    }
+
+    // One more has to wait.
+    let blocked = tokio::spawn({
+        let tx = tx.clone();
+        async move { set(&tx, "last", b"").await }
+    });
+    time::sleep(Duration::from_millis(50)).await;
+    assert!(!blocked.is_finished());
+
+    // Once the manager runs, everything goes through.
+    tokio::spawn(manager(client().await, rx));
+    blocked.await.unwrap().unwrap();
+    for resp_rx in responses {
+        resp_rx.await.unwrap().unwrap();
+    }
+    assert_eq!(get(&tx, "last").await.unwrap().as_deref(), Some(&b""[..]));
+}
@@ -0,0 +1,14 @@
+//! CPU-bound work on the runtime's blocking threads.
+
+use tokio::task;
+
+/// `is_prime`, computed on a thread of the blocking pool.
+///
+/// The blocking pool grows as needed, up to 512 threads by default: fine
+/// for occasional work, but it isn't sized for the CPU. A lot of
+/// concurrent calls end up with many more threads than cores.
+pub async fn is_prime(n: u64) -> bool {
+    task::spawn_blocking(move || crate::is_prime(n))
+        .await
+        .expect("is_prime panicked")
+}
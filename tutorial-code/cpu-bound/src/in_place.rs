@@ -0,0 +1,18 @@
+//! CPU-bound work on the current worker thread.
+
+use tokio::task;
+
+/// `is_prime`, computed right here.
+///
+/// `block_in_place` hands the other tasks of this worker thread to a new
+/// one, then blocks. It saves moving the work to another thread, with two
+/// caveats:
+///
+/// * It panics on a `current_thread` runtime, which has no other worker
+///   to hand the tasks to.
+/// * Only other tasks are moved. Whatever runs within the same task, like
+///   the other branches of a `join!` or `select!`, is blocked until the
+///   work is done.
+pub async fn is_prime(n: u64) -> bool {
+    task::block_in_place(|| crate::is_prime(n))
+}
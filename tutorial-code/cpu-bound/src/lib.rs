@@ -0,0 +1,41 @@
+//! Running CPU-bound work from asynchronous code.
+//!
+//! A task that computes for a long time without reaching an `.await` keeps
+//! its worker thread from running any other task. The work has to move to
+//! a thread meant for blocking:
+//!
+//! * `blocking`: with `spawn_blocking`, on the runtime's blocking threads.
+//! * `in_place`: with `block_in_place`, turning the current worker thread
+//!   into a blocking one.
+//! * `pool`: on a dedicated thread pool, the result coming back through a
+//!   `oneshot` channel. This is what bridging to rayon looks like; the
+//!   pool here is a small one made with `std`.
+//!
+//! The work in each case is `is_prime`.
+
+pub mod blocking;
+pub mod in_place;
+pub mod pool;
+
+/// Whether `n` is prime, by trial division: slow on purpose for large
+/// primes, which makes it a stand-in for any CPU-bound work.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if n.is_multiple_of(2) {
+        return n == 2;
+    }
+
+    // `i <= n / i` rather than `i * i <= n`, which overflows for `n` close
+    // to `u64::MAX`.
+    let mut i = 3;
+    while i <= n / i {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+        i += 2;
+    }
+
+    true
+}
@@ -0,0 +1,84 @@
+//! CPU-bound work on a dedicated thread pool.
+//!
+//! With rayon, this is `rayon::spawn` and a `oneshot` channel. `ThreadPool`
+//! has the part of rayon that matters here: a fixed number of threads,
+//! running closures in the order they are spawned.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed number of threads running closures.
+pub struct ThreadPool {
+    // `None` once dropped, which closes the channel.
+    sender: Option<mpsc::Sender<Job>>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub fn new(threads: usize) -> ThreadPool {
+        assert!(threads > 0, "a pool needs at least one thread");
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let threads = (0..threads)
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || loop {
+                    // The lock is released before running the job.
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+
+        ThreadPool {
+            sender: Some(sender),
+            threads,
+        }
+    }
+
+    /// Run `f` on one of the threads.
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Box::new(f))
+            .expect("the pool's threads are gone");
+    }
+}
+
+impl Drop for ThreadPool {
+    /// Wait for the jobs spawned so far to finish.
+    fn drop(&mut self) {
+        drop(self.sender.take());
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// `is_prime`, computed on `pool`.
+///
+/// Waiting on the `oneshot` receiver doesn't block: the task yields until
+/// the pool sends the result, and the runtime goes on with other tasks.
+pub async fn is_prime(pool: &ThreadPool, n: u64) -> bool {
+    let (send, recv) = oneshot::channel();
+
+    pool.spawn(move || {
+        let _ = send.send(crate::is_prime(n));
+    });
+
+    recv.await.expect("is_prime panicked")
+}
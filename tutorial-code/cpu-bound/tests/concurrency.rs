@@ -0,0 +1,147 @@
+//! Checks that the work runs off the runtime's worker threads.
+//!
+//! Timings depend on the machine, so the bounds are generous: eight checks
+//! at once must take well under what they would take one after the other,
+//! as much under as there are cores to run them on. With a single core,
+//! only the runtime staying responsive can be checked.
+
+use cpu_bound::{blocking, in_place, pool};
+use std::future::Future;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::time;
+
+/// A prime taking tens of milliseconds to check, in a debug build.
+const PRIME: u64 = 100_000_000_000_031;
+
+const CHECKS: usize = 8;
+
+/// The longest `CHECKS` checks at once may take, given how long they take
+/// one after the other.
+fn bound(sequential: Duration) -> Duration {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let parallel = cores.min(CHECKS) as u32;
+
+    sequential / parallel * 2
+}
+
+fn sequential() -> Duration {
+    let start = Instant::now();
+    for _ in 0..CHECKS {
+        assert!(cpu_bound::is_prime(PRIME));
+    }
+    start.elapsed()
+}
+
+/// Run `CHECKS` tasks made by `check` at once, on a multi-threaded runtime.
+fn concurrent<F, Fut>(check: F) -> Duration
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = bool> + Send + 'static,
+{
+    let rt = Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..CHECKS).map(|_| tokio::spawn(check())).collect();
+        for task in tasks {
+            assert!(task.await.unwrap());
+        }
+        start.elapsed()
+    })
+}
+
+fn assert_not_serialized(name: &str, concurrent: Duration) {
+    let sequential = sequential();
+
+    assert!(
+        concurrent < bound(sequential),
+        "{}: {} checks at once took {:?}, and {:?} one after the other",
+        name,
+        CHECKS,
+        concurrent,
+        sequential
+    );
+}
+
+#[test]
+fn spawn_blocking_runs_checks_at_once() {
+    let concurrent = concurrent(|| blocking::is_prime(PRIME));
+    assert_not_serialized("spawn_blocking", concurrent);
+}
+
+#[test]
+fn block_in_place_runs_checks_at_once() {
+    let concurrent = concurrent(|| in_place::is_prime(PRIME));
+    assert_not_serialized("block_in_place", concurrent);
+}
+
+#[test]
+fn pool_runs_checks_at_once() {
+    let pool = Arc::new(pool::ThreadPool::new(CHECKS));
+    let concurrent = concurrent(|| {
+        let pool = pool.clone();
+        async move { pool::is_prime(&pool, PRIME).await }
+    });
+    assert_not_serialized("pool", concurrent);
+}
+
+/// How many times a 1ms interval ticks on this task while `checks` runs.
+async fn ticks_while(checks: impl Future<Output = ()>) -> usize {
+    tokio::pin!(checks);
+    let mut interval = time::interval(Duration::from_millis(1));
+    let mut ticks = 0;
+
+    loop {
+        tokio::select! {
+            _ = &mut checks => return ticks,
+            _ = interval.tick() => ticks += 1,
+        }
+    }
+}
+
+// On a `current_thread` runtime, where a check computed on the runtime's
+// only thread would stop the interval until it is done.
+
+#[tokio::test]
+async fn spawn_blocking_leaves_the_runtime_responsive() {
+    let ticks = ticks_while(async {
+        let tasks: Vec<_> = (0..CHECKS)
+            .map(|_| tokio::spawn(blocking::is_prime(PRIME)))
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap());
+        }
+    })
+    .await;
+
+    assert!(ticks >= 5, "only {} ticks", ticks);
+}
+
+#[tokio::test]
+async fn pool_leaves_the_runtime_responsive() {
+    let pool = Arc::new(pool::ThreadPool::new(CHECKS));
+
+    let ticks = ticks_while(async {
+        let tasks: Vec<_> = (0..CHECKS)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool::is_prime(&pool, PRIME).await })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap());
+        }
+    })
+    .await;
+
+    assert!(ticks >= 5, "only {} ticks", ticks);
+}
+
+#[tokio::test]
+#[should_panic(expected = "can call blocking only when running on the multi-threaded runtime")]
+async fn block_in_place_needs_a_multi_threaded_runtime() {
+    in_place::is_prime(PRIME).await;
+}
@@ -0,0 +1,16 @@
+use cpu_bound::is_prime;
+
+#[test]
+fn small_numbers() {
+    let primes: Vec<u64> = (0..30).filter(|&n| is_prime(n)).collect();
+    assert_eq!(primes, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+}
+
+#[test]
+fn large_numbers() {
+    assert!(is_prime(1_000_000_007));
+    // The square of a prime, only divisible by its square root.
+    assert!(!is_prime(1_000_003 * 1_000_003));
+    // Would overflow with `i * i <= n`.
+    assert!(!is_prime(u64::MAX));
+}
@@ -0,0 +1,169 @@
+//! Reading and writing frames.
+//!
+//! Reads go through a `BytesMut` buffer: bytes are read into it until a
+//! whole frame is there, which `Frame::check` tells without allocating,
+//! and only then is the frame parsed and its bytes discarded. Writes go
+//! through a `BufWriter`, so writing a frame piece by piece doesn't take a
+//! system call per piece; `write_frame` flushes once the frame is written.
+//!
+//! The chapter uses a `TcpStream`. Any `AsyncRead + AsyncWrite` works the
+//! same, which lets the tests use `tokio::io::duplex`.
+
+use bytes::{Buf, BytesMut};
+use mini_redis::frame::Error::Incomplete;
+use mini_redis::{Frame, Result};
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+pub struct Connection<S> {
+    stream: BufWriter<S>,
+    buffer: BytesMut,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Connection<S> {
+    pub fn new(stream: S) -> Connection<S> {
+        Connection {
+            stream: BufWriter::new(stream),
+            // Allocate the buffer with 4kb of capacity.
+            buffer: BytesMut::with_capacity(4096),
+        }
+    }
+
+    /// Read a frame from the connection.
+    ///
+    /// Returns `None` if EOF is reached
+    pub async fn read_frame(&mut self) -> Result<Option<Frame>> {
+        loop {
+            // Attempt to parse a frame from the buffered data. If
+            // enough data has been buffered, the frame is
+            // returned.
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            // There is not enough buffered data to read a frame.
+            // Attempt to read more data from the socket.
+            //
+            // On success, the number of bytes is returned. `0`
+            // indicates "end of stream".
+            if 0 == self.stream.read_buf(&mut self.buffer).await? {
+                // The remote closed the connection. For this to be
+                // a clean shutdown, there should be no data in the
+                // read buffer. If there is, this means that the
+                // peer closed the socket while sending a frame.
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                } else {
+                    return Err("connection reset by peer".into());
+                }
+            }
+        }
+    }
+
+    fn parse_frame(&mut self) -> Result<Option<Frame>> {
+        // Create the `T: Buf` type.
+        let mut buf = Cursor::new(&self.buffer[..]);
+
+        // Check whether a full frame is available
+        match Frame::check(&mut buf) {
+            Ok(_) => {
+                // Get the byte length of the frame
+                let len = buf.position() as usize;
+
+                // Reset the internal cursor for the
+                // call to `parse`.
+                buf.set_position(0);
+
+                // Parse the frame
+                let frame = Frame::parse(&mut buf)?;
+
+                // Discard the frame from the buffer
+                self.buffer.advance(len);
+
+                // Return the frame to the caller.
+                Ok(Some(frame))
+            }
+            // Not enough data has been buffered
+            Err(Incomplete) => Ok(None),
+            // An error was encountered
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write a frame to the connection.
+    pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        self.write_value(frame).await?;
+
+        // The frame is in the `BufWriter`'s buffer, and possibly only
+        // there: write it out to the socket.
+        self.stream.flush().await
+    }
+
+    /// Write `frame` to the buffer, without flushing it.
+    ///
+    /// An array holds frames of its own, arrays included, so this calls
+    /// itself. An `async fn` can't: its future would have to contain
+    /// itself. Boxing the future breaks the cycle.
+    fn write_value<'a>(
+        &'a mut self,
+        frame: &'a Frame,
+    ) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            match frame {
+                Frame::Simple(val) => {
+                    self.stream.write_u8(b'+').await?;
+                    self.stream.write_all(val.as_bytes()).await?;
+                    self.stream.write_all(b"\r\n").await?;
+                }
+                Frame::Error(val) => {
+                    self.stream.write_u8(b'-').await?;
+                    self.stream.write_all(val.as_bytes()).await?;
+                    self.stream.write_all(b"\r\n").await?;
+                }
+                Frame::Integer(val) => {
+                    self.stream.write_u8(b':').await?;
+                    self.write_decimal(*val).await?;
+                }
+                Frame::Null => {
+                    self.stream.write_all(b"$-1\r\n").await?;
+                }
+                Frame::Bulk(val) => {
+                    let len = val.len();
+
+                    self.stream.write_u8(b'$').await?;
+                    self.write_decimal(len as u64).await?;
+                    self.stream.write_all(val).await?;
+                    self.stream.write_all(b"\r\n").await?;
+                }
+                Frame::Array(entries) => {
+                    self.stream.write_u8(b'*').await?;
+                    self.write_decimal(entries.len() as u64).await?;
+
+                    for entry in entries {
+                        self.write_value(entry).await?;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Write a decimal frame to the stream
+    async fn write_decimal(&mut self, val: u64) -> io::Result<()> {
+        use std::io::Write;
+
+        // Convert the value to a string
+        let mut buf = [0u8; 20];
+        let mut buf = Cursor::new(&mut buf[..]);
+        write!(&mut buf, "{}", val)?;
+
+        let pos = buf.position() as usize;
+        self.stream.write_all(&buf.get_ref()[..pos]).await?;
+        self.stream.write_all(b"\r\n").await?;
+
+        Ok(())
+    }
+}
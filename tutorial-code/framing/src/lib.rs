@@ -0,0 +1,23 @@
+//! The `Connection` of the framing chapter, built from scratch on top of
+//! mini-redis's `Frame`, and a server echoing frames with it.
+
+pub mod connection;
+
+pub use connection::Connection;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Send every frame read from `socket` back to it, until the peer closes
+/// the connection.
+pub async fn echo<S>(socket: S) -> mini_redis::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    let mut connection = Connection::new(socket);
+
+    while let Some(frame) = connection.read_frame().await? {
+        connection.write_frame(&frame).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use tokio::net::TcpListener;
+
+/// Try it with `mini-redis-cli ping hello`: the server sends the command
+/// back as it is, which the client shows as an error.
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:6379").await?;
+
+    loop {
+        let (socket, addr) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            if let Err(err) = framing::echo(socket).await {
+                eprintln!("connection from {} failed: {}", addr, err);
+            }
+        });
+    }
+}
@@ -0,0 +1,128 @@
+use bytes::Bytes;
+use framing::{echo, Connection};
+use mini_redis::Frame;
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+use tokio::task;
+
+/// A frame of every kind, arrays in arrays included, and its encoding.
+fn every_kind() -> (Frame, &'static [u8]) {
+    let frame = Frame::Array(vec![
+        Frame::Simple("OK".to_string()),
+        Frame::Error("ERR oops".to_string()),
+        Frame::Integer(42),
+        Frame::Bulk(Bytes::from("hello")),
+        Frame::Null,
+        Frame::Array(vec![
+            Frame::Integer(0),
+            Frame::Array(vec![]),
+            Frame::Bulk(Bytes::new()),
+        ]),
+    ]);
+    let encoded = b"*6\r\n+OK\r\n-ERR oops\r\n:42\r\n$5\r\nhello\r\n$-1\r\n\
+                    *3\r\n:0\r\n*0\r\n$0\r\n\r\n";
+
+    (frame, encoded)
+}
+
+/// `Frame` isn't `PartialEq`, but its `Debug` output is as good.
+fn same(a: &Frame, b: &Frame) -> bool {
+    format!("{:?}", a) == format!("{:?}", b)
+}
+
+#[tokio::test]
+async fn writes_every_kind_of_frame() {
+    let (ours, mut theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+
+    let (frame, encoded) = every_kind();
+    connection.write_frame(&frame).await.unwrap();
+    drop(connection);
+
+    let mut written = vec![];
+    theirs.read_to_end(&mut written).await.unwrap();
+    assert_eq!(written, encoded);
+}
+
+#[tokio::test]
+async fn reads_a_frame_byte_by_byte() {
+    let (ours, mut theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+
+    let (frame, encoded) = every_kind();
+    let reader = tokio::spawn(async move { connection.read_frame().await.unwrap() });
+
+    for byte in encoded {
+        theirs.write_all(&[*byte]).await.unwrap();
+        // Give the reader a chance to see each byte on its own.
+        task::yield_now().await;
+    }
+
+    assert!(same(&reader.await.unwrap().unwrap(), &frame));
+}
+
+#[tokio::test]
+async fn reads_frames_split_and_coalesced() {
+    let (ours, mut theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+
+    // The end of the first frame comes with the start of the second.
+    theirs.write_all(b"+hel").await.unwrap();
+    theirs.write_all(b"lo\r\n:1").await.unwrap();
+    theirs.write_all(b"7\r\n").await.unwrap();
+    drop(theirs);
+
+    let first = connection.read_frame().await.unwrap().unwrap();
+    assert!(same(&first, &Frame::Simple("hello".to_string())));
+    let second = connection.read_frame().await.unwrap().unwrap();
+    assert!(same(&second, &Frame::Integer(17)));
+    assert!(connection.read_frame().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn closing_between_frames_is_clean() {
+    let (ours, theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+    drop(theirs);
+
+    assert!(connection.read_frame().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn closing_mid_frame_is_a_reset() {
+    let (ours, mut theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+
+    theirs.write_all(b"$5\r\nhel").await.unwrap();
+    drop(theirs);
+
+    let err = connection.read_frame().await.unwrap_err();
+    assert_eq!(err.to_string(), "connection reset by peer");
+}
+
+#[tokio::test]
+async fn invalid_frames_are_errors() {
+    let (ours, mut theirs) = duplex(1024);
+    let mut connection = Connection::new(ours);
+
+    theirs.write_all(b"?what\r\n").await.unwrap();
+
+    assert!(connection.read_frame().await.is_err());
+}
+
+#[tokio::test]
+async fn echoes_frames() {
+    let (ours, theirs) = duplex(64);
+    let server = tokio::spawn(echo(theirs));
+    let mut connection = Connection::new(ours);
+
+    // Larger than the duplex buffer, so it goes through in pieces.
+    let (frame, _) = every_kind();
+    for _ in 0..3 {
+        connection.write_frame(&frame).await.unwrap();
+        let echoed = connection.read_frame().await.unwrap().unwrap();
+        assert!(same(&echoed, &frame));
+    }
+
+    drop(connection);
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,127 @@
+//! A pool of workers shutting down gracefully.
+//!
+//! Shutting down takes three things: knowing when to stop, telling every
+//! task to stop, and waiting for them to be done.
+//!
+//! * When to stop is up to the caller, as a future completing at that
+//!   point, like `tokio::signal::ctrl_c()`.
+//! * Telling the workers is a `broadcast` channel: each one gets a
+//!   receiver, and `select!`s on it next to its job. A single send reaches
+//!   all of them.
+//! * Waiting uses an `mpsc` channel that no one sends on. Each worker holds
+//!   a sender, and drops it when it is done. Once the pool drops its own
+//!   sender too, `recv` returns `None` exactly when the last worker is done.
+//!
+//! `run_with_timeout` waits only so long for the workers to clean up, and
+//! aborts the ones that are still at it.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinSet;
+use tokio::time;
+
+/// How long a simulated job takes.
+pub const JOB: Duration = Duration::from_millis(100);
+
+/// What the workers did, for the caller to check.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Jobs finished. A job interrupted by the shutdown isn't.
+    pub jobs: AtomicUsize,
+
+    /// Workers that finished cleaning up.
+    pub cleaned_up: AtomicUsize,
+}
+
+/// Run a worker per entry of `cleanups` until `shutdown` completes, then
+/// wait for all of them to be done. The entry is how long the worker takes
+/// to clean up.
+pub async fn run(cleanups: &[Duration], shutdown: impl Future, stats: Arc<Stats>) {
+    let (notify, _) = broadcast::channel(1);
+    let (done, mut all_done) = mpsc::channel::<()>(1);
+
+    for &cleanup in cleanups {
+        let worker = worker(notify.subscribe(), done.clone(), cleanup, stats.clone());
+        tokio::spawn(worker);
+    }
+
+    shutdown.await;
+
+    // An error only means every worker is already gone.
+    let _ = notify.send(());
+
+    // Only the workers' senders are left. `recv` returns `None` once they
+    // have all dropped theirs.
+    drop(done);
+    let _ = all_done.recv().await;
+}
+
+/// Like `run`, except that the workers get `grace` to clean up after
+/// `shutdown` completes. The ones not done by then are aborted; their
+/// number is returned.
+pub async fn run_with_timeout(
+    cleanups: &[Duration],
+    shutdown: impl Future,
+    stats: Arc<Stats>,
+    grace: Duration,
+) -> usize {
+    let (notify, _) = broadcast::channel(1);
+    let (done, mut all_done) = mpsc::channel::<()>(1);
+
+    // Aborting takes the handles of the tasks.
+    let mut workers = JoinSet::new();
+    for &cleanup in cleanups {
+        workers.spawn(worker(
+            notify.subscribe(),
+            done.clone(),
+            cleanup,
+            stats.clone(),
+        ));
+    }
+
+    shutdown.await;
+
+    let _ = notify.send(());
+    drop(done);
+
+    if time::timeout(grace, all_done.recv()).await.is_ok() {
+        return 0;
+    }
+
+    workers.abort_all();
+
+    let mut aborted = 0;
+    while let Some(res) = workers.join_next().await {
+        if res.is_err_and(|err| err.is_cancelled()) {
+            aborted += 1;
+        }
+    }
+    aborted
+}
+
+/// Do jobs until told to stop, then clean up for `cleanup`.
+async fn worker(
+    mut notify: broadcast::Receiver<()>,
+    _done: mpsc::Sender<()>,
+    cleanup: Duration,
+    stats: Arc<Stats>,
+) {
+    loop {
+        tokio::select! {
+            _ = time::sleep(JOB) => {
+                stats.jobs.fetch_add(1, Ordering::SeqCst);
+            }
+            // A job in progress is dropped half-way.
+            _ = notify.recv() => break,
+        }
+    }
+
+    // Flushing buffers, saying goodbye to peers, and so on.
+    time::sleep(cleanup).await;
+    stats.cleaned_up.fetch_add(1, Ordering::SeqCst);
+
+    // `_done` is dropped here, telling the pool this worker is done.
+}
@@ -0,0 +1,29 @@
+use graceful_shutdown::{run_with_timeout, Stats};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal;
+
+#[tokio::main]
+async fn main() {
+    // The last worker takes too long to clean up, and gets aborted.
+    let cleanups = [
+        Duration::from_millis(100),
+        Duration::from_millis(500),
+        Duration::from_secs(60),
+    ];
+    let stats = Arc::new(Stats::default());
+
+    println!("working; press ctrl-c to stop");
+    let ctrl_c = async {
+        signal::ctrl_c().await.unwrap();
+    };
+    let aborted = run_with_timeout(&cleanups, ctrl_c, stats.clone(), Duration::from_secs(1)).await;
+
+    println!(
+        "{} jobs done, {} workers cleaned up, {} aborted",
+        stats.jobs.load(Ordering::SeqCst),
+        stats.cleaned_up.load(Ordering::SeqCst),
+        aborted
+    );
+}
@@ -0,0 +1,64 @@
+use graceful_shutdown::{run, run_with_timeout, Stats, JOB};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{self, Instant};
+
+#[tokio::test(start_paused = true)]
+async fn every_worker_cleans_up() {
+    let cleanups = [Duration::from_millis(10); 4];
+    let stats = Arc::new(Stats::default());
+
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let pool = tokio::spawn({
+        let stats = stats.clone();
+        async move { run(&cleanups, signal, stats).await }
+    });
+
+    time::sleep(JOB * 5 + JOB / 2).await;
+    shutdown.send(()).unwrap();
+    let start = Instant::now();
+
+    pool.await.unwrap();
+
+    // Each worker finished five jobs, and the sixth was interrupted.
+    assert_eq!(stats.jobs.load(Ordering::SeqCst), 4 * 5);
+    assert_eq!(stats.cleaned_up.load(Ordering::SeqCst), 4);
+    // `run` waited for the cleanups, and no longer.
+    assert_eq!(start.elapsed(), Duration::from_millis(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stragglers_are_aborted() {
+    let cleanups = [
+        Duration::from_millis(10),
+        Duration::from_millis(20),
+        Duration::from_secs(60),
+    ];
+    let grace = Duration::from_secs(1);
+    let stats = Arc::new(Stats::default());
+
+    let shutdown = time::sleep(JOB / 2);
+    let start = Instant::now();
+    let aborted = run_with_timeout(&cleanups, shutdown, stats.clone(), grace).await;
+
+    assert_eq!(aborted, 1);
+    assert_eq!(stats.cleaned_up.load(Ordering::SeqCst), 2);
+    assert_eq!(start.elapsed(), JOB / 2 + grace);
+}
+
+#[tokio::test(start_paused = true)]
+async fn nothing_is_aborted_within_the_grace_period() {
+    let cleanups = [Duration::from_millis(10), Duration::from_millis(500)];
+    let stats = Arc::new(Stats::default());
+
+    let start = Instant::now();
+    let aborted =
+        run_with_timeout(&cleanups, async {}, stats.clone(), Duration::from_secs(1)).await;
+
+    assert_eq!(aborted, 0);
+    assert_eq!(stats.cleaned_up.load(Ordering::SeqCst), 2);
+    // Done as soon as the slowest worker is.
+    assert_eq!(start.elapsed(), Duration::from_millis(500));
+}
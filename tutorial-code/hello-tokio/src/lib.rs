@@ -0,0 +1,19 @@
+//! The program of the hello-tokio chapter, returning the value it gets
+//! back instead of printing it, so a test can run it against a server of
+//! its own.
+
+use bytes::Bytes;
+use mini_redis::{client, Result};
+use tokio::net::ToSocketAddrs;
+
+/// Set the key "hello" to "world" on the server at `addr`, then get it.
+pub async fn hello<T: ToSocketAddrs>(addr: T) -> Result<Option<Bytes>> {
+    // Open a connection to the mini-redis address.
+    let mut client = client::connect(addr).await?;
+
+    // Set the key "hello" with value "world"
+    client.set("hello", "world".into()).await?;
+
+    // Get key "hello"
+    client.get("hello").await
+}
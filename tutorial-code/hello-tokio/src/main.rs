@@ -1,7 +1,13 @@
-use mini_redis::client;
+//! The program of the hello-tokio chapter, as it is shown there.
+//!
+//! The chapter's listing is checked against the region marked with
+//! `// [start: main]` and `// [end: main]`, so keep the two in sync.
+
+// [start: main]
+use mini_redis::{client, Result};
 
 #[tokio::main]
-async fn main() -> mini_redis::Result<()> {
+pub async fn main() -> Result<()> {
     // Open a connection to the mini-redis address.
     let mut client = client::connect("127.0.0.1:6379").await?;
 
@@ -15,3 +21,4 @@ async fn main() -> mini_redis::Result<()> {
 
     Ok(())
 }
+// [end: main]
@@ -0,0 +1,25 @@
+use hello_tokio::hello;
+use std::future;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn gets_back_what_it_set() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mini_redis::server::run(listener, future::pending::<()>()));
+
+    let result = hello(addr).await.unwrap();
+
+    assert_eq!(result.as_deref(), Some(&b"world"[..]));
+}
+
+#[tokio::test]
+async fn fails_without_a_server() {
+    // An address nothing listens on anymore.
+    let addr = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    assert!(hello(addr).await.is_err());
+}
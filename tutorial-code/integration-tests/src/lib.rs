@@ -0,0 +1,162 @@
+//! Running the programs of the tutorial, and checking that they do what the
+//! chapters say they do.
+//!
+//! The other crates test their code piece by piece. The tests here run the
+//! binaries themselves, as a reader would, and look at what they print.
+//! Where a binary is hard-wired to a port, such as 6379, the test calls the
+//! library entry point it is built on instead, so that tests never fight
+//! over a port with each other, or with a server the reader left running.
+//! Servers are told to listen on port 0 instead, and print the port they
+//! got.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::time;
+
+/// How long a program gets to do what a test expects of it. Generous, for
+/// debug builds on busy machines.
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The path to the binary `bin` of the package `package`, built first.
+///
+/// The binaries of other packages aren't built for the tests of this one,
+/// so this runs `cargo build` for it. That is quick once it's built, and
+/// concurrent builds wait for each other.
+pub fn binary(package: &str, bin: &str) -> PathBuf {
+    let status = std::process::Command::new(env!("CARGO"))
+        .args(["build", "--quiet", "--package", package, "--bin", bin])
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build {} of {}", bin, package);
+
+    // The binaries are next to `deps`, where this test runs from.
+    let mut path = std::env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {
+        path.pop();
+    }
+    path.join(format!("{}{}", bin, std::env::consts::EXE_SUFFIX))
+}
+
+/// Run `cmd` to the end, capturing what it prints, and killing it if it
+/// takes longer than `timeout`.
+pub async fn output(cmd: &mut Command, timeout: Duration) -> io::Result<Output> {
+    cmd.stdin(Stdio::null()).kill_on_drop(true);
+
+    match time::timeout(timeout, cmd.output()).await {
+        Ok(output) => output,
+        Err(_) => Err(timed_out(cmd, "to exit")),
+    }
+}
+
+/// Like `output`, but failing unless `cmd` succeeds, and only returning
+/// its stdout.
+pub async fn stdout(cmd: &mut Command, timeout: Duration) -> io::Result<String> {
+    let output = output(cmd, timeout).await?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{:?} failed with {}: {}",
+            cmd.as_std(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// A program left running while the test talks to it. It is killed when
+/// dropped.
+#[derive(Debug)]
+pub struct Running {
+    child: Child,
+    stdout: Lines<BufReader<ChildStdout>>,
+
+    /// For error messages.
+    name: String,
+}
+
+impl Running {
+    /// Start `cmd`, reading its stdout line by line. Its stderr goes to the
+    /// test's.
+    pub fn spawn(cmd: &mut Command) -> io::Result<Running> {
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+
+        Ok(Running {
+            child,
+            stdout,
+            name: format!("{:?}", cmd.as_std()),
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.id().expect("already exited")
+    }
+
+    /// Skip lines until one starting with `prefix`, and return the rest of
+    /// it. Fails if the program closes its stdout first, or takes longer
+    /// than `timeout`.
+    pub async fn wait_for_line(&mut self, prefix: &str, timeout: Duration) -> io::Result<String> {
+        let wait = async {
+            while let Some(line) = self.stdout.next_line().await? {
+                if let Some(rest) = line.strip_prefix(prefix) {
+                    return Ok(rest.to_string());
+                }
+            }
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} exited without printing {:?}", self.name, prefix),
+            ))
+        };
+
+        match time::timeout(timeout, wait).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{} didn't print {:?} in time", self.name, prefix),
+            )),
+        }
+    }
+
+    /// Wait for the program to exit, and return what else it printed.
+    pub async fn finish(
+        mut self,
+        timeout: Duration,
+    ) -> io::Result<(std::process::ExitStatus, String)> {
+        let wait = async {
+            let mut rest = String::new();
+            while let Some(line) = self.stdout.next_line().await? {
+                rest.push_str(&line);
+                rest.push('\n');
+            }
+            Ok::<_, io::Error>((self.child.wait().await?, rest))
+        };
+
+        match time::timeout(timeout, wait).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{} didn't exit in time", self.name),
+            )),
+        }
+    }
+
+    pub async fn kill(mut self) -> io::Result<()> {
+        self.child.kill().await
+    }
+}
+
+fn timed_out(cmd: &Command, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("{:?} took too long {}", cmd.as_std(), what),
+    )
+}
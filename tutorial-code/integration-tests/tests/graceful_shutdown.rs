@@ -0,0 +1,40 @@
+// Sending ctrl-c to another process is done with `kill -INT`.
+#![cfg(unix)]
+
+use integration_tests::{binary, Running, TIMEOUT};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time;
+
+#[tokio::test]
+async fn ctrl_c_cleans_up_the_workers_it_can() {
+    let mut program = Running::spawn(&mut Command::new(binary(
+        "graceful-shutdown",
+        "graceful-shutdown",
+    )))
+    .unwrap();
+    program.wait_for_line("working", TIMEOUT).await.unwrap();
+
+    // Let the workers get some jobs done.
+    time::sleep(Duration::from_millis(300)).await;
+    let status = Command::new("kill")
+        .args(["-INT", &program.id().to_string()])
+        .status()
+        .await
+        .unwrap();
+    assert!(status.success());
+
+    let (status, rest) = program.finish(TIMEOUT).await.unwrap();
+    assert!(status.success(), "{}", status);
+
+    // The workers cleaning up in 100ms and 500ms are done within the
+    // second they get, and the one needing a minute is aborted.
+    let summary = rest.trim_end();
+    assert!(
+        summary.ends_with(" jobs done, 2 workers cleaned up, 1 aborted"),
+        "{}",
+        summary
+    );
+    let jobs: u64 = summary.split(' ').next().unwrap().parse().unwrap();
+    assert!(jobs > 0, "{}", summary);
+}
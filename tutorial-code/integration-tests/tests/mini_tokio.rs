@@ -0,0 +1,15 @@
+use integration_tests::{binary, stdout, TIMEOUT};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn prints_hello_then_world() {
+    let out = stdout(
+        &mut Command::new(binary("mini-tokio", "mini-tokio")),
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+
+    // "world" is printed by the task spawned first, after a delay.
+    assert_eq!(out, "hello\nworld\n");
+}
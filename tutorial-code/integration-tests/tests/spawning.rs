@@ -0,0 +1,85 @@
+use integration_tests::{binary, stdout, Running, TIMEOUT};
+use mini_redis::client;
+use std::net::SocketAddr;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+
+/// The server of the spawning chapter, on a port of its own, run from an
+/// empty directory so it doesn't load a snapshot left from elsewhere.
+async fn server(dir: &TempDir) -> (Running, SocketAddr) {
+    let mut server = Running::spawn(
+        Command::new(binary("spawning", "spawning"))
+            .env("SPAWNING_ADDR", "127.0.0.1:0")
+            .env_remove("SPAWNING_PASSWORD")
+            .current_dir(dir.path()),
+    )
+    .unwrap();
+
+    let addr = server
+        .wait_for_line("listening on ", TIMEOUT)
+        .await
+        .unwrap()
+        .parse()
+        .unwrap();
+    (server, addr)
+}
+
+#[tokio::test]
+async fn server_serves_clients() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr) = server(&dir).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(client.get("hello").await.unwrap(), Some("world".into()));
+
+    // Another connection, handled by another task, sees the same data.
+    let mut other = client::connect(addr).await.unwrap();
+    assert_eq!(other.get("hello").await.unwrap(), Some("world".into()));
+
+    server.kill().await.unwrap();
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn client_binary_talks_to_the_server() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr) = server(&dir).await;
+
+    let out = stdout(
+        Command::new(binary("spawning", "client")).arg(addr.to_string()),
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+
+    // Whether the concurrent GETs saw the SETs depends on timing, so only
+    // the SETs and the final values are checked.
+    let lines: Vec<_> = out.lines().collect();
+    assert!(lines.contains(&"tasks: SET hello = Ok(())"), "{}", out);
+    assert!(lines.contains(&"manager: SET foo = Ok(Ok(()))"), "{}", out);
+    assert!(
+        lines.ends_with(&[
+            "final: hello = Some(b\"world\")",
+            "final: foo = Some(b\"bar\")",
+        ]),
+        "{}",
+        out
+    );
+
+    server.kill().await.unwrap();
+}
+
+#[tokio::test]
+async fn hello_tokio_gets_its_value_back() {
+    let dir = TempDir::new().unwrap();
+    let (server, addr) = server(&dir).await;
+
+    // The hello-tokio binary always connects to port 6379, so its library
+    // entry point is run instead.
+    let value = hello_tokio::hello(addr).await.unwrap();
+    assert_eq!(value, Some("world".into()));
+
+    server.kill().await.unwrap();
+}
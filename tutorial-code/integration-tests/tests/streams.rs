@@ -0,0 +1,55 @@
+use integration_tests::{binary, stdout, TIMEOUT};
+use std::future;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::process::Command;
+
+/// A mini-redis server, for its `PUBLISH` and `SUBSCRIBE`.
+async fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mini_redis::server::run(listener, future::pending::<()>()));
+    addr
+}
+
+#[tokio::test]
+async fn merge_unsubscribes_from_letters_after_c() {
+    let addr = server().await;
+
+    let out = stdout(
+        Command::new(binary("streams", "merge")).arg(addr.to_string()),
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+    let lines: Vec<_> = out.lines().collect();
+
+    // Both channels are merged into one stream, in the order published.
+    let letters: Vec<_> = lines
+        .iter()
+        .copied()
+        .filter(|l| l.starts_with("letters = "))
+        .collect();
+    assert_eq!(
+        letters,
+        [
+            r#"letters = b"a""#,
+            r#"letters = b"b""#,
+            r#"letters = b"c""#
+        ],
+        "{}",
+        out
+    );
+
+    let numbers: Vec<_> = lines
+        .iter()
+        .copied()
+        .filter(|l| l.starts_with("numbers = "))
+        .collect();
+    assert_eq!(numbers.len(), 6, "{}", out);
+    assert_eq!(numbers[5], r#"numbers = b"6""#, "{}", out);
+
+    let unsubscribed = lines.iter().position(|l| *l == "unsubscribed from letters");
+    let c = lines.iter().position(|l| *l == r#"letters = b"c""#);
+    assert_eq!(unsubscribed, c.map(|c| c + 1), "{}", out);
+}
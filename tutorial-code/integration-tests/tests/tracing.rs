@@ -0,0 +1,28 @@
+use integration_tests::{binary, stdout, TIMEOUT};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn json_logs_follow_each_request() {
+    let out = stdout(
+        Command::new(binary("tracing-example", "tracing-example"))
+            .arg("--json")
+            .env_remove("RUST_LOG"),
+        TIMEOUT,
+    )
+    .await
+    .unwrap();
+
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 5, "{}", out);
+    assert!(lines.iter().all(|line| line.starts_with('{')), "{}", out);
+
+    // alice is found...
+    assert!(lines[0].contains(r#""message":"received""#), "{}", lines[0]);
+    assert!(lines[1].contains(r#""status":200"#), "{}", lines[1]);
+
+    // ...and mallory isn't, which is logged within the request's span.
+    assert!(lines[3].contains(r#""level":"WARN""#), "{}", lines[3]);
+    assert!(lines[3].contains(r#""user":"mallory""#), "{}", lines[3]);
+    assert!(lines[3].contains(r#""id":1"#), "{}", lines[3]);
+    assert!(lines[4].contains(r#""status":404"#), "{}", lines[4]);
+}
@@ -0,0 +1,13 @@
+use tokio::io;
+use tokio::net::TcpStream;
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let socket = TcpStream::connect("127.0.0.1:6142").await?;
+
+    let messages = vec![b"hello\r\n".to_vec(), b"world\r\n".to_vec()];
+    let received = echo::client(socket, messages).await?;
+    println!("GOT {:?}", String::from_utf8_lossy(&received));
+
+    Ok(())
+}
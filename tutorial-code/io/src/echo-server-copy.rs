@@ -6,12 +6,10 @@ async fn main() -> io::Result<()> {
     let listener = TcpListener::bind("127.0.0.1:6142").await.unwrap();
 
     loop {
-        let (mut socket, _) = listener.accept().await?;
+        let (socket, _) = listener.accept().await?;
 
         tokio::spawn(async move {
-            let (mut rd, mut wr) = socket.split();
-
-            if io::copy(&mut rd, &mut wr).await.is_err() {
+            if echo::echo_copy(socket).await.is_err() {
                 eprintln!("failed to copy");
             }
         });
@@ -0,0 +1,17 @@
+use tokio::io;
+use tokio::net::TcpListener;
+
+/// Don't run this one for long: each client that disconnects leaves a task
+/// spinning at 100% CPU.
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:6142").await.unwrap();
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            let _ = echo::echo_without_eof(socket).await;
+        });
+    }
+}
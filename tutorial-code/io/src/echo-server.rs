@@ -1,4 +1,4 @@
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::io;
 use tokio::net::TcpListener;
 
 #[tokio::main]
@@ -6,31 +6,12 @@ async fn main() -> io::Result<()> {
     let listener = TcpListener::bind("127.0.0.1:6142").await.unwrap();
 
     loop {
-        let (mut socket, _) = listener.accept().await?;
+        let (socket, _) = listener.accept().await?;
 
         tokio::spawn(async move {
-            let mut buf = vec![0; 1024];
-
-            loop {
-                match socket.read(&mut buf).await {
-                    // Return value of `Ok(0)` signifies that the remote has
-                    // closed
-                    Ok(0) => return,
-                    Ok(n) => {
-                        // Copy the data back to socket
-                        if socket.write_all(&buf[..n]).await.is_err() {
-                            // Unexpected socket error. There isn't much we can
-                            // do here so just stop processing.
-                            return;
-                        }
-                    }
-                    Err(_) => {
-                        // Unexpected socket error. There isn't much we can do
-                        // here so just stop processing.
-                        return;
-                    }
-                }
-            }
+            // The read loop is in `src/lib.rs`. On an unexpected socket
+            // error, there isn't much we can do, so just stop processing.
+            let _ = echo::echo(socket).await;
         });
     }
 }
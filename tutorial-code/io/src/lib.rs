@@ -0,0 +1,90 @@
+//! The echo server of the I/O chapter, both ways, and its client.
+//!
+//! Each takes any `AsyncRead + AsyncWrite` rather than a `TcpStream`, so
+//! the tests can drive them over `tokio::io::duplex`. The binaries run them
+//! on real sockets.
+
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The size of the buffer `echo` reads into.
+pub const BUF_SIZE: usize = 1024;
+
+/// Echo with `io::copy`, which needs a reader and a writer at once: the
+/// stream is split into the two.
+///
+/// Returns how many bytes were echoed.
+pub async fn echo_copy<T: AsyncRead + AsyncWrite>(socket: T) -> io::Result<u64> {
+    let (mut rd, mut wr) = io::split(socket);
+
+    io::copy(&mut rd, &mut wr).await
+}
+
+/// Echo by hand, reading into a buffer and writing back what was read.
+pub async fn echo<T: AsyncRead + AsyncWrite + Unpin>(mut socket: T) -> io::Result<()> {
+    let mut buf = vec![0; BUF_SIZE];
+
+    loop {
+        match socket.read(&mut buf).await? {
+            // Return value of `Ok(0)` signifies that the remote has
+            // closed
+            0 => return Ok(()),
+            // Copy the data back to socket
+            n => socket.write_all(&buf[..n]).await?,
+        }
+    }
+}
+
+/// `echo` with the classic bug: `Ok(0)` isn't handled.
+///
+/// Once the client closes the connection, every `read` returns `Ok(0)`
+/// right away, writing nothing back does too, and the loop spins forever,
+/// using a whole CPU.
+pub async fn echo_without_eof<T: AsyncRead + AsyncWrite + Unpin>(mut socket: T) -> io::Result<()> {
+    let mut buf = vec![0; BUF_SIZE];
+
+    loop {
+        let n = socket.read(&mut buf).await?;
+        socket.write_all(&buf[..n]).await?;
+    }
+}
+
+/// Send `messages` to an echo server from a task of its own, while reading
+/// the echoes on this one, and return them.
+///
+/// Once done writing, the client shuts its writer down, which is how the
+/// server learns that it can close the connection. That ends the reads.
+pub async fn client<T>(socket: T, messages: Vec<Vec<u8>>) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (mut rd, mut wr) = io::split(socket);
+
+    // Write data in the background
+    let write_task = tokio::spawn(async move {
+        for message in messages {
+            wr.write_all(&message).await?;
+        }
+        wr.shutdown().await?;
+
+        // Sometimes, the rust type inferencer needs
+        // a little help
+        Ok::<_, io::Error>(())
+    });
+
+    let mut buf = vec![0; 128];
+    let mut received = vec![];
+
+    loop {
+        let n = rd.read(&mut buf).await?;
+
+        if n == 0 {
+            break;
+        }
+
+        received.extend_from_slice(&buf[..n]);
+    }
+
+    write_task.await??;
+
+    Ok(received)
+}
@@ -0,0 +1,98 @@
+use echo::{client, echo, echo_copy, echo_without_eof, BUF_SIZE};
+use std::future::Future;
+use std::time::Duration;
+use tokio::io::{self, duplex, AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::time;
+
+/// Run `server` on one end of a duplex, and `client` with `transcript` on
+/// the other, returning what the client got back.
+async fn drive<F>(server: fn(DuplexStream) -> F, transcript: Vec<Vec<u8>>) -> Vec<u8>
+where
+    F: Future + Send + 'static,
+    F::Output: Send,
+{
+    // Smaller than `BUF_SIZE`, so some writes only go through in parts.
+    let (ours, theirs) = duplex(256);
+    let server = tokio::spawn(server(theirs));
+
+    let received = time::timeout(Duration::from_secs(5), client(ours, transcript))
+        .await
+        .expect("the echo never ended")
+        .unwrap();
+
+    server.await.unwrap();
+    received
+}
+
+/// Run `server`, and close the connection without sending anything.
+async fn close<F>(server: fn(DuplexStream) -> F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (ours, theirs) = duplex(256);
+    let server = tokio::spawn(server(theirs));
+    drop(ours);
+
+    time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("the server didn't stop")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn both_servers_echo_the_same() {
+    let transcript = vec![
+        b"hello\r\n".to_vec(),
+        // Larger than the buffer of `echo`.
+        (0..BUF_SIZE * 10).map(|i| i as u8).collect(),
+        b"world\r\n".to_vec(),
+    ];
+    let expected = transcript.concat();
+
+    assert!(drive(echo, transcript.clone()).await == expected);
+    assert!(drive(echo_copy, transcript).await == expected);
+}
+
+#[tokio::test]
+async fn servers_stop_when_the_client_closes() {
+    close(echo).await.unwrap();
+    assert_eq!(close(echo_copy).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn abrupt_close_in_the_middle() {
+    let (mut ours, theirs) = duplex(256);
+    let server = tokio::spawn(echo(theirs));
+
+    ours.write_all(b"hello").await.unwrap();
+    let mut buf = [0; 5];
+    ours.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello");
+
+    // Gone without shutting down first, with data still on the way.
+    ours.write_all(&[0; 100]).await.unwrap();
+    drop(ours);
+
+    // Writing the echo back fails, and that ends the server.
+    let res = time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("the server didn't stop")
+        .unwrap();
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[tokio::test]
+async fn forgetting_eof_spins_forever() {
+    let (ours, theirs) = duplex(256);
+    let server = tokio::spawn(echo_without_eof(theirs));
+
+    drop(ours);
+
+    // Tokio makes the spinning task yield now and then, which is the only
+    // reason this test gets to run again.
+    time::sleep(Duration::from_millis(100)).await;
+    assert!(!server.is_finished());
+
+    server.abort();
+}
@@ -0,0 +1,32 @@
+//! Branches borrowing the same data.
+//!
+//! Unlike a spawned task, a branch doesn't need to own what it uses: both
+//! branches here borrow `data`, and send it to a different address. The
+//! first to succeed wins. A branch that fails doesn't match `Ok(_)`, so the
+//! other one keeps going.
+
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+/// Send `data` to `addr1` or `addr2`, whichever accepts it first.
+///
+/// Like in the chapter, failing to send it anywhere isn't an error.
+pub async fn race(data: &[u8], addr1: SocketAddr, addr2: SocketAddr) -> io::Result<()> {
+    tokio::select! {
+        Ok(_) = async {
+            let mut socket = TcpStream::connect(addr1).await?;
+            socket.write_all(data).await?;
+            Ok::<_, io::Error>(())
+        } => {}
+        Ok(_) = async {
+            let mut socket = TcpStream::connect(addr2).await?;
+            socket.write_all(data).await?;
+            Ok::<_, io::Error>(())
+        } => {}
+        else => {}
+    };
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+//! The patterns of the select chapter, as functions the tests can drive one
+//! branch at a time.
+
+pub mod borrow;
+pub mod modify;
+pub mod pattern;
+pub mod race;
+pub mod resume;
@@ -0,0 +1,56 @@
+//! Modifying a branch.
+//!
+//! The operation starts on the first even number received, and starts over
+//! on each even number received before it completes. Until there is an
+//! operation to wait on, and once it is done, its branch is disabled with
+//! an `if` precondition: a future from an `async fn` panics if it is polled
+//! again after completing.
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time;
+
+/// How long `action` takes.
+pub const ACTION: Duration = Duration::from_millis(100);
+
+/// The operation, which does nothing for `None`.
+pub async fn action(input: Option<i32>) -> Option<String> {
+    // If the input is `None`, return `None`.
+    // This could also be written as `let i = input?;`
+    let i = match input {
+        Some(input) => input,
+        None => return None,
+    };
+    time::sleep(ACTION).await;
+    Some(i.to_string())
+}
+
+/// The result of `action` for the last even number received before it
+/// completes, or `None` if the channel closes first.
+pub async fn latest_even(rx: &mut mpsc::Receiver<i32>) -> Option<String> {
+    let mut done = false;
+    let operation = action(None);
+    tokio::pin!(operation);
+
+    loop {
+        tokio::select! {
+            res = &mut operation, if !done => {
+                done = true;
+
+                if let Some(v) = res {
+                    return Some(v);
+                }
+            }
+            Some(v) = rx.recv() => {
+                if v % 2 == 0 {
+                    // `.set` is a method on `Pin`.
+                    operation.set(action(Some(v)));
+                    done = false;
+                }
+            }
+            // The channel is closed, and the operation is done or was never
+            // started.
+            else => return None,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Pattern matching the result of a branch, with an `else`.
+//!
+//! A closed channel makes `recv()` return `None`, which doesn't match
+//! `Some(v)`: the branch is disabled, and `select!` waits on the others.
+//! Once every branch is disabled, the `else` branch is evaluated.
+
+use tokio::sync::mpsc;
+
+/// The first value of either channel, or `None` once both are closed.
+pub async fn recv_either(
+    rx1: &mut mpsc::Receiver<i32>,
+    rx2: &mut mpsc::Receiver<i32>,
+) -> Option<i32> {
+    tokio::select! {
+        Some(v) = rx1.recv() => Some(v),
+        Some(v) = rx2.recv() => Some(v),
+        else => None,
+    }
+}
@@ -0,0 +1,46 @@
+//! Racing two oneshot channels.
+//!
+//! Whichever receiver completes first wins, and the other one is dropped.
+//! A receiver whose sender is dropped completes too, with an error, so it
+//! can win the race without a value.
+
+use tokio::sync::oneshot;
+
+/// Which of the two receivers completed first, and with what.
+#[derive(Debug, PartialEq)]
+pub enum Winner {
+    First(Result<&'static str, oneshot::error::RecvError>),
+    Second(Result<&'static str, oneshot::error::RecvError>),
+}
+
+/// Wait for `rx1` or `rx2`, whichever completes first.
+pub async fn race(
+    rx1: oneshot::Receiver<&'static str>,
+    rx2: oneshot::Receiver<&'static str>,
+) -> Winner {
+    tokio::select! {
+        val = rx1 => Winner::First(val),
+        val = rx2 => Winner::Second(val),
+    }
+}
+
+/// Like `race`, but saying who won in a string both handlers write to.
+///
+/// Only one handler ever runs, so both may borrow `out` mutably.
+pub async fn describe(
+    rx1: oneshot::Receiver<&'static str>,
+    rx2: oneshot::Receiver<&'static str>,
+) -> String {
+    let mut out = String::new();
+
+    tokio::select! {
+        _ = rx1 => {
+            out.push_str("rx1 completed");
+        }
+        _ = rx2 => {
+            out.push_str("rx2 completed");
+        }
+    }
+
+    out
+}
@@ -0,0 +1,39 @@
+//! Resuming an async operation across loop iterations.
+//!
+//! The operation is created once, outside the loop, and pinned, so each
+//! `select!` polls the same operation through `&mut operation`. Calling
+//! the operation inside `select!` instead would start it over on every
+//! iteration.
+
+use std::future::Future;
+use tokio::sync::mpsc;
+
+/// Why `until_even` stopped.
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    /// The operation completed.
+    Done,
+
+    /// An even number came first.
+    Even(i32),
+}
+
+/// Run `operation` until it completes, or an even number is received on
+/// `rx`.
+///
+/// Once `rx` is closed, its branch is disabled, and only the operation is
+/// waited on.
+pub async fn until_even(operation: impl Future, rx: &mut mpsc::Receiver<i32>) -> Stop {
+    tokio::pin!(operation);
+
+    loop {
+        tokio::select! {
+            _ = &mut operation => return Stop::Done,
+            Some(v) = rx.recv() => {
+                if v % 2 == 0 {
+                    return Stop::Even(v);
+                }
+            }
+        }
+    }
+}
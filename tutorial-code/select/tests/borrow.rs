@@ -0,0 +1,34 @@
+use select::borrow::race;
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+
+/// An address nothing listens on anymore.
+async fn closed() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap()
+}
+
+#[tokio::test]
+async fn sends_to_the_one_that_works() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let open = listener.local_addr().unwrap();
+    let closed = closed().await;
+
+    // Either way around, the failing branch doesn't end the race.
+    for &(addr1, addr2) in [(open, closed), (closed, open)].iter() {
+        race(b"hello", addr1, addr2).await.unwrap();
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = vec![];
+        socket.read_to_end(&mut received).await.unwrap();
+        assert_eq!(received, b"hello");
+    }
+}
+
+#[tokio::test]
+async fn nowhere_to_send_is_not_an_error() {
+    race(b"hello", closed().await, closed().await)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,62 @@
+use select::modify::{action, latest_even, ACTION};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
+
+#[tokio::test(start_paused = true)]
+async fn starts_on_the_first_even_number() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tx.send(1).await.unwrap();
+    tx.send(3).await.unwrap();
+    tx.send(2).await.unwrap();
+
+    let start = Instant::now();
+    assert_eq!(latest_even(&mut rx).await.as_deref(), Some("2"));
+    assert_eq!(start.elapsed(), ACTION);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restarts_on_each_even_number() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        tx.send(2).await.unwrap();
+        time::sleep(ACTION / 2).await;
+        tx.send(4).await.unwrap();
+        // Odd numbers leave the operation alone.
+        time::sleep(ACTION / 2).await;
+        tx.send(5).await.unwrap();
+        // Keep the channel open.
+        time::sleep(ACTION * 10).await;
+    });
+
+    let start = Instant::now();
+    assert_eq!(latest_even(&mut rx).await.as_deref(), Some("4"));
+    assert_eq!(start.elapsed(), ACTION / 2 + ACTION);
+}
+
+#[tokio::test(start_paused = true)]
+async fn closed_before_any_even_number() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tx.send(1).await.unwrap();
+    drop(tx);
+
+    // The operation's branch is disabled after `action(None)`, and the
+    // channel's once it is closed: only `else` is left.
+    assert_eq!(latest_even(&mut rx).await, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_closed_channel_lets_the_operation_finish() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tx.send(2).await.unwrap();
+    drop(tx);
+
+    assert_eq!(latest_even(&mut rx).await.as_deref(), Some("2"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn action_without_input() {
+    let start = Instant::now();
+    assert_eq!(action(None).await, None);
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
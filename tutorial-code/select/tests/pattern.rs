@@ -0,0 +1,35 @@
+use select::pattern::recv_either;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn receives_from_either() {
+    let (tx1, mut rx1) = mpsc::channel(128);
+    let (tx2, mut rx2) = mpsc::channel(128);
+
+    tx2.send(2).await.unwrap();
+    assert_eq!(recv_either(&mut rx1, &mut rx2).await, Some(2));
+
+    tx1.send(1).await.unwrap();
+    assert_eq!(recv_either(&mut rx1, &mut rx2).await, Some(1));
+}
+
+#[tokio::test]
+async fn a_closed_channel_is_skipped() {
+    let (tx1, mut rx1) = mpsc::channel(128);
+    let (tx2, mut rx2) = mpsc::channel(128);
+
+    // `rx1` returns `None` right away, which doesn't match.
+    drop(tx1);
+    tx2.send(2).await.unwrap();
+
+    assert_eq!(recv_either(&mut rx1, &mut rx2).await, Some(2));
+}
+
+#[tokio::test]
+async fn else_once_both_are_closed() {
+    let (tx1, mut rx1) = mpsc::channel::<i32>(128);
+    let (tx2, mut rx2) = mpsc::channel::<i32>(128);
+    drop((tx1, tx2));
+
+    assert_eq!(recv_either(&mut rx1, &mut rx2).await, None);
+}
@@ -0,0 +1,46 @@
+use select::race::{describe, race, Winner};
+use tokio::sync::oneshot;
+
+#[tokio::test]
+async fn first_value_wins() {
+    let (tx1, rx1) = oneshot::channel();
+    let (_tx2, rx2) = oneshot::channel();
+    tx1.send("one").unwrap();
+
+    assert_eq!(race(rx1, rx2).await, Winner::First(Ok("one")));
+
+    let (_tx1, rx1) = oneshot::channel();
+    let (tx2, rx2) = oneshot::channel();
+    tx2.send("two").unwrap();
+
+    assert_eq!(race(rx1, rx2).await, Winner::Second(Ok("two")));
+}
+
+#[tokio::test]
+async fn a_dropped_sender_wins_too() {
+    let (tx1, rx1) = oneshot::channel::<&str>();
+    let (_tx2, rx2) = oneshot::channel();
+    drop(tx1);
+
+    assert!(matches!(race(rx1, rx2).await, Winner::First(Err(_))));
+}
+
+#[tokio::test]
+async fn the_loser_is_dropped() {
+    let (tx1, rx1) = oneshot::channel();
+    let (tx2, rx2) = oneshot::channel::<&str>();
+    tx1.send("one").unwrap();
+
+    race(rx1, rx2).await;
+
+    assert!(tx2.is_closed());
+}
+
+#[tokio::test]
+async fn handlers_share_the_output() {
+    let (_tx1, rx1) = oneshot::channel();
+    let (tx2, rx2) = oneshot::channel();
+    tx2.send("two").unwrap();
+
+    assert_eq!(describe(rx1, rx2).await, "rx2 completed");
+}
@@ -0,0 +1,50 @@
+use select::resume::{until_even, Stop};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
+
+const OPERATION: Duration = Duration::from_millis(100);
+
+#[tokio::test(start_paused = true)]
+async fn odd_numbers_do_not_restart_the_operation() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tokio::spawn(async move {
+        for i in [1, 3, 5, 7].iter() {
+            time::sleep(Duration::from_millis(30)).await;
+            tx.send(*i).await.unwrap();
+        }
+    });
+
+    let start = Instant::now();
+    assert_eq!(
+        until_even(time::sleep(OPERATION), &mut rx).await,
+        Stop::Done
+    );
+    // A new operation on each number would still be going.
+    assert_eq!(start.elapsed(), OPERATION);
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_even_number_stops_it() {
+    let (tx, mut rx) = mpsc::channel(128);
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+
+    assert_eq!(
+        until_even(time::sleep(OPERATION), &mut rx).await,
+        Stop::Even(2)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_closed_channel_leaves_the_operation() {
+    let (tx, mut rx) = mpsc::channel::<i32>(128);
+    drop(tx);
+
+    let start = Instant::now();
+    assert_eq!(
+        until_even(time::sleep(OPERATION), &mut rx).await,
+        Stop::Done
+    );
+    assert_eq!(start.elapsed(), OPERATION);
+}
@@ -0,0 +1,59 @@
+//! The ways around holding a `std::sync::MutexGuard` across an `.await`,
+//! from the shared-state chapter.
+//!
+//! Each function increments the counter behind a mutex, then does something
+//! async. The version holding the guard across the `.await` doesn't
+//! compile once spawned, as its future isn't `Send`: it is in
+//! `tests/ui/guard_across_await.rs`, and `tests/not_send.rs` checks that
+//! the compiler still rejects it with the error the chapter quotes.
+
+use std::sync::{Mutex, MutexGuard};
+
+/// Stands in for any async work done after incrementing.
+pub async fn do_something_async() {
+    tokio::task::yield_now().await;
+}
+
+/// The lock is taken in a block of its own, so the guard is dropped before
+/// the `.await`.
+pub async fn increment_in_scope(mutex: &Mutex<i32>) {
+    {
+        let mut lock: MutexGuard<i32> = mutex.lock().unwrap();
+        *lock += 1;
+    } // lock goes out of scope here
+
+    do_something_async().await;
+}
+
+/// The mutex is only ever locked inside non-async methods, so the guard
+/// can't end up in an async function at all.
+#[derive(Debug, Default)]
+pub struct CanIncrement {
+    mutex: Mutex<i32>,
+}
+
+impl CanIncrement {
+    // This function is not marked async.
+    pub fn increment(&self) {
+        let mut lock = self.mutex.lock().unwrap();
+        *lock += 1;
+    }
+
+    pub fn get(&self) -> i32 {
+        *self.mutex.lock().unwrap()
+    }
+}
+
+pub async fn increment_and_do_stuff(can_incr: &CanIncrement) {
+    can_incr.increment();
+    do_something_async().await;
+}
+
+/// Tokio's mutex can be held across an `.await`. It costs more than the
+/// two other ways, which are usually better.
+pub async fn increment_with_tokio_mutex(mutex: &tokio::sync::Mutex<i32>) {
+    let mut lock = mutex.lock().await;
+    *lock += 1;
+
+    do_something_async().await;
+} // lock goes out of scope here
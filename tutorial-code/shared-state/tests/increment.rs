@@ -0,0 +1,73 @@
+use shared_state::{
+    increment_and_do_stuff, increment_in_scope, increment_with_tokio_mutex, CanIncrement,
+};
+use std::sync::{Arc, Mutex};
+
+const TASKS: i32 = 8;
+const TIMES: i32 = 100;
+
+// Each test spawns its increments on a multi-threaded runtime, which is
+// what requires the futures to be `Send`.
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn scoped_guard() {
+    let mutex = Arc::new(Mutex::new(0));
+
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let mutex = mutex.clone();
+            tokio::spawn(async move {
+                for _ in 0..TIMES {
+                    increment_in_scope(&mutex).await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(*mutex.lock().unwrap(), TASKS * TIMES);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sync_methods() {
+    let can_incr = Arc::new(CanIncrement::default());
+
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let can_incr = can_incr.clone();
+            tokio::spawn(async move {
+                for _ in 0..TIMES {
+                    increment_and_do_stuff(&can_incr).await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(can_incr.get(), TASKS * TIMES);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn tokio_mutex() {
+    let mutex = Arc::new(tokio::sync::Mutex::new(0));
+
+    let tasks: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let mutex = mutex.clone();
+            tokio::spawn(async move {
+                for _ in 0..TIMES {
+                    increment_with_tokio_mutex(&mutex).await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(*mutex.lock().await, TASKS * TIMES);
+}
@@ -0,0 +1,80 @@
+//! Checks that holding a `MutexGuard` across an `.await` still fails the
+//! way the chapter says.
+//!
+//! The chapter quotes the compiler's error. Its `error:` and `note:` lines,
+//! and the line naming the guard's type, must all be in what the compiler
+//! says about `tests/ui/guard_across_await.rs`. The rest of the quote is
+//! left alone, as its paths and line numbers vary.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The lines of the error quoted after `heading` in `markdown`.
+fn quoted_error<'a>(markdown: &'a str, heading: &str) -> Vec<&'a str> {
+    let section = &markdown[markdown.find(heading).unwrap()..];
+    let block = section.split("```text\n").nth(1).unwrap();
+    let block = &block[..block.find("```").unwrap()];
+
+    block.lines().collect()
+}
+
+/// What `rustc` says when compiling `file`, which must fail.
+fn compile_error(file: &Path) -> String {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("guard_across_await");
+
+    let output = Command::new(rustc)
+        .args([
+            "--edition",
+            "2018",
+            "--crate-type",
+            "lib",
+            "--emit",
+            "metadata",
+        ])
+        .arg("-o")
+        .arg(&out)
+        .arg(file)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success(), "{} compiles", file.display());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+/// `line` without the line numbers and markers to the left of the code.
+fn strip_gutter(line: &str) -> &str {
+    line.trim_start_matches(|c: char| " |0123456789-".contains(c))
+}
+
+#[test]
+fn guard_across_await_is_not_send() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let markdown =
+        fs::read_to_string(root.join("../../content/tokio/tutorial/shared-state.md")).unwrap();
+    let stderr = compile_error(&root.join("tests/ui/guard_across_await.rs"));
+
+    let said: Vec<_> = stderr.lines().map(strip_gutter).collect();
+
+    let checked: Vec<_> = quoted_error(&markdown, "# Holding a `MutexGuard`")
+        .into_iter()
+        .map(strip_gutter)
+        .filter(|line| {
+            line.starts_with("error:")
+                || line.starts_with("note: future")
+                || line.starts_with("has type")
+        })
+        .collect();
+    assert_eq!(checked.len(), 3, "{:?}", checked);
+
+    for line in checked {
+        assert!(
+            said.contains(&line),
+            "the chapter quotes `{}`, but the compiler now says:\n{}",
+            line,
+            stderr
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// The chapter's code holding a `MutexGuard` across an `.await`. This must
+// not compile: see `tests/not_send.rs`.
+//
+// `spawn` has the bound of `tokio::spawn`, without depending on tokio.
+
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+
+fn spawn<T: Future + Send + 'static>(_: T) {}
+
+async fn increment_and_do_stuff(mutex: &Mutex<i32>) {
+    let mut lock: MutexGuard<i32> = mutex.lock().unwrap();
+    *lock += 1;
+
+    do_something_async().await;
+} // lock goes out of scope here
+
+async fn do_something_async() {}
+
+pub fn main() {
+    static MUTEX: Mutex<i32> = Mutex::new(0);
+
+    spawn(async move {
+        increment_and_do_stuff(&MUTEX).await;
+    });
+}
@@ -0,0 +1,32 @@
+use crate::Config;
+
+use mini_redis::Frame;
+
+/// Handle an `AUTH` command, updating the connection's `authenticated` flag.
+///
+/// A wrong password leaves the connection open so the client may try again.
+pub(crate) fn authenticate(config: &Config, given: &[u8], authenticated: &mut bool) -> Frame {
+    let expected = match &config.password {
+        Some(expected) => expected,
+        None => return Frame::Error("ERR AUTH called without any password configured".to_string()),
+    };
+
+    if constant_time_eq(expected.as_bytes(), given) {
+        *authenticated = true;
+        Frame::Simple("OK".to_string())
+    } else {
+        Frame::Error("ERR invalid password".to_string())
+    }
+}
+
+/// Compare two byte strings in time that depends only on their lengths.
+///
+/// An early return on the first mismatching byte would let a client recover
+/// the password one byte at a time by timing responses.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
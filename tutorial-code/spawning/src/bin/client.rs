@@ -0,0 +1,114 @@
+//! A client for the server, written with the tools from the spawning chapter.
+//!
+//! It talks to the server twice over: first with one connection per spawned
+//! task, then with a single connection shared between tasks through a manager
+//! task. The second half is the pattern the channels chapter builds up to,
+//! and uses its `Command` enum and manager from the `channels` crate.
+//!
+//! Pass the server address as the first argument. It defaults to
+//! `127.0.0.1:6379`.
+
+use channels::{manager, Command};
+use mini_redis::client;
+use tokio::sync::oneshot;
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string());
+
+    connection_per_task(&addr).await?;
+    shared_connection(&addr).await?;
+
+    // Both tasks of each half ran concurrently, so whether a GET saw the SET
+    // depends on timing. Once everything is done, the values are there.
+    let mut client = client::connect(&addr).await?;
+    for key in ["hello", "foo"] {
+        println!("final: {} = {:?}", key, client.get(key).await?);
+    }
+
+    Ok(())
+}
+
+/// Each spawned task opens its own connection.
+async fn connection_per_task(addr: &str) -> mini_redis::Result<()> {
+    let mut setter = client::connect(addr).await?;
+    let mut getter = client::connect(addr).await?;
+
+    // `tokio::spawn` returns a `JoinHandle`. Awaiting it yields the value
+    // returned by the task.
+    let t1 = tokio::spawn(async move { setter.set("hello", "world".into()).await });
+    let t2 = tokio::spawn(async move { getter.get("hello").await });
+
+    // The outer `Result` is the task's: it is `Err` if the task panicked.
+    let set = t1.await.unwrap();
+    let get = t2.await.unwrap();
+
+    println!("tasks: SET hello = {:?}", set);
+    println!("tasks: GET hello = {:?}", get);
+
+    set?;
+    get?;
+    Ok(())
+}
+
+/// The tasks share a single connection, owned by a manager task. Requests are
+/// sent over an mpsc channel and each carries a oneshot for the response.
+async fn shared_connection(addr: &str) -> mini_redis::Result<()> {
+    let client = client::connect(addr).await?;
+
+    let (tx, rx) = channels::channel();
+    // Clone a `tx` handle for the second task
+    let tx2 = tx.clone();
+
+    let manager = tokio::spawn(manager(client, rx));
+
+    // Spawn two tasks, one setting a value and other querying for key that was
+    // set.
+    let t1 = tokio::spawn(async move {
+        let (resp_tx, resp_rx) = oneshot::channel();
+        let cmd = Command::Set {
+            key: "foo".to_string(),
+            val: b"bar".to_vec(),
+            resp: resp_tx,
+        };
+
+        // Send the SET request
+        if tx.send(cmd).await.is_err() {
+            eprintln!("connection task shutdown");
+            return;
+        }
+
+        // Await the response
+        let res = resp_rx.await;
+        println!("manager: SET foo = {:?}", res);
+    });
+
+    let t2 = tokio::spawn(async move {
+        let (resp_tx, resp_rx) = oneshot::channel();
+        let cmd = Command::Get {
+            key: "foo".to_string(),
+            resp: resp_tx,
+        };
+
+        // Send the GET request
+        if tx2.send(cmd).await.is_err() {
+            eprintln!("connection task shutdown");
+            return;
+        }
+
+        // Await the response
+        let res = resp_rx.await;
+        println!("manager: GET foo = {:?}", res);
+    });
+
+    t1.await.unwrap();
+    t2.await.unwrap();
+
+    // Both senders were moved into the tasks and dropped when they completed,
+    // so the manager's `recv` returns `None` and the manager exits.
+    manager.await.unwrap();
+
+    Ok(())
+}
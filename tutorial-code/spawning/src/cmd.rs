@@ -0,0 +1,283 @@
+//! Commands this server supports on top of the ones parsed by
+//! `mini_redis::Command`.
+//!
+//! mini-redis only parses the commands it implements itself. Everything else
+//! is recognized here from the raw frame, before the frame is handed to
+//! `Command::from_frame`. Each command is described by an entry in
+//! `COMMANDS`, which is used to check the number of arguments up front, so
+//! the code building each `Extension` can rely on it. Commands the connection
+//! may not run are rejected before their arguments are parsed.
+
+use crate::State;
+
+use bytes::Bytes;
+use mini_redis::Frame;
+use std::time::Duration;
+
+/// An extension command, parsed and ready to run.
+#[derive(Debug, PartialEq)]
+pub enum Extension {
+    /// `SAVE`: write the database to the snapshot path.
+    Save,
+
+    /// `AUTH <password>`: authenticate the connection.
+    Auth(Bytes),
+
+    /// `STATS`: a few counters describing the database.
+    Stats,
+
+    /// `TYPE <key>`: `string` if the key exists, `none` otherwise.
+    Type(String),
+
+    /// `SLOWLOG GET`: list the slowest commands seen so far.
+    SlowlogGet,
+
+    /// `SLOWLOG RESET`: forget all recorded latencies.
+    SlowlogReset,
+
+    /// `LATENCY HISTOGRAM`: command counts per latency bucket.
+    LatencyHistogram,
+
+    /// `DEBUG OBJECT <key>`: the stored length and remaining TTL of a key.
+    DebugObject(String),
+
+    /// `DEBUG SLEEP <seconds>`: block the connection for a while. Only
+    /// available when `Config::debug_commands` is set.
+    DebugSleep(Duration),
+}
+
+/// What a connection is allowed to run, checked by `Extension::from_frame`.
+#[derive(Debug, Clone, Copy)]
+pub struct Access {
+    /// Whether the connection has authenticated. Before it has, `AUTH` is the
+    /// only command available.
+    pub authenticated: bool,
+
+    /// Whether `DEBUG SLEEP` is available, see `Config::debug_commands`.
+    pub debug_commands: bool,
+}
+
+impl Access {
+    /// Every command is available.
+    pub const ALL: Access = Access {
+        authenticated: true,
+        debug_commands: true,
+    };
+}
+
+/// Describes one command, or one subcommand of a container command like
+/// `SLOWLOG`.
+struct Spec {
+    name: &'static str,
+    sub: Option<&'static str>,
+
+    /// Number of frames in the command, including the name and subcommand.
+    arity: usize,
+
+    /// Only available when `Config::debug_commands` is set.
+    debug: bool,
+}
+
+/// Every extension command. Subcommands of the same command are listed
+/// together.
+#[rustfmt::skip]
+const COMMANDS: &[Spec] = &[
+    Spec { name: "auth", sub: None, arity: 2, debug: false },
+    Spec { name: "debug", sub: Some("object"), arity: 3, debug: false },
+    Spec { name: "debug", sub: Some("sleep"), arity: 3, debug: true },
+    Spec { name: "latency", sub: Some("histogram"), arity: 2, debug: false },
+    Spec { name: "save", sub: None, arity: 1, debug: false },
+    Spec { name: "slowlog", sub: Some("get"), arity: 2, debug: false },
+    Spec { name: "slowlog", sub: Some("reset"), arity: 2, debug: false },
+    Spec { name: "stats", sub: None, arity: 1, debug: false },
+    Spec { name: "type", sub: None, arity: 2, debug: false },
+];
+
+impl Extension {
+    /// Recognize an extension command from the raw frame.
+    ///
+    /// Returns `None` when the frame should be handed to mini-redis instead,
+    /// and `Some(Err(msg))` when the command is recognized but malformed, or
+    /// not allowed by `access`.
+    pub fn from_frame(frame: &Frame, access: Access) -> Option<Result<Extension, String>> {
+        let parts = match frame {
+            Frame::Array(parts) => parts,
+            _ => return None,
+        };
+
+        let name = as_str(parts.first()?)?.to_ascii_lowercase();
+        let mut specs = COMMANDS.iter().filter(|spec| spec.name == name).peekable();
+
+        // Not one of ours.
+        specs.peek()?;
+
+        if !access.authenticated && name != "auth" {
+            return Some(Err("NOAUTH Authentication required.".to_string()));
+        }
+
+        Some(parse(&name, specs, parts, access))
+    }
+
+    /// Run the command against the server state, returning the response
+    /// frame.
+    pub(crate) async fn apply(self, state: &State) -> Frame {
+        match self {
+            Extension::Save => {
+                let db = &state.db;
+
+                // Only one snapshot may be written at a time. A second `SAVE`
+                // would race the first one on the temporary file.
+                let guard = match db.try_begin_save() {
+                    Some(guard) => guard,
+                    None => {
+                        return Frame::Error("ERR Background save already in progress".to_string())
+                    }
+                };
+
+                match db.save(guard).await {
+                    Ok(()) => Frame::Simple("OK".to_string()),
+                    Err(err) => Frame::Error(format!("ERR {}", err)),
+                }
+            }
+            // Authentication changes connection state, so it is handled by
+            // the connection loop rather than here.
+            Extension::Auth(_) => unreachable!("AUTH is handled by the connection"),
+            Extension::SlowlogGet => {
+                // Redis replies with an array per entry. A line of text per
+                // entry is easier to read from a tutorial client.
+                let entries = state.metrics.slowest();
+                Frame::Array(
+                    entries
+                        .iter()
+                        .map(|entry| Frame::Bulk(entry.to_string().into()))
+                        .collect(),
+                )
+            }
+            Extension::SlowlogReset => {
+                state.metrics.reset();
+                Frame::Simple("OK".to_string())
+            }
+            Extension::LatencyHistogram => {
+                let buckets = state.metrics.histogram();
+                Frame::Array(
+                    buckets
+                        .iter()
+                        .map(|bucket| Frame::Bulk(bucket.to_string().into()))
+                        .collect(),
+                )
+            }
+            Extension::Stats => {
+                let stats = [
+                    ("keys", state.db.len() as u64),
+                    ("evictions", state.db.evictions()),
+                ];
+                Frame::Array(
+                    stats
+                        .iter()
+                        .map(|(name, value)| Frame::Bulk(format!("{}:{}", name, value).into()))
+                        .collect(),
+                )
+            }
+            Extension::Type(key) => match state.db.inspect(&key) {
+                Some(_) => Frame::Simple("string".to_string()),
+                None => Frame::Simple("none".to_string()),
+            },
+            Extension::DebugObject(key) => match state.db.inspect(&key) {
+                Some(info) => {
+                    // Like Redis' `TTL`, -1 means the key never expires.
+                    let ttl = match info.ttl {
+                        Some(ttl) => ttl.as_millis() as i64,
+                        None => -1,
+                    };
+                    Frame::Simple(format!("serializedlength:{} ttl:{}", info.len, ttl))
+                }
+                None => Frame::Error("ERR no such key".to_string()),
+            },
+            Extension::DebugSleep(duration) => {
+                tokio::time::sleep(duration).await;
+                Frame::Simple("OK".to_string())
+            }
+        }
+    }
+}
+
+/// Match `parts` against the specs sharing `name`, then build the command.
+fn parse<'a>(
+    name: &str,
+    mut specs: impl Iterator<Item = &'a Spec>,
+    parts: &[Frame],
+    access: Access,
+) -> Result<Extension, String> {
+    let sub = parts.get(1).and_then(as_str).map(str::to_ascii_lowercase);
+
+    let spec = specs
+        .find(|spec| spec.sub.is_none() || spec.sub == sub.as_deref())
+        .ok_or_else(|| match &sub {
+            Some(_) => format!("ERR unknown subcommand for '{}' command", name),
+            None => wrong_arity(name, None),
+        })?;
+
+    if parts.len() != spec.arity {
+        return Err(wrong_arity(name, spec.sub));
+    }
+
+    if spec.debug && !access.debug_commands {
+        return Err("ERR DEBUG commands are disabled".to_string());
+    }
+
+    // Arguments after the name and subcommand.
+    let skip = if spec.sub.is_some() { 2 } else { 1 };
+    let args = parts[skip..]
+        .iter()
+        .map(|part| match part {
+            Frame::Bulk(bytes) => Ok(bytes.clone()),
+            Frame::Simple(string) => Ok(Bytes::copy_from_slice(string.as_bytes())),
+            _ => Err("ERR Protocol error: expected a string argument".to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ext = match (spec.name, spec.sub) {
+        ("auth", _) => Extension::Auth(args[0].clone()),
+        ("debug", Some("object")) => Extension::DebugObject(key(&args[0])?),
+        ("debug", Some("sleep")) => {
+            let duration = std::str::from_utf8(&args[0])
+                .ok()
+                .and_then(|secs| secs.parse::<f64>().ok())
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| "ERR value is not a valid float".to_string())?;
+            Extension::DebugSleep(duration)
+        }
+        ("latency", Some("histogram")) => Extension::LatencyHistogram,
+        ("save", _) => Extension::Save,
+        ("slowlog", Some("get")) => Extension::SlowlogGet,
+        ("slowlog", Some("reset")) => Extension::SlowlogReset,
+        ("stats", _) => Extension::Stats,
+        ("type", _) => Extension::Type(key(&args[0])?),
+        (name, sub) => unreachable!("no parser for {} {:?}", name, sub),
+    };
+
+    Ok(ext)
+}
+
+/// The textual contents of a bulk or simple string frame.
+fn as_str(frame: &Frame) -> Option<&str> {
+    match frame {
+        Frame::Bulk(bytes) => std::str::from_utf8(bytes).ok(),
+        Frame::Simple(string) => Some(string),
+        _ => None,
+    }
+}
+
+fn key(arg: &Bytes) -> Result<String, String> {
+    String::from_utf8(arg.to_vec()).map_err(|_| "ERR key is not valid UTF-8".to_string())
+}
+
+fn wrong_arity(name: &str, sub: Option<&str>) -> String {
+    match sub {
+        Some(sub) => format!(
+            "ERR wrong number of arguments for '{}|{}' command",
+            name, sub
+        ),
+        None => format!("ERR wrong number of arguments for '{}' command", name),
+    }
+}
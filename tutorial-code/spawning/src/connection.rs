@@ -0,0 +1,297 @@
+//! A frame connection that batches responses to pipelined requests.
+//!
+//! `mini_redis::Connection` flushes after every frame it writes. That is
+//! simple, but a client that pipelines a hundred requests then pays for a
+//! hundred separate writes to the socket. This version only flushes once it
+//! runs out of requests to answer, that is, right before it would have to wait
+//! for the client.
+//!
+//! It also guards against clients declaring enormous frames. A bulk string
+//! header says up front how many bytes will follow, so an oversized frame is
+//! rejected as soon as its header arrives, instead of after buffering it.
+
+use bytes::{Buf, BytesMut};
+use futures::FutureExt;
+use mini_redis::frame::{self, Frame};
+use std::fmt;
+use std::io::{self, Cursor, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+#[derive(Debug)]
+pub struct Connection<S> {
+    // Writes go through a `BufWriter`, and only reach the socket when it is
+    // full or when `flush` is called.
+    stream: BufWriter<S>,
+
+    // The buffer for reading frames.
+    buffer: BytesMut,
+
+    // Largest bulk string or array a peer may declare.
+    max_frame_size: Option<usize>,
+}
+
+/// A peer declared a frame larger than the connection's limit.
+#[derive(Debug)]
+pub struct FrameTooLarge {
+    pub declared: usize,
+    pub limit: usize,
+}
+
+/// The peer closed the connection partway through sending a frame.
+#[derive(Debug)]
+pub struct TruncatedFrame {
+    /// Bytes of the incomplete frame that were received.
+    pub buffered: usize,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    pub fn new(stream: S) -> Connection<S> {
+        Connection::with_limits(stream, 4 * 1024, None)
+    }
+
+    /// Create a connection with a read buffer of `read_buffer_size` bytes
+    /// that rejects frames larger than `max_frame_size`.
+    pub fn with_limits(
+        stream: S,
+        read_buffer_size: usize,
+        max_frame_size: Option<usize>,
+    ) -> Connection<S> {
+        Connection {
+            stream: BufWriter::new(stream),
+            buffer: BytesMut::with_capacity(read_buffer_size),
+            max_frame_size,
+        }
+    }
+
+    /// Read a single `Frame` value from the underlying stream.
+    ///
+    /// Before waiting for more data from the peer, any responses written so far
+    /// are flushed. Without this, a client waiting on a response would wait
+    /// forever.
+    ///
+    /// Returns `None` if the peer closed the connection cleanly, after a
+    /// complete frame. If it closes partway through a frame instead, the error
+    /// is a `TruncatedFrame`. If the peer declares a frame over the size
+    /// limit, the error is a `FrameTooLarge`.
+    ///
+    /// A closed connection may only be half closed: the peer is done sending
+    /// requests, but still waiting for the responses. Call `flush` before
+    /// dropping the connection.
+    pub async fn read_frame(&mut self) -> mini_redis::Result<Option<Frame>> {
+        loop {
+            if let Some(frame) = self.parse_frame()? {
+                return Ok(Some(frame));
+            }
+
+            if let Some(limit) = self.max_frame_size {
+                self.check_limit(limit)?;
+            }
+
+            // There is no complete frame buffered. If the peer already sent
+            // more data, it can be read without waiting, and the responses
+            // written so far can keep accumulating. `now_or_never` polls the
+            // read exactly once: `None` means the read would have to wait.
+            let n = match self.stream.read_buf(&mut self.buffer).now_or_never() {
+                Some(res) => res?,
+                None => {
+                    // The peer is (probably) waiting on us. Send everything
+                    // before going to sleep.
+                    self.stream.flush().await?;
+                    self.stream.read_buf(&mut self.buffer).await?
+                }
+            };
+
+            if n == 0 {
+                // The remote closed the connection. For this to be a clean
+                // shutdown, there should be no data in the read buffer. If
+                // there is, this means that the peer closed the socket while
+                // sending a frame.
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                } else {
+                    return Err(TruncatedFrame {
+                        buffered: self.buffer.len(),
+                    }
+                    .into());
+                }
+            }
+        }
+    }
+
+    fn parse_frame(&mut self) -> mini_redis::Result<Option<Frame>> {
+        let mut buf = Cursor::new(&self.buffer[..]);
+
+        match Frame::check(&mut buf) {
+            Ok(_) => {
+                let len = buf.position() as usize;
+                buf.set_position(0);
+
+                let frame = Frame::parse(&mut buf)?;
+                self.buffer.advance(len);
+
+                Ok(Some(frame))
+            }
+            Err(frame::Error::Incomplete) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check the partially received frame against `limit`.
+    fn check_limit(&self, limit: usize) -> Result<(), FrameTooLarge> {
+        // A frame that doesn't declare anything large can still grow without
+        // bound, for example a simple string that never ends. Allow a little
+        // slack for the headers.
+        if self.buffer.len() > limit + 1024 {
+            return Err(FrameTooLarge {
+                declared: self.buffer.len(),
+                limit,
+            });
+        }
+
+        match check_declared(&mut Cursor::new(&self.buffer[..]), limit) {
+            Err(Declared::TooLarge(declared)) => Err(FrameTooLarge { declared, limit }),
+            // Either the whole buffered prefix is fine, or the rest of the
+            // headers haven't arrived yet and will be checked next time.
+            Ok(()) | Err(Declared::Incomplete) => Ok(()),
+        }
+    }
+
+    /// Write a single `Frame` value to the write buffer.
+    ///
+    /// Nothing is sent until the buffer fills up, `read_frame` runs out of
+    /// buffered requests, or `flush` is called.
+    pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        // Encoding into a plain `Vec` first sidesteps the problem of recursive
+        // `async fn`s, so nested arrays work too.
+        let mut encoded = vec![];
+        encode(frame, &mut encoded)?;
+
+        self.stream.write_all(&encoded).await
+    }
+
+    /// Send any buffered responses to the peer.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush().await
+    }
+}
+
+fn encode(frame: &Frame, dst: &mut Vec<u8>) -> io::Result<()> {
+    match frame {
+        Frame::Simple(val) => write!(dst, "+{}\r\n", val),
+        Frame::Error(val) => write!(dst, "-{}\r\n", val),
+        Frame::Integer(val) => write!(dst, ":{}\r\n", val),
+        Frame::Null => write!(dst, "$-1\r\n"),
+        Frame::Bulk(val) => {
+            write!(dst, "${}\r\n", val.len())?;
+            dst.extend_from_slice(val);
+            write!(dst, "\r\n")
+        }
+        Frame::Array(entries) => {
+            write!(dst, "*{}\r\n", entries.len())?;
+
+            for entry in entries {
+                encode(entry, dst)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+enum Declared {
+    Incomplete,
+    TooLarge(usize),
+}
+
+/// Walk the frame headers in `src`, checking every declared bulk string and
+/// array length against `limit`. Unlike `Frame::check`, this never needs the
+/// frame's contents, only its headers.
+fn check_declared(src: &mut Cursor<&[u8]>, limit: usize) -> Result<(), Declared> {
+    if !src.has_remaining() {
+        return Err(Declared::Incomplete);
+    }
+
+    match src.get_u8() {
+        b'+' | b'-' | b':' => get_line(src).map(drop),
+        b'$' => {
+            let line = get_line(src)?;
+
+            // `$-1` is the null bulk string.
+            if line == b"-1" {
+                return Ok(());
+            }
+
+            let len = parse_len(line)?;
+            if len > limit {
+                return Err(Declared::TooLarge(len));
+            }
+
+            if src.remaining() < len + 2 {
+                return Err(Declared::Incomplete);
+            }
+            src.advance(len + 2);
+
+            Ok(())
+        }
+        b'*' => {
+            let len = parse_len(get_line(src)?)?;
+            if len > limit {
+                return Err(Declared::TooLarge(len));
+            }
+
+            for _ in 0..len {
+                check_declared(src, limit)?;
+            }
+
+            Ok(())
+        }
+        // Not a valid frame. `Frame::check` reports the real error.
+        _ => Ok(()),
+    }
+}
+
+fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Declared> {
+    let start = src.position() as usize;
+    let buf: &'a [u8] = src.get_ref();
+
+    match buf[start..].windows(2).position(|w| w == b"\r\n") {
+        Some(pos) => {
+            src.set_position((start + pos + 2) as u64);
+            Ok(&buf[start..start + pos])
+        }
+        None => Err(Declared::Incomplete),
+    }
+}
+
+fn parse_len(line: &[u8]) -> Result<usize, Declared> {
+    // A malformed length is left for `Frame::check` to report. Treating it as
+    // zero lets the walk carry on harmlessly.
+    Ok(std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse().ok())
+        .unwrap_or(0))
+}
+
+impl fmt::Display for FrameTooLarge {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "frame of {} bytes exceeds the limit of {} bytes",
+            self.declared, self.limit
+        )
+    }
+}
+
+impl std::error::Error for FrameTooLarge {}
+
+impl fmt::Display for TruncatedFrame {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "connection closed with an incomplete frame of {} bytes",
+            self.buffered
+        )
+    }
+}
+
+impl std::error::Error for TruncatedFrame {}
@@ -0,0 +1,307 @@
+use crate::eviction::{EvictionPolicy, ScanLru};
+use crate::snapshot::{self, Entry};
+
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Handle to the database shared by all connections.
+///
+/// Cloning the handle is cheap, all clones refer to the same data.
+#[derive(Clone)]
+pub struct Db {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    // A std `Mutex` is fine here: the lock is never held across an `.await`.
+    state: Mutex<State>,
+
+    // Number of keys dropped to stay under `max_keys`.
+    evictions: AtomicU64,
+
+    // Where `SAVE` writes the snapshot.
+    snapshot_path: PathBuf,
+
+    // Set while a snapshot is being written.
+    saving: AtomicBool,
+}
+
+struct State {
+    entries: HashMap<String, Bytes>,
+
+    // When keys set with an expiration stop being visible. Expired keys are
+    // removed lazily, the next time they are looked up.
+    expirations: HashMap<String, Instant>,
+
+    // Tracks key usage, and picks which key to drop when full.
+    policy: Box<dyn EvictionPolicy>,
+
+    // When set, the database never holds more than this many keys.
+    max_keys: Option<usize>,
+}
+
+/// What `DEBUG OBJECT` reports about a key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyInfo {
+    /// Length of the value in bytes.
+    pub len: usize,
+
+    /// Time until the key expires, or `None` if it never does.
+    pub ttl: Option<Duration>,
+}
+
+/// Proof that the caller is the only one currently writing a snapshot.
+///
+/// Returned by [`Db::try_begin_save`]. Another save can start once the guard
+/// is dropped.
+pub struct SaveGuard {
+    shared: Arc<Shared>,
+}
+
+impl Db {
+    /// Create an empty database that snapshots to `snapshot_path`.
+    pub fn new(snapshot_path: impl Into<PathBuf>) -> Db {
+        Db::with_entries(snapshot_path.into(), HashMap::new())
+    }
+
+    /// Create a database populated from the snapshot at `snapshot_path`.
+    ///
+    /// A missing snapshot is not an error, the database just starts empty.
+    pub fn load(snapshot_path: impl Into<PathBuf>) -> io::Result<Db> {
+        let snapshot_path = snapshot_path.into();
+
+        let entries = match snapshot::load(&snapshot_path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Db::with_entries(snapshot_path, entries))
+    }
+
+    fn with_entries(snapshot_path: PathBuf, snapshot: HashMap<String, Entry>) -> Db {
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+
+        let mut entries = HashMap::with_capacity(snapshot.len());
+        let mut expirations = HashMap::new();
+        let mut policy = ScanLru::new();
+
+        for (key, entry) in snapshot {
+            if let Some(expires_at) = entry.expires_at {
+                // Keys that expired while the server was down are dropped.
+                match expires_at.duration_since(wall_now) {
+                    Ok(ttl) if ttl > Duration::ZERO => {
+                        expirations.insert(key.clone(), now + ttl);
+                    }
+                    _ => continue,
+                }
+            }
+
+            policy.touch(&key);
+            entries.insert(key, entry.value);
+        }
+
+        Db {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    entries,
+                    expirations,
+                    policy: Box::new(policy),
+                    max_keys: None,
+                }),
+                evictions: AtomicU64::new(0),
+                snapshot_path,
+                saving: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Cap the number of stored keys. Once full, each `SET` of a new key
+    /// evicts one according to the eviction policy.
+    pub fn set_max_keys(&self, max_keys: Option<usize>) {
+        self.shared.state.lock().unwrap().max_keys = max_keys;
+    }
+
+    /// Replace the eviction policy. It starts out knowing every stored key.
+    pub fn set_eviction_policy(&self, mut policy: Box<dyn EvictionPolicy>) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        for key in state.entries.keys() {
+            policy.touch(key);
+        }
+        state.policy = policy;
+    }
+
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.expire(key);
+
+        let value = state.entries.get(key).cloned();
+
+        if value.is_some() {
+            state.policy.touch(key);
+        }
+
+        value
+    }
+
+    pub fn set(&self, key: String, value: Bytes) {
+        self.insert(key, value, None);
+    }
+
+    /// Like `set`, but the key disappears once `expire` has elapsed.
+    pub fn set_expires(&self, key: String, value: Bytes, expire: Duration) {
+        self.insert(key, value, Some(Instant::now() + expire));
+    }
+
+    fn insert(&self, key: String, value: Bytes, expires_at: Option<Instant>) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if let Some(max_keys) = state.max_keys {
+            // Overwriting a key doesn't grow the database.
+            while !state.entries.contains_key(&key) && state.entries.len() >= max_keys {
+                let victim = match state.policy.victim() {
+                    Some(victim) => victim,
+                    None => break,
+                };
+
+                state.remove(&victim);
+                self.shared.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // A plain `SET` clears any previous expiration.
+        match expires_at {
+            Some(when) => state.expirations.insert(key.clone(), when),
+            None => state.expirations.remove(&key),
+        };
+
+        state.policy.touch(&key);
+        state.entries.insert(key, value);
+    }
+
+    /// Describe the value stored at `key`, without counting as an access.
+    pub fn inspect(&self, key: &str) -> Option<KeyInfo> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.expire(key);
+
+        let len = state.entries.get(key)?.len();
+        let ttl = state
+            .expirations
+            .get(key)
+            .map(|when| when.saturating_duration_since(Instant::now()));
+
+        Some(KeyInfo { len, ttl })
+    }
+
+    /// Number of stored keys.
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of keys evicted to stay under `max_keys`.
+    pub fn evictions(&self) -> u64 {
+        self.shared.evictions.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot_path(&self) -> &Path {
+        &self.shared.snapshot_path
+    }
+
+    /// Claim the right to write a snapshot, or `None` if a save is already in
+    /// progress.
+    pub fn try_begin_save(&self) -> Option<SaveGuard> {
+        let busy = self.shared.saving.swap(true, Ordering::AcqRel);
+
+        if busy {
+            None
+        } else {
+            Some(SaveGuard {
+                shared: self.shared.clone(),
+            })
+        }
+    }
+
+    /// Write the current contents of the database to the snapshot path.
+    ///
+    /// Returns once the snapshot is durably on disk.
+    pub async fn save(&self, guard: SaveGuard) -> io::Result<()> {
+        // Copying the map is cheap: each `Bytes` value is reference counted,
+        // so only the keys and the table itself are copied. The lock is
+        // released as soon as the copy is made.
+        let entries = self.shared.state.lock().unwrap().to_snapshot();
+        let path = self.shared.snapshot_path.clone();
+
+        // Encoding and writing a large map is blocking work that would stall
+        // every other task scheduled on this worker thread. Run it on the
+        // blocking pool instead. The guard moves along so the save is
+        // considered in progress until the file is in place, even if the
+        // connection that asked for it goes away.
+        tokio::task::spawn_blocking(move || {
+            let _guard = guard;
+            snapshot::write(&path, &entries)
+        })
+        .await?
+    }
+}
+
+impl State {
+    /// Remove `key` if it has expired.
+    fn expire(&mut self, key: &str) {
+        let expired = match self.expirations.get(key) {
+            Some(&when) => when <= Instant::now(),
+            None => false,
+        };
+
+        if expired {
+            self.remove(key);
+        }
+    }
+
+    /// Copy the live entries for a snapshot. Keys that have expired but not
+    /// been removed yet are left out.
+    fn to_snapshot(&self) -> HashMap<String, Entry> {
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+
+        let mut snapshot = HashMap::with_capacity(self.entries.len());
+
+        for (key, value) in &self.entries {
+            let expires_at = match self.expirations.get(key) {
+                Some(&when) if when <= now => continue,
+                Some(&when) => Some(wall_now + (when - now)),
+                None => None,
+            };
+
+            let entry = Entry {
+                value: value.clone(),
+                expires_at,
+            };
+            snapshot.insert(key.clone(), entry);
+        }
+
+        snapshot
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+        self.expirations.remove(key);
+        self.policy.remove(key);
+    }
+}
+
+impl Drop for SaveGuard {
+    fn drop(&mut self) {
+        self.shared.saving.store(false, Ordering::Release);
+    }
+}
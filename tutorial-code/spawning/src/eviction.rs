@@ -0,0 +1,60 @@
+//! Choosing which key to drop once the database is full.
+
+use std::collections::HashMap;
+
+/// Decides which key is evicted when a `SET` would exceed `max_keys`.
+///
+/// The database reports every access and removal, then asks for a victim when
+/// it needs room.
+pub trait EvictionPolicy: Send {
+    /// `key` was read or written.
+    fn touch(&mut self, key: &str);
+
+    /// `key` is no longer stored.
+    fn remove(&mut self, key: &str);
+
+    /// Pick the key to evict, or `None` if no keys are tracked.
+    fn victim(&mut self) -> Option<String>;
+}
+
+/// Least-recently-used eviction by scanning every key.
+///
+/// Each key is stamped with a counter that increases on every access. Finding
+/// the victim is O(n) in the number of keys, which is plenty for a tutorial.
+/// A production cache would keep the keys in a linked list ordered by access
+/// instead.
+#[derive(Debug, Default)]
+pub struct ScanLru {
+    clock: u64,
+    last_used: HashMap<String, u64>,
+}
+
+impl ScanLru {
+    pub fn new() -> ScanLru {
+        ScanLru::default()
+    }
+}
+
+impl EvictionPolicy for ScanLru {
+    fn touch(&mut self, key: &str) {
+        self.clock += 1;
+
+        match self.last_used.get_mut(key) {
+            Some(last_used) => *last_used = self.clock,
+            None => {
+                self.last_used.insert(key.to_string(), self.clock);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.last_used.remove(key);
+    }
+
+    fn victim(&mut self) -> Option<String> {
+        self.last_used
+            .iter()
+            .min_by_key(|(_, &last_used)| last_used)
+            .map(|(key, _)| key.clone())
+    }
+}
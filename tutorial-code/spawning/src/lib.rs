@@ -0,0 +1,250 @@
+//! The Redis server from the spawning chapter.
+//!
+//! The chapter stops once connections are processed concurrently. The code
+//! here keeps going, adding the features readers tend to ask about next, while
+//! keeping the same shape: an accept loop that spawns one task per socket.
+
+mod auth;
+
+pub mod cmd;
+use cmd::{Access, Extension};
+
+pub mod connection;
+use connection::{Connection, FrameTooLarge, TruncatedFrame};
+
+mod db;
+pub use db::{Db, KeyInfo, SaveGuard};
+
+mod eviction;
+pub use eviction::{EvictionPolicy, ScanLru};
+
+mod metrics;
+use metrics::Metrics;
+
+mod server;
+pub use server::{BuildError, Server, ServerBuilder, ShutdownHandle};
+
+pub mod snapshot;
+
+use bytes::Bytes;
+use mini_redis::Frame;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Server settings. Set through [`ServerBuilder`].
+#[derive(Debug)]
+pub struct Config {
+    /// Addresses to listen on.
+    pub addrs: Vec<SocketAddr>,
+
+    /// When set, at most this many connections are served at once.
+    pub max_connections: Option<usize>,
+
+    /// When set, connections that send nothing for this long are closed.
+    pub idle_timeout: Option<Duration>,
+
+    /// Where `SAVE` writes the database, and where it is loaded from on
+    /// startup.
+    pub snapshot_path: PathBuf,
+
+    /// When set, `SET`s of larger values are rejected.
+    pub max_value_size: Option<usize>,
+
+    /// When set, a connection must `AUTH` with this password before it may
+    /// run any other command.
+    pub password: Option<String>,
+
+    /// When set, the database holds at most this many keys, evicting the
+    /// least recently used one to make room.
+    pub max_keys: Option<usize>,
+
+    /// Enables `DEBUG SLEEP`, which tests use to simulate a slow command.
+    pub debug_commands: bool,
+
+    /// Initial capacity of each connection's read buffer.
+    pub read_buffer_size: usize,
+
+    /// When set, a client declaring a bulk string or array larger than this
+    /// gets an error and is disconnected, before anything is allocated for it.
+    pub max_frame_size: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            addrs: vec![
+                SocketAddr::from((Ipv4Addr::LOCALHOST, 6379)),
+                SocketAddr::from((Ipv6Addr::LOCALHOST, 6379)),
+            ],
+            max_connections: None,
+            idle_timeout: None,
+            snapshot_path: PathBuf::from("dump.snap"),
+            max_value_size: None,
+            password: None,
+            max_keys: None,
+            debug_commands: false,
+            read_buffer_size: 4 * 1024,
+            max_frame_size: None,
+        }
+    }
+}
+
+/// Everything a connection needs access to.
+pub(crate) struct State {
+    pub(crate) db: Db,
+    pub(crate) config: Config,
+    pub(crate) metrics: Metrics,
+}
+
+impl State {
+    pub(crate) fn new(db: Db, config: Config) -> Arc<State> {
+        db.set_max_keys(config.max_keys);
+
+        Arc::new(State {
+            db,
+            config,
+            metrics: Metrics::new(),
+        })
+    }
+}
+
+/// Serve a single client until it disconnects.
+async fn process<S>(socket: S, state: Arc<State>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    use mini_redis::Command::{self, Get, Set};
+
+    let db = &state.db;
+
+    // Our `Connection` handles parsing frames from the socket, and buffers
+    // responses so pipelined requests are answered with a single write.
+    let mut connection = Connection::with_limits(
+        socket,
+        state.config.read_buffer_size,
+        state.config.max_frame_size,
+    );
+
+    // Authentication is tracked per connection. Without a password, every
+    // connection starts out authenticated.
+    let mut authenticated = state.config.password.is_none();
+
+    loop {
+        // Use `read_frame` to receive a command from the connection. Idle
+        // clients are dropped once the timeout elapses.
+        let read = connection.read_frame();
+        let res = match state.config.idle_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, read).await {
+                Ok(res) => res,
+                Err(_) => break,
+            },
+            None => read.await,
+        };
+
+        let frame = match res {
+            Ok(Some(frame)) => frame,
+            // The client closed its side after a complete frame. It may have
+            // only shut down its write half, and still be waiting to read the
+            // responses, so they are flushed below before returning.
+            Ok(None) => break,
+            Err(err) => {
+                if let Some(err) = err.downcast_ref::<FrameTooLarge>() {
+                    // Tell the client why it is being disconnected.
+                    let response = Frame::Error(format!("ERR Protocol error: {}", err));
+                    let _ = connection.write_frame(&response).await;
+                } else if let Some(err) = err.downcast_ref::<TruncatedFrame>() {
+                    // The client can't send the rest of the frame anymore, so
+                    // there is nothing to reply to.
+                    eprintln!("protocol error: {}", err);
+                }
+                // Anything else is a broken connection, and there is no one
+                // to tell.
+                break;
+            }
+        };
+
+        let start = Instant::now();
+        let (name, key) = describe(&frame);
+
+        // `Command::from_frame` only knows the commands mini-redis implements.
+        // Anything else is recognized by looking at the raw frame first.
+        let access = Access {
+            authenticated,
+            debug_commands: state.config.debug_commands,
+        };
+        let response = match Extension::from_frame(&frame, access) {
+            Some(Ok(Extension::Auth(password))) => {
+                auth::authenticate(&state.config, &password, &mut authenticated)
+            }
+            Some(Ok(ext)) => ext.apply(&state).await,
+            Some(Err(msg)) => Frame::Error(msg),
+            None if !authenticated => Frame::Error("NOAUTH Authentication required.".to_string()),
+            None => match Command::from_frame(frame).unwrap() {
+                Set(cmd) => match state.config.max_value_size {
+                    Some(max) if cmd.value().len() > max => {
+                        Frame::Error(format!("ERR value is larger than {} bytes", max))
+                    }
+                    _ => {
+                        let key = cmd.key().to_string();
+                        let value = cmd.value().clone();
+
+                        match cmd.expire() {
+                            Some(expire) => db.set_expires(key, value, expire),
+                            None => db.set(key, value),
+                        }
+                        Frame::Simple("OK".to_string())
+                    }
+                },
+                Get(cmd) => {
+                    if let Some(value) = db.get(cmd.key()) {
+                        Frame::Bulk(value)
+                    } else {
+                        Frame::Null
+                    }
+                }
+                cmd => panic!("unimplemented {:?}", cmd),
+            },
+        };
+
+        // Queue the response. It is sent once there are no more requests
+        // ready to be processed.
+        connection.write_frame(&response).await.unwrap();
+
+        state.metrics.record(name, key, start.elapsed());
+    }
+
+    // Send whatever is left in the write buffer. Every way out of the loop
+    // ends up here, including a client that half closed the connection.
+    if let Err(err) = connection.flush().await {
+        eprintln!("failed to flush responses: {}", err);
+    }
+}
+
+/// The command name and, if there is one, the key of a command frame.
+///
+/// The argument of `AUTH` is a password, so it is left out of the slowlog.
+///
+/// Cloning `Bytes` only bumps a reference count, so this is cheap enough to do
+/// for every command.
+fn describe(frame: &Frame) -> (Bytes, Option<Bytes>) {
+    let as_bytes = |frame: &Frame| match frame {
+        Frame::Bulk(bytes) => Some(bytes.clone()),
+        Frame::Simple(string) => Some(Bytes::copy_from_slice(string.as_bytes())),
+        _ => None,
+    };
+
+    match frame {
+        Frame::Array(parts) => {
+            let name = parts.first().and_then(as_bytes).unwrap_or_default();
+            let key = match &name[..] {
+                name if name.eq_ignore_ascii_case(b"auth") => None,
+                _ => parts.get(1).and_then(as_bytes),
+            };
+            (name, key)
+        }
+        _ => (Bytes::new(), None),
+    }
+}
@@ -1,56 +1,35 @@
-use mini_redis::{Connection, Frame};
-use tokio::net::{TcpListener, TcpStream};
+use spawning::ServerBuilder;
 
 #[tokio::main]
 async fn main() {
-    // Bind the listener to the address
-    let listener = TcpListener::bind("127.0.0.1:6379").await.unwrap();
+    // By default the server listens on both the IPv4 and IPv6 loopback
+    // addresses, and picks up where the last `SAVE` left off.
+    let mut builder = ServerBuilder::new();
 
-    loop {
-        // The second item contains the ip and port of the new connection.
-        let (socket, _) = listener.accept().await.unwrap();
-
-        // A new task is spawned for each inbound socket.  The socket is
-        // moved to the new task and processed there.
-        tokio::spawn(async move {
-            process(socket).await;
-        });
+    // Set `SPAWNING_PASSWORD` to require clients to `AUTH` first.
+    if let Ok(password) = std::env::var("SPAWNING_PASSWORD") {
+        builder = builder.password(password);
     }
-}
 
-async fn process(socket: TcpStream) {
-    use mini_redis::Command::{self, Get, Set};
-    use std::collections::HashMap;
+    // Set `SPAWNING_ADDR` to listen on that address alone instead, such as
+    // `127.0.0.1:0` for a port picked by the OS.
+    if let Ok(addr) = std::env::var("SPAWNING_ADDR") {
+        builder = builder.addr(addr.parse().expect("invalid SPAWNING_ADDR"));
+    }
 
-    // A hashmap is used to store data
-    let mut db = HashMap::new();
+    let server = builder.build().await.unwrap();
 
-    // Connection, provided by `mini-redis`, handles parsing frames from
-    // the socket
-    let mut connection = Connection::new(socket);
+    // Once this is printed, the server is accepting connections.
+    for addr in server.local_addrs() {
+        println!("listening on {}", addr);
+    }
 
-    // Use `read_frame` to receive a command from the connection.
-    while let Some(frame) = connection.read_frame().await.unwrap() {
-        let response = match Command::from_frame(frame).unwrap() {
-            Set(cmd) => {
-                // The value is stored as `Vec<u8>`
-                db.insert(cmd.key().to_string(), cmd.value().to_vec());
-                Frame::Simple("OK".to_string())
-            }
-            Get(cmd) => {
-                if let Some(value) = db.get(cmd.key()) {
-                    // `Frame::Bulk` expects data to be of type `Bytes`. This
-                    // type will be covered later in the tutorial. For now,
-                    // `&Vec<u8>` is converted to `Bytes` using `into()`.
-                    Frame::Bulk(value.clone().into())
-                } else {
-                    Frame::Null
-                }
-            }
-            cmd => panic!("unimplemented {:?}", cmd),
-        };
+    // Stop accepting connections on ctrl-c.
+    let shutdown = server.shutdown_handle();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c().await.unwrap();
+        shutdown.shutdown();
+    });
 
-        // Write the response to the client
-        connection.write_frame(&response).await.unwrap();
-    }
+    server.run().await;
 }
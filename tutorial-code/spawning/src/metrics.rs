@@ -0,0 +1,136 @@
+//! Command latency tracking, exposed through `SLOWLOG` and `LATENCY`.
+//!
+//! Every command passes through `record`, so it has to stay cheap: the
+//! histogram is a handful of atomics and the slow log is a short list behind a
+//! std `Mutex` that is only locked for a few comparisons.
+
+use bytes::Bytes;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many entries `SLOWLOG GET` keeps.
+const SLOWLOG_LEN: usize = 10;
+
+/// Upper bounds of the histogram buckets, in microseconds. Anything slower
+/// than the last bound lands in a final overflow bucket.
+const BUCKETS_MICROS: [u64; 5] = [100, 1_000, 10_000, 100_000, 1_000_000];
+
+pub(crate) struct Metrics {
+    buckets: [AtomicU64; BUCKETS_MICROS.len() + 1],
+    slowlog: Mutex<Vec<SlowEntry>>,
+}
+
+/// A recorded slow command.
+#[derive(Debug, Clone)]
+pub(crate) struct SlowEntry {
+    pub(crate) name: Bytes,
+    pub(crate) key: Option<Bytes>,
+    pub(crate) duration: Duration,
+}
+
+/// The number of commands that completed within `le`.
+#[derive(Debug)]
+pub(crate) struct Bucket {
+    /// `None` for the overflow bucket.
+    pub(crate) le: Option<Duration>,
+    pub(crate) count: u64,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Metrics {
+        Metrics {
+            buckets: Default::default(),
+            slowlog: Mutex::new(Vec::with_capacity(SLOWLOG_LEN + 1)),
+        }
+    }
+
+    /// Record that the command `name` took `duration`.
+    pub(crate) fn record(&self, name: Bytes, key: Option<Bytes>, duration: Duration) {
+        let micros = duration.as_micros() as u64;
+        let idx = BUCKETS_MICROS
+            .iter()
+            .position(|&bound| micros <= bound)
+            .unwrap_or(BUCKETS_MICROS.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+
+        let mut slowlog = self.slowlog.lock().unwrap();
+
+        // The list is kept sorted, slowest first. Most commands are faster
+        // than the fastest entry of a full list and bail out here.
+        if slowlog.len() == SLOWLOG_LEN && slowlog[SLOWLOG_LEN - 1].duration >= duration {
+            return;
+        }
+
+        let pos = slowlog
+            .iter()
+            .position(|entry| entry.duration < duration)
+            .unwrap_or(slowlog.len());
+        slowlog.insert(
+            pos,
+            SlowEntry {
+                name,
+                key,
+                duration,
+            },
+        );
+        slowlog.truncate(SLOWLOG_LEN);
+    }
+
+    /// The slowest commands, slowest first.
+    pub(crate) fn slowest(&self) -> Vec<SlowEntry> {
+        self.slowlog.lock().unwrap().clone()
+    }
+
+    pub(crate) fn histogram(&self) -> Vec<Bucket> {
+        let bounds = BUCKETS_MICROS
+            .iter()
+            .map(|&micros| Some(Duration::from_micros(micros)))
+            .chain(Some(None));
+
+        bounds
+            .zip(&self.buckets)
+            .map(|(le, count)| Bucket {
+                le,
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    pub(crate) fn reset(&self) {
+        self.slowlog.lock().unwrap().clear();
+
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Display for SlowEntry {
+    /// Formats as `<micros> <name> [key]`.
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{} {}",
+            self.duration.as_micros(),
+            String::from_utf8_lossy(&self.name)
+        )?;
+
+        if let Some(key) = &self.key {
+            write!(fmt, " {}", String::from_utf8_lossy(key))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bucket {
+    /// Formats as `<=<micros> <count>`, or `+inf <count>` for overflow.
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.le {
+            Some(le) => write!(fmt, "<={} {}", le.as_micros(), self.count),
+            None => write!(fmt, "+inf {}", self.count),
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{process, Config, Db, State};
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time;
+
+/// How long an accept loop waits after a failed `accept` before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Configures and builds a [`Server`].
+///
+/// Every setting has a default, so `ServerBuilder::new().build()` gives a
+/// server listening on the loopback addresses on port 6379.
+#[derive(Debug, Default)]
+pub struct ServerBuilder {
+    config: Config,
+}
+
+/// A configured server, bound to its listen addresses but not yet accepting.
+pub struct Server {
+    listeners: Vec<TcpListener>,
+    state: Arc<State>,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+/// Stops a running [`Server`] from accepting new connections.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+/// Why a [`Server`] could not be built.
+#[derive(Debug)]
+pub enum BuildError {
+    /// The settings contradict each other, or can never work.
+    Config(String),
+
+    /// Loading the snapshot or binding the listeners failed.
+    Io(io::Error),
+}
+
+impl ServerBuilder {
+    pub fn new() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    /// The settings the server will be built with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Listen on `addr` only.
+    pub fn addr(self, addr: SocketAddr) -> ServerBuilder {
+        self.addrs(vec![addr])
+    }
+
+    /// Listen on all of `addrs`. Addresses that fail to bind are skipped with
+    /// a warning, as long as at least one succeeds.
+    pub fn addrs(mut self, addrs: Vec<SocketAddr>) -> ServerBuilder {
+        self.config.addrs = addrs;
+        self
+    }
+
+    /// Limit the number of connections served at once. Further clients wait
+    /// in the listen backlog until a slot frees up.
+    pub fn max_connections(mut self, max: usize) -> ServerBuilder {
+        self.config.max_connections = Some(max);
+        self
+    }
+
+    /// Disconnect clients that send nothing for `timeout`.
+    pub fn idle_timeout(mut self, timeout: Duration) -> ServerBuilder {
+        self.config.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Where `SAVE` writes the database, and where it is loaded from on
+    /// startup.
+    pub fn snapshot_path(mut self, path: impl Into<PathBuf>) -> ServerBuilder {
+        self.config.snapshot_path = path.into();
+        self
+    }
+
+    /// Reject `SET`s of values larger than `max` bytes.
+    pub fn max_value_size(mut self, max: usize) -> ServerBuilder {
+        self.config.max_value_size = Some(max);
+        self
+    }
+
+    /// Require clients to `AUTH` with `password` first.
+    pub fn password(mut self, password: impl Into<String>) -> ServerBuilder {
+        self.config.password = Some(password.into());
+        self
+    }
+
+    /// Hold at most `max` keys, evicting the least recently used one to make
+    /// room.
+    pub fn max_keys(mut self, max: usize) -> ServerBuilder {
+        self.config.max_keys = Some(max);
+        self
+    }
+
+    /// Enable `DEBUG SLEEP`.
+    pub fn debug_commands(mut self, enabled: bool) -> ServerBuilder {
+        self.config.debug_commands = enabled;
+        self
+    }
+
+    /// Initial capacity of each connection's read buffer.
+    pub fn read_buffer_size(mut self, size: usize) -> ServerBuilder {
+        self.config.read_buffer_size = size;
+        self
+    }
+
+    /// Disconnect clients declaring frames larger than `max` bytes.
+    pub fn max_frame_size(mut self, max: usize) -> ServerBuilder {
+        self.config.max_frame_size = Some(max);
+        self
+    }
+
+    /// Check the settings, load the snapshot and bind the listeners.
+    pub async fn build(self) -> Result<Server, BuildError> {
+        self.config.validate().map_err(BuildError::Config)?;
+
+        let db = Db::load(&self.config.snapshot_path)?;
+        let listeners = bind(&self.config.addrs).await?;
+        let (shutdown, _) = watch::channel(false);
+
+        Ok(Server {
+            listeners,
+            state: State::new(db, self.config),
+            shutdown: Arc::new(shutdown),
+        })
+    }
+}
+
+impl Config {
+    fn validate(&self) -> Result<(), String> {
+        if self.addrs.is_empty() {
+            return Err("at least one listen address is required".to_string());
+        }
+
+        let at_least_one = [
+            ("max_connections", self.max_connections),
+            ("max_keys", self.max_keys),
+            ("max_frame_size", self.max_frame_size),
+            ("read_buffer_size", Some(self.read_buffer_size)),
+        ];
+
+        for (name, value) in &at_least_one {
+            if *value == Some(0) {
+                return Err(format!("{} must be at least 1", name));
+            }
+        }
+
+        if self.idle_timeout == Some(Duration::ZERO) {
+            return Err("idle_timeout must not be zero".to_string());
+        }
+
+        if let (Some(value), Some(frame)) = (self.max_value_size, self.max_frame_size) {
+            if value > frame {
+                return Err(format!(
+                    "max_value_size ({}) is larger than max_frame_size ({})",
+                    value, frame
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Server {
+    /// The addresses the server is listening on.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        self.listeners
+            .iter()
+            .map(|listener| listener.local_addr().unwrap())
+            .collect()
+    }
+
+    /// The database served by this server.
+    pub fn db(&self) -> &Db {
+        &self.state.db
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
+    /// Serve a single client over any byte stream, such as an in-memory pipe
+    /// in a test.
+    pub fn serve_connection<S>(&self, socket: S) -> impl Future<Output = ()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        process(socket, self.state.clone())
+    }
+
+    /// Accept connections on all listeners, processing each one on its own
+    /// task, until shut down.
+    ///
+    /// Every listener runs its own accept loop, and all of them feed the same
+    /// `process` function with the same state. Shutting down stops the accept
+    /// loops, even while waiting for a free connection slot. Connections
+    /// already accepted are served until their clients disconnect. A failed
+    /// accept is logged, and retried after a short pause.
+    pub async fn run(self) {
+        let limit = self.state.config.max_connections.map(Semaphore::new);
+        let limit = limit.map(Arc::new);
+        let mut accept_loops = JoinSet::new();
+
+        for listener in self.listeners {
+            accept_loops.spawn(accept_loop(
+                listener,
+                self.state.clone(),
+                limit.clone(),
+                self.shutdown.subscribe(),
+            ));
+        }
+
+        while let Some(res) = accept_loops.join_next().await {
+            if let Err(err) = res {
+                eprintln!("accept loop failed: {}", err);
+            }
+        }
+    }
+}
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    state: Arc<State>,
+    limit: Option<Arc<Semaphore>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    loop {
+        // Wait for a free connection slot before accepting. The permit is
+        // moved into the connection's task and released when it completes.
+        let permit = match &limit {
+            Some(limit) => tokio::select! {
+                permit = limit.clone().acquire_owned() => Some(permit.unwrap()),
+                _ = shutdown.wait_for(|&stop| stop) => return,
+            },
+            None => None,
+        };
+
+        let res = tokio::select! {
+            res = listener.accept() => res,
+            _ = shutdown.wait_for(|&stop| stop) => return,
+        };
+
+        // The second item contains the ip and port of the new connection.
+        let socket = match res {
+            Ok((socket, _)) => socket,
+            Err(err) => {
+                // Errors such as running out of file descriptors are usually
+                // temporary, so wait a little and accept again.
+                eprintln!("failed to accept a connection: {}", err);
+                tokio::select! {
+                    _ = time::sleep(ACCEPT_BACKOFF) => continue,
+                    _ = shutdown.wait_for(|&stop| stop) => return,
+                }
+            }
+        };
+
+        // Each task gets its own handle to the shared state.
+        let state = state.clone();
+
+        // A new task is spawned for each inbound socket.  The socket is
+        // moved to the new task and processed there.
+        tokio::spawn(async move {
+            process(socket, state).await;
+            drop(permit);
+        });
+    }
+}
+
+/// Bind a listener for each of `addrs`.
+///
+/// An address that fails to bind, for example `[::1]` on a host without IPv6,
+/// is skipped with a warning. An error is only returned when no address could
+/// be bound at all.
+async fn bind(addrs: &[SocketAddr]) -> io::Result<Vec<TcpListener>> {
+    let mut listeners = vec![];
+    let mut last_err = None;
+
+    for addr in addrs {
+        match TcpListener::bind(addr).await {
+            Ok(listener) => listeners.push(listener),
+            Err(err) => {
+                eprintln!("warning: failed to bind {}: {}", addr, err);
+                last_err = Some(err);
+            }
+        }
+    }
+
+    match last_err {
+        Some(err) if listeners.is_empty() => Err(err),
+        _ => Ok(listeners),
+    }
+}
+
+impl From<io::Error> for BuildError {
+    fn from(err: io::Error) -> BuildError {
+        BuildError::Io(err)
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Config(msg) => write!(fmt, "invalid configuration: {}", msg),
+            BuildError::Io(err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
@@ -0,0 +1,168 @@
+//! Reading and writing database snapshots.
+//!
+//! The format is deliberately simple: a magic header followed by the number of
+//! entries, then each key and value as a length-prefixed byte string, followed
+//! by when the key expires. Lengths are big-endian `u32`s. Expirations are
+//! big-endian `u64` milliseconds since the Unix epoch, with `0` meaning the key
+//! never expires. Wall-clock time is stored, rather than the time left, so the
+//! time the server spends stopped counts against the key.
+//!
+//! Snapshots written before expirations were saved use a different magic
+//! header and are still read. Their keys never expire.
+
+use bytes::{Buf, BufMut, Bytes};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAGIC: &[u8] = b"SPAWNDB2";
+
+// Header of snapshots without expirations.
+const MAGIC_V1: &[u8] = b"SPAWNDB1";
+
+/// A value stored in a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub value: Bytes,
+
+    /// When the key stops being visible, or `None` if it never does.
+    pub expires_at: Option<SystemTime>,
+}
+
+/// Encode `entries` into the snapshot format.
+pub fn encode(entries: &HashMap<String, Entry>) -> Vec<u8> {
+    let mut dst = Vec::new();
+    dst.put_slice(MAGIC);
+    dst.put_u32(entries.len() as u32);
+
+    for (key, entry) in entries {
+        dst.put_u32(key.len() as u32);
+        dst.put_slice(key.as_bytes());
+        dst.put_u32(entry.value.len() as u32);
+        dst.put_slice(&entry.value);
+        dst.put_u64(entry.expires_at.map_or(0, to_millis));
+    }
+
+    dst
+}
+
+/// Decode a snapshot produced by [`encode`].
+pub fn decode(mut src: &[u8]) -> io::Result<HashMap<String, Entry>> {
+    let has_expirations = if src.starts_with(MAGIC) {
+        true
+    } else if src.starts_with(MAGIC_V1) {
+        false
+    } else {
+        return Err(invalid("not a snapshot file"));
+    };
+    src.advance(MAGIC.len());
+
+    // The count comes from the file, so it isn't trusted to size the map up
+    // front. A corrupt header would otherwise allocate before any entry is
+    // read.
+    let len = get_u32(&mut src)?;
+    let mut entries = HashMap::new();
+
+    for _ in 0..len {
+        let key = get_bytes(&mut src)?;
+        let key = String::from_utf8(key.to_vec()).map_err(|_| invalid("key is not UTF-8"))?;
+        let value = get_bytes(&mut src)?;
+
+        let expires_at = if has_expirations {
+            match get_u64(&mut src)? {
+                0 => None,
+                millis => Some(UNIX_EPOCH + Duration::from_millis(millis)),
+            }
+        } else {
+            None
+        };
+
+        entries.insert(key, Entry { value, expires_at });
+    }
+
+    if src.has_remaining() {
+        return Err(invalid("trailing data after last entry"));
+    }
+
+    Ok(entries)
+}
+
+/// Read and decode the snapshot at `path`.
+pub fn load(path: &Path) -> io::Result<HashMap<String, Entry>> {
+    decode(&fs::read(path)?)
+}
+
+/// Atomically replace the snapshot at `path` with `entries`.
+///
+/// The data is written to a temporary file next to `path`, fsynced, then
+/// renamed into place. A crash part way through leaves the previous snapshot
+/// intact.
+pub fn write(path: &Path, entries: &HashMap<String, Entry>) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+
+    let mut file = File::create(&tmp)?;
+    file.write_all(&encode(entries))?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path)?;
+
+    // The rename itself only becomes durable once the directory is synced.
+    // Directories can't be opened this way on every platform, so this is best
+    // effort.
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+fn get_u32(src: &mut &[u8]) -> io::Result<u32> {
+    if src.remaining() < 4 {
+        return Err(invalid("unexpected end of snapshot"));
+    }
+
+    Ok(src.get_u32())
+}
+
+fn get_u64(src: &mut &[u8]) -> io::Result<u64> {
+    if src.remaining() < 8 {
+        return Err(invalid("unexpected end of snapshot"));
+    }
+
+    Ok(src.get_u64())
+}
+
+fn get_bytes(src: &mut &[u8]) -> io::Result<Bytes> {
+    let len = get_u32(src)? as usize;
+
+    if src.remaining() < len {
+        return Err(invalid("unexpected end of snapshot"));
+    }
+
+    Ok(src.copy_to_bytes(len))
+}
+
+// Deadlines before the epoch can't be represented, round them up to the
+// smallest one that can. They have long passed either way.
+fn to_millis(when: SystemTime) -> u64 {
+    let millis = when
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64);
+
+    millis.max(1)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
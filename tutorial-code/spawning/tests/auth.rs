@@ -0,0 +1,90 @@
+mod common;
+
+use mini_redis::Frame;
+use tempfile::TempDir;
+
+fn with_password(dir: &TempDir) -> spawning::ServerBuilder {
+    common::builder(dir).password("s3cret")
+}
+
+fn assert_error(frame: Frame, prefix: &str) {
+    match frame {
+        Frame::Error(msg) => assert!(msg.starts_with(prefix), "{}", msg),
+        frame => panic!("expected `{}` error, got {:?}", prefix, frame),
+    }
+}
+
+#[tokio::test]
+async fn no_password_ignores_auth() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    assert_error(
+        common::command(&mut connection, &["AUTH", "anything"]).await,
+        "ERR",
+    );
+
+    // The connection is still usable.
+    let response = common::command(&mut connection, &["SET", "foo", "bar"]).await;
+    assert!(response == "OK", "{:?}", response);
+}
+
+#[tokio::test]
+async fn commands_require_auth() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(with_password(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    assert_error(
+        common::command(&mut connection, &["GET", "foo"]).await,
+        "NOAUTH",
+    );
+    assert_error(common::command(&mut connection, &["SAVE"]).await, "NOAUTH");
+    assert_error(
+        common::command(&mut connection, &["DEBUG", "SLEEP", "1e300"]).await,
+        "NOAUTH",
+    );
+
+    // The connection is still there to authenticate.
+    let response = common::command(&mut connection, &["AUTH", "s3cret"]).await;
+    assert!(response == "OK", "{:?}", response);
+}
+
+#[tokio::test]
+async fn wrong_then_right_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(with_password(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    let response = common::command(&mut connection, &["AUTH", "guess"]).await;
+    assert_error(response, "ERR invalid password");
+    assert_error(
+        common::command(&mut connection, &["GET", "foo"]).await,
+        "NOAUTH",
+    );
+
+    let response = common::command(&mut connection, &["AUTH", "s3cret"]).await;
+    assert!(response == "OK", "{:?}", response);
+
+    let response = common::command(&mut connection, &["SET", "foo", "bar"]).await;
+    assert!(response == "OK", "{:?}", response);
+    let response = common::command(&mut connection, &["GET", "foo"]).await;
+    assert!(response == "bar", "{:?}", response);
+}
+
+#[tokio::test]
+async fn auth_is_per_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(with_password(&dir)).await;
+
+    let mut first = common::connect(addr).await;
+    let response = common::command(&mut first, &["AUTH", "s3cret"]).await;
+    assert!(response == "OK", "{:?}", response);
+
+    let mut second = common::connect(addr).await;
+    assert_error(
+        common::command(&mut second, &["GET", "foo"]).await,
+        "NOAUTH",
+    );
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use mini_redis::client;
+use spawning::{BuildError, ServerBuilder};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+#[test]
+fn defaults_match_the_plain_server() {
+    let builder = ServerBuilder::new();
+    let config = builder.config();
+
+    let addrs: Vec<SocketAddr> = vec![
+        "127.0.0.1:6379".parse().unwrap(),
+        "[::1]:6379".parse().unwrap(),
+    ];
+    assert_eq!(config.addrs, addrs);
+    assert_eq!(config.snapshot_path, Path::new("dump.snap"));
+    assert_eq!(config.max_connections, None);
+    assert_eq!(config.idle_timeout, None);
+    assert_eq!(config.max_value_size, None);
+    assert_eq!(config.password, None);
+    assert_eq!(config.max_keys, None);
+    assert!(!config.debug_commands);
+    assert_eq!(config.read_buffer_size, 4 * 1024);
+    assert_eq!(config.max_frame_size, None);
+}
+
+#[tokio::test]
+async fn invalid_settings_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let cases = vec![
+        ("max_connections", common::builder(&dir).max_connections(0)),
+        ("max_keys", common::builder(&dir).max_keys(0)),
+        ("max_frame_size", common::builder(&dir).max_frame_size(0)),
+        (
+            "read_buffer_size",
+            common::builder(&dir).read_buffer_size(0),
+        ),
+        (
+            "idle_timeout",
+            common::builder(&dir).idle_timeout(Duration::ZERO),
+        ),
+        ("listen address", common::builder(&dir).addrs(vec![])),
+        (
+            "max_value_size",
+            common::builder(&dir).max_frame_size(10).max_value_size(20),
+        ),
+    ];
+
+    for (setting, builder) in cases {
+        match builder.build().await {
+            Err(BuildError::Config(msg)) => assert!(msg.contains(setting), "{}", msg),
+            Err(err) => panic!("{}: unexpected error {}", setting, err),
+            Ok(_) => panic!("{}: invalid setting accepted", setting),
+        }
+    }
+}
+
+#[tokio::test]
+async fn max_value_size_rejects_large_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).max_value_size(4)).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("small", "1234".into()).await.unwrap();
+    assert!(client.set("large", "12345".into()).await.is_err());
+}
+
+#[tokio::test]
+async fn idle_connections_are_closed() {
+    let dir = tempfile::tempdir().unwrap();
+    let builder = common::builder(&dir).idle_timeout(Duration::from_millis(50));
+    let addr = common::start(builder).await;
+
+    let mut socket = TcpStream::connect(addr).await.unwrap();
+    let mut buf = vec![];
+    let n = tokio::time::timeout(Duration::from_secs(5), socket.read_to_end(&mut buf))
+        .await
+        .expect("idle connection was not closed")
+        .unwrap();
+    assert_eq!(n, 0);
+}
+
+#[tokio::test]
+async fn max_connections_holds_back_extra_clients() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).max_connections(1)).await;
+
+    let mut first = client::connect(addr).await.unwrap();
+    first.set("key", "value".into()).await.unwrap();
+
+    // The second connection completes the TCP handshake through the backlog,
+    // but isn't served while the first one is open.
+    let mut second = client::connect(addr).await.unwrap();
+    let get = tokio::time::timeout(Duration::from_millis(100), second.get("key")).await;
+    assert!(get.is_err(), "second connection was served");
+
+    drop(first);
+    assert_eq!(second.get("key").await.unwrap().unwrap(), "value");
+}
+
+#[tokio::test]
+async fn shutdown_stops_accepting() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = common::builder(&dir).build().await.unwrap();
+    let addr = server.local_addrs()[0];
+    let shutdown = server.shutdown_handle();
+    let run = tokio::spawn(server.run());
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("key", "value".into()).await.unwrap();
+
+    shutdown.shutdown();
+    run.await.unwrap();
+
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_while_at_max_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = common::builder(&dir)
+        .max_connections(1)
+        .build()
+        .await
+        .unwrap();
+    let addr = server.local_addrs()[0];
+    let shutdown = server.shutdown_handle();
+    let run = tokio::spawn(server.run());
+
+    // The only connection slot stays taken while shutting down.
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("key", "value".into()).await.unwrap();
+
+    shutdown.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), run)
+        .await
+        .expect("server didn't stop")
+        .unwrap();
+
+    // The connection accepted before is still served.
+    assert_eq!(client.get("key").await.unwrap().unwrap(), "value");
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use tokio::process::Command;
+
+#[tokio::test]
+async fn client_against_server() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_client"))
+        .arg(addr.to_string())
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("tasks: SET hello = Ok(())"), "{}", stdout);
+    assert!(
+        stdout.contains("manager: SET foo = Ok(Ok(()))"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("final: hello = Some(b\"world\")"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("final: foo = Some(b\"bar\")"), "{}", stdout);
+}
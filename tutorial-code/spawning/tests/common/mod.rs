@@ -0,0 +1,45 @@
+// Not every test file uses every helper.
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use mini_redis::{Connection, Frame};
+use spawning::{Server, ServerBuilder};
+use std::net::SocketAddr;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+
+/// A builder for a server on an ephemeral port that snapshots into `dir`.
+pub fn builder(dir: &TempDir) -> ServerBuilder {
+    ServerBuilder::new()
+        .addr("127.0.0.1:0".parse().unwrap())
+        .snapshot_path(dir.path().join("dump.snap"))
+}
+
+/// Run `server` in the background, returning the address it listens on.
+pub fn spawn(server: Server) -> SocketAddr {
+    let addr = server.local_addrs()[0];
+    tokio::spawn(server.run());
+    addr
+}
+
+/// Build and run the server in the background.
+pub async fn start(builder: ServerBuilder) -> SocketAddr {
+    spawn(builder.build().await.unwrap())
+}
+
+/// Open a raw frame connection, for commands `mini_redis::client` can't send.
+pub async fn connect(addr: SocketAddr) -> Connection {
+    Connection::new(TcpStream::connect(addr).await.unwrap())
+}
+
+/// Send `args` as a command and wait for the response frame.
+pub async fn command(connection: &mut Connection, args: &[&str]) -> Frame {
+    let frame = Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    );
+
+    connection.write_frame(&frame).await.unwrap();
+    connection.read_frame().await.unwrap().unwrap()
+}
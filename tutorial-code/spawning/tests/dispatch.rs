@@ -0,0 +1,218 @@
+mod common;
+
+use bytes::Bytes;
+use mini_redis::Frame;
+use spawning::cmd::{Access, Extension};
+use std::time::Duration;
+
+fn frame(args: &[&str]) -> Frame {
+    Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    )
+}
+
+#[test]
+fn parses_every_command() {
+    let cases = vec![
+        (vec!["SAVE"], Extension::Save),
+        (
+            vec!["AUTH", "secret"],
+            Extension::Auth(Bytes::from("secret")),
+        ),
+        (vec!["STATS"], Extension::Stats),
+        (vec!["TYPE", "foo"], Extension::Type("foo".to_string())),
+        (vec!["SLOWLOG", "GET"], Extension::SlowlogGet),
+        (vec!["SLOWLOG", "RESET"], Extension::SlowlogReset),
+        (vec!["LATENCY", "HISTOGRAM"], Extension::LatencyHistogram),
+        (
+            vec!["DEBUG", "OBJECT", "foo"],
+            Extension::DebugObject("foo".to_string()),
+        ),
+        (
+            vec!["DEBUG", "SLEEP", "0.5"],
+            Extension::DebugSleep(Duration::from_millis(500)),
+        ),
+        // Names and subcommands are case insensitive.
+        (vec!["slowlog", "Get"], Extension::SlowlogGet),
+    ];
+
+    for (args, expected) in cases {
+        assert_eq!(
+            Extension::from_frame(&frame(&args), Access::ALL),
+            Some(Ok(expected)),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn rejects_wrong_arity() {
+    let cases = vec![
+        (vec!["SAVE", "now"], "'save'"),
+        (vec!["AUTH"], "'auth'"),
+        (vec!["AUTH", "user", "secret"], "'auth'"),
+        (vec!["STATS", "all"], "'stats'"),
+        (vec!["TYPE"], "'type'"),
+        (vec!["TYPE", "foo", "bar"], "'type'"),
+        (vec!["SLOWLOG"], "'slowlog'"),
+        (vec!["SLOWLOG", "GET", "10"], "'slowlog|get'"),
+        (vec!["SLOWLOG", "RESET", "now"], "'slowlog|reset'"),
+        (vec!["LATENCY"], "'latency'"),
+        (vec!["LATENCY", "HISTOGRAM", "get"], "'latency|histogram'"),
+        (vec!["DEBUG"], "'debug'"),
+        (vec!["DEBUG", "OBJECT"], "'debug|object'"),
+        (vec!["DEBUG", "SLEEP"], "'debug|sleep'"),
+        (vec!["DEBUG", "SLEEP", "1", "2"], "'debug|sleep'"),
+    ];
+
+    for (args, name) in cases {
+        let expected = format!("ERR wrong number of arguments for {} command", name);
+        assert_eq!(
+            Extension::from_frame(&frame(&args), Access::ALL),
+            Some(Err(expected)),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn rejects_malformed_arguments() {
+    let cases = vec![
+        (
+            vec!["SLOWLOG", "LEN"],
+            "ERR unknown subcommand for 'slowlog' command",
+        ),
+        (
+            vec!["DEBUG", "RELOAD"],
+            "ERR unknown subcommand for 'debug' command",
+        ),
+        (
+            vec!["DEBUG", "SLEEP", "soon"],
+            "ERR value is not a valid float",
+        ),
+        (
+            vec!["DEBUG", "SLEEP", "-1"],
+            "ERR value is not a valid float",
+        ),
+        // Finite, but too long for a `Duration`.
+        (
+            vec!["DEBUG", "SLEEP", "1e300"],
+            "ERR value is not a valid float",
+        ),
+    ];
+
+    for (args, expected) in cases {
+        assert_eq!(
+            Extension::from_frame(&frame(&args), Access::ALL),
+            Some(Err(expected.to_string())),
+            "{:?}",
+            args
+        );
+    }
+
+    let frame = Frame::Array(vec![Frame::Bulk("TYPE".into()), Frame::Integer(1)]);
+    assert_eq!(
+        Extension::from_frame(&frame, Access::ALL),
+        Some(Err(
+            "ERR Protocol error: expected a string argument".to_string()
+        ))
+    );
+}
+
+#[test]
+fn rejects_commands_before_parsing_arguments() {
+    let unauthenticated = Access {
+        authenticated: false,
+        debug_commands: true,
+    };
+    for args in [vec!["SAVE"], vec!["DEBUG", "SLEEP", "1e300"], vec!["TYPE"]] {
+        assert_eq!(
+            Extension::from_frame(&frame(&args), unauthenticated),
+            Some(Err("NOAUTH Authentication required.".to_string())),
+            "{:?}",
+            args
+        );
+    }
+    assert_eq!(
+        Extension::from_frame(&frame(&["AUTH", "secret"]), unauthenticated),
+        Some(Ok(Extension::Auth(Bytes::from("secret"))))
+    );
+
+    let without_debug = Access {
+        authenticated: true,
+        debug_commands: false,
+    };
+    for args in [vec!["DEBUG", "SLEEP", "1"], vec!["DEBUG", "SLEEP", "1e300"]] {
+        assert_eq!(
+            Extension::from_frame(&frame(&args), without_debug),
+            Some(Err("ERR DEBUG commands are disabled".to_string())),
+            "{:?}",
+            args
+        );
+    }
+    assert_eq!(
+        Extension::from_frame(&frame(&["DEBUG", "OBJECT", "foo"]), without_debug),
+        Some(Ok(Extension::DebugObject("foo".to_string())))
+    );
+}
+
+#[test]
+fn leaves_other_commands_to_mini_redis() {
+    for args in [vec!["GET", "foo"], vec!["SET", "foo", "bar"], vec!["PING"]] {
+        assert_eq!(
+            Extension::from_frame(&frame(&args), Access::ALL),
+            None,
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[tokio::test]
+async fn type_of_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    common::command(&mut connection, &["SET", "foo", "bar"]).await;
+
+    let response = common::command(&mut connection, &["TYPE", "foo"]).await;
+    assert!(response == "string", "{:?}", response);
+
+    let response = common::command(&mut connection, &["TYPE", "missing"]).await;
+    assert!(response == "none", "{:?}", response);
+}
+
+#[tokio::test]
+async fn debug_object() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    common::command(&mut connection, &["SET", "foo", "hello"]).await;
+    let response = common::command(&mut connection, &["DEBUG", "OBJECT", "foo"]).await;
+    assert!(response == "serializedlength:5 ttl:-1", "{:?}", response);
+
+    common::command(&mut connection, &["SET", "bar", "hi", "PX", "60000"]).await;
+    let response = common::command(&mut connection, &["DEBUG", "OBJECT", "bar"]).await;
+    let ttl = match &response {
+        Frame::Simple(line) => line
+            .strip_prefix("serializedlength:2 ttl:")
+            .and_then(|ttl| ttl.parse::<u64>().ok()),
+        _ => None,
+    };
+    assert!(
+        matches!(ttl, Some(ttl) if ttl > 0 && ttl <= 60_000),
+        "{:?}",
+        response
+    );
+
+    match common::command(&mut connection, &["DEBUG", "OBJECT", "missing"]).await {
+        Frame::Error(msg) => assert_eq!(msg, "ERR no such key"),
+        frame => panic!("expected an error, got {:?}", frame),
+    }
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use mini_redis::client;
+use spawning::BuildError;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn both_families_share_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let addrs = vec!["127.0.0.1:0".parse().unwrap(), "[::1]:0".parse().unwrap()];
+    let server = common::builder(&dir).addrs(addrs).build().await.unwrap();
+    let local = server.local_addrs();
+    tokio::spawn(server.run());
+
+    let v4 = local.iter().find(|addr| addr.is_ipv4()).unwrap();
+    let mut client = client::connect(v4).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+
+    // Not every CI machine has IPv6 loopback.
+    let v6 = match local.iter().find(|addr| addr.is_ipv6()) {
+        Some(v6) => v6,
+        None => {
+            eprintln!("IPv6 unavailable, skipping");
+            return;
+        }
+    };
+
+    let mut client = client::connect(v6).await.unwrap();
+    assert_eq!(client.get("hello").await.unwrap().unwrap(), "world");
+}
+
+#[tokio::test]
+async fn bind_failure_is_not_fatal() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // Occupy a port so that binding it again fails.
+    let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let taken = taken.local_addr().unwrap();
+    let addrs = vec![taken, "127.0.0.1:0".parse().unwrap()];
+
+    let server = common::builder(&dir).addrs(addrs).build().await.unwrap();
+    assert_eq!(server.local_addrs().len(), 1);
+
+    match common::builder(&dir).addr(taken).build().await {
+        Err(BuildError::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("bound an address that is in use"),
+    }
+}
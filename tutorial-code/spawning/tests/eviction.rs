@@ -0,0 +1,53 @@
+mod common;
+
+use mini_redis::Frame;
+use spawning::{EvictionPolicy, ScanLru};
+
+#[tokio::test]
+async fn least_recently_used_key_is_evicted() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).max_keys(3)).await;
+    let mut connection = common::connect(addr).await;
+
+    common::command(&mut connection, &["SET", "key1", "1"]).await;
+    common::command(&mut connection, &["SET", "key2", "2"]).await;
+    common::command(&mut connection, &["SET", "key3", "3"]).await;
+
+    // Reading key1 makes key2 the least recently used.
+    common::command(&mut connection, &["GET", "key1"]).await;
+    common::command(&mut connection, &["SET", "key4", "4"]).await;
+
+    assert!(matches!(
+        common::command(&mut connection, &["GET", "key2"]).await,
+        Frame::Null
+    ));
+    for (key, value) in [("key1", "1"), ("key3", "3"), ("key4", "4")] {
+        let response = common::command(&mut connection, &["GET", key]).await;
+        assert!(response == value, "{}: {:?}", key, response);
+    }
+
+    // Overwriting an existing key doesn't evict anything.
+    common::command(&mut connection, &["SET", "key1", "one"]).await;
+
+    match common::command(&mut connection, &["STATS"]).await {
+        Frame::Array(stats) => {
+            assert!(stats[0] == "keys:3", "{:?}", stats);
+            assert!(stats[1] == "evictions:1", "{:?}", stats);
+        }
+        frame => panic!("expected an array, got {:?}", frame),
+    }
+}
+
+#[test]
+fn scan_lru_picks_oldest_access() {
+    let mut lru = ScanLru::new();
+    assert_eq!(lru.victim(), None);
+
+    lru.touch("a");
+    lru.touch("b");
+    lru.touch("a");
+    assert_eq!(lru.victim().as_deref(), Some("b"));
+
+    lru.remove("b");
+    assert_eq!(lru.victim().as_deref(), Some("a"));
+}
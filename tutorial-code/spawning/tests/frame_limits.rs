@@ -0,0 +1,56 @@
+mod common;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const LIMIT: usize = 1024;
+
+async fn start() -> (TcpStream, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).max_frame_size(LIMIT)).await;
+
+    (TcpStream::connect(addr).await.unwrap(), dir)
+}
+
+fn set_header(len: usize) -> Vec<u8> {
+    format!("*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n${}\r\n", len).into_bytes()
+}
+
+#[tokio::test]
+async fn frame_under_limit_is_accepted() {
+    let (mut socket, _dir) = start().await;
+
+    let mut request = set_header(LIMIT - 1);
+    request.extend(vec![b'x'; LIMIT - 1]);
+    request.extend_from_slice(b"\r\n");
+    socket.write_all(&request).await.unwrap();
+
+    let mut response = [0; 5];
+    socket.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, b"+OK\r\n");
+}
+
+#[tokio::test]
+async fn frame_over_limit_is_rejected_before_buffering() {
+    let (mut socket, _dir) = start().await;
+
+    // Only the header is sent: the server must not wait for the body.
+    socket.write_all(&set_header(LIMIT + 1)).await.unwrap();
+
+    let mut response = vec![];
+    socket.read_to_end(&mut response).await.unwrap();
+    let response = String::from_utf8(response).unwrap();
+    assert!(response.starts_with("-ERR Protocol error"), "{}", response);
+    assert!(response.contains("1025"), "{}", response);
+}
+
+#[tokio::test]
+async fn huge_array_is_rejected() {
+    let (mut socket, _dir) = start().await;
+
+    socket.write_all(b"*100000000\r\n").await.unwrap();
+
+    let mut response = vec![];
+    socket.read_to_end(&mut response).await.unwrap();
+    assert!(response.starts_with(b"-ERR Protocol error"));
+}
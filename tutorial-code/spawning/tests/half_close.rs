@@ -0,0 +1,45 @@
+mod common;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn responds_after_write_half_closed() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+
+    let mut socket = TcpStream::connect(addr).await.unwrap();
+    socket
+        .write_all(
+            b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n",
+        )
+        .await
+        .unwrap();
+
+    // No more requests are coming, but the responses still are.
+    socket.shutdown().await.unwrap();
+
+    // `read_to_end` only returns once the server closes its side.
+    let mut response = vec![];
+    socket.read_to_end(&mut response).await.unwrap();
+    assert_eq!(response, b"+OK\r\n$5\r\nworld\r\n");
+}
+
+#[tokio::test]
+async fn closes_on_truncated_frame() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+
+    let mut socket = TcpStream::connect(addr).await.unwrap();
+    socket
+        .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n*2\r\n$3\r\nGET\r\n$5\r\nhel")
+        .await
+        .unwrap();
+    socket.shutdown().await.unwrap();
+
+    // The complete request is answered. The truncated one is dropped, and the
+    // server hangs up.
+    let mut response = vec![];
+    socket.read_to_end(&mut response).await.unwrap();
+    assert_eq!(response, b"$-1\r\n");
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+/// Passes everything through to `inner`, counting calls to `poll_flush`.
+struct CountFlushes<S> {
+    inner: S,
+    flushes: Arc<AtomicUsize>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountFlushes<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountFlushes<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.flushes.fetch_add(1, Ordering::Relaxed);
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[tokio::test]
+async fn pipelined_responses_share_flushes() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = common::builder(&dir).build().await.unwrap();
+
+    let (mut client, socket) = tokio::io::duplex(64 * 1024);
+    let flushes = Arc::new(AtomicUsize::new(0));
+    let socket = CountFlushes {
+        inner: socket,
+        flushes: flushes.clone(),
+    };
+    let task = tokio::spawn(server.serve_connection(socket));
+
+    // Send all the requests before reading any response.
+    let mut requests = vec![];
+    for i in 0..100 {
+        let key = format!("key{}", i);
+        requests.extend_from_slice(
+            format!(
+                "*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n$1\r\nv\r\n",
+                key.len(),
+                key
+            )
+            .as_bytes(),
+        );
+    }
+    client.write_all(&requests).await.unwrap();
+
+    let expected = b"+OK\r\n".repeat(100);
+    let mut responses = vec![0; expected.len()];
+    client.read_exact(&mut responses).await.unwrap();
+    assert_eq!(responses, expected);
+
+    drop(client);
+    task.await.unwrap();
+
+    let flushes = flushes.load(Ordering::Relaxed);
+    assert!(flushes < 10, "{} flushes for 100 requests", flushes);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use bytes::Bytes;
+use mini_redis::{client, Frame};
+use spawning::{snapshot, Db};
+use std::time::Duration;
+
+#[tokio::test]
+async fn save_writes_loadable_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.snap");
+    let addr = common::start(common::builder(&dir)).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    client.set("empty", Bytes::new()).await.unwrap();
+
+    let mut connection = common::connect(addr).await;
+    let response = common::command(&mut connection, &["SAVE"]).await;
+    assert!(response == "OK", "unexpected response {:?}", response);
+
+    let entries = snapshot::load(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries["hello"].value, "world");
+    assert_eq!(entries["empty"].value, "");
+    assert_eq!(entries["hello"].expires_at, None);
+
+    // Restarting from the snapshot brings the data back.
+    let db = Db::load(&path).unwrap();
+    assert_eq!(db.get("hello").unwrap(), "world");
+}
+
+#[tokio::test]
+async fn concurrent_save_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.snap");
+    let server = common::builder(&dir).build().await.unwrap();
+    let db = server.db().clone();
+    let addr = common::spawn(server);
+
+    // Pretend a save is already running.
+    let guard = db.try_begin_save().unwrap();
+
+    let mut connection = common::connect(addr).await;
+    match common::command(&mut connection, &["SAVE"]).await {
+        Frame::Error(msg) => assert!(msg.contains("in progress"), "{}", msg),
+        frame => panic!("expected an error, got {:?}", frame),
+    }
+    assert!(!path.exists());
+
+    // Once the other save finishes, saving works again.
+    drop(guard);
+    let response = common::command(&mut connection, &["SAVE"]).await;
+    assert!(response == "OK", "unexpected response {:?}", response);
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn save_keeps_expirations() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.snap");
+
+    let db = Db::new(&path);
+    db.set("forever".to_string(), "value".into());
+    db.set_expires(
+        "soon".to_string(),
+        "value".into(),
+        Duration::from_millis(300),
+    );
+    db.set_expires("gone".to_string(), "value".into(), Duration::from_millis(1));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let guard = db.try_begin_save().unwrap();
+    db.save(guard).await.unwrap();
+
+    // The already expired key is not written out.
+    let entries = snapshot::load(&path).unwrap();
+    assert!(!entries.contains_key("gone"));
+    assert!(entries["soon"].expires_at.is_some());
+
+    let db = Db::load(&path).unwrap();
+    assert_eq!(db.inspect("forever").unwrap().ttl, None);
+    let ttl = db.inspect("soon").unwrap().ttl.unwrap();
+    assert!(ttl <= Duration::from_millis(300), "{:?}", ttl);
+
+    // The reloaded key still expires.
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(db.get("soon"), None);
+    assert_eq!(db.get("forever").unwrap(), "value");
+}
+
+#[test]
+fn decode_rejects_truncated_snapshot() {
+    let mut entries = std::collections::HashMap::new();
+    let entry = snapshot::Entry {
+        value: Bytes::from("value"),
+        expires_at: None,
+    };
+    entries.insert("key".to_string(), entry);
+
+    let encoded = snapshot::encode(&entries);
+    assert_eq!(snapshot::decode(&encoded).unwrap(), entries);
+    assert!(snapshot::decode(&encoded[..encoded.len() - 1]).is_err());
+}
+
+#[test]
+fn decode_does_not_trust_entry_count() {
+    let mut encoded = snapshot::encode(&Default::default());
+    encoded[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    assert!(snapshot::decode(&encoded).is_err());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use mini_redis::Frame;
+
+fn lines(frame: Frame) -> Vec<String> {
+    match frame {
+        Frame::Array(entries) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                Frame::Bulk(line) => String::from_utf8(line.to_vec()).unwrap(),
+                frame => panic!("unexpected entry {:?}", frame),
+            })
+            .collect(),
+        frame => panic!("expected an array, got {:?}", frame),
+    }
+}
+
+#[tokio::test]
+async fn slow_command_tops_slowlog() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).debug_commands(true)).await;
+    let mut connection = common::connect(addr).await;
+
+    common::command(&mut connection, &["SET", "foo", "bar"]).await;
+    common::command(&mut connection, &["DEBUG", "SLEEP", "0.05"]).await;
+    common::command(&mut connection, &["GET", "foo"]).await;
+
+    let entries = lines(common::command(&mut connection, &["SLOWLOG", "GET"]).await);
+    assert_eq!(entries.len(), 3, "{:?}", entries);
+
+    let slowest: Vec<_> = entries[0].split(' ').collect();
+    assert_eq!(&slowest[1..], ["DEBUG", "SLEEP"]);
+    assert!(slowest[0].parse::<u64>().unwrap() >= 50_000);
+
+    // Every command so far, except the `SLOWLOG GET` itself, is counted once
+    // in the histogram.
+    let buckets = lines(common::command(&mut connection, &["LATENCY", "HISTOGRAM"]).await);
+    let total: u64 = buckets
+        .iter()
+        .map(|bucket| bucket.split(' ').nth(1).unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(total, 4);
+
+    let response = common::command(&mut connection, &["SLOWLOG", "RESET"]).await;
+    assert!(response == "OK", "{:?}", response);
+
+    // Only the `SLOWLOG RESET` was recorded since.
+    let entries = lines(common::command(&mut connection, &["SLOWLOG", "GET"]).await);
+    assert_eq!(entries.len(), 1, "{:?}", entries);
+    assert!(entries[0].ends_with("SLOWLOG RESET"), "{:?}", entries);
+}
+
+#[tokio::test]
+async fn debug_sleep_disabled_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir)).await;
+    let mut connection = common::connect(addr).await;
+
+    match common::command(&mut connection, &["DEBUG", "SLEEP", "1"]).await {
+        Frame::Error(msg) => assert!(msg.contains("disabled"), "{}", msg),
+        frame => panic!("expected an error, got {:?}", frame),
+    }
+}
+
+#[tokio::test]
+async fn passwords_stay_out_of_slowlog() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = common::start(common::builder(&dir).password("s3cret")).await;
+    let mut connection = common::connect(addr).await;
+
+    common::command(&mut connection, &["AUTH", "guess"]).await;
+    common::command(&mut connection, &["auth", "s3cret"]).await;
+
+    let entries = lines(common::command(&mut connection, &["SLOWLOG", "GET"]).await);
+    assert_eq!(entries.len(), 2, "{:?}", entries);
+    for entry in &entries {
+        let name = entry.split(' ').nth(1).unwrap();
+        assert!(name.eq_ignore_ascii_case("auth"), "{:?}", entries);
+        assert!(
+            !entry.contains("guess") && !entry.contains("s3cret"),
+            "{:?}",
+            entries
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Handling messages in batches.
+//!
+//! Writing each message to a database or a file one at a time is wasteful
+//! when they arrive in bursts. `StreamExt::chunks_timeout` groups them
+//! instead: a batch is yielded as soon as it is full, or once `WINDOW` has
+//! passed since its first message, whichever comes first.
+//!
+//! A subscription never ends, and neither do its batches. Limiting the
+//! subscription with `take` before batching it makes both end: the last
+//! batch, full or not, is yielded as soon as the last message is in.
+
+use std::time::Duration;
+use tokio_stream::{Stream, StreamExt};
+
+/// Largest number of messages in a batch.
+pub const BATCH_SIZE: usize = 10;
+
+/// Longest a message waits for its batch to fill up.
+pub const WINDOW: Duration = Duration::from_millis(100);
+
+/// Group the items of `stream` into batches.
+///
+/// A window only starts once an item arrives, so a quiet stream yields no
+/// empty batches.
+pub fn batches<S: Stream>(stream: S) -> impl Stream<Item = Vec<S::Item>> {
+    stream.chunks_timeout(BATCH_SIZE, WINDOW)
+}
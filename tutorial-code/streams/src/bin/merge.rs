@@ -0,0 +1,55 @@
+use mini_redis::client;
+use std::env;
+use streams::merge::subscribe_each;
+use tokio_stream::StreamExt;
+
+async fn publish(addr: &str) -> mini_redis::Result<()> {
+    let mut client = client::connect(addr).await?;
+
+    for (number, letter) in ["1", "2", "3", "4", "5", "6"]
+        .iter()
+        .zip(["a", "b", "c", "d", "e", "f"].iter())
+    {
+        client.publish("numbers", number.to_string().into()).await?;
+        client.publish("letters", letter.to_string().into()).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> mini_redis::Result<()> {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string());
+
+    // Both subscriptions are in place once this returns, so nothing published
+    // from here on is missed.
+    let mut messages = subscribe_each(&addr, &["numbers", "letters"]).await?;
+
+    let publish_addr = addr.clone();
+    tokio::spawn(async move { publish(&publish_addr).await });
+
+    let mut all_numbers = false;
+    while let Some((channel, msg)) = messages.next().await {
+        println!("{} = {:?}", channel, msg);
+
+        if channel == "letters" && msg == "c" {
+            // Dropping the stream closes its connection, unsubscribing. The
+            // other channel keeps going.
+            messages.remove("letters");
+            println!("unsubscribed from letters");
+        }
+
+        if channel == "numbers" && msg == "6" {
+            all_numbers = true;
+        }
+
+        // The subscriptions never end on their own.
+        if all_numbers && !messages.contains_key("letters") {
+            break;
+        }
+    }
+
+    Ok(())
+}
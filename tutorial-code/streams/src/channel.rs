@@ -0,0 +1,61 @@
+//! The adapters from the chapter, applied to a channel instead of a
+//! subscription.
+//!
+//! Nothing about `filter`, `map` or `take` is specific to mini-redis. Wrapping
+//! an `mpsc::Receiver` in a `ReceiverStream` turns it into a `Stream`, and the
+//! same pipeline works on it unchanged.
+
+use std::num::ParseIntError;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Capacity of the channel between the producer and the stream. Once it
+/// holds this many numbers, the producer waits for the consumer.
+pub const CAPACITY: usize = 2;
+
+/// The pipeline from `subscribe` in `main.rs`, for any stream of `Result`s:
+/// skip the errors and stop after three items.
+pub fn first_three<S, T, E>(stream: S) -> impl Stream<Item = T>
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    stream.filter_map(|item| item.ok()).take(3)
+}
+
+/// Parse `inputs` on a separate task, sending the results through a bounded
+/// channel.
+///
+/// Returns the receiving end as a stream, and a handle to the producer task,
+/// which resolves to how long each `send` waited for room in the channel.
+pub fn produce(
+    inputs: Vec<String>,
+) -> (
+    ReceiverStream<Result<u32, ParseIntError>>,
+    JoinHandle<Vec<Duration>>,
+) {
+    let (tx, rx) = mpsc::channel(CAPACITY);
+
+    let producer = tokio::spawn(async move {
+        let mut waits = vec![];
+
+        for input in inputs {
+            let start = Instant::now();
+
+            // Fails once the stream is dropped, for example after `take` is
+            // done. There is no one left to produce for.
+            if tx.send(input.parse()).await.is_err() {
+                break;
+            }
+
+            waits.push(start.elapsed());
+        }
+
+        waits
+    });
+
+    (ReceiverStream::new(rx), producer)
+}
@@ -0,0 +1,59 @@
+//! Handling several messages at once.
+//!
+//! `then` runs an async handler on each item, but one at a time: the next
+//! message waits until the previous one is handled. Mapping every message to
+//! the handler's future, then using `buffered` or `buffer_unordered`, runs up
+//! to `CONCURRENCY` of those futures at once.
+//!
+//! The two differ in the order of the results. `buffer_unordered` yields each
+//! result as soon as it is ready, so a quick handler overtakes a slow one
+//! started before it. `buffered` holds results back until all earlier ones
+//! are yielded, keeping the input order.
+//!
+//! Neither stops at a failed handler. Each result is just an item, here a
+//! `Result`, and it is up to the consumer what to do with errors.
+
+use crate::random_fraction;
+
+use bytes::Bytes;
+use futures::StreamExt as _;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time;
+use tokio_stream::Stream;
+
+/// Most handlers running at the same time.
+pub const CONCURRENCY: usize = 4;
+
+/// A stand-in for real work, such as a request to another service: waits up
+/// to 50ms, then parses the message as a number.
+pub async fn handle(msg: Bytes) -> Result<u32, String> {
+    time::sleep(Duration::from_millis(50).mul_f64(random_fraction())).await;
+
+    std::str::from_utf8(&msg)
+        .ok()
+        .and_then(|msg| msg.parse().ok())
+        .ok_or_else(|| format!("not a number: {:?}", msg))
+}
+
+/// Run `handler` on the items of `stream` concurrently, yielding results in
+/// the order they complete.
+pub fn unordered<S, F, Fut>(stream: S, handler: F) -> impl Stream<Item = Fut::Output>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    stream.map(handler).buffer_unordered(CONCURRENCY)
+}
+
+/// Run `handler` on the items of `stream` concurrently, yielding results in
+/// the order of the items.
+pub fn ordered<S, F, Fut>(stream: S, handler: F) -> impl Stream<Item = Fut::Output>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    stream.map(handler).buffered(CONCURRENCY)
+}
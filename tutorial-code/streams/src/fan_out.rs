@@ -0,0 +1,64 @@
+//! Sharing one subscription between several tasks.
+//!
+//! A `Subscriber`, and the stream made from it, can only be read by one task.
+//! To give several tasks every message, a forwarding task reads the stream
+//! and sends each message into a `broadcast` channel. Each consumer gets its
+//! own receiver, wrapped in a `BroadcastStream` to use the stream adapters on
+//! it.
+//!
+//! A `broadcast` channel holds a fixed number of messages. A consumer falling
+//! further behind than that misses the oldest ones, and is told how many it
+//! missed with a `Lagged` error. It can carry on from there.
+
+use bytes::Bytes;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Messages the channel holds for consumers that are behind.
+pub const CAPACITY: usize = 16;
+
+/// Forward `messages` to `consumers` streams.
+///
+/// The receivers are created before forwarding starts, so every consumer
+/// sees every message, unless it lags.
+pub fn fan_out<S>(messages: S, consumers: usize) -> Vec<BroadcastStream<Bytes>>
+where
+    S: Stream<Item = Bytes> + Send + 'static,
+{
+    let (tx, _) = broadcast::channel(CAPACITY);
+    let streams = (0..consumers)
+        .map(|_| BroadcastStream::new(tx.subscribe()))
+        .collect();
+
+    tokio::spawn(async move {
+        tokio::pin!(messages);
+
+        while let Some(msg) = messages.next().await {
+            // Only fails once every consumer is gone.
+            if tx.send(msg).is_err() {
+                break;
+            }
+        }
+
+        // Dropping `tx` ends the consumers' streams, once they've read what
+        // is left in the channel.
+    });
+
+    streams
+}
+
+/// Log and skip over lag notices, yielding only messages.
+pub fn skip_lagged(
+    stream: BroadcastStream<Bytes>,
+    name: &'static str,
+) -> impl Stream<Item = Bytes> {
+    stream.filter_map(move |msg| match msg {
+        Ok(msg) => Some(msg),
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            eprintln!("{} lagged behind, missed {} messages", name, missed);
+            None
+        }
+    })
+}
@@ -0,0 +1,74 @@
+//! A stream adapter written by hand.
+//!
+//! Adapters like `filter` and `take` are streams wrapping another stream:
+//! their `poll_next` polls the inner stream and decides what to do with the
+//! result. `Instrumented` does the same, only to count what happens.
+
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_stream::Stream;
+
+/// What an `Instrumented` stream observed so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    /// Items yielded.
+    pub items: u64,
+
+    /// Polls that returned `Pending`.
+    pub pending: u64,
+
+    /// Time spent in the inner stream's `poll_next`.
+    pub poll_time: Duration,
+}
+
+pin_project! {
+    /// Wraps a stream, counting its items and polls.
+    pub struct Instrumented<S> {
+        // `S` may be `!Unpin`, so it can only be polled through a
+        // `Pin<&mut S>`. The projection provides one.
+        #[pin]
+        stream: S,
+        stats: Stats,
+    }
+}
+
+impl<S> Instrumented<S> {
+    pub fn new(stream: S) -> Instrumented<S> {
+        Instrumented {
+            stream,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl<S: Stream> Stream for Instrumented<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.project();
+
+        let start = Instant::now();
+        let res = this.stream.poll_next(cx);
+        this.stats.poll_time += start.elapsed();
+
+        match &res {
+            Poll::Ready(Some(_)) => this.stats.items += 1,
+            Poll::Ready(None) => {}
+            // The inner stream registered the waker, so there is nothing
+            // else to do before returning `Pending` ourselves.
+            Poll::Pending => this.stats.pending += 1,
+        }
+
+        res
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
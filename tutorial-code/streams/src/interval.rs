@@ -0,0 +1,84 @@
+//! The `Interval` stream from the "Implementing `Stream`" section, built on
+//! Tokio's `Sleep` instead of the chapter's hand-written `Delay`, and the same
+//! stream written with `async-stream`.
+
+use async_stream::stream;
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, sleep_until, Instant, Sleep};
+use tokio_stream::Stream;
+
+/// Time between two items.
+pub const PERIOD: Duration = Duration::from_millis(10);
+
+pin_project! {
+    /// Yields `()` `rem` times, `PERIOD` apart.
+    pub struct Interval {
+        rem: usize,
+
+        // Unlike the chapter's `Delay`, `Sleep` is `!Unpin`, so
+        // `Pin::new(&mut self.delay)` doesn't compile. `pin_project!` gives
+        // us a `Pin<&mut Sleep>` for this field instead, and makes
+        // `Interval` `!Unpin` in turn.
+        #[pin]
+        delay: Sleep,
+    }
+}
+
+impl Interval {
+    pub fn new(rem: usize) -> Interval {
+        Interval {
+            rem,
+            delay: sleep(PERIOD),
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        let mut this = self.project();
+
+        if *this.rem == 0 {
+            // No more delays
+            return Poll::Ready(None);
+        }
+
+        match this.delay.as_mut().poll(cx) {
+            Poll::Ready(_) => {
+                // The chapter replaces the `Delay` with a new one. A pinned
+                // `Sleep` can't be moved out of or overwritten, but it can be
+                // reset to a new deadline, which also avoids allocating a
+                // new timer for every item.
+                let when = this.delay.deadline() + PERIOD;
+                this.delay.reset(when);
+                *this.rem -= 1;
+                Poll::Ready(Some(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rem, Some(self.rem))
+    }
+}
+
+/// The same stream as `Interval`, from the "`async-stream`" section.
+///
+/// The returned stream is `!Unpin` too, as it holds the `Sleep` across a
+/// `yield`. It has to be pinned before calling `StreamExt::next` on it.
+pub fn interval_stream(rem: usize) -> impl Stream<Item = ()> {
+    stream! {
+        let mut when = Instant::now() + PERIOD;
+        for _ in 0..rem {
+            sleep_until(when).await;
+            yield ();
+            when += PERIOD;
+        }
+    }
+}
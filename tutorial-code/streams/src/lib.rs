@@ -0,0 +1,44 @@
+//! Code from the streams chapter that is easier to exercise from tests than
+//! from `main`.
+
+pub mod batch;
+pub mod channel;
+pub mod concurrent;
+pub mod fan_out;
+pub mod instrumented;
+pub mod interval;
+pub mod lines;
+pub mod merge;
+pub mod pipeline;
+pub mod publish;
+pub mod resilient;
+pub mod tick;
+pub mod timeout;
+pub mod unsubscribe;
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use tokio_stream::{Stream, StreamExt};
+
+/// Collect every item of `stream`.
+///
+/// `stream` may be `!Unpin`, as both interval implementations are. Pinning it
+/// on the stack with `tokio::pin!` shadows it with a `Pin<&mut S>`, which is
+/// `Unpin` and can be passed to `next`.
+pub async fn drain<S: Stream>(stream: S) -> Vec<S::Item> {
+    tokio::pin!(stream);
+
+    let mut items = vec![];
+    while let Some(item) = stream.next().await {
+        items.push(item);
+    }
+    items
+}
+
+/// A number in `[0, 1)`, random enough for jitter and simulated delays.
+pub(crate) fn random_fraction() -> f64 {
+    // Every `RandomState` is seeded differently, which saves depending on a
+    // random number crate.
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,79 @@
+//! A socket read as a stream of lines.
+//!
+//! Subscriptions and channels aren't the only things that can be streams.
+//! `FramedRead` turns anything implementing `AsyncRead` into a stream of
+//! frames, as decoded by a codec. With `LinesCodec`, the frames are lines of
+//! text, and the usual `StreamExt` adapters apply.
+
+use bytes::BytesMut;
+use tokio::io::AsyncRead;
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::codec::{Decoder, FramedRead, LinesCodec, LinesCodecError};
+
+/// Longest line accepted, in bytes. Without a limit, a peer that never sends
+/// a newline makes the buffer grow forever.
+pub const MAX_LINE_LENGTH: usize = 256;
+
+/// Read lines from `reader` until it is closed.
+///
+/// Lines over `MAX_LINE_LENGTH` are logged and skipped. An I/O error is
+/// logged too, and ends the stream.
+pub fn lines<R: AsyncRead>(reader: R) -> impl Stream<Item = String> {
+    let codec = SkipLongLines(LinesCodec::new_with_max_length(MAX_LINE_LENGTH));
+
+    FramedRead::new(reader, codec).filter_map(|line| match line {
+        Ok(line) => Some(line),
+        // `FramedRead` ends the stream after any error.
+        Err(err) => {
+            eprintln!("read failed: {}", err);
+            None
+        }
+    })
+}
+
+/// A `LinesCodec` that skips over lines that are too long.
+///
+/// `FramedRead` ends the stream at the first error, including
+/// `MaxLineLengthExceeded`. That one is only about a single line, though:
+/// the codec discards the rest of it and carries on with the next, if asked
+/// to. Doing so here, before `FramedRead` sees the error, keeps the stream
+/// going.
+struct SkipLongLines(LinesCodec);
+
+impl Decoder for SkipLongLines {
+    type Item = String;
+    type Error = LinesCodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<String>, LinesCodecError> {
+        loop {
+            match self.0.decode(buf) {
+                Err(LinesCodecError::MaxLineLengthExceeded) => skipped(),
+                res => return res,
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<String>, LinesCodecError> {
+        loop {
+            match self.0.decode_eof(buf) {
+                Err(LinesCodecError::MaxLineLengthExceeded) => skipped(),
+                res => return res,
+            }
+        }
+    }
+}
+
+fn skipped() {
+    eprintln!("skipping line over {} bytes", MAX_LINE_LENGTH);
+}
+
+/// Connect to `addr`, reading lines from the socket.
+pub async fn connect<A: ToSocketAddrs>(addr: A) -> std::io::Result<impl Stream<Item = String>> {
+    let socket = TcpStream::connect(addr).await?;
+
+    // Only the read half is needed. `into_split` gives an owned half, so the
+    // stream doesn't borrow from anything.
+    let (read, _write) = socket.into_split();
+    Ok(lines(read))
+}
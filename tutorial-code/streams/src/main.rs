@@ -1,30 +1,40 @@
+use tokio::sync::oneshot;
 use tokio_stream::StreamExt;
 use mini_redis::client;
+use streams::instrumented::Instrumented;
+use streams::pipeline::skip_errors;
+use streams::publish::publish_numbers;
+use std::env;
 
-async fn publish() -> mini_redis::Result<()> {
-    let mut client = client::connect("127.0.0.1:6379").await?;
+async fn publish(
+    addr: &str,
+    ready: oneshot::Receiver<()>,
+    throttle: bool,
+) -> mini_redis::Result<()> {
+    let mut client = client::connect(addr).await?;
 
-    // Publish some data
-    client.publish("numbers", "1".into()).await?;
-    client.publish("numbers", "two".into()).await?;
-    client.publish("numbers", "3".into()).await?;
-    client.publish("numbers", "four".into()).await?;
-    client.publish("numbers", "five".into()).await?;
-    client.publish("numbers", "6".into()).await?;
-    Ok(())
+    // Messages published before the subscriber is listening are never
+    // delivered to it, so wait until it is.
+    if ready.await.is_err() {
+        // The subscriber gave up before subscribing.
+        return Ok(());
+    }
+
+    // Publish some data, paced if `--throttle` was given
+    publish_numbers(&mut client, throttle).await
 }
 
-async fn subscribe() -> mini_redis::Result<()> {
-    let client = client::connect("127.0.0.1:6379").await?;
+async fn subscribe(addr: &str, ready: oneshot::Sender<()>) -> mini_redis::Result<()> {
+    let client = client::connect(addr).await?;
     let subscriber = client.subscribe(vec!["numbers".to_string()]).await?;
-    let messages = subscriber
-        .into_stream()
-        .filter(|msg| match msg {
-            Ok(msg) if msg.content.len() == 1 => true,
-            _ => false,
-        })
-        .map(|msg| msg.unwrap().content)
-        .take(3);
+
+    // `subscribe` returns once the server confirmed the subscription. From
+    // here on, every published message reaches us.
+    let _ = ready.send(());
+
+    // Keep the first three single digit numbers. See `pipeline.rs` for how.
+    // Wrapping the result counts how often it is polled.
+    let messages = Instrumented::new(skip_errors(subscriber.into_stream()));
 
     tokio::pin!(messages);
 
@@ -32,16 +42,26 @@ async fn subscribe() -> mini_redis::Result<()> {
         println!("got = {:?}", msg);
     }
 
+    eprintln!("stats = {:?}", messages.stats());
+
     Ok(())
 }
 
 #[tokio::main]
 async fn main() -> mini_redis::Result<()> {
-    tokio::spawn(async {
-        publish().await
+    let throttle = env::args().any(|arg| arg == "--throttle");
+    let addr = env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string());
+    let (ready_tx, ready_rx) = oneshot::channel();
+
+    let publish_addr = addr.clone();
+    tokio::spawn(async move {
+        publish(&publish_addr, ready_rx, throttle).await
     });
 
-    subscribe().await?;
+    subscribe(&addr, ready_tx).await?;
 
     Ok(())
 }
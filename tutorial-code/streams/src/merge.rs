@@ -0,0 +1,42 @@
+//! Reading several subscriptions as one stream.
+//!
+//! `select!` waits on a fixed set of branches written out in the code.
+//! `StreamMap` merges however many streams it holds, and streams can be added
+//! or removed while it is being read. Each item is tagged with the key of the
+//! stream it came from.
+
+use bytes::Bytes;
+use mini_redis::client;
+use std::pin::Pin;
+use tokio_stream::{Stream, StreamExt, StreamMap};
+
+/// The messages of a single channel.
+///
+/// A `StreamMap` holds values of a single type, while every subscription
+/// stream has its own anonymous type. Boxing them makes them the same type.
+/// Pinning the box takes care of the streams being `!Unpin`.
+pub type Messages = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
+
+/// Subscribe to each of `channels` on its own connection, merged into a
+/// single stream of `(channel, message)` pairs.
+pub async fn subscribe_each(
+    addr: &str,
+    channels: &[&str],
+) -> mini_redis::Result<StreamMap<String, Messages>> {
+    let mut map = StreamMap::new();
+
+    for &channel in channels {
+        let client = client::connect(addr).await?;
+        let subscriber = client.subscribe(vec![channel.to_string()]).await?;
+
+        // A failed read ends the subscription.
+        let messages = subscriber
+            .into_stream()
+            .map_while(|msg| msg.ok())
+            .map(|msg| msg.content);
+
+        map.insert(channel.to_string(), Box::pin(messages) as Messages);
+    }
+
+    Ok(map)
+}
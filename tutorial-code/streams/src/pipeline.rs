@@ -0,0 +1,45 @@
+//! The pipeline `subscribe` applies to the subscription, with two ways of
+//! dealing with errors.
+//!
+//! Filtering out the errors with `filter`, then calling `unwrap` in `map`,
+//! works, but only as long as the two closures agree. Change the `filter` and
+//! the `map` starts panicking. `filter_map` does both in one closure, so there
+//! is nothing to keep in sync.
+
+use bytes::Bytes;
+use mini_redis::client::Message;
+use tokio_stream::{Stream, StreamExt};
+
+/// Yield the first three single character messages. Errors are logged and
+/// skipped.
+pub fn skip_errors<S>(messages: S) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = mini_redis::Result<Message>>,
+{
+    messages
+        .filter_map(|msg| match msg {
+            Ok(msg) if msg.content.len() == 1 => Some(msg.content),
+            Ok(_) => None,
+            Err(err) => {
+                eprintln!("skipping message: {}", err);
+                None
+            }
+        })
+        .take(3)
+}
+
+/// Yield the first three single character messages, ending the stream at the
+/// first error instead.
+pub fn stop_on_error<S>(messages: S) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = mini_redis::Result<Message>>,
+{
+    messages
+        .take_while(|msg| msg.is_ok())
+        .filter_map(|msg| match msg {
+            Ok(msg) if msg.content.len() == 1 => Some(msg.content),
+            // `take_while` already ended the stream at the first error.
+            _ => None,
+        })
+        .take(3)
+}
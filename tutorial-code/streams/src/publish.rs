@@ -0,0 +1,60 @@
+//! Pacing the publisher, and observing the pace from the subscriber.
+//!
+//! Adapters shape a stream's timing as well as its items. Running the
+//! messages through `StreamExt::throttle` before publishing them spaces them
+//! out, and the subscriber sees the same spacing between arrivals.
+
+use mini_redis::client::Client;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_stream::{self as stream, Stream, StreamExt};
+
+/// The messages `main.rs` publishes.
+pub const NUMBERS: [&str; 6] = ["1", "two", "3", "four", "five", "6"];
+
+/// Time between two messages when throttled.
+pub const THROTTLE: Duration = Duration::from_millis(100);
+
+/// Publish `NUMBERS` to the `numbers` channel, all at once, or `THROTTLE`
+/// apart if `throttle` is set.
+pub async fn publish_numbers(client: &mut Client, throttle: bool) -> mini_redis::Result<()> {
+    let numbers = stream::iter(NUMBERS.iter().copied());
+
+    if throttle {
+        publish_all(client, numbers.throttle(THROTTLE)).await
+    } else {
+        publish_all(client, numbers).await
+    }
+}
+
+async fn publish_all<S>(client: &mut Client, numbers: S) -> mini_redis::Result<()>
+where
+    S: Stream<Item = &'static str>,
+{
+    // `Throttle` is `!Unpin`.
+    tokio::pin!(numbers);
+
+    while let Some(number) = numbers.next().await {
+        client.publish("numbers", number.into()).await?;
+    }
+
+    Ok(())
+}
+
+/// Consume `stream`, returning the time between every two consecutive items.
+pub async fn arrival_gaps<S: Stream>(stream: S) -> Vec<Duration> {
+    tokio::pin!(stream);
+
+    let mut gaps = vec![];
+    let mut last = None;
+
+    while stream.next().await.is_some() {
+        let now = Instant::now();
+        if let Some(last) = last {
+            gaps.push(now - last);
+        }
+        last = Some(now);
+    }
+
+    gaps
+}
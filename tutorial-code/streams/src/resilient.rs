@@ -0,0 +1,95 @@
+//! A subscription that survives the server going away.
+//!
+//! The stream returned by `Subscriber::into_stream` ends as soon as the
+//! connection is lost, for example when the server restarts. Here, the
+//! subscription is wrapped in a loop that reconnects and subscribes again,
+//! while the caller keeps reading from a single stream.
+
+use crate::random_fraction;
+
+use async_stream::stream;
+use mini_redis::client::{self, Message, Subscriber};
+use std::time::Duration;
+use tokio::time::sleep;
+use tokio_stream::{Stream, StreamExt};
+
+/// How long to wait between attempts to reconnect.
+///
+/// The delay doubles after every failed attempt, up to `max`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// Delay after the first failed attempt.
+    pub initial: Duration,
+
+    /// Upper bound on the delay.
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(5),
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the attempt following `failures` failed ones.
+    ///
+    /// Half of the delay is random, so that many clients disconnected at the
+    /// same time don't all reconnect at the same time too.
+    pub fn delay(&self, failures: u32) -> Duration {
+        let delay = self
+            .initial
+            .checked_mul(1 << failures.min(16))
+            .map_or(self.max, |delay| delay.min(self.max));
+
+        delay / 2 + delay.mul_f64(random_fraction() / 2.0)
+    }
+}
+
+/// Subscribe to `channels` on the server at `addr`, resubscribing whenever
+/// the connection is lost. The stream never ends.
+pub fn resilient_subscribe(addr: String, channels: Vec<String>) -> impl Stream<Item = Message> {
+    resilient_subscribe_with(addr, channels, Backoff::default())
+}
+
+/// Like `resilient_subscribe`, waiting between attempts according to
+/// `backoff`.
+pub fn resilient_subscribe_with(
+    addr: String,
+    channels: Vec<String>,
+    backoff: Backoff,
+) -> impl Stream<Item = Message> {
+    stream! {
+        let mut failures = 0;
+
+        loop {
+            let subscriber = match subscribe(&addr, &channels).await {
+                Ok(subscriber) => subscriber,
+                Err(_) => {
+                    sleep(backoff.delay(failures)).await;
+                    failures = failures.saturating_add(1);
+                    continue;
+                }
+            };
+            failures = 0;
+
+            let messages = subscriber.into_stream();
+            tokio::pin!(messages);
+
+            // The inner stream yields an error, then ends, once the
+            // connection is lost. Messages published until we're subscribed
+            // again are missed.
+            while let Some(Ok(message)) = messages.next().await {
+                yield message;
+            }
+        }
+    }
+}
+
+async fn subscribe(addr: &str, channels: &[String]) -> mini_redis::Result<Subscriber> {
+    let client = client::connect(addr).await?;
+    client.subscribe(channels.to_vec()).await
+}
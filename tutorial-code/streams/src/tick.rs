@@ -0,0 +1,53 @@
+//! Doing something every so often while reading a stream.
+//!
+//! `select!` can wait on `StreamExt::next` and `Interval::tick` at the same
+//! time. The catch is the end of the stream: once `next` returns `None`, it
+//! keeps returning `None` right away, and a `select!` that keeps polling it
+//! spins, never waiting on the other branch. An `if` guard disables the
+//! branch once the stream is done.
+
+use std::time::Duration;
+use tokio::time;
+use tokio_stream::{Stream, StreamExt};
+
+/// Length of the window items are counted over.
+pub const WINDOW: Duration = Duration::from_secs(1);
+
+/// Count the items of `stream` arriving in every `WINDOW`, logging each
+/// count as its window closes.
+///
+/// Returns the counts once the stream ended, including the window it ended
+/// in.
+pub async fn count_per_window<S: Stream>(stream: S) -> Vec<usize> {
+    tokio::pin!(stream);
+
+    let mut interval = time::interval(WINDOW);
+    // The first tick completes immediately. Windows start from there.
+    interval.tick().await;
+
+    let mut counts = vec![];
+    let mut count = 0;
+    let mut open = true;
+
+    loop {
+        tokio::select! {
+            // Without the guard, this branch would win every time once the
+            // stream is done.
+            item = stream.next(), if open => match item {
+                Some(_) => count += 1,
+                None => open = false,
+            },
+            _ = interval.tick() => {
+                println!("{} messages in the last {:?}", count, WINDOW);
+                counts.push(count);
+                count = 0;
+
+                if !open {
+                    break;
+                }
+            }
+        }
+    }
+
+    counts
+}
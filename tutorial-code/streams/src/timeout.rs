@@ -0,0 +1,46 @@
+//! Noticing when a subscription goes quiet.
+//!
+//! `StreamExt::timeout` wraps every item in a `Result`, with `Err(Elapsed)`
+//! standing for "nothing arrived in time". The subscription stream already
+//! yields `Result`s, so the items end up as
+//! `Result<Result<Message, Error>, Elapsed>`. `flatten` turns that into
+//! something easier to match on.
+
+use std::time::Duration;
+use tokio_stream::{Elapsed, Stream, StreamExt};
+
+/// How long the subscription may be quiet before an `Event::Idle`.
+pub const IDLE: Duration = Duration::from_millis(500);
+
+/// An item of a stream, or a notice that none arrived for a while.
+#[derive(Debug, PartialEq)]
+pub enum Event<T> {
+    Item(T),
+    Idle,
+}
+
+/// Flatten an item of `StreamExt::timeout` applied to a stream of `Result`s.
+///
+/// A timeout isn't an error: the stream is still there, and the consumer may
+/// keep waiting. Only errors of the inner stream are returned as `Err`.
+pub fn flatten<T, E>(item: Result<Result<T, E>, Elapsed>) -> Result<Event<T>, E> {
+    match item {
+        Ok(Ok(item)) => Ok(Event::Item(item)),
+        Ok(Err(err)) => Err(err),
+        Err(_elapsed) => Ok(Event::Idle),
+    }
+}
+
+/// Yield an `Event::Idle` whenever `stream` yields nothing for `idle`.
+///
+/// Timeouts don't repeat: however long the stream stays quiet, there is a
+/// single `Event::Idle` until the next item.
+pub fn with_idle_notices<S, T, E>(
+    stream: S,
+    idle: Duration,
+) -> impl Stream<Item = Result<Event<T>, E>>
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    stream.timeout(idle).map(flatten)
+}
@@ -0,0 +1,45 @@
+//! Ending a subscription on purpose.
+//!
+//! `into_stream` takes the `Subscriber` by value, so once a subscription is
+//! turned into a stream, there is no way to call `unsubscribe` on it anymore.
+//! The stream only ends when the connection does. To be able to unsubscribe,
+//! keep the `Subscriber` and call `next_message` in a loop instead. Being a
+//! method on `&mut Subscriber`, it leaves the subscriber in our hands once the
+//! loop is done.
+
+use bytes::Bytes;
+use mini_redis::client::Subscriber;
+use tokio::sync::oneshot;
+
+/// Receive up to `max` messages, or until `shutdown` fires, then unsubscribe
+/// from every channel.
+///
+/// Returns the messages, and the subscriber, which is subscribed to nothing
+/// anymore. Messages published from then on are not delivered to it.
+pub async fn receive_then_unsubscribe(
+    mut subscriber: Subscriber,
+    max: usize,
+    mut shutdown: oneshot::Receiver<()>,
+) -> mini_redis::Result<(Vec<Bytes>, Subscriber)> {
+    let mut received = vec![];
+
+    while received.len() < max {
+        tokio::select! {
+            // Either the signal was sent, or its sender dropped. Both mean
+            // we are done.
+            _ = &mut shutdown => break,
+            msg = subscriber.next_message() => match msg? {
+                Some(msg) => received.push(msg.content),
+                // The server closed the subscription.
+                None => break,
+            },
+        }
+    }
+
+    // A message published after our last read, but before the server
+    // processed the `UNSUBSCRIBE`, arrives first. mini-redis' client treats it
+    // as an unexpected response and returns an error.
+    subscriber.unsubscribe(&[]).await?;
+
+    Ok((received, subscriber))
+}
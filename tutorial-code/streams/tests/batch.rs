@@ -0,0 +1,95 @@
+mod common;
+
+use mini_redis::client;
+use streams::batch::{batches, WINDOW};
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn fills_batches_then_flushes_on_window() {
+    let addr = common::start_server().await;
+
+    let subscriber = client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["numbers".to_string()])
+        .await
+        .unwrap();
+
+    // 25 messages in a burst, then 3 more, each after the previous batch's
+    // window has closed.
+    tokio::spawn(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        for i in 0..25 {
+            client
+                .publish("numbers", i.to_string().into())
+                .await
+                .unwrap();
+        }
+        for i in 25..28 {
+            time::sleep(WINDOW * 2).await;
+            client
+                .publish("numbers", i.to_string().into())
+                .await
+                .unwrap();
+        }
+    });
+
+    // Without `take`, the last batch would never be yielded, as the
+    // subscription never ends.
+    let messages = subscriber.into_stream().map(Result::unwrap).take(28);
+    let batches = batches(messages);
+    tokio::pin!(batches);
+
+    let mut sizes = vec![];
+    let mut received = vec![];
+    while let Some(batch) = batches.next().await {
+        // Each batch is handled in one go.
+        sizes.push(batch.len());
+        received.extend(batch.into_iter().map(|msg| msg.content));
+    }
+
+    assert_eq!(sizes, [10, 10, 5, 1, 1, 1]);
+    let expected: Vec<_> = (0..28).map(|i| i.to_string()).collect();
+    assert_eq!(received, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn quiet_window_yields_nothing() {
+    let (tx, rx) = mpsc::channel(16);
+    let batches = batches(ReceiverStream::new(rx));
+    tokio::pin!(batches);
+
+    // Several windows pass without a message.
+    let res = time::timeout(WINDOW * 5, batches.next()).await;
+    assert!(res.is_err(), "{:?}", res);
+
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    drop(tx);
+
+    // The stream ending flushes the partial batch right away.
+    let start = time::Instant::now();
+    assert_eq!(batches.next().await, Some(vec![1, 2]));
+    assert!(start.elapsed() < WINDOW);
+    assert_eq!(batches.next().await, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn partial_batch_after_window() {
+    let (tx, rx) = mpsc::channel(16);
+    let batches = batches(ReceiverStream::new(rx));
+    tokio::pin!(batches);
+
+    tx.send(1).await.unwrap();
+
+    let start = time::Instant::now();
+    assert_eq!(batches.next().await, Some(vec![1]));
+    assert_eq!(start.elapsed(), WINDOW);
+
+    // Only `tx` keeps the stream going.
+    drop(tx);
+    assert_eq!(batches.next().await, None);
+}
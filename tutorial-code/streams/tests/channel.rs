@@ -0,0 +1,67 @@
+use std::time::Duration;
+use streams::channel::{first_three, produce, CAPACITY};
+use tokio::time;
+use tokio_stream::{self as stream, StreamExt};
+
+fn inputs() -> Vec<String> {
+    ["1", "two", "3", "four", "five", "6", "7"]
+        .iter()
+        .map(|input| input.to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn skips_errors_and_takes_three() {
+    let items = vec![Ok(1), Err("two"), Ok(3), Err("four"), Ok(5), Ok(6)];
+    let out: Vec<_> = first_three(stream::iter(items)).collect().await;
+    assert_eq!(out, [1, 3, 5]);
+}
+
+#[tokio::test]
+async fn ends_with_source() {
+    let items = vec![Err("one"), Ok(2)];
+    let out: Vec<_> = first_three(stream::iter(items)).collect().await;
+    assert_eq!(out, [2]);
+}
+
+#[tokio::test]
+async fn same_pipeline_over_channel() {
+    let (numbers, producer) = produce(inputs());
+
+    let out: Vec<_> = first_three(numbers).collect().await;
+    assert_eq!(out, [1, 3, 6]);
+
+    // Once `take` is done, the stream is dropped, and so is the receiver.
+    // The producer notices and stops early.
+    let waits = producer.await.unwrap();
+    assert!(waits.len() < inputs().len(), "{:?}", waits);
+}
+
+#[tokio::test(start_paused = true)]
+async fn producer_waits_for_slow_consumer() {
+    let (numbers, producer) = produce(inputs());
+
+    // A consumer that takes a while with every number.
+    let numbers = first_three(numbers).then(|n| async move {
+        time::sleep(Duration::from_millis(100)).await;
+        n
+    });
+    let out: Vec<_> = numbers.collect().await;
+    assert_eq!(out, [1, 3, 6]);
+
+    // The first sends fill the channel without waiting. After that, the
+    // producer can only send once the consumer made room.
+    let waits = producer.await.unwrap();
+    assert!(
+        waits[..CAPACITY].iter().all(|wait| wait.is_zero()),
+        "{:?}",
+        waits
+    );
+    assert!(
+        waits[CAPACITY..]
+            .iter()
+            .any(|wait| *wait >= Duration::from_millis(100)),
+        "{:?}",
+        waits
+    );
+}
@@ -0,0 +1,12 @@
+use std::future;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+/// Run a mini-redis server in the background, returning its address.
+pub async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(mini_redis::server::run(listener, future::pending::<()>()));
+    addr
+}
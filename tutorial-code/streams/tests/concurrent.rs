@@ -0,0 +1,65 @@
+use std::time::Duration;
+use streams::concurrent::{handle, ordered, unordered};
+use tokio::time;
+use tokio_stream::{self as stream, StreamExt};
+
+/// Earlier items take longer, so later ones finish first whenever they run
+/// at the same time. Every fifth item fails.
+async fn handler(i: u64) -> Result<u64, u64> {
+    time::sleep(Duration::from_millis(10 * (20 - i))).await;
+
+    if i.is_multiple_of(5) {
+        Err(i)
+    } else {
+        Ok(i)
+    }
+}
+
+fn expected() -> Vec<Result<u64, u64>> {
+    (0..20u64)
+        .map(|i| if i.is_multiple_of(5) { Err(i) } else { Ok(i) })
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn buffered_keeps_order() {
+    let results: Vec<_> = ordered(stream::iter(0..20), handler).collect().await;
+    assert_eq!(results, expected());
+}
+
+#[tokio::test(start_paused = true)]
+async fn buffer_unordered_reorders() {
+    let mut results: Vec<_> = unordered(stream::iter(0..20), handler).collect().await;
+
+    // Items 0 to 3 start together, and 3 is the quickest of them.
+    assert_eq!(results[0], Ok(3));
+    assert_ne!(results, expected());
+
+    // Nothing is lost, errors included.
+    results.sort_by_key(|res| match res {
+        Ok(i) | Err(i) => *i,
+    });
+    assert_eq!(results, expected());
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_handlers_concurrently() {
+    let start = time::Instant::now();
+    let _: Vec<_> = unordered(stream::iter(0..4), handler).collect().await;
+
+    // The four handlers overlap: the whole batch takes as long as the
+    // slowest, not the sum of all of them.
+    assert_eq!(start.elapsed(), Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn handler_errors_are_items() {
+    let messages = stream::iter(vec!["1".into(), "two".into(), "3".into()]);
+    let mut results: Vec<_> = unordered(messages, handle).collect().await;
+    results.sort();
+
+    assert_eq!(
+        results,
+        [Ok(1), Ok(3), Err("not a number: b\"two\"".to_string())]
+    );
+}
@@ -0,0 +1,61 @@
+use bytes::Bytes;
+use std::time::Duration;
+use streams::fan_out::{fan_out, skip_lagged, CAPACITY};
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+const MESSAGES: usize = CAPACITY * 4;
+
+/// A message every millisecond, standing in for a subscription.
+fn messages() -> ReceiverStream<Bytes> {
+    let (tx, rx) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        for i in 0..MESSAGES {
+            tx.send(i.to_string().into()).await.unwrap();
+            time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_consumer_lags_but_sees_last_message() {
+    let mut streams = fan_out(messages(), 2);
+    let slow = streams.pop().unwrap();
+    let fast = streams.pop().unwrap();
+
+    let fast = tokio::spawn(skip_lagged(fast, "fast").collect::<Vec<_>>());
+
+    let slow = tokio::spawn(async move {
+        tokio::pin!(slow);
+
+        let mut received = vec![];
+        let mut lagged = false;
+        while let Some(msg) = slow.next().await {
+            match msg {
+                Ok(msg) => received.push(msg),
+                Err(BroadcastStreamRecvError::Lagged(_)) => lagged = true,
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        (received, lagged)
+    });
+
+    let last = Bytes::from((MESSAGES - 1).to_string());
+
+    // The fast consumer keeps up, seeing everything.
+    let fast = fast.await.unwrap();
+    assert_eq!(fast.len(), MESSAGES);
+    assert_eq!(fast.last(), Some(&last));
+
+    // The slow one misses some, is told so, and still gets to the end.
+    let (received, lagged) = slow.await.unwrap();
+    assert!(lagged);
+    assert!(received.len() < MESSAGES, "{:?}", received);
+    assert_eq!(received.last(), Some(&last));
+}
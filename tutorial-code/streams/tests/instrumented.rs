@@ -0,0 +1,41 @@
+use std::time::Duration;
+use streams::instrumented::{Instrumented, Stats};
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{self as stream, StreamExt};
+
+#[tokio::test]
+async fn counts_items() {
+    let mut numbers = Instrumented::new(stream::iter(0..100));
+    assert_eq!(numbers.stats(), Stats::default());
+
+    let mut count = 0;
+    while numbers.next().await.is_some() {
+        count += 1;
+    }
+
+    assert_eq!(count, 100);
+    assert_eq!(numbers.stats().items, 100);
+}
+
+#[tokio::test]
+async fn counts_pending_polls() {
+    let (tx, rx) = mpsc::channel(1);
+    tokio::spawn(async move {
+        for i in 0..3 {
+            time::sleep(Duration::from_millis(10)).await;
+            tx.send(i).await.unwrap();
+        }
+    });
+
+    let numbers = Instrumented::new(ReceiverStream::new(rx));
+    tokio::pin!(numbers);
+
+    let items: Vec<_> = numbers.as_mut().collect().await;
+    assert_eq!(items, [0, 1, 2]);
+
+    let stats = numbers.stats();
+    assert_eq!(stats.items, 3);
+    assert!(stats.pending > 0, "{:?}", stats);
+}
@@ -0,0 +1,35 @@
+use streams::drain;
+use streams::interval::{interval_stream, Interval, PERIOD};
+use tokio::time::Instant;
+use tokio_stream::{Stream, StreamExt};
+
+// With the clock paused, time only advances when every task is waiting on a
+// timer, so the spacing is exact rather than roughly 10ms.
+#[tokio::test(start_paused = true)]
+async fn yields_three_items_a_period_apart() {
+    let interval = Interval::new(3);
+    assert_eq!(interval.size_hint(), (3, Some(3)));
+
+    // `Interval` is `!Unpin`, and `next` requires `Unpin`.
+    tokio::pin!(interval);
+
+    let start = Instant::now();
+    let mut ticks = vec![];
+    while let Some(()) = interval.next().await {
+        ticks.push(start.elapsed());
+    }
+
+    assert_eq!(ticks, [PERIOD, PERIOD * 2, PERIOD * 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn async_stream_matches_manual_impl() {
+    let start = Instant::now();
+    let manual = drain(Interval::new(3).map(|()| start.elapsed())).await;
+
+    let start = Instant::now();
+    let generated = drain(interval_stream(3).map(|()| start.elapsed())).await;
+
+    assert_eq!(manual, [PERIOD, PERIOD * 2, PERIOD * 3]);
+    assert_eq!(manual, generated);
+}
@@ -0,0 +1,47 @@
+use streams::lines::{connect, MAX_LINE_LENGTH};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn skips_long_line() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+
+        let long = "x".repeat(MAX_LINE_LENGTH + 1);
+        for line in ["1", "two", &long, "3", "four"].iter() {
+            socket.write_all(line.as_bytes()).await.unwrap();
+            socket.write_all(b"\n").await.unwrap();
+        }
+        // Dropping the socket closes it, ending the stream.
+    });
+
+    let lines: Vec<_> = connect(addr).await.unwrap().collect().await;
+    assert_eq!(lines, ["1", "two", "3", "four"]);
+}
+
+#[tokio::test]
+async fn same_adapters_as_subscription() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        socket
+            .write_all(b"1\ntwo\n3\nfour\nfive\n6\n")
+            .await
+            .unwrap();
+    });
+
+    // The filter from the chapter, on a socket this time.
+    let lines = connect(addr)
+        .await
+        .unwrap()
+        .filter(|line| line.len() == 1)
+        .take(3);
+    let lines: Vec<_> = lines.collect().await;
+    assert_eq!(lines, ["1", "3", "6"]);
+}
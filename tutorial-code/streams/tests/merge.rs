@@ -0,0 +1,94 @@
+mod common;
+
+use mini_redis::client;
+use std::collections::HashMap;
+use streams::merge::subscribe_each;
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn keeps_per_channel_order() {
+    let addr = common::start_server().await;
+    let mut messages = subscribe_each(&addr.to_string(), &["numbers", "letters"])
+        .await
+        .unwrap();
+
+    let mut client = client::connect(addr).await.unwrap();
+    for (number, letter) in ["1", "2", "3"].iter().zip(["a", "b", "c"].iter()) {
+        client
+            .publish("numbers", number.to_string().into())
+            .await
+            .unwrap();
+        client
+            .publish("letters", letter.to_string().into())
+            .await
+            .unwrap();
+    }
+
+    // The two subscriptions are separate connections, so how the channels
+    // interleave is up to the scheduler. Within a channel, order is kept.
+    let mut received: HashMap<String, Vec<String>> = HashMap::new();
+    for _ in 0..6 {
+        let (channel, msg) = messages.next().await.unwrap();
+        received
+            .entry(channel)
+            .or_default()
+            .push(String::from_utf8(msg.to_vec()).unwrap());
+    }
+
+    assert_eq!(received["numbers"], ["1", "2", "3"]);
+    assert_eq!(received["letters"], ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn removed_channel_stops_while_other_continues() {
+    let addr = common::start_server().await;
+    let mut messages = subscribe_each(&addr.to_string(), &["numbers", "letters"])
+        .await
+        .unwrap();
+
+    assert!(messages.remove("letters").is_some());
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.publish("letters", "a".into()).await.unwrap();
+    client.publish("numbers", "1".into()).await.unwrap();
+    client.publish("numbers", "2".into()).await.unwrap();
+
+    let (channel, msg) = messages.next().await.unwrap();
+    assert_eq!((channel.as_str(), &msg[..]), ("numbers", &b"1"[..]));
+    let (channel, msg) = messages.next().await.unwrap();
+    assert_eq!((channel.as_str(), &msg[..]), ("numbers", &b"2"[..]));
+}
+
+#[tokio::test]
+async fn merge_example() {
+    let addr = common::start_server().await;
+
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_merge"))
+        .arg(addr.to_string())
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+
+    let lines: Vec<_> = stdout.lines().collect();
+    let numbers: Vec<_> = lines
+        .iter()
+        .filter(|line| line.starts_with("numbers"))
+        .collect();
+    assert_eq!(numbers.len(), 6, "{}", stdout);
+
+    // Nothing arrives from `letters` once it's removed.
+    let removed = lines
+        .iter()
+        .position(|line| *line == "unsubscribed from letters")
+        .unwrap();
+    assert_eq!(lines[removed - 1], "letters = b\"c\"");
+    assert!(
+        !lines[removed..]
+            .iter()
+            .any(|line| line.starts_with("letters")),
+        "{}",
+        stdout
+    );
+}
@@ -0,0 +1,43 @@
+use mini_redis::client::Message;
+use streams::pipeline::{skip_errors, stop_on_error};
+use tokio_stream::{self as stream, Stream, StreamExt};
+
+fn ok(content: &'static str) -> mini_redis::Result<Message> {
+    Ok(Message {
+        channel: "numbers".to_string(),
+        content: content.into(),
+    })
+}
+
+fn messages() -> impl Stream<Item = mini_redis::Result<Message>> {
+    stream::iter(vec![
+        ok("1"),
+        ok("two"),
+        Err("connection reset".into()),
+        ok("3"),
+        ok("4"),
+        ok("5"),
+    ])
+}
+
+#[tokio::test]
+async fn skip_errors_continues_past_error() {
+    let out: Vec<_> = skip_errors(messages()).collect().await;
+    assert_eq!(out, ["1", "3", "4"]);
+}
+
+#[tokio::test]
+async fn stop_on_error_ends_at_error() {
+    let out: Vec<_> = stop_on_error(messages()).collect().await;
+    assert_eq!(out, ["1"]);
+}
+
+#[tokio::test]
+async fn both_agree_without_errors() {
+    let items = || stream::iter(vec![ok("1"), ok("two"), ok("3"), ok("4"), ok("5")]);
+
+    let skipped: Vec<_> = skip_errors(items()).collect().await;
+    let stopped: Vec<_> = stop_on_error(items()).collect().await;
+    assert_eq!(skipped, ["1", "3", "4"]);
+    assert_eq!(skipped, stopped);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use mini_redis::client;
+use std::time::Duration;
+use streams::publish::{arrival_gaps, publish_numbers, NUMBERS, THROTTLE};
+use tokio_stream::StreamExt;
+
+async fn gaps(throttle: bool) -> Vec<Duration> {
+    let addr = common::start_server().await;
+
+    let subscriber = client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["numbers".to_string()])
+        .await
+        .unwrap();
+
+    tokio::spawn(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        publish_numbers(&mut client, throttle).await.unwrap();
+    });
+
+    arrival_gaps(subscriber.into_stream().take(NUMBERS.len())).await
+}
+
+#[tokio::test]
+async fn throttled_arrivals_are_spaced() {
+    let gaps = gaps(true).await;
+    assert_eq!(gaps.len(), NUMBERS.len() - 1);
+
+    // Some slack for the trip through the server.
+    assert!(
+        gaps.iter().all(|gap| *gap >= THROTTLE * 8 / 10),
+        "{:?}",
+        gaps
+    );
+}
+
+#[tokio::test]
+async fn unthrottled_arrivals_are_bunched() {
+    let gaps = gaps(false).await;
+    assert_eq!(gaps.len(), NUMBERS.len() - 1);
+
+    // Everything arrives in less time than a single throttled gap.
+    let total: Duration = gaps.iter().sum();
+    assert!(total < THROTTLE, "{:?}", gaps);
+}
@@ -0,0 +1,74 @@
+use mini_redis::client;
+use std::net::SocketAddr;
+use std::time::Duration;
+use streams::resilient::{resilient_subscribe_with, Backoff};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+
+/// Run a mini-redis server on `listener` until the returned sender is used.
+fn serve(listener: TcpListener) -> (oneshot::Sender<()>, JoinHandle<()>) {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        mini_redis::server::run(listener, shutdown_rx)
+            .await
+            .unwrap();
+    });
+    (shutdown_tx, handle)
+}
+
+/// Publish `content` until a subscriber receives it.
+async fn publish(addr: SocketAddr, content: &'static str) {
+    let mut client = client::connect(addr).await.unwrap();
+    while client.publish("numbers", content.into()).await.unwrap() == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[test]
+fn backoff_doubles_up_to_max() {
+    let backoff = Backoff {
+        initial: Duration::from_millis(100),
+        max: Duration::from_secs(1),
+    };
+
+    for (failures, full) in [
+        (0, 100),
+        (1, 200),
+        (2, 400),
+        (3, 800),
+        (4, 1000),
+        (40, 1000),
+    ] {
+        let full = Duration::from_millis(full);
+        let delay = backoff.delay(failures);
+        assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+    }
+}
+
+#[tokio::test]
+async fn resubscribes_after_restart() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, server) = serve(listener);
+
+    let backoff = Backoff {
+        initial: Duration::from_millis(10),
+        max: Duration::from_millis(50),
+    };
+    let messages = resilient_subscribe_with(addr.to_string(), vec!["numbers".to_string()], backoff);
+    tokio::pin!(messages);
+
+    let (message, ()) = tokio::join!(messages.next(), publish(addr, "before"));
+    assert_eq!(message.unwrap().content, "before");
+
+    // Stop the server, closing every connection, and start a new one on the
+    // same address.
+    shutdown.send(()).unwrap();
+    server.await.unwrap();
+    let (_shutdown, _server) = serve(TcpListener::bind(addr).await.unwrap());
+
+    let (message, ()) = tokio::join!(messages.next(), publish(addr, "after"));
+    assert_eq!(message.unwrap().content, "after");
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use tokio::process::Command;
+
+#[tokio::test]
+async fn prints_single_digit_numbers_in_order() {
+    let addr = common::start_server().await;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_streams"))
+        .arg(addr.to_string())
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{}", stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["got = b\"1\"", "got = b\"3\"", "got = b\"6\""]
+    );
+}
+
+#[tokio::test]
+async fn throttled_output_is_the_same() {
+    let addr = common::start_server().await;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_streams"))
+        .arg("--throttle")
+        .arg(addr.to_string())
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{}", stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["got = b\"1\"", "got = b\"3\"", "got = b\"6\""]
+    );
+}
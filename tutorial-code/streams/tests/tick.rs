@@ -0,0 +1,50 @@
+use std::time::Duration;
+use streams::tick::{count_per_window, WINDOW};
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Send bursts of messages, sleeping before each according to `schedule`.
+/// The stream ends after the last burst.
+fn bursts(schedule: Vec<(Duration, usize)>) -> ReceiverStream<()> {
+    let (tx, rx) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        for (delay, len) in schedule {
+            time::sleep(delay).await;
+            for _ in 0..len {
+                tx.send(()).await.unwrap();
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[tokio::test(start_paused = true)]
+async fn counts_bursts_per_window() {
+    // Bursts at 0.5s, 1.5s and 2.5s, each in its own window.
+    let messages = bursts(vec![(WINDOW / 2, 3), (WINDOW, 2), (WINDOW, 1)]);
+
+    let start = time::Instant::now();
+    assert_eq!(count_per_window(messages).await, [3, 2, 1]);
+
+    // The last window was waited out, rather than ended early or skipped.
+    assert_eq!(start.elapsed(), WINDOW * 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn quiet_window_counts_zero() {
+    let messages = bursts(vec![(WINDOW / 2, 1), (WINDOW * 2, 4)]);
+    assert_eq!(count_per_window(messages).await, [1, 0, 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn ended_stream_does_not_spin() {
+    let messages = bursts(vec![]);
+
+    // The stream is done right away. Had `select!` kept polling it, the
+    // interval would never get a turn and this would never complete.
+    let counts = time::timeout(WINDOW * 2, count_per_window(messages)).await;
+    assert_eq!(counts.unwrap(), [0]);
+}
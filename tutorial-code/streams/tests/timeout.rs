@@ -0,0 +1,66 @@
+mod common;
+
+use mini_redis::client;
+use std::time::Duration;
+use streams::timeout::{flatten, with_idle_notices, Event, IDLE};
+use tokio::time;
+use tokio_stream::{self as stream, Elapsed, StreamExt};
+
+/// `Elapsed` has no public constructor. Get one from a stream that never
+/// yields anything.
+async fn elapsed() -> Elapsed {
+    let never = stream::pending::<()>().timeout(Duration::ZERO);
+    tokio::pin!(never);
+
+    never.next().await.unwrap().unwrap_err()
+}
+
+#[test]
+fn flatten_item() {
+    assert_eq!(flatten::<_, ()>(Ok(Ok(1))), Ok(Event::Item(1)));
+}
+
+#[test]
+fn flatten_inner_error() {
+    assert_eq!(flatten::<u32, _>(Ok(Err("closed"))), Err("closed"));
+}
+
+#[tokio::test]
+async fn flatten_timeout() {
+    assert_eq!(flatten::<u32, ()>(Err(elapsed().await)), Ok(Event::Idle));
+}
+
+#[tokio::test]
+async fn idle_notice_between_messages() {
+    let addr = common::start_server().await;
+
+    let subscriber = client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["numbers".to_string()])
+        .await
+        .unwrap();
+    let events = with_idle_notices(subscriber.into_stream(), IDLE);
+    tokio::pin!(events);
+
+    tokio::spawn(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        client.publish("numbers", "1".into()).await.unwrap();
+        time::sleep(Duration::from_millis(700)).await;
+        client.publish("numbers", "2".into()).await.unwrap();
+    });
+
+    let mut seen = vec![];
+    while seen.len() < 3 {
+        match events.next().await.unwrap().unwrap() {
+            Event::Item(msg) => seen.push(String::from_utf8(msg.content.to_vec()).unwrap()),
+            Event::Idle => {
+                // Log it and keep waiting.
+                println!("no message for {:?}", IDLE);
+                seen.push("idle".to_string());
+            }
+        }
+    }
+
+    assert_eq!(seen, ["1", "idle", "2"]);
+}
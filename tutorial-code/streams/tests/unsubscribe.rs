@@ -0,0 +1,67 @@
+mod common;
+
+use mini_redis::client;
+use std::net::SocketAddr;
+use std::time::Duration;
+use streams::unsubscribe::receive_then_unsubscribe;
+use tokio::sync::oneshot;
+use tokio::time;
+
+async fn subscribe(addr: SocketAddr) -> client::Subscriber {
+    client::connect(addr)
+        .await
+        .unwrap()
+        .subscribe(vec!["numbers".to_string()])
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn unsubscribes_after_three_messages() {
+    let addr = common::start_server().await;
+    let subscriber = subscribe(addr).await;
+
+    let (_shutdown, shutdown_rx) = oneshot::channel();
+    let consumer = tokio::spawn(receive_then_unsubscribe(subscriber, 3, shutdown_rx));
+
+    let mut client = client::connect(addr).await.unwrap();
+    for n in ["1", "2", "3"].iter() {
+        assert_eq!(
+            client
+                .publish("numbers", n.to_string().into())
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    // The consumer task exits on its own.
+    let (received, mut subscriber) = consumer.await.unwrap().unwrap();
+    assert_eq!(received, ["1", "2", "3"]);
+    assert!(subscriber.get_subscribed().is_empty());
+
+    // The server no longer counts the subscriber, and nothing reaches it.
+    assert_eq!(client.publish("numbers", "4".into()).await.unwrap(), 0);
+    let res = time::timeout(Duration::from_millis(100), subscriber.next_message()).await;
+    assert!(res.is_err(), "{:?}", res);
+}
+
+#[tokio::test]
+async fn unsubscribes_on_shutdown() {
+    let addr = common::start_server().await;
+    let subscriber = subscribe(addr).await;
+
+    let (shutdown, shutdown_rx) = oneshot::channel();
+    let consumer = tokio::spawn(receive_then_unsubscribe(subscriber, 3, shutdown_rx));
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.publish("numbers", "1".into()).await.unwrap();
+
+    // Give the consumer a moment to read the message before interrupting it.
+    time::sleep(Duration::from_millis(50)).await;
+    shutdown.send(()).unwrap();
+
+    let (received, _subscriber) = consumer.await.unwrap().unwrap();
+    assert_eq!(received, ["1"]);
+    assert_eq!(client.publish("numbers", "2".into()).await.unwrap(), 0);
+}
@@ -0,0 +1,34 @@
+//! A hand-written future.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Completes on its `n + 1`th poll.
+///
+/// Each time it returns `Pending`, it wakes its task right away, since
+/// nothing else would: a future returning `Pending` without arranging for
+/// a wake-up is never polled again.
+pub struct Countdown {
+    remaining: u32,
+}
+
+impl Countdown {
+    pub fn new(n: u32) -> Countdown {
+        Countdown { remaining: n }
+    }
+}
+
+impl Future for Countdown {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.remaining == 0 {
+            return Poll::Ready(());
+        }
+
+        self.remaining -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
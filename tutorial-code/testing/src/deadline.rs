@@ -0,0 +1,17 @@
+//! Waiting for a value, but only so long.
+
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time;
+
+/// How long `recv_or` waits.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The value sent on `rx`, or `default` if none comes within `TIMEOUT`, or
+/// the sender is dropped.
+pub async fn recv_or(rx: oneshot::Receiver<u32>, default: u32) -> u32 {
+    match time::timeout(TIMEOUT, rx).await {
+        Ok(Ok(value)) => value,
+        Ok(Err(_)) | Err(_) => default,
+    }
+}
@@ -0,0 +1,20 @@
+//! Testing async code, without waiting on real time or real sockets.
+//!
+//! The code under test is in `deadline`, `ping` and `countdown`; how to test
+//! it is in `tests/`:
+//!
+//! * `tests/deadline.rs` pauses time, so a five second timeout takes no
+//!   time at all.
+//! * `tests/ping.rs` drives a protocol handler with a scripted stream,
+//!   which checks every byte the handler writes and can fail a read.
+//! * `tests/countdown.rs` polls a hand-written future one step at a time.
+//!
+//! `tokio-test` has ready-made versions of the last two, in
+//! `tokio_test::io::Builder` and `tokio_test::task::spawn`. This crate
+//! sticks to `tokio` itself: `mock` is a small scripted stream of the same
+//! shape, and the tests poll futures with a waker of their own.
+
+pub mod countdown;
+pub mod deadline;
+pub mod mock;
+pub mod ping;
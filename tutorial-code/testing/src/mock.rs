@@ -0,0 +1,141 @@
+//! A stream playing a script of reads and writes.
+//!
+//! Each read returns the next bytes of the script, or its next error. Each
+//! write must be the next bytes the script expects, or the mock panics,
+//! failing the test. A mock dropped before the end of its script panics
+//! too, so a handler stopping early doesn't go unnoticed.
+
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[derive(Debug)]
+enum Action {
+    Read(Vec<u8>),
+    ReadError(io::Error),
+    Write(Vec<u8>),
+}
+
+/// Builds a `Mock`, one action at a time.
+#[derive(Debug, Default)]
+pub struct Builder {
+    actions: VecDeque<Action>,
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// The next read returns `data`, or as much of it as fits.
+    pub fn read(&mut self, data: &[u8]) -> &mut Builder {
+        self.actions.push_back(Action::Read(data.to_vec()));
+        self
+    }
+
+    /// The next read fails with `err`.
+    pub fn read_error(&mut self, err: io::Error) -> &mut Builder {
+        self.actions.push_back(Action::ReadError(err));
+        self
+    }
+
+    /// The next writes must add up to `data`.
+    pub fn write(&mut self, data: &[u8]) -> &mut Builder {
+        self.actions.push_back(Action::Write(data.to_vec()));
+        self
+    }
+
+    pub fn build(&mut self) -> Mock {
+        Mock {
+            actions: std::mem::take(&mut self.actions),
+        }
+    }
+}
+
+/// Reads and writes following a script. Once the script is over, reads
+/// return EOF.
+#[derive(Debug)]
+pub struct Mock {
+    actions: VecDeque<Action>,
+}
+
+impl AsyncRead for Mock {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.actions.pop_front() {
+            Some(Action::Read(mut data)) => {
+                let n = data.len().min(buf.remaining());
+                buf.put_slice(&data[..n]);
+
+                // The rest is for the next read.
+                if n < data.len() {
+                    data.drain(..n);
+                    self.actions.push_front(Action::Read(data));
+                }
+                Poll::Ready(Ok(()))
+            }
+            Some(Action::ReadError(err)) => Poll::Ready(Err(err)),
+            Some(Action::Write(expected)) => panic!(
+                "mock: read, while expecting a write of {:?}",
+                String::from_utf8_lossy(&expected)
+            ),
+            // EOF.
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let expected = match self.actions.front_mut() {
+            Some(Action::Write(expected)) => expected,
+            other => panic!(
+                "mock: unexpected write of {:?}, the script has {:?}",
+                String::from_utf8_lossy(buf),
+                other
+            ),
+        };
+
+        let n = buf.len().min(expected.len());
+        if buf[..n] != expected[..n] {
+            panic!(
+                "mock: expected write of {:?}, got {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(buf)
+            );
+        }
+
+        expected.drain(..n);
+        if expected.is_empty() {
+            self.actions.pop_front();
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for Mock {
+    fn drop(&mut self) {
+        // Panicking while unwinding would abort, hiding the first panic.
+        if !thread::panicking() && !self.actions.is_empty() {
+            panic!("mock: dropped with {:?} left", self.actions);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! A line-based protocol handler.
+//!
+//! `PING` is answered with `PONG`, `ECHO <text>` with the text, and
+//! anything else with an error line. The handler works on any
+//! `AsyncRead + AsyncWrite`, which is what lets a test hand it a mock
+//! instead of a socket.
+
+use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Answer the commands of `stream` until it ends, and return how many there
+/// were.
+pub async fn serve<S: AsyncRead + AsyncWrite + Unpin>(stream: S) -> io::Result<usize> {
+    // `BufReader` passes writes through to the stream.
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    let mut handled = 0;
+
+    loop {
+        line.clear();
+        if stream.read_line(&mut line).await? == 0 {
+            return Ok(handled);
+        }
+
+        let command = line.trim_end_matches(&['\r', '\n'][..]);
+        let reply = match command.split_once(' ') {
+            _ if command == "PING" => "PONG".to_string(),
+            Some(("ECHO", text)) => text.to_string(),
+            _ => format!("ERR unknown command {:?}", command),
+        };
+
+        stream.write_all(reply.as_bytes()).await?;
+        stream.write_all(b"\n").await?;
+        handled += 1;
+    }
+}
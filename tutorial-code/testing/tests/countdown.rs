@@ -0,0 +1,101 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use testing::countdown::Countdown;
+
+/// Counts how often it is woken.
+#[derive(Default)]
+struct Wakes(AtomicUsize);
+
+impl Wake for Wakes {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A future polled by hand, with a waker counting its wake-ups.
+struct Task<F> {
+    future: Pin<Box<F>>,
+    wakes: Arc<Wakes>,
+    waker: Waker,
+}
+
+impl<F: Future> Task<F> {
+    fn new(future: F) -> Task<F> {
+        let wakes = Arc::new(Wakes::default());
+        Task {
+            future: Box::pin(future),
+            waker: Waker::from(wakes.clone()),
+            wakes,
+        }
+    }
+
+    fn poll(&mut self) -> Poll<F::Output> {
+        self.future
+            .as_mut()
+            .poll(&mut Context::from_waker(&self.waker))
+    }
+
+    fn wakes(&self) -> usize {
+        self.wakes.0.load(Ordering::SeqCst)
+    }
+}
+
+macro_rules! assert_pending {
+    ($e:expr) => {
+        assert!($e.is_pending(), "expected `Pending`")
+    };
+}
+
+macro_rules! assert_ready {
+    ($e:expr) => {
+        match $e {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("expected `Ready`"),
+        }
+    };
+}
+
+#[test]
+fn completes_on_the_last_poll() {
+    let mut task = Task::new(Countdown::new(2));
+
+    assert_pending!(task.poll());
+    assert_pending!(task.poll());
+    assert_ready!(task.poll());
+}
+
+#[test]
+fn wakes_itself_when_pending() {
+    let mut task = Task::new(Countdown::new(2));
+
+    assert_pending!(task.poll());
+    assert_eq!(task.wakes(), 1);
+    assert_pending!(task.poll());
+    assert_eq!(task.wakes(), 2);
+
+    // Done: no need for another poll.
+    assert_ready!(task.poll());
+    assert_eq!(task.wakes(), 2);
+}
+
+#[test]
+fn async_blocks_compose_with_it() {
+    let mut task = Task::new(async {
+        Countdown::new(1).await;
+        Countdown::new(1).await;
+        "done"
+    });
+
+    assert_pending!(task.poll());
+    assert_pending!(task.poll());
+    assert_eq!(assert_ready!(task.poll()), "done");
+}
+
+#[tokio::test]
+async fn runs_on_tokio() {
+    // Waking itself is what gets it polled again.
+    Countdown::new(100).await;
+}
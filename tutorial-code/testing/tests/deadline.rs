@@ -0,0 +1,42 @@
+use testing::deadline::{recv_or, TIMEOUT};
+use tokio::sync::oneshot;
+use tokio::time::{self, Duration, Instant};
+
+// With time paused, a sleep completes as soon as nothing else can run,
+// and the clock jumps ahead by its duration: the timeout takes no real
+// time.
+#[tokio::test(start_paused = true)]
+async fn gives_up_after_the_timeout() {
+    let (_tx, rx) = oneshot::channel();
+
+    let start = Instant::now();
+    let real = std::time::Instant::now();
+    assert_eq!(recv_or(rx, 7).await, 7);
+
+    assert_eq!(start.elapsed(), TIMEOUT);
+    assert!(real.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn gets_a_value_sent_in_time() {
+    let (tx, rx) = oneshot::channel();
+    let recv = tokio::spawn(recv_or(rx, 7));
+
+    // Move the clock by hand: just short of the timeout.
+    time::advance(TIMEOUT - Duration::from_millis(1)).await;
+    tx.send(1).unwrap();
+
+    assert_eq!(recv.await.unwrap(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_value_sent_too_late_is_ignored() {
+    let (tx, rx) = oneshot::channel();
+    let recv = tokio::spawn(recv_or(rx, 7));
+
+    time::advance(TIMEOUT).await;
+    assert_eq!(recv.await.unwrap(), 7);
+
+    // Nobody is waiting anymore.
+    assert!(tx.send(1).is_err());
+}
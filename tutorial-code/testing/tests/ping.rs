@@ -0,0 +1,53 @@
+use std::io;
+use testing::mock::Builder;
+use testing::ping::serve;
+
+#[tokio::test]
+async fn answers_commands() {
+    let mock = Builder::new()
+        .read(b"PING\n")
+        .write(b"PONG\n")
+        // One command split across two reads.
+        .read(b"ECHO hel")
+        .read(b"lo\r\n")
+        .write(b"hello\n")
+        .read(b"QUIT\n")
+        .write(b"ERR unknown command \"QUIT\"\n")
+        .build();
+
+    assert_eq!(serve(mock).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn stops_on_read_errors() {
+    let mock = Builder::new()
+        .read(b"PING\n")
+        .write(b"PONG\n")
+        .read_error(io::Error::new(io::ErrorKind::ConnectionReset, "gone"))
+        .build();
+
+    let err = serve(mock).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+}
+
+// The mock checks every byte written against the script.
+#[tokio::test]
+#[should_panic(expected = "mock: expected write of \"pong\\n\", got \"PONG\"")]
+async fn transcript_mismatch() {
+    let mock = Builder::new().read(b"PING\n").write(b"pong\n").build();
+
+    let _ = serve(mock).await;
+}
+
+// So does dropping it before the end of the script.
+#[tokio::test]
+#[should_panic(expected = "mock: dropped with")]
+async fn unfinished_transcript() {
+    let mock = Builder::new()
+        .read_error(io::Error::new(io::ErrorKind::ConnectionReset, "gone"))
+        .read(b"PING\n")
+        .build();
+
+    // The handler stops at the error, before the last command.
+    let _ = serve(mock).await;
+}
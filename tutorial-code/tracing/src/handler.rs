@@ -0,0 +1,83 @@
+//! A request handler, traced.
+//!
+//! `handle` runs in a `request` span, with the request's id and path as
+//! fields, and the status once it is known. Its two steps, loading the user
+//! and rendering the page, each have a span within it.
+
+use std::time::Duration;
+use tokio::time;
+use tracing::field::Empty;
+use tracing::{debug, info, info_span, instrument, warn, Instrument, Span};
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub id: u64,
+    pub user: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// The users `load_user` knows about.
+const USERS: &[&str] = &["alice", "bob"];
+
+/// Answer `req`: 404 for an unknown user, the page otherwise.
+#[instrument(
+    name = "request",
+    skip(req),
+    fields(id = req.id, path = %req.path, status = Empty)
+)]
+pub async fn handle(req: Request) -> Response {
+    info!("received");
+
+    // An async step: `.instrument` enters the span each time the future
+    // is polled, and leaves it each time it yields.
+    let user = load_user(&req.user)
+        .instrument(info_span!("load_user", user = %req.user))
+        .await;
+
+    let response = match user {
+        Some(user) => {
+            // A step that doesn't yield can enter its span for as long as it
+            // runs. `bytes` is only known at the end, so it is declared
+            // `Empty` and recorded then.
+            let span = info_span!("render", bytes = Empty);
+            let _enter = span.enter();
+
+            let body = render(user, &req.path);
+            span.record("bytes", body.len());
+
+            Response { status: 200, body }
+        }
+        None => {
+            warn!(user = %req.user, "unknown user");
+
+            Response {
+                status: 404,
+                body: String::new(),
+            }
+        }
+    };
+
+    Span::current().record("status", response.status);
+    info!("done");
+
+    response
+}
+
+async fn load_user(name: &str) -> Option<&'static str> {
+    // Stands in for a database query.
+    time::sleep(Duration::from_millis(1)).await;
+
+    let user = USERS.iter().copied().find(|user| *user == name);
+    debug!(found = user.is_some());
+    user
+}
+
+fn render(user: &str, path: &str) -> String {
+    format!("<h1>{} for {}</h1>", path, user)
+}
@@ -0,0 +1,9 @@
+//! Tracing a request handler.
+//!
+//! * `handler`: a simulated request handler, in a span made by
+//!   `#[instrument]`, with spans of its own around each step.
+//! * `subscriber`: printing the spans and events, as text or as JSON, and
+//!   choosing which with an env-filter.
+
+pub mod handler;
+pub mod subscriber;
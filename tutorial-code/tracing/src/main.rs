@@ -0,0 +1,33 @@
+use std::env;
+use std::process;
+use tracing_example::handler::{self, Request};
+use tracing_example::subscriber::{self, Format};
+
+#[tokio::main]
+async fn main() {
+    let format = if env::args().any(|arg| arg == "--json") {
+        Format::Json
+    } else {
+        Format::Text
+    };
+
+    let directives =
+        env::var("RUST_LOG").unwrap_or_else(|_| subscriber::DEFAULT_FILTER.to_string());
+    let filter = match subscriber::filter(&directives) {
+        Ok(filter) => filter,
+        Err(err) => {
+            eprintln!("invalid RUST_LOG: {}", err);
+            process::exit(2);
+        }
+    };
+    subscriber::init(format, filter);
+
+    for (id, user) in ["alice", "mallory"].iter().enumerate() {
+        handler::handle(Request {
+            id: id as u64,
+            user: user.to_string(),
+            path: "/index.html".to_string(),
+        })
+        .await;
+    }
+}
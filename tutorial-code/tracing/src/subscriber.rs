@@ -0,0 +1,72 @@
+//! Printing what is traced.
+
+use std::error::Error;
+use std::fmt;
+use tracing_subscriber::filter::{Directive, EnvFilter, ParseError};
+
+/// What is printed when `RUST_LOG` isn't set.
+pub const DEFAULT_FILTER: &str = "info";
+
+/// How spans and events are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// A line of text for each event, after the spans it is in.
+    Text,
+
+    /// A JSON object for each event, with the spans it is in.
+    Json,
+}
+
+/// A directive of an env-filter can't be parsed.
+#[derive(Debug)]
+pub struct InvalidFilter {
+    /// The directive, as written.
+    pub directive: String,
+    pub source: ParseError,
+}
+
+/// The filter made of env-filter `directives`, like
+/// `"info,tracing_example::handler=debug"`.
+///
+/// Unlike `EnvFilter::new`, which skips the directives it can't parse,
+/// this fails; and unlike `EnvFilter::try_new`, it says which directive is
+/// wrong.
+pub fn filter(directives: &str) -> Result<EnvFilter, InvalidFilter> {
+    let mut filter = EnvFilter::default();
+
+    for directive in directives.split(',').filter(|d| !d.trim().is_empty()) {
+        let parsed = directive
+            .parse::<Directive>()
+            .map_err(|source| InvalidFilter {
+                directive: directive.to_string(),
+                source,
+            })?;
+        filter = filter.add_directive(parsed);
+    }
+
+    Ok(filter)
+}
+
+/// Print to stdout, in `format`, what `filter` lets through.
+///
+/// Panics if a global subscriber is already set.
+pub fn init(format: Format, filter: EnvFilter) {
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match format {
+        Format::Text => builder.init(),
+        Format::Json => builder.json().init(),
+    }
+}
+
+impl fmt::Display for InvalidFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "`{}`: {}", self.directive, self.source)
+    }
+}
+
+impl Error for InvalidFilter {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
@@ -0,0 +1,44 @@
+use tracing_example::subscriber::filter;
+
+#[test]
+fn accepts_directives() {
+    let filter = filter("warn,tracing_example::handler=debug,[render]=trace").unwrap();
+
+    let shown = filter.to_string();
+    assert!(
+        shown.contains("tracing_example::handler=debug"),
+        "{}",
+        shown
+    );
+    assert!(shown.contains("warn"), "{}", shown);
+}
+
+#[test]
+fn accepts_empty_directives() {
+    filter("").unwrap();
+    filter("info,,debug,").unwrap();
+}
+
+#[test]
+fn rejects_an_unknown_level() {
+    let err = filter("info,tracing_example=loud").unwrap_err();
+
+    assert_eq!(err.directive, "tracing_example=loud");
+    assert_eq!(
+        err.to_string(),
+        "`tracing_example=loud`: invalid filter directive"
+    );
+}
+
+#[test]
+fn rejects_an_invalid_field_filter() {
+    let err = filter("[request{path=/(}]").unwrap_err();
+
+    assert_eq!(err.directive, "[request{path=/(}]");
+    assert!(
+        err.to_string()
+            .starts_with("`[request{path=/(}]`: invalid field filter: regex parse error"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,187 @@
+//! Runs a request with a layer capturing its spans and events.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_example::handler::{handle, Request, Response};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+#[derive(Debug, Clone, PartialEq)]
+struct CapturedSpan {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CapturedEvent {
+    level: tracing::Level,
+    /// The span the event happened in.
+    span: Option<&'static str>,
+    fields: BTreeMap<String, String>,
+}
+
+#[derive(Default)]
+struct Captured {
+    spans: Vec<CapturedSpan>,
+    // Where in `spans` the span with this id is. The registry reuses the
+    // ids of closed spans, but fields are only recorded on open ones.
+    index: HashMap<Id, usize>,
+    events: Vec<CapturedEvent>,
+}
+
+/// A layer keeping every span and event, in order.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Captured>>);
+
+/// Writes the fields it visits into a map, `Display` values without
+/// quotes.
+struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S> Layer<S> for Capture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut captured = CapturedSpan {
+            name: span.name(),
+            parent: span.parent().map(|parent| parent.name()),
+            fields: BTreeMap::new(),
+        };
+        attrs.record(&mut Fields(&mut captured.fields));
+
+        let mut all = self.0.lock().unwrap();
+        let index = all.spans.len();
+        all.spans.push(captured);
+        all.index.insert(id.clone(), index);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut all = self.0.lock().unwrap();
+        let index = all.index[id];
+        values.record(&mut Fields(&mut all.spans[index].fields));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut captured = CapturedEvent {
+            level: *event.metadata().level(),
+            span: ctx.lookup_current().map(|span| span.name()),
+            fields: BTreeMap::new(),
+        };
+        event.record(&mut Fields(&mut captured.fields));
+
+        self.0.lock().unwrap().events.push(captured);
+    }
+}
+
+/// Handle `req` with everything traced captured.
+fn run(req: Request) -> (Response, Captured) {
+    let capture = Capture::default();
+    let subscriber = Registry::default().with(capture.clone());
+
+    let response = tracing::subscriber::with_default(subscriber, || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(handle(req))
+    });
+
+    let captured = std::mem::take(&mut *capture.0.lock().unwrap());
+    (response, captured)
+}
+
+fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn traces_a_request() {
+    let (response, captured) = run(Request {
+        id: 7,
+        user: "alice".to_string(),
+        path: "/index.html".to_string(),
+    });
+    assert_eq!(response.status, 200);
+
+    assert_eq!(
+        captured.spans,
+        [
+            CapturedSpan {
+                name: "request",
+                parent: None,
+                fields: fields(&[("id", "7"), ("path", "/index.html"), ("status", "200")]),
+            },
+            CapturedSpan {
+                name: "load_user",
+                parent: Some("request"),
+                fields: fields(&[("user", "alice")]),
+            },
+            CapturedSpan {
+                name: "render",
+                parent: Some("request"),
+                fields: fields(&[("bytes", &response.body.len().to_string())]),
+            },
+        ]
+    );
+
+    let events: Vec<_> = captured
+        .events
+        .iter()
+        .map(|event| (event.span, event.fields.get("message").map(String::as_str)))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (Some("request"), Some("received")),
+            (Some("load_user"), None),
+            (Some("request"), Some("done")),
+        ]
+    );
+    assert_eq!(captured.events[1].fields, fields(&[("found", "true")]));
+}
+
+#[test]
+fn traces_an_unknown_user() {
+    let (response, captured) = run(Request {
+        id: 8,
+        user: "mallory".to_string(),
+        path: "/".to_string(),
+    });
+    assert_eq!(response.status, 404);
+
+    let names: Vec<_> = captured.spans.iter().map(|span| span.name).collect();
+    assert_eq!(names, ["request", "load_user"]);
+    assert_eq!(captured.spans[0].fields["status"], "404");
+
+    let warning = captured
+        .events
+        .iter()
+        .find(|event| event.level == tracing::Level::WARN)
+        .unwrap();
+    assert_eq!(warning.span, Some("request"));
+    assert_eq!(
+        warning.fields,
+        fields(&[("message", "unknown user"), ("user", "mallory")])
+    );
+}
@@ -0,0 +1,126 @@
+//! Finding the docs.rs links of the content.
+
+use crate::link::DocsRsLink;
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A docs.rs link, and where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Found {
+    /// The markdown file, relative to the content directory, with `/`
+    /// between components.
+    pub file: String,
+
+    /// The line the URL is written on, from 1.
+    pub line: usize,
+
+    /// The URL, as written.
+    pub url: String,
+
+    pub link: DocsRsLink,
+}
+
+/// The docs.rs links of a markdown file, in the order they're written.
+///
+/// Reference-style links are found at their definition, where the URL is,
+/// even when nothing uses them. Links in code blocks aren't links.
+pub fn links(file: &str, markdown: &str) -> Vec<Found> {
+    let line = |offset: usize| markdown[..offset].matches('\n').count() + 1;
+    let mut found = vec![];
+    let mut push = |offset: usize, url: &str| {
+        if let Some(link) = DocsRsLink::parse(url) {
+            found.push(Found {
+                file: file.to_string(),
+                line: line(offset),
+                url: url.to_string(),
+                link,
+            });
+        }
+    };
+
+    // Front matter isn't markdown, and would otherwise read as a heading.
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let mut parser = Parser::new_ext(markdown, options).into_offset_iter();
+
+    for (event, range) in &mut parser {
+        match event {
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline | LinkType::Autolink,
+                dest_url,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type: LinkType::Inline,
+                dest_url,
+                ..
+            }) => {
+                // The URL is after the text of the link, which may span
+                // lines.
+                let offset = markdown[range.clone()]
+                    .rfind(&*dest_url)
+                    .map_or(range.start, |i| range.start + i);
+                push(offset, &dest_url);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for (offset, url) in hrefs(&html) {
+                    push(range.start + offset, &url);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (_, def) in parser.reference_definitions().iter() {
+        push(def.span.start, &def.dest);
+    }
+
+    found.sort_by_key(|found| found.line);
+    found
+}
+
+/// The values of the `href` attributes in some HTML, and where they start.
+fn hrefs(html: &str) -> Vec<(usize, String)> {
+    html.match_indices(" href=\"")
+        .filter_map(|(start, attr)| {
+            let value = &html[start + attr.len()..];
+            let end = value.find('"')?;
+            Some((start, value[..end].to_string()))
+        })
+        .collect()
+}
+
+/// The docs.rs links of every markdown file under `content`, sorted by file.
+pub fn content(content: &Path) -> io::Result<Vec<Found>> {
+    let mut files = vec![];
+    markdown_files(content, &mut files)?;
+    files.sort();
+
+    let mut found = vec![];
+    for path in files {
+        let rel = path
+            .strip_prefix(content)
+            .unwrap()
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        found.extend(links(&rel, &fs::read_to_string(&path)?));
+    }
+    Ok(found)
+}
+
+fn markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            markdown_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,515 @@
+//! The pages of the docs of a crate, and the items on each.
+//!
+//! The list is kept in a text file, one line per page, and one per item on
+//! a page, like `#method.block_on`. It is generated from the JSON rustdoc
+//! writes with `--output-format json`, which describes every item, and the
+//! modules they're in, but not where rustdoc puts their pages. That follows
+//! rustdoc's rules:
+//!
+//! * An item gets a page in every public module that defines it.
+//! * A `pub use` of an item without one gets a page where it is, as
+//!   rustdoc inlines it. So does one marked `#[doc(inline)]`.
+//! * Any other `pub use` is only listed in its module. Its would-be page
+//!   is kept as a redirect to the page of the item, since links to it are
+//!   easily written: `tokio::spawn` is documented at `tokio::task::spawn`.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+/// The pages of the docs of one version of a crate.
+#[derive(Debug, Default, PartialEq)]
+pub struct Items {
+    pub krate: String,
+    pub version: String,
+
+    /// The pages, and the ids of the items on each, when known.
+    pages: BTreeMap<String, Option<BTreeSet<String>>>,
+
+    /// Pages that don't exist, and the page to link to instead.
+    redirects: BTreeMap<String, String>,
+}
+
+/// What `Items::lookup` found.
+#[derive(Debug, PartialEq)]
+pub enum Lookup<'a> {
+    Found,
+
+    /// The page exists, but has no item with the id of the fragment.
+    NoItem,
+
+    /// The page doesn't exist, but this one does.
+    Redirect(&'a str),
+
+    NoPage,
+}
+
+/// The fragments that are the ids of items, rather than of headings written
+/// in the docs, which the list doesn't have.
+const ITEM_IDS: &[&str] = &[
+    "method.",
+    "tymethod.",
+    "variant.",
+    "structfield.",
+    "associatedtype.",
+    "associatedconstant.",
+];
+
+impl Items {
+    /// Read the list written by `to_text`.
+    pub fn parse(text: &str) -> Result<Items, String> {
+        let mut lines = text.lines().enumerate();
+
+        let header = lines.next().map(|(_, line)| line).unwrap_or_default();
+        let (krate, version) = header
+            .strip_prefix("# ")
+            .and_then(|header| header.split_once(' '))
+            .ok_or_else(|| format!("line 1: expected `# <crate> <version>`, got {:?}", header))?;
+
+        let mut items = Items {
+            krate: krate.to_string(),
+            version: version.to_string(),
+            ..Items::default()
+        };
+
+        for (n, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((from, to)) = line.split_once(" -> ") {
+                items.redirects.insert(from.to_string(), to.to_string());
+            } else if let Some((page, id)) = line.split_once('#') {
+                match items.pages.get_mut(page) {
+                    Some(ids) => {
+                        ids.get_or_insert_with(BTreeSet::new).insert(id.to_string());
+                    }
+                    None => {
+                        return Err(format!("line {}: `{}` comes before its page", n + 1, line))
+                    }
+                }
+            } else {
+                items.pages.insert(line.to_string(), None);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// The list, as `parse` reads it, after a `# <crate> <version>` header
+    /// and `comment`.
+    pub fn to_text(&self, comment: &str) -> String {
+        let mut text = format!("# {} {}\n", self.krate, self.version);
+        for line in comment.lines() {
+            writeln!(text, "# {}", line).unwrap();
+        }
+
+        for (page, ids) in &self.pages {
+            writeln!(text, "{}", page).unwrap();
+            for id in ids.iter().flatten() {
+                writeln!(text, "{}#{}", page, id).unwrap();
+            }
+        }
+        for (from, to) in &self.redirects {
+            writeln!(text, "{} -> {}", from, to).unwrap();
+        }
+        text
+    }
+
+    /// Whether `page`, like `tokio/sync/index.html`, exists, and has an item
+    /// with the id `fragment`. Fragments that aren't ids of items are
+    /// assumed to exist.
+    pub fn lookup(&self, page: &str, fragment: Option<&str>) -> Lookup<'_> {
+        let ids = match self.pages.get(page) {
+            Some(ids) => ids,
+            None => {
+                return match self.redirects.get(page) {
+                    Some(to) => Lookup::Redirect(to),
+                    None => Lookup::NoPage,
+                }
+            }
+        };
+
+        match (fragment, ids) {
+            (Some(fragment), Some(ids))
+                if ITEM_IDS.iter().any(|prefix| fragment.starts_with(prefix))
+                    && !ids.contains(fragment) =>
+            {
+                Lookup::NoItem
+            }
+            _ => Lookup::Found,
+        }
+    }
+
+    /// The pages described by the JSON output of rustdoc for a crate.
+    pub fn from_rustdoc(json: &Value) -> Result<Items, String> {
+        let index = json["index"]
+            .as_object()
+            .ok_or("no `index`; is this rustdoc's JSON output?")?;
+        let root = id(&json["root"]).ok_or("no `root`")?;
+        let krate = index
+            .get(&root)
+            .and_then(|root| root["name"].as_str())
+            .ok_or("no root module")?;
+
+        let mut docs = Docs {
+            index,
+            paths: &json["paths"],
+            defined: HashMap::new(),
+            inlined: HashSet::new(),
+            items: Items {
+                krate: krate.to_string(),
+                version: json["crate_version"]
+                    .as_str()
+                    .ok_or("no `crate_version`")?
+                    .to_string(),
+                ..Items::default()
+            },
+        };
+
+        // Where items are defined is needed to tell which `pub use`s are
+        // inlined, so it's found first.
+        docs.define(&root, krate);
+        docs.reexport(&mut vec![root.clone()], krate, Pass::Inline);
+        docs.reexport(&mut vec![root], krate, Pass::Rest);
+
+        Ok(docs.items)
+    }
+}
+
+/// Walking the JSON of rustdoc.
+struct Docs<'a> {
+    index: &'a serde_json::Map<String, Value>,
+    paths: &'a Value,
+
+    /// The page of each item defined in a public module, or inlined.
+    defined: HashMap<String, String>,
+
+    /// The `pub use`s inlined.
+    inlined: HashSet<String>,
+
+    items: Items,
+}
+
+impl Docs<'_> {
+    /// Add the pages of the items defined in `module`, whose pages are in
+    /// `dir`, and in the modules within.
+    fn define(&mut self, module: &str, dir: &str) {
+        let index = self.index;
+        self.items.pages.insert(format!("{}/index.html", dir), None);
+
+        for item in module_items(&index[module]) {
+            let inner = &index[&item]["inner"];
+            if inner.get("use").is_some() {
+                continue;
+            }
+
+            let name = match index[&item]["name"].as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            if inner.get("module").is_some() {
+                let dir = format!("{}/{}", dir, name);
+                self.defined
+                    .insert(item.clone(), format!("{}/index.html", dir));
+                self.define(&item, &dir);
+            } else if let Some(page) = page(dir, kind(inner), name) {
+                self.items
+                    .pages
+                    .insert(page.clone(), Some(members(index, &item)));
+                self.defined.insert(item, page);
+            }
+        }
+    }
+
+    /// Add the pages, or redirects, of the `pub use`s in the last of
+    /// `modules`, whose pages are in `dir`, and in the modules within.
+    /// `modules` goes from the root down, to resolve `super::`.
+    ///
+    /// Called twice: first for the `pub use`s that rustdoc inlines, then
+    /// for the others, which need to know where the first ones went.
+    fn reexport(&mut self, modules: &mut Vec<String>, dir: &str, pass: Pass) {
+        let index = self.index;
+        let module = modules.last().unwrap().clone();
+
+        for item in module_items(&index[&module]) {
+            let inner = &index[&item]["inner"];
+
+            let reexport = match inner.get("use") {
+                Some(reexport) if !reexport["is_glob"].as_bool().unwrap_or(false) => reexport,
+                Some(_) => continue,
+                None => {
+                    if inner.get("module").is_some() {
+                        let name = index[&item]["name"].as_str().unwrap();
+                        modules.push(item.clone());
+                        self.reexport(modules, &format!("{}/{}", dir, name), pass);
+                        modules.pop();
+                    }
+                    continue;
+                }
+            };
+
+            let name = reexport["name"].as_str().unwrap();
+            let target = match id(&reexport["id"]) {
+                Some(target) => target,
+                None => continue,
+            };
+            let kind = match index.get(&target) {
+                Some(target) => kind(&target["inner"]),
+                None => self.paths[&target]["kind"].as_str().unwrap_or(""),
+            };
+            let attr = |attr: &str| {
+                index[&item]["attrs"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|a| a["other"] == attr)
+            };
+
+            // A `pub use` naming an item through private modules is inlined,
+            // unless the item has a page already. One naming it through
+            // public ones is only a link to where that leads.
+            let inline = !attr("#[doc(no_inline)]")
+                && (attr("#[doc(inline)]")
+                    || !self.defined.contains_key(&target)
+                        && (pass == Pass::Rest
+                            || !self
+                                .public_path(modules, reexport["source"].as_str().unwrap_or(""))));
+
+            match pass {
+                Pass::Inline if inline => {}
+                Pass::Rest if !inline && !self.inlined.contains(&item) => {
+                    let to = match self.defined.get(&target) {
+                        Some(to) => to.clone(),
+                        None => continue,
+                    };
+                    let from = match kind {
+                        "module" => format!("{}/{}/index.html", dir, name),
+                        _ => match page(dir, kind, name) {
+                            Some(page) => page,
+                            None => continue,
+                        },
+                    };
+                    self.items.redirects.insert(from, to);
+                    continue;
+                }
+                Pass::Rest if inline && !self.inlined.contains(&item) => {}
+                _ => continue,
+            }
+            self.inlined.insert(item.clone());
+
+            if kind == "module" {
+                if index.contains_key(&target) {
+                    let dir = format!("{}/{}", dir, name);
+                    self.defined
+                        .entry(target.clone())
+                        .or_insert_with(|| format!("{}/index.html", dir));
+                    self.define(&target, &dir);
+                    modules.push(target);
+                    self.reexport(modules, &dir, Pass::Inline);
+                    self.reexport(modules, &dir, Pass::Rest);
+                    modules.pop();
+                }
+            } else if let Some(page) = page(dir, kind, name) {
+                // Items of other crates aren't in the index.
+                let ids = index.get(&target).map(|_| members(index, &target));
+                self.items.pages.insert(page.clone(), ids);
+                self.defined.entry(target).or_insert(page);
+            }
+        }
+    }
+
+    /// Whether `path`, as written in a `use` in the last of `modules`, only
+    /// goes through public modules.
+    fn public_path(&self, modules: &[String], path: &str) -> bool {
+        let mut segments: Vec<&str> = path.split("::").collect();
+        let mut depth = modules.len();
+
+        match segments.first() {
+            Some(&"crate") => {
+                depth = 1;
+                segments.remove(0);
+            }
+            Some(&"self") => {
+                segments.remove(0);
+            }
+            _ => {}
+        }
+        while segments.first() == Some(&"super") {
+            depth = depth.saturating_sub(1).max(1);
+            segments.remove(0);
+        }
+
+        let mut module = modules[depth - 1].clone();
+        let (last, dirs) = match segments.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+
+        for dir in dirs {
+            let child = module_items(&self.index[&module])
+                .into_iter()
+                .find_map(|item| {
+                    let item = &self.index[&item];
+                    let inner = &item["inner"];
+                    if inner.get("module").is_some() && item["name"] == *dir {
+                        id(&item["id"])
+                    } else if inner["use"]["name"] == *dir {
+                        id(&inner["use"]["id"]).filter(|id| {
+                            self.index
+                                .get(id)
+                                .is_some_and(|target| target["inner"].get("module").is_some())
+                        })
+                    } else {
+                        None
+                    }
+                });
+            match child {
+                Some(child) => module = child,
+                None => return false,
+            }
+        }
+
+        module_items(&self.index[&module]).into_iter().any(|item| {
+            let item = &self.index[&item];
+            item["name"] == *last || item["inner"]["use"]["name"] == *last
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pass {
+    Inline,
+    Rest,
+}
+
+/// The ids of `item` as a string, the way `index` is keyed.
+fn id(id: &Value) -> Option<String> {
+    match id {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn module_items(module: &Value) -> Vec<String> {
+    module["inner"]["module"]["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(id)
+        .collect()
+}
+
+/// The kind of an item, as rustdoc's JSON names it.
+fn kind(inner: &Value) -> &str {
+    inner
+        .as_object()
+        .and_then(|inner| inner.keys().next())
+        .map_or("", String::as_str)
+}
+
+/// The page of an item of `kind` called `name`, in the module whose pages
+/// are in `dir`. `None` for kinds without pages.
+fn page(dir: &str, kind: &str, name: &str) -> Option<String> {
+    let prefix = match kind {
+        "struct" => "struct",
+        "enum" => "enum",
+        "union" => "union",
+        "trait" => "trait",
+        "trait_alias" => "traitalias",
+        "function" => "fn",
+        "type_alias" => "type",
+        "constant" => "constant",
+        "static" => "static",
+        "macro" => "macro",
+        "proc_macro" | "proc_attribute" => "attr",
+        "proc_derive" => "derive",
+        _ => return None,
+    };
+    Some(format!("{}/{}.{}.html", dir, prefix, name))
+}
+
+/// The ids of what the page of `item` documents: its fields, variants,
+/// methods, and associated types and constants.
+fn members(index: &serde_json::Map<String, Value>, item: &str) -> BTreeSet<String> {
+    let mut ids = BTreeSet::new();
+    let inner = &index[item]["inner"];
+    let ids_of = |value: &Value| -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(id)
+            .collect()
+    };
+
+    let mut members = vec![];
+    let mut impls = vec![];
+
+    match kind(inner) {
+        "struct" => {
+            let s = &inner["struct"];
+            members.extend(ids_of(&s["kind"]["plain"]["fields"]));
+            members.extend(ids_of(&s["kind"]["tuple"]));
+            impls.extend(ids_of(&s["impls"]));
+        }
+        "union" => {
+            members.extend(ids_of(&inner["union"]["fields"]));
+            impls.extend(ids_of(&inner["union"]["impls"]));
+        }
+        "enum" => {
+            members.extend(ids_of(&inner["enum"]["variants"]));
+            impls.extend(ids_of(&inner["enum"]["impls"]));
+        }
+        "trait" => {
+            members.extend(ids_of(&inner["trait"]["items"]));
+            // Implementations on types of other crates are on its page.
+            impls.extend(ids_of(&inner["trait"]["implementations"]));
+        }
+        _ => {}
+    }
+
+    for imp in impls.iter().filter_map(|imp| index.get(imp)) {
+        let imp = &imp["inner"]["impl"];
+        members.extend(ids_of(&imp["items"]));
+        for name in imp["provided_trait_methods"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            if let Some(name) = name.as_str() {
+                ids.insert(format!("method.{}", name));
+            }
+        }
+    }
+
+    for member in members.iter().filter_map(|member| index.get(member)) {
+        if member["attrs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|attr| attr["other"] == "#[doc(hidden)]")
+        {
+            continue;
+        }
+        let name = match member["name"].as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        let inner = &member["inner"];
+        let prefix = match kind(inner) {
+            "function" if inner["function"]["has_body"] == false => "tymethod",
+            "function" => "method",
+            "variant" => "variant",
+            "struct_field" => "structfield",
+            "assoc_type" => "associatedtype",
+            "assoc_const" => "associatedconstant",
+            _ => continue,
+        };
+        ids.insert(format!("{}.{}", prefix, name));
+    }
+
+    ids
+}
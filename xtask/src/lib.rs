@@ -0,0 +1,105 @@
+//! Tasks for maintaining the website, run with `cargo xtask <task>` from
+//! the root of the repository.
+//!
+//! `docs-rs-links` checks the links to docs.rs in `content/`, which break
+//! when items move between versions. By default it works offline: links to
+//! tokio are checked against `tokio-items.txt`, the pages of the docs of
+//! the version of tokio it names, and other links are left alone. With
+//! `ONLINE=1`, every link is fetched instead.
+//!
+//! `docs-rs-items` regenerates `tokio-items.txt`, from the JSON rustdoc
+//! writes for tokio.
+
+pub mod extract;
+pub mod items;
+pub mod link;
+pub mod online;
+
+use extract::Found;
+use items::{Items, Lookup};
+use link::DocsRsLink;
+use std::fmt;
+
+/// What is wrong with a link.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The page, or the item on it, doesn't exist.
+    Dead(String),
+
+    /// The page moved here.
+    Redirected(DocsRsLink),
+}
+
+/// A link that has a problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub found: Found,
+    pub problem: Problem,
+}
+
+/// Check `link` against `items`. `None` if it's fine, and if it is to
+/// another crate, or another version, as `items` doesn't know about those.
+pub fn check_offline(items: &Items, link: &DocsRsLink) -> Option<Problem> {
+    if link.krate != items.krate || !same_version(&link.version, &items.version) {
+        return None;
+    }
+    // The crate's page on docs.rs.
+    let page = link.page.as_deref()?;
+
+    match items.lookup(page, link.fragment.as_deref()) {
+        Lookup::Found => None,
+        Lookup::NoItem => Some(Problem::Dead(format!(
+            "`{}` has no item `#{}` in {} {}",
+            page,
+            link.fragment.as_deref().unwrap_or_default(),
+            items.krate,
+            items.version
+        ))),
+        Lookup::Redirect(to) => Some(Problem::Redirected(DocsRsLink {
+            page: Some(to.to_string()),
+            ..link.clone()
+        })),
+        Lookup::NoPage => Some(Problem::Dead(format!(
+            "no page `{}` in {} {}",
+            page, items.krate, items.version
+        ))),
+    }
+}
+
+/// Whether docs.rs shows `version` for a link to `link_version`: `1` and
+/// `1.53` both mean the latest 1.53.x.
+fn same_version(link_version: &str, version: &str) -> bool {
+    link_version == "latest"
+        || link_version == version
+        || version
+            .strip_prefix(link_version)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Check every link in `found` against `items`.
+pub fn check_all_offline(items: &Items, found: &[Found]) -> Vec<Report> {
+    found
+        .iter()
+        .filter_map(|found| {
+            let problem = check_offline(items, &found.link)?;
+            Some(Report {
+                found: found.clone(),
+                problem,
+            })
+        })
+        .collect()
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{}:{}: {}: ",
+            self.found.file, self.found.line, self.found.url
+        )?;
+        match &self.problem {
+            Problem::Dead(reason) => write!(fmt, "dead: {}", reason),
+            Problem::Redirected(to) => write!(fmt, "redirected to {}", to),
+        }
+    }
+}
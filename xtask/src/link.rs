@@ -0,0 +1,92 @@
+//! What a docs.rs link points to.
+
+use std::fmt;
+
+/// A link to docs.rs, normalized so that links to the same page compare
+/// equal however they were written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DocsRsLink {
+    /// The crate, as its name is written in URLs.
+    pub krate: String,
+
+    /// `latest`, for a link without a version, or with `*`.
+    pub version: String,
+
+    /// The page in the docs of the crate, like `tokio/sync/index.html`.
+    /// `None` for the crate's page on docs.rs, which links to its docs.
+    pub page: Option<String>,
+
+    pub fragment: Option<String>,
+}
+
+impl DocsRsLink {
+    /// The link `url` is, or `None` if it isn't one to docs.rs.
+    pub fn parse(url: &str) -> Option<DocsRsLink> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let rest = rest
+            .strip_prefix("docs.rs/")
+            .or_else(|| rest.strip_prefix("www.docs.rs/"))?;
+
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        // Like `?search=spawn`, which doesn't change the page.
+        let rest = rest.split('?').next().unwrap();
+
+        // `docs.rs/crate/tokio/1.0.0` is the page of the crate, like
+        // `docs.rs/tokio/1.0.0`.
+        let rest = rest.strip_prefix("crate/").unwrap_or(rest);
+
+        let mut segments = rest.split('/');
+        let krate = segments.next().filter(|krate| !krate.is_empty())?;
+        let version = match segments.next() {
+            None | Some("") | Some("*") => "latest",
+            Some(version) => version,
+        };
+
+        let path: Vec<&str> = segments.collect();
+        let page = match path.split_last() {
+            None => None,
+            Some((&"", [])) => None,
+            // The same page as `index.html`.
+            Some((&"", dirs)) => Some(format!("{}/index.html", dirs.join("/"))),
+            Some(_) if !path.last().unwrap().contains('.') => {
+                Some(format!("{}/index.html", path.join("/")))
+            }
+            Some(_) => Some(path.join("/")),
+        };
+
+        Some(DocsRsLink {
+            krate: krate.to_string(),
+            version: version.to_string(),
+            page,
+            fragment: fragment
+                .filter(|fragment| !fragment.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// The same link, without its fragment: the page to fetch.
+    pub fn without_fragment(&self) -> DocsRsLink {
+        DocsRsLink {
+            fragment: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl fmt::Display for DocsRsLink {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "https://docs.rs/{}/{}/", self.krate, self.version)?;
+        if let Some(page) = &self.page {
+            write!(fmt, "{}", page)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(fmt, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use xtask::items::Items;
+use xtask::online::{self, Cache};
+use xtask::{check_all_offline, extract};
+
+const USAGE: &str = "\
+usage: cargo xtask <task>
+
+tasks:
+    docs-rs-links         check the docs.rs links in content/, against
+                          tokio-items.txt, or fetching them with ONLINE=1
+    docs-rs-items <json>  regenerate tokio-items.txt from the JSON output of
+                          rustdoc for tokio";
+
+const ITEMS_COMMENT: &str = "\
+The pages of the docs of tokio, and the items on each, for `cargo xtask
+docs-rs-links`. Generated by `cargo xtask docs-rs-items`: see its docs in
+xtask/src/main.rs to update it.";
+
+/// The directory of the xtask crate.
+fn xtask_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let res = match args[..] {
+        ["docs-rs-links"] => docs_rs_links().await,
+        ["docs-rs-items", json] => docs_rs_items(Path::new(json)),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    match res {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Check the links, and report the broken ones. Returns whether all of them
+/// are fine.
+async fn docs_rs_links() -> Result<bool, String> {
+    let content = xtask_dir().join("../content");
+    let found = extract::content(&content)
+        .map_err(|err| format!("reading {}: {}", content.display(), err))?;
+
+    let reports = if env::var("ONLINE").is_ok_and(|online| online == "1") {
+        let mut cache = Cache::load(&cache_path());
+        let reports = online::check_all(&found, &mut cache).await;
+        cache
+            .save()
+            .map_err(|err| format!("saving the cache: {}", err))?;
+        reports
+    } else {
+        let items = load_items()?;
+        println!(
+            "checking {} docs.rs links offline, against {} {}",
+            found.len(),
+            items.krate,
+            items.version
+        );
+        check_all_offline(&items, &found)
+    };
+
+    for report in &reports {
+        println!("{}", report);
+    }
+    println!("{} of {} links have problems", reports.len(), found.len());
+
+    Ok(reports.is_empty())
+}
+
+fn items_path() -> PathBuf {
+    xtask_dir().join("tokio-items.txt")
+}
+
+fn cache_path() -> PathBuf {
+    xtask_dir().join("target/docs-rs-links.json")
+}
+
+fn load_items() -> Result<Items, String> {
+    let path = items_path();
+    let text =
+        fs::read_to_string(&path).map_err(|err| format!("reading {}: {}", path.display(), err))?;
+    Items::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Write `tokio-items.txt` from the JSON rustdoc wrote for tokio. To get
+/// it, in a crate depending on the version of tokio to pin, with its `full`
+/// and `test-util` features:
+///
+/// ```text
+/// RUSTC_BOOTSTRAP=1 RUSTDOCFLAGS="--cfg docsrs" cargo rustdoc -p tokio \
+///     -- -Zunstable-options --output-format json
+/// ```
+///
+/// which writes `target/doc/tokio.json`.
+fn docs_rs_items(json: &Path) -> Result<bool, String> {
+    let text =
+        fs::read_to_string(json).map_err(|err| format!("reading {}: {}", json.display(), err))?;
+    let json = serde_json::from_str(&text).map_err(|err| format!("{}: {}", json.display(), err))?;
+
+    let items = Items::from_rustdoc(&json)?;
+    let path = items_path();
+    fs::write(&path, items.to_text(ITEMS_COMMENT))
+        .map_err(|err| format!("writing {}: {}", path.display(), err))?;
+
+    println!(
+        "wrote {} for {} {}",
+        path.display(),
+        items.krate,
+        items.version
+    );
+    Ok(true)
+}
@@ -0,0 +1,200 @@
+//! Checking docs.rs links by fetching them, with `ONLINE=1`.
+//!
+//! Each page is only fetched once, with a `HEAD` request, and a few at a
+//! time, so as to go easy on docs.rs. The answers are kept in a cache file
+//! for a day, so that checking again soon after is quick.
+
+use crate::extract::Found;
+use crate::link::DocsRsLink;
+use crate::{Problem, Report};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many requests are made at once.
+pub const CONCURRENCY: usize = 8;
+
+/// How long an answer is kept in the cache.
+pub const CACHE_FOR: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What docs.rs answered for a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    /// The HTTP status, after following redirects. 0 if there was none.
+    pub status: u16,
+
+    /// The URL that answered, after following redirects.
+    pub url: String,
+}
+
+/// Answers to previous requests, in a file.
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+
+    /// The answers by URL, with when they came as seconds since the epoch.
+    answers: BTreeMap<String, (Answer, u64)>,
+}
+
+impl Cache {
+    /// The answers in the file at `path`, ignoring those too old. A missing
+    /// or broken file is an empty cache.
+    pub fn load(path: &Path) -> Cache {
+        let mut answers = BTreeMap::new();
+        let json: Value = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        for (url, entry) in json.as_object().into_iter().flatten() {
+            let answer = (
+                entry["status"].as_u64(),
+                entry["url"].as_str(),
+                entry["checked"].as_u64(),
+            );
+            if let (Some(status), Some(to), Some(checked)) = answer {
+                if now().saturating_sub(checked) < CACHE_FOR.as_secs() {
+                    let answer = Answer {
+                        status: status as u16,
+                        url: to.to_string(),
+                    };
+                    answers.insert(url.clone(), (answer, checked));
+                }
+            }
+        }
+
+        Cache {
+            path: path.to_path_buf(),
+            answers,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Answer> {
+        self.answers.get(url).map(|(answer, _)| answer)
+    }
+
+    /// Keep `answer`, unless the request failed, which is worth trying
+    /// again.
+    pub fn insert(&mut self, url: String, answer: Answer) {
+        if answer.status != 0 {
+            self.answers.insert(url, (answer, now()));
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut json = Map::new();
+        for (url, (answer, checked)) in &self.answers {
+            json.insert(
+                url.clone(),
+                json!({ "status": answer.status, "url": answer.url, "checked": checked }),
+            );
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&json)?)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Fetch the head of `url` with curl, following redirects.
+pub async fn fetch(url: &str) -> Answer {
+    let output = Command::new("curl")
+        .args(["--silent", "--head", "--location", "--max-time", "30"])
+        .args(["--output", if cfg!(windows) { "NUL" } else { "/dev/null" }])
+        .args(["--write-out", "%{http_code} %{url_effective}"])
+        .arg(url)
+        .output()
+        .await;
+
+    let stdout = output
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    let (status, effective) = stdout.split_once(' ').unwrap_or(("0", url));
+
+    Answer {
+        status: status.parse().unwrap_or(0),
+        url: effective.to_string(),
+    }
+}
+
+/// What is wrong with a link to `link`, given the answer for its page.
+pub fn problem(link: &DocsRsLink, answer: &Answer) -> Option<Problem> {
+    match answer.status {
+        0 => return Some(Problem::Dead("no answer".to_string())),
+        400.. => return Some(Problem::Dead(format!("HTTP {}", answer.status))),
+        _ => {}
+    }
+
+    // docs.rs redirects a version like `1` to the latest that matches, and
+    // the page of a crate to its docs, which aren't worth reporting.
+    let to = DocsRsLink::parse(&answer.url)?;
+    let moved = to.krate != link.krate
+        || link
+            .page
+            .as_ref()
+            .is_some_and(|page| to.page.as_ref() != Some(page));
+
+    if moved {
+        Some(Problem::Redirected(DocsRsLink {
+            fragment: link.fragment.clone(),
+            ..to
+        }))
+    } else {
+        None
+    }
+}
+
+/// Fetch the page of every link in `found`, but those in `cache`, and
+/// report those with problems. New answers are added to `cache`.
+pub async fn check_all(found: &[Found], cache: &mut Cache) -> Vec<Report> {
+    let pages: BTreeSet<String> = found
+        .iter()
+        .map(|found| found.link.without_fragment().to_string())
+        .collect();
+
+    let limit = Arc::new(Semaphore::new(CONCURRENCY));
+    let mut requests = JoinSet::new();
+    for url in pages.into_iter().filter(|url| cache.get(url).is_none()) {
+        let limit = limit.clone();
+        requests.spawn(async move {
+            let _permit = limit.acquire_owned().await.unwrap();
+            let answer = fetch(&url).await;
+            (url, answer)
+        });
+    }
+    while let Some(res) = requests.join_next().await {
+        let (url, answer) = res.unwrap();
+        cache.insert(url, answer);
+    }
+
+    found
+        .iter()
+        .filter_map(|found| {
+            let page = found.link.without_fragment().to_string();
+            let problem = match cache.get(&page) {
+                Some(answer) => problem(&found.link, answer)?,
+                // Not cached, because it failed.
+                None => Problem::Dead("no answer".to_string()),
+            };
+            Some(Report {
+                found: found.clone(),
+                problem,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use xtask::extract::{self, Found};
+use xtask::link::DocsRsLink;
+
+/// The line and URL of each link found.
+fn lines_and_urls(found: &[Found]) -> Vec<(usize, &str)> {
+    found
+        .iter()
+        .map(|found| (found.line, &found.url[..]))
+        .collect()
+}
+
+#[test]
+fn finds_links_of_every_kind() {
+    let markdown = "\
+---
+title: \"See https://docs.rs/tokio/1/tokio/index.html\"
+---
+
+An [inline link](https://docs.rs/tokio/1/tokio/sync/index.html), a
+[reference][mpsc], an <https://docs.rs/tokio/1/tokio/net/index.html>,
+and <a href=\"https://docs.rs/tokio/1/tokio/io/index.html\">some HTML</a>.
+
+A [link with
+text over two lines](https://docs.rs/tokio/1/tokio/fs/index.html).
+
+```rust
+// [not a link](https://docs.rs/tokio/1/tokio/time/index.html)
+```
+
+Not to docs.rs: [tokio.rs](https://tokio.rs), [a page](/tokio/glossary).
+
+[mpsc]: https://docs.rs/tokio/1/tokio/sync/mpsc/index.html
+[unused]: https://docs.rs/tokio/1/tokio/process/index.html
+";
+    let found = extract::links("page.md", markdown);
+
+    assert_eq!(
+        lines_and_urls(&found),
+        [
+            (5, "https://docs.rs/tokio/1/tokio/sync/index.html"),
+            (6, "https://docs.rs/tokio/1/tokio/net/index.html"),
+            (7, "https://docs.rs/tokio/1/tokio/io/index.html"),
+            (10, "https://docs.rs/tokio/1/tokio/fs/index.html"),
+            // Reference-style links are found where they're defined, even
+            // when unused.
+            (18, "https://docs.rs/tokio/1/tokio/sync/mpsc/index.html"),
+            (19, "https://docs.rs/tokio/1/tokio/process/index.html"),
+        ]
+    );
+    assert!(found.iter().all(|found| found.file == "page.md"));
+}
+
+#[test]
+fn finds_links_in_content() {
+    let content = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/content");
+    let found = extract::content(&content).unwrap();
+
+    let files: Vec<_> = found
+        .iter()
+        .map(|found| (&found.file[..], found.line))
+        .collect();
+    assert_eq!(
+        files,
+        [
+            ("index.md", 6),
+            ("index.md", 8),
+            ("index.md", 9),
+            ("tutorial/runtime.md", 4),
+            ("tutorial/runtime.md", 6),
+            ("tutorial/runtime.md", 7),
+            ("tutorial/runtime.md", 9),
+            ("tutorial/runtime.md", 10),
+        ]
+    );
+}
+
+#[test]
+fn normalizes_links() {
+    let link = |url: &str| DocsRsLink::parse(url).map(|link| link.to_string());
+
+    // The version defaults to `latest`, and `*` means the same.
+    for url in [
+        "https://docs.rs/tokio",
+        "https://docs.rs/tokio/",
+        "https://docs.rs/tokio/*",
+        "http://docs.rs/tokio/latest",
+        "https://docs.rs/crate/tokio/latest",
+    ] {
+        assert_eq!(
+            link(url).as_deref(),
+            Some("https://docs.rs/tokio/latest/"),
+            "{}",
+            url
+        );
+    }
+
+    // A module is its `index.html`.
+    for url in [
+        "https://docs.rs/tokio/1/tokio/sync",
+        "https://docs.rs/tokio/1/tokio/sync/",
+        "https://docs.rs/tokio/1/tokio/sync/index.html",
+    ] {
+        assert_eq!(
+            link(url).as_deref(),
+            Some("https://docs.rs/tokio/1/tokio/sync/index.html"),
+            "{}",
+            url
+        );
+    }
+
+    assert_eq!(
+        link("https://docs.rs/tokio/1/tokio/runtime/struct.Runtime.html?search=x#method.block_on")
+            .as_deref(),
+        Some("https://docs.rs/tokio/1/tokio/runtime/struct.Runtime.html#method.block_on")
+    );
+    assert_eq!(
+        DocsRsLink::parse("https://docs.rs/tokio/*/tokio/fn.spawn.html#"),
+        Some(DocsRsLink {
+            krate: "tokio".to_string(),
+            version: "latest".to_string(),
+            page: Some("tokio/fn.spawn.html".to_string()),
+            fragment: None,
+        })
+    );
+
+    for url in [
+        "https://tokio.rs/tokio/tutorial",
+        "https://doc.rust-lang.org/std/",
+        "https://docs.rs/",
+        "/tokio/glossary",
+    ] {
+        assert_eq!(link(url), None, "{}", url);
+    }
+}
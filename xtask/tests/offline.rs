@@ -0,0 +1,140 @@
+use std::fs;
+use std::path::Path;
+use xtask::extract;
+use xtask::items::{Items, Lookup};
+use xtask::link::DocsRsLink;
+use xtask::{check_all_offline, check_offline, Problem};
+
+fn fixtures() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"))
+}
+
+fn items() -> Items {
+    Items::parse(&fs::read_to_string(fixtures().join("items.txt")).unwrap()).unwrap()
+}
+
+fn check(url: &str) -> Option<Problem> {
+    check_offline(&items(), &DocsRsLink::parse(url).unwrap())
+}
+
+#[test]
+fn looks_up_pages_and_items() {
+    let items = items();
+    assert_eq!((&items.krate[..], &items.version[..]), ("tokio", "1.2.3"));
+
+    let runtime = "tokio/runtime/struct.Runtime.html";
+    assert_eq!(items.lookup(runtime, None), Lookup::Found);
+    assert_eq!(
+        items.lookup(runtime, Some("method.block_on")),
+        Lookup::Found
+    );
+    assert_eq!(items.lookup(runtime, Some("method.enter")), Lookup::NoItem);
+
+    // Headings in the docs aren't known, so they're assumed to be there.
+    assert_eq!(items.lookup(runtime, Some("examples")), Lookup::Found);
+
+    // Pages without items listed can't be checked for any.
+    assert_eq!(
+        items.lookup("tokio/io/struct.Error.html", Some("method.kind")),
+        Lookup::Found
+    );
+
+    assert_eq!(
+        items.lookup("tokio/fn.spawn.html", None),
+        Lookup::Redirect("tokio/task/fn.spawn.html")
+    );
+    assert_eq!(items.lookup("tokio/fn.block_on.html", None), Lookup::NoPage);
+}
+
+#[test]
+fn checks_links_to_the_same_version() {
+    assert_eq!(check("https://docs.rs/tokio"), None);
+    assert_eq!(
+        check("https://docs.rs/tokio/1/tokio/task/fn.spawn.html"),
+        None
+    );
+    assert_eq!(check("https://docs.rs/tokio/1.2/tokio/task/"), None);
+
+    let dead = Some(Problem::Dead(
+        "no page `tokio/task/fn.spawn_local.html` in tokio 1.2.3".to_string(),
+    ));
+    for version in ["1", "1.2", "1.2.3", "latest", "*"] {
+        let url = format!(
+            "https://docs.rs/tokio/{}/tokio/task/fn.spawn_local.html",
+            version
+        );
+        assert_eq!(check(&url), dead, "{}", url);
+    }
+
+    assert_eq!(
+        check("https://docs.rs/tokio/1/tokio/runtime/struct.Runtime.html#method.enter"),
+        Some(Problem::Dead(
+            "`tokio/runtime/struct.Runtime.html` has no item `#method.enter` in tokio 1.2.3"
+                .to_string()
+        ))
+    );
+
+    // Redirected with the fragment, which may have moved too.
+    assert_eq!(
+        check("https://docs.rs/tokio/1/tokio/fn.spawn.html#examples"),
+        Some(Problem::Redirected(
+            DocsRsLink::parse("https://docs.rs/tokio/1/tokio/task/fn.spawn.html#examples").unwrap()
+        ))
+    );
+}
+
+#[test]
+fn leaves_other_versions_and_crates_alone() {
+    for url in [
+        "https://docs.rs/tokio/0.2/tokio/fn.block_on.html",
+        "https://docs.rs/tokio/1.20/tokio/fn.block_on.html",
+        "https://docs.rs/tokio/1.2.4/tokio/fn.block_on.html",
+        "https://docs.rs/tokio/12/tokio/fn.block_on.html",
+        "https://docs.rs/bytes/1/bytes/struct.Missing.html",
+    ] {
+        assert_eq!(check(url), None, "{}", url);
+    }
+}
+
+#[test]
+fn reports_problems_with_file_and_line() {
+    let found = extract::content(&fixtures().join("content")).unwrap();
+    let reports: Vec<_> = check_all_offline(&items(), &found)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    assert_eq!(
+        reports,
+        [
+            "index.md:8: https://docs.rs/tokio/1/tokio/fn.spawn.html: \
+             redirected to https://docs.rs/tokio/1/tokio/task/fn.spawn.html",
+            "tutorial/runtime.md:4: https://docs.rs/tokio/1.2/tokio/runtime/struct.Handle.html: \
+             dead: no page `tokio/runtime/struct.Handle.html` in tokio 1.2.3",
+            "tutorial/runtime.md:10: \
+             https://docs.rs/tokio/1/tokio/runtime/struct.Runtime.html#method.enter: \
+             dead: `tokio/runtime/struct.Runtime.html` has no item `#method.enter` in tokio 1.2.3",
+        ]
+    );
+}
+
+#[test]
+fn parses_what_it_writes() {
+    let items = items();
+    let text = items.to_text("A comment,\nover two lines.");
+
+    assert!(text.starts_with("# tokio 1.2.3\n# A comment,\n# over two lines.\n"));
+    assert_eq!(Items::parse(&text).unwrap(), items);
+}
+
+#[test]
+fn rejects_broken_lists() {
+    assert_eq!(
+        Items::parse("tokio/index.html\n"),
+        Err("line 1: expected `# <crate> <version>`, got \"tokio/index.html\"".to_string())
+    );
+    assert_eq!(
+        Items::parse("# tokio 1.0.0\ntokio/struct.A.html#method.a\ntokio/struct.A.html\n"),
+        Err("line 2: `tokio/struct.A.html#method.a` comes before its page".to_string())
+    );
+}